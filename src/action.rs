@@ -1,4 +1,6 @@
-use crate::{backend::Backend, context::{IO, IOID}, input::InputActionID};
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{backend::Backend, context::{HasContext, IO, IOID, TreeContext}, input::{InputActionID, InputLayer, InputStrokeItem}, node::Node, resource::Resource};
 
 
 /// Basic input actions necessary for input actions to work.
@@ -49,12 +51,16 @@ pub trait ActionIO<B: Backend>: IO<B> {
     ///   triggered by an inactive, or active event.
     ///   The number passed into the `emit_event` function will be passed as the third argument to this callback.
     ///   The return value of the callback should indicate if the action was handled or not.
-    fn emit_event(event: &mut InputEvent, number: i32, callback: dyn FnOnce(&InputActionID, bool, i32) -> bool);
+    fn emit_event(&mut self, event: InputEvent, number: i32, callback: ActionCallback);
 }
 
+/// Callback passed to [`ActionIO::emit_event`], called if the event triggers an input action.
+pub type ActionCallback = Box<dyn FnOnce(&InputActionID, bool, i32) -> bool>;
+
 /// Uniquely codes a pressed key, button or a gesture, by using an I/O ID and event code map.
 /// Each I/O interface can define its own keys and buttons it needs to map. The way it maps
 /// codes to buttons is left up to the interface to define, but it usually is with an enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct InputEventCode {
     /// ID for the I/O interface representing the input device. The I/O interface defines a code
     /// for each event it may send. This means the I/O ID along with the event code should uniquely identify events.
@@ -76,6 +82,7 @@ pub struct InputEventCode {
 ///
 /// This only covers events with binary outcomes: the source of event is active, or it is not.
 /// Analog sources like joysticks may be translated into input events but they won't be precise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct InputEvent {
     /// Code uniquely identifying the source of the event, such as a key, button or gesture.
     pub code: InputEventCode,
@@ -91,6 +98,28 @@ pub struct InputEvent {
     pub is_active: bool,
 }
 
+/// Keys and buttons that can be sent as input events.
+pub trait ToEventCode {
+    /// Get the event code representing this key or button.
+    fn event_code(&self) -> InputEventCode;
+}
+
+impl<B: Backend> InputStrokeItem<B>
+where
+    B::KeyboardKey: ToEventCode,
+    B::MouseButton: ToEventCode,
+    B::GamepadButton: ToEventCode,
+{
+    /// Get the event code of the key or button.
+    pub fn event_code(&self) -> InputEventCode {
+        match self {
+            Self::KeyboardKey(key) => key.event_code(),
+            Self::MouseButton(button) => button.event_code(),
+            Self::GamepadButton(button) => button.event_code(),
+        }
+    }
+}
+
 /// This is a base interface for nodes that respond to input actions. While [`ActionIO`] shouldn't interact
 /// with nodes directly, input handling systems like [`FocusIO`] or [`HoverIO`] will expect nodes to implement
 /// this interface if they support input actions.
@@ -138,3 +167,232 @@ pub trait Actionable<B: Backend> {
     /// True if the action was handled, false if not.
     fn action_impl(&mut self, io: Option<&mut dyn IO<B>>, number: i32, action: &InputActionID, is_active: bool) -> bool;
 }
+
+/// Pass an action to a node, bubbling it up to its ancestors until one of them handles it.
+///
+/// The target is given the action first. If it isn't handled, the action is passed to the target's parent, and so
+/// on, until a node handles it or the root is reached. Nodes that [block input](Actionable::blocks_input) are
+/// skipped. Bubbling stops early at a node with [`crate::node::NodeData::stop_propagation`] set, even if the node
+/// didn't handle the action.
+///
+/// # Params
+///
+/// - `target`: Innermost node the action is meant for, for example the focused node.
+/// - `handle`: Function passing the action to a node. Returns true if the node handled the action.
+///
+/// # Returns
+///
+/// True if any node handled the action.
+///
+/// # Panics
+///
+/// Panics if any node on the branch is borrowed mutably.
+///
+/// ```
+/// # use std::{cell::{Cell, RefCell}, rc::Rc};
+/// # use amity_ui::{backend::headless::HeadlessBackend, focus::Focusable, input::*,
+/// #     node::{Node, NodeVariant, ParentNode, button}, tree::LayoutTree, utils::simple_constructor};
+/// # use raylib::consts::KeyboardKey;
+/// type Ref = Rc<RefCell<Node<HeadlessBackend>>>;
+/// let count_presses = |node: &Ref| {
+///     let presses = Rc::new(Cell::new(0));
+///     if let NodeVariant::Button(button) = &mut node.borrow_mut().variant {
+///         let presses = presses.clone();
+///         button.on_press.subscribe(move |_| presses.set(presses.get() + 1));
+///     }
+///     presses
+/// };
+/// let parent: Ref = Rc::new(RefCell::new(button((), "Parent")));
+/// let inner: Ref = Rc::new(RefCell::new(button((), "Inner")));
+/// let space: Ref = Rc::new(RefCell::new(simple_constructor(NodeVariant::Space, ())));
+/// parent.append_child(inner.clone());
+/// parent.append_child(space.clone());
+/// let parent_presses = count_presses(&parent);
+/// let inner_presses = count_presses(&inner);
+///
+/// let mut tree = LayoutTree::new(parent.clone(), HeadlessBackend::new());
+/// tree.bound_inputs.push(InputLayer {
+///     modifiers: InputStroke { input: Vec::new() },
+///     bindings: vec![InputBinding {
+///         action: FluidInputAction::Press.id(),
+///         trigger: InputStrokeItem::KeyboardKey(KeyboardKey::KEY_ENTER),
+///     }],
+/// });
+/// let press = |tree: &mut LayoutTree<HeadlessBackend>, node: &Ref| {
+///     node.clone().focus(&mut tree.focus);
+///     tree.backend.press_key(KeyboardKey::KEY_ENTER);
+///     tree.draw();
+///     tree.backend.release_key(KeyboardKey::KEY_ENTER);
+///     tree.backend.next_frame();
+/// };
+///
+/// // The space doesn't handle the press, so it bubbles up to the parent button
+/// press(&mut tree, &space);
+/// assert_eq!((parent_presses.get(), inner_presses.get()), (1, 0));
+///
+/// // The inner button handles the press itself
+/// press(&mut tree, &inner);
+/// assert_eq!((parent_presses.get(), inner_presses.get()), (1, 1));
+///
+/// // Propagation can be stopped without handling the action
+/// space.borrow_mut().data.stop_propagation = true;
+/// press(&mut tree, &space);
+/// assert_eq!((parent_presses.get(), inner_presses.get()), (1, 1));
+/// ```
+pub fn bubble_action<B: Backend>(
+    target: &Rc<RefCell<Node<B>>>,
+    mut handle: impl FnMut(&mut Rc<RefCell<Node<B>>>) -> bool,
+) -> bool {
+    let mut node = Some(target.clone());
+
+    while let Some(mut current) = node {
+        if !current.blocks_input() && handle(&mut current) { return true; }
+
+        let data = &current.borrow().data;
+        if data.stop_propagation { return false; }
+        node = data.parent();
+    }
+
+    false
+}
+
+/// Event saved by [`InputMapSpace`], waiting to be processed.
+struct PendingEvent {
+    event: InputEvent,
+    number: i32,
+    callback: ActionCallback,
+}
+
+/// Reference [`ActionIO`] implementation, mapping input events to actions using a list of input layers.
+///
+/// Events are saved as they are emitted, and processed once [`Self::after_draw`] is called. Events are emitted every
+/// frame a key or button is held, so all events emitted during the frame are treated as held at the same time. An
+/// event triggers an action if it is the trigger of a binding in the most specific layer whose modifiers are all
+/// held; keys that only act as modifiers don't trigger anything.
+///
+/// ```
+/// # use std::{cell::RefCell, rc::Rc};
+/// # use amity_ui::action::{ActionIO, InputEvent, InputMapSpace, ToEventCode};
+/// # use amity_ui::backend::headless::HeadlessBackend;
+/// # use amity_ui::input::{FluidInputAction, InputActionID, InputBinding, InputLayer, InputStroke, InputStrokeItem};
+/// # use raylib::consts::KeyboardKey;
+/// type B = HeadlessBackend;
+/// let copy = InputBinding::<B> {
+///     action: FluidInputAction::Copy.id(),
+///     trigger: InputStrokeItem::KeyboardKey(KeyboardKey::KEY_C),
+/// };
+/// let mut space = InputMapSpace::new(vec![
+///     InputLayer { modifiers: InputStroke::parse("ctrl").unwrap(), bindings: vec![copy] },
+/// ]);
+///
+/// // Emit events for held keys, and log actions they trigger
+/// let log = Rc::new(RefCell::new(Vec::<(InputActionID, bool, i32)>::new()));
+/// let mut frame = |keys: &[(KeyboardKey, bool)]| {
+///     for &(key, is_active) in keys {
+///         let log = log.clone();
+///         let event = InputEvent { code: key.event_code(), is_active };
+///         space.emit_event(event, key as i32, Box::new(move |action, is_active, number| {
+///             log.borrow_mut().push((*action, is_active, number));
+///             true
+///         }));
+///     }
+///     space.after_draw();
+///     log.take()
+/// };
+///
+/// // Pressing ctrl alone does nothing
+/// assert_eq!(frame(&[(KeyboardKey::KEY_LEFT_CONTROL, true)]), []);
+///
+/// // Pressing C while ctrl is held copies; only the C key runs its callback
+/// assert_eq!(
+///     frame(&[(KeyboardKey::KEY_LEFT_CONTROL, false), (KeyboardKey::KEY_C, true)]),
+///     [(FluidInputAction::Copy.id(), true, KeyboardKey::KEY_C as i32)],
+/// );
+///
+/// // Holding both keeps emitting the action, but inactive
+/// assert_eq!(
+///     frame(&[(KeyboardKey::KEY_LEFT_CONTROL, false), (KeyboardKey::KEY_C, false)]),
+///     [(FluidInputAction::Copy.id(), false, KeyboardKey::KEY_C as i32)],
+/// );
+///
+/// // Without ctrl, C doesn't copy
+/// assert_eq!(frame(&[(KeyboardKey::KEY_C, true)]), []);
+/// ```
+pub struct InputMapSpace<B: Backend> {
+    /// Input layers, sorted so the layers with the most modifiers come first.
+    layers: Vec<InputLayer<B>>,
+
+    /// Events emitted during the current frame.
+    events: Vec<PendingEvent>,
+}
+
+impl<B: Backend> InputMapSpace<B>
+where
+    B::KeyboardKey: ToEventCode,
+    B::MouseButton: ToEventCode,
+    B::GamepadButton: ToEventCode,
+{
+    /// Create a system using the given input layers. The layers don't have to be sorted.
+    pub fn new(mut layers: Vec<InputLayer<B>>) -> Self {
+        layers.sort();
+
+        Self {
+            layers,
+            events: Vec::new(),
+        }
+    }
+
+    /// Input layers, sorted so the layers with the most modifiers come first.
+    #[inline]
+    pub fn layers(&self) -> &[InputLayer<B>] {
+        &self.layers
+    }
+
+    /// Process events emitted during the frame, running callbacks of those that triggered an action. All saved
+    /// events are discarded afterwards.
+    pub fn after_draw(&mut self) {
+        let events = std::mem::take(&mut self.events);
+        let held: Vec<InputEventCode> = events.iter().map(|pending| pending.event.code).collect();
+
+        for pending in events {
+            let Some(action) = self.resolve(&held, pending.event.code) else { continue };
+
+            (pending.callback)(&action, pending.event.is_active, pending.number);
+        }
+    }
+
+    /// Find the action triggered by an event.
+    ///
+    /// # Params
+    ///
+    /// - `held`:    Codes of all events that are currently held.
+    /// - `trigger`: Code of the event to resolve.
+    fn resolve(&self, held: &[InputEventCode], trigger: InputEventCode) -> Option<InputActionID> {
+        self.layers.iter()
+            .filter(|layer| layer.modifiers.iter().all(|modifier| held.contains(&modifier.event_code())))
+            .find_map(|layer| {
+                layer.bindings.iter()
+                    .find(|binding| binding.trigger.event_code() == trigger)
+                    .map(|binding| binding.action)
+            })
+    }
+}
+
+impl<B: Backend> HasContext<B> for InputMapSpace<B> {
+    #[inline]
+    fn tree_context(&self) -> TreeContext<'_, B> {
+        TreeContext { ptr: None }
+    }
+}
+
+impl<B: Backend> IO<B> for InputMapSpace<B> {
+    #[inline]
+    fn load_to(&self, _resource: &mut dyn Resource<B>) {}
+}
+
+impl<B: Backend> ActionIO<B> for InputMapSpace<B> {
+    #[inline]
+    fn emit_event(&mut self, event: InputEvent, number: i32, callback: ActionCallback) {
+        self.events.push(PendingEvent { event, number, callback });
+    }
+}
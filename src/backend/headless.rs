@@ -0,0 +1,604 @@
+use std::{collections::{HashMap, HashSet, VecDeque}, path::{Path, PathBuf}, time::Duration};
+use raylib::consts::{GamepadButton, KeyboardKey, MouseButton};
+use crate::typeface::GlyphTarget;
+use super::{Backend, Color, MouseCursor, Rectangle, Vector2};
+
+/// Backend that doesn't open a window. Input is set by hand, and everything drawn is recorded as a list of
+/// [`DrawCommand`]s, which makes it possible to test nodes deterministically.
+///
+/// Keys and buttons use the same types as the Raylib backend, so input bindings work the same in both.
+///
+/// Input state changes immediately, and lasts until [`Self::next_frame`] is called. A key pressed with
+/// [`Self::press_key`] is both down and pressed until the next frame, and only down afterwards.
+///
+/// ```
+/// # use amity_ui::backend::{Backend, headless::HeadlessBackend};
+/// let mut backend = HeadlessBackend::new();
+/// backend.push_character('a');
+///
+/// assert_eq!(backend.input_character(), Some('a'));
+/// assert_eq!(backend.input_character(), None);
+/// ```
+pub struct HeadlessBackend {
+    keys_down: HashSet<KeyboardKey>,
+    previous_keys_down: HashSet<KeyboardKey>,
+    repeated_keys: HashSet<KeyboardKey>,
+    mouse_buttons_down: HashSet<MouseButton>,
+    previous_mouse_buttons_down: HashSet<MouseButton>,
+    gamepad_buttons_down: HashSet<(i32, GamepadButton)>,
+    previous_gamepad_buttons_down: HashSet<(i32, GamepadButton)>,
+    repeated_gamepad_buttons: HashSet<(i32, GamepadButton)>,
+    left_sticks: HashMap<i32, Vector2>,
+
+    /// Characters to return from `input_character`, in order.
+    characters: VecDeque<char>,
+
+    /// Text to return from `input_composition`, in order, before any queued characters.
+    compositions: VecDeque<String>,
+
+    mouse_position: Vector2,
+    scroll: Vector2,
+    clipboard: String,
+    delta_time: Duration,
+    has_just_resized: bool,
+    window_size: Vector2,
+    scale: f32,
+    dpi: Vector2,
+    mouse_cursor: MouseCursor,
+    tint: Color,
+
+    draw_area: Rectangle,
+    /// Areas set by enclosing `set_area` calls, restored in reverse order by `restore_area`.
+    area_stack: Vec<Rectangle>,
+
+    /// Number of textures loaded so far, used to assign texture IDs.
+    texture_count: u32,
+
+    /// Everything drawn since the last call to `next_frame`.
+    pub draw_commands: Vec<DrawCommand>,
+}
+
+impl HeadlessBackend {
+    /// Default value for `window_size`.
+    pub const WINDOW_SIZE: Vector2 = Vector2::new(800.0, 600.0);
+
+    /// Default value for `delta_time`; 60 frames per second.
+    pub const DELTA_TIME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+    /// Create a backend with nothing held down, and the mouse in the top-left corner of an 800×600 window.
+    pub fn new() -> Self {
+        Self {
+            keys_down: HashSet::new(),
+            previous_keys_down: HashSet::new(),
+            repeated_keys: HashSet::new(),
+            mouse_buttons_down: HashSet::new(),
+            previous_mouse_buttons_down: HashSet::new(),
+            gamepad_buttons_down: HashSet::new(),
+            previous_gamepad_buttons_down: HashSet::new(),
+            repeated_gamepad_buttons: HashSet::new(),
+            left_sticks: HashMap::new(),
+            characters: VecDeque::new(),
+            compositions: VecDeque::new(),
+            mouse_position: Vector2::default(),
+            scroll: Vector2::default(),
+            clipboard: String::new(),
+            delta_time: Self::DELTA_TIME,
+            has_just_resized: false,
+            window_size: Self::WINDOW_SIZE,
+            scale: 1.0,
+            dpi: Vector2::new(96.0, 96.0),
+            mouse_cursor: MouseCursor::SystemDefault,
+            tint: Color::new(255, 255, 255, 255),
+            draw_area: Rectangle::from_parts(Vector2::default(), Self::WINDOW_SIZE),
+            area_stack: Vec::new(),
+            texture_count: 0,
+            draw_commands: Vec::new(),
+        }
+    }
+
+    /// Start a new frame. Keys and buttons pressed or released during the last frame are no longer reported as
+    /// such, queued characters and scroll are cleared, and so are the recorded draw commands.
+    pub fn next_frame(&mut self) {
+        self.previous_keys_down.clone_from(&self.keys_down);
+        self.previous_mouse_buttons_down.clone_from(&self.mouse_buttons_down);
+        self.previous_gamepad_buttons_down.clone_from(&self.gamepad_buttons_down);
+        self.repeated_keys.clear();
+        self.repeated_gamepad_buttons.clear();
+        self.characters.clear();
+        self.compositions.clear();
+        self.scroll = Vector2::default();
+        self.has_just_resized = false;
+        self.draw_commands.clear();
+    }
+
+    /// Hold a key down.
+    #[inline]
+    pub fn press_key(&mut self, key: KeyboardKey) {
+        self.keys_down.insert(key);
+    }
+
+    /// Release a held key.
+    #[inline]
+    pub fn release_key(&mut self, key: KeyboardKey) {
+        self.keys_down.remove(&key);
+    }
+
+    /// Report a held key as repeated during this frame.
+    #[inline]
+    pub fn repeat_key(&mut self, key: KeyboardKey) {
+        self.repeated_keys.insert(key);
+    }
+
+    /// Hold a mouse button down.
+    #[inline]
+    pub fn press_mouse_button(&mut self, button: MouseButton) {
+        self.mouse_buttons_down.insert(button);
+    }
+
+    /// Release a held mouse button.
+    #[inline]
+    pub fn release_mouse_button(&mut self, button: MouseButton) {
+        self.mouse_buttons_down.remove(&button);
+    }
+
+    /// Hold a gamepad button down.
+    #[inline]
+    pub fn press_gamepad_button(&mut self, gamepad: i32, button: GamepadButton) {
+        self.gamepad_buttons_down.insert((gamepad, button));
+    }
+
+    /// Release a held gamepad button.
+    #[inline]
+    pub fn release_gamepad_button(&mut self, gamepad: i32, button: GamepadButton) {
+        self.gamepad_buttons_down.remove(&(gamepad, button));
+    }
+
+    /// Report a held gamepad button as repeated during this frame.
+    #[inline]
+    pub fn repeat_gamepad_button(&mut self, gamepad: i32, button: GamepadButton) {
+        self.repeated_gamepad_buttons.insert((gamepad, button));
+    }
+
+    /// Tilt the left analog stick of a gamepad. The stick stays in place until moved again.
+    #[inline]
+    pub fn set_left_stick(&mut self, gamepad: i32, value: Vector2) {
+        self.left_sticks.insert(gamepad, value);
+    }
+
+    /// Queue a character to be returned by `input_character`.
+    #[inline]
+    pub fn push_character(&mut self, value: char) {
+        self.characters.push_back(value);
+    }
+
+    /// Queue every character of the text to be returned by `input_character`.
+    #[inline]
+    pub fn push_text(&mut self, text: &str) {
+        self.characters.extend(text.chars());
+    }
+
+    /// Queue text to be returned, as a whole, by `input_composition`.
+    #[inline]
+    pub fn push_composition(&mut self, text: &str) {
+        self.compositions.push_back(text.to_string());
+    }
+
+    /// Set the scroll motion for this frame.
+    #[inline]
+    pub fn set_scroll(&mut self, value: Vector2) {
+        self.scroll = value;
+    }
+
+    /// Set the time elapsed since the last frame.
+    #[inline]
+    pub fn set_delta_time(&mut self, value: Duration) {
+        self.delta_time = value;
+    }
+
+    /// Set the DPI of the window. Draw commands are recorded in dots, so at a DPI other than 96, they are scaled
+    /// relative to the pixels they were drawn with.
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use amity_ui::{backend::{Rectangle, Vector2, headless::{DrawCommand, HeadlessBackend}},
+    /// #     node::{Node, NodeData, NodeVariant}, tree::LayoutTree};
+    /// let root = Rc::new(RefCell::new(Node {
+    ///     data: NodeData::new(),
+    ///     variant: NodeVariant::Space,
+    /// }));
+    /// let mut backend = HeadlessBackend::new();
+    /// backend.set_dpi(Vector2::new(192.0, 192.0));
+    ///
+    /// let mut tree = LayoutTree::new(root, backend);
+    /// tree.draw();
+    ///
+    /// // The root node fills the 800×600 window, but is drawn at twice the size
+    /// let Some(DrawCommand::Rectangle { rectangle, .. }) = tree.backend.draw_commands.first() else { panic!() };
+    /// assert_eq!(*rectangle, Rectangle::new(0.0, 0.0, 1600.0, 1200.0));
+    /// ```
+    #[inline]
+    pub fn set_dpi(&mut self, value: Vector2) {
+        self.dpi = value;
+    }
+
+    /// Convert a position or size in pixels to dots.
+    fn to_dots(&self, value: Vector2) -> Vector2 {
+        let scale = self.hidpi_scale();
+        Vector2::new(value.x * scale.x, value.y * scale.y)
+    }
+
+    /// Convert a rectangle in pixels to dots.
+    fn rectangle_to_dots(&self, value: Rectangle) -> Rectangle {
+        Rectangle::from_parts(self.to_dots(value.start()), self.to_dots(value.size()))
+    }
+}
+
+impl Default for HeadlessBackend {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Operation recorded by [`HeadlessBackend`] when drawing. Commands are recorded as passed, except positions and sizes
+/// are converted to dots, and colors are multiplied by the current [tint](Backend::tint). Shapes outside of the draw
+/// area are kept.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCommand {
+    Line { start: Vector2, end: Vector2, color: Color },
+    Triangle { a: Vector2, b: Vector2, c: Vector2, color: Color },
+    Circle { center: Vector2, radius: f32, color: Color },
+    CircleOutline { center: Vector2, radius: f32, color: Color },
+    Rectangle { rectangle: Rectangle, color: Color },
+    Texture { texture: u32, rectangle: Rectangle, tint: Color },
+}
+
+/// Texture "loaded" by [`HeadlessBackend`]. Only the size and a unique ID are kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadlessTexture {
+    /// Unique ID of the texture, used to refer to it in [`DrawCommand::Texture`].
+    pub id: u32,
+    pub width: i32,
+    pub height: i32,
+    /// File the texture was loaded from, if any.
+    pub path: Option<PathBuf>,
+}
+
+/// Image in CPU memory, for use with [`HeadlessBackend`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadlessImage {
+    pub width: i32,
+    pub height: i32,
+    /// Pixels of the image, row by row.
+    pub pixels: Vec<Color>,
+}
+
+impl HeadlessImage {
+    /// Create an image filled with a single color.
+    pub fn new(width: i32, height: i32, color: Color) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![color; (width.max(0) * height.max(0)) as usize],
+        }
+    }
+
+    /// Get the color of a pixel.
+    ///
+    /// # Returns
+    ///
+    /// The color, or `None` if the pixel is out of bounds.
+    pub fn get(&self, x: i32, y: i32) -> Option<Color> {
+        if !(0..self.width).contains(&x) || !(0..self.height).contains(&y) { return None; }
+
+        Some(self.pixels[(y * self.width + x) as usize])
+    }
+}
+
+impl GlyphTarget for HeadlessImage {
+    #[inline]
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    #[inline]
+    fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Glyphs are drawn in white, with coverage as alpha, like in the Raylib backend.
+    fn blend_glyph_pixel(&mut self, x: i32, y: i32, coverage: u8, _palette_index: u8) {
+        let Some(existing) = self.get(x, y) else { return };
+
+        self.pixels[(y * self.width + x) as usize] = Color::new(255, 255, 255, existing.a.max(coverage));
+    }
+}
+
+impl Backend for HeadlessBackend {
+    type MouseButton = MouseButton;
+    type KeyboardKey = KeyboardKey;
+    type GamepadButton = GamepadButton;
+    type GamepadID = i32;
+    type Vector2 = Vector2;
+    type Rectangle = Rectangle;
+    type Texture = HeadlessTexture;
+    type Image = HeadlessImage;
+    type Color = Color;
+
+    #[inline]
+    fn is_mouse_button_pressed(&self, button: Self::MouseButton) -> bool {
+        self.mouse_buttons_down.contains(&button) && !self.previous_mouse_buttons_down.contains(&button)
+    }
+
+    #[inline]
+    fn is_mouse_button_released(&self, button: Self::MouseButton) -> bool {
+        !self.mouse_buttons_down.contains(&button) && self.previous_mouse_buttons_down.contains(&button)
+    }
+
+    #[inline]
+    fn is_mouse_button_down(&self, button: Self::MouseButton) -> bool {
+        self.mouse_buttons_down.contains(&button)
+    }
+
+    #[inline]
+    fn is_mouse_button_up(&self, button: Self::MouseButton) -> bool {
+        !self.mouse_buttons_down.contains(&button)
+    }
+
+    #[inline]
+    fn is_key_pressed(&self, key: Self::KeyboardKey) -> bool {
+        self.keys_down.contains(&key) && !self.previous_keys_down.contains(&key)
+    }
+
+    #[inline]
+    fn is_key_released(&self, key: Self::KeyboardKey) -> bool {
+        !self.keys_down.contains(&key) && self.previous_keys_down.contains(&key)
+    }
+
+    #[inline]
+    fn is_key_down(&self, key: Self::KeyboardKey) -> bool {
+        self.keys_down.contains(&key)
+    }
+
+    #[inline]
+    fn is_key_up(&self, key: Self::KeyboardKey) -> bool {
+        !self.keys_down.contains(&key)
+    }
+
+    #[inline]
+    fn is_key_repeated(&self, key: Self::KeyboardKey) -> bool {
+        self.repeated_keys.contains(&key)
+    }
+
+    #[inline]
+    fn input_character(&mut self) -> Option<char> {
+        self.characters.pop_front()
+    }
+
+    fn input_composition(&mut self) -> Option<String> {
+        if let Some(text) = self.compositions.pop_front() {
+            return Some(text);
+        }
+
+        let text: String = self.characters.drain(..).collect();
+        (!text.is_empty()).then_some(text)
+    }
+
+    #[inline]
+    fn is_gamepad_button_pressed(&self, gamepad: Self::GamepadID, button: Self::GamepadButton) -> bool {
+        self.gamepad_buttons_down.contains(&(gamepad, button))
+            && !self.previous_gamepad_buttons_down.contains(&(gamepad, button))
+    }
+
+    #[inline]
+    fn is_gamepad_button_released(&self, gamepad: Self::GamepadID, button: Self::GamepadButton) -> bool {
+        !self.gamepad_buttons_down.contains(&(gamepad, button))
+            && self.previous_gamepad_buttons_down.contains(&(gamepad, button))
+    }
+
+    #[inline]
+    fn is_gamepad_button_down(&self, gamepad: Self::GamepadID, button: Self::GamepadButton) -> bool {
+        self.gamepad_buttons_down.contains(&(gamepad, button))
+    }
+
+    #[inline]
+    fn is_gamepad_button_up(&self, gamepad: Self::GamepadID, button: Self::GamepadButton) -> bool {
+        !self.gamepad_buttons_down.contains(&(gamepad, button))
+    }
+
+    #[inline]
+    fn is_gamepad_button_repeated(&self, gamepad: Self::GamepadID, button: Self::GamepadButton) -> bool {
+        self.repeated_gamepad_buttons.contains(&(gamepad, button))
+    }
+
+    #[inline]
+    fn gamepad_left_stick(&self, gamepad: Self::GamepadID) -> Self::Vector2 {
+        self.left_sticks.get(&gamepad).copied().unwrap_or_default()
+    }
+
+    #[inline]
+    fn set_mouse_position(&mut self, value: Self::Vector2) {
+        self.mouse_position = value;
+    }
+
+    #[inline]
+    fn mouse_position(&self) -> Self::Vector2 {
+        self.mouse_position
+    }
+
+    #[inline]
+    fn scroll(&self) -> Self::Vector2 {
+        self.scroll
+    }
+
+    #[inline]
+    fn set_clipboard(&mut self, value: &str) {
+        value.clone_into(&mut self.clipboard);
+    }
+
+    #[inline]
+    fn clipboard(&self) -> String {
+        self.clipboard.clone()
+    }
+
+    #[inline]
+    fn delta_time(&self) -> Duration {
+        self.delta_time
+    }
+
+    #[inline]
+    fn has_just_resized(&self) -> bool {
+        self.has_just_resized
+    }
+
+    /// Resize the window. `has_just_resized` reports true until the next frame.
+    fn set_window_size(&mut self, value: Self::Vector2) {
+        self.window_size = value;
+        self.has_just_resized = true;
+
+        if self.area_stack.is_empty() {
+            self.draw_area = Rectangle::from_parts(Vector2::default(), value);
+        }
+    }
+
+    #[inline]
+    fn window_size(&self) -> Self::Vector2 {
+        self.window_size
+    }
+
+    #[inline]
+    fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    #[inline]
+    fn set_scale(&mut self, value: f32) {
+        self.scale = value;
+    }
+
+    #[inline]
+    fn dpi(&self) -> Self::Vector2 {
+        self.dpi * self.scale
+    }
+
+    fn set_area(&mut self, rect: Self::Rectangle) {
+        let area = rect.intersection(&self.draw_area).unwrap_or_default();
+
+        self.area_stack.push(self.draw_area);
+        self.draw_area = area;
+    }
+
+    #[inline]
+    fn area(&self) -> Self::Rectangle {
+        self.draw_area
+    }
+
+    fn restore_area(&mut self) {
+        self.draw_area = self.area_stack.pop()
+            .unwrap_or_else(|| Rectangle::from_parts(Vector2::default(), self.window_size));
+    }
+
+    #[inline]
+    fn set_mouse_cursor(&mut self, value: MouseCursor) {
+        self.mouse_cursor = value;
+    }
+
+    #[inline]
+    fn mouse_cursor(&self) -> MouseCursor {
+        self.mouse_cursor.clone()
+    }
+
+    #[inline]
+    fn new_image(&self, width: i32, height: i32) -> Self::Image {
+        HeadlessImage::new(width, height, Color::new(0, 0, 0, 0))
+    }
+
+    unsafe fn load_texture_from_image(&mut self, image: &Self::Image) -> Self::Texture {
+        self.texture_count += 1;
+
+        HeadlessTexture {
+            id: self.texture_count,
+            width: image.width,
+            height: image.height,
+            path: None,
+        }
+    }
+
+    /// The file isn't read; the texture is empty.
+    unsafe fn load_texture(&mut self, filename: &Path) -> Self::Texture {
+        self.texture_count += 1;
+
+        HeadlessTexture {
+            id: self.texture_count,
+            width: 0,
+            height: 0,
+            path: Some(filename.to_owned()),
+        }
+    }
+
+    #[inline]
+    unsafe fn update_texture(&mut self, texture: &mut Self::Texture, image: &Self::Image) {
+        texture.width = image.width;
+        texture.height = image.height;
+    }
+
+    #[inline]
+    unsafe fn unload_texture(&mut self, _texture: Self::Texture) {}
+
+    #[inline]
+    fn set_tint(&mut self, value: Self::Color) {
+        self.tint = value;
+    }
+
+    #[inline]
+    fn tint(&self) -> Self::Color {
+        self.tint
+    }
+
+    #[inline]
+    fn draw_line(&mut self, start: Self::Vector2, end: Self::Vector2, color: Self::Color) {
+        let (start, end) = (self.to_dots(start), self.to_dots(end));
+        self.draw_commands.push(DrawCommand::Line { start, end, color: color.multiply(self.tint) });
+    }
+
+    #[inline]
+    fn draw_triangle(&mut self, a: Self::Vector2, b: Self::Vector2, c: Self::Vector2, color: Self::Color) {
+        let (a, b, c) = (self.to_dots(a), self.to_dots(b), self.to_dots(c));
+        self.draw_commands.push(DrawCommand::Triangle { a, b, c, color: color.multiply(self.tint) });
+    }
+
+    #[inline]
+    fn draw_circle(&mut self, center: Self::Vector2, radius: f32, color: Self::Color) {
+        let (center, radius) = (self.to_dots(center), radius * self.hidpi_scale().x);
+        self.draw_commands.push(DrawCommand::Circle { center, radius, color: color.multiply(self.tint) });
+    }
+
+    #[inline]
+    fn draw_circle_outline(&mut self, center: Self::Vector2, radius: f32, color: Self::Color) {
+        let (center, radius) = (self.to_dots(center), radius * self.hidpi_scale().x);
+        self.draw_commands.push(DrawCommand::CircleOutline { center, radius, color: color.multiply(self.tint) });
+    }
+
+    #[inline]
+    fn draw_rectangle(&mut self, rectangle: Self::Rectangle, color: Self::Color) {
+        let rectangle = self.rectangle_to_dots(rectangle);
+        self.draw_commands.push(DrawCommand::Rectangle { rectangle, color: color.multiply(self.tint) });
+    }
+
+    #[inline]
+    fn draw_texture(&mut self, texture: &Self::Texture, rectangle: Self::Rectangle, tint: Self::Color) {
+        let rectangle = self.rectangle_to_dots(rectangle);
+        self.draw_commands.push(DrawCommand::Texture { texture: texture.id, rectangle, tint: tint.multiply(self.tint) });
+    }
+
+    fn draw_texture_align(&mut self, texture: &Self::Texture, rectangle: Self::Rectangle, tint: Self::Color) {
+        // Snap each edge in dots rather than the size, so adjacent textures still meet without a gap
+        let rectangle = self.rectangle_to_dots(rectangle);
+        let left   = rectangle.x.round();
+        let top    = rectangle.y.round();
+        let right  = (rectangle.x + rectangle.width).round();
+        let bottom = (rectangle.y + rectangle.height).round();
+        let rectangle = Rectangle::new(left, top, right - left, bottom - top);
+        self.draw_commands.push(DrawCommand::Texture { texture: texture.id, rectangle, tint: tint.multiply(self.tint) });
+    }
+}
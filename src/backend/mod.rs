@@ -1,7 +1,8 @@
-use std::{path::Path, time::Duration};
+use std::{collections::{HashMap, HashSet}, hash::Hash, ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign}, path::Path, rc::Rc, time::Duration};
+pub mod headless;
 pub mod raylib;
 
-#[derive(Clone, Copy, PartialEq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub struct Vector2 {
     pub x: f32,
     pub y: f32
@@ -11,9 +12,142 @@ impl Vector2 {
     pub const fn new(x: f32, y: f32) -> Self {
         Self { x, y }
     }
+
+    /// Length of the vector.
+    #[inline]
+    pub fn length(self) -> f32 {
+        self.length_squared().sqrt()
+    }
+
+    /// Square of the length of the vector. Cheaper than [`Self::length`], and good enough for comparing lengths.
+    #[inline]
+    pub const fn length_squared(self) -> f32 {
+        self.dot(self)
+    }
+
+    /// Get a vector of length `1` pointing in the same direction.
+    ///
+    /// # Returns
+    ///
+    /// The normalized vector, or a zero vector if this vector is zero.
+    ///
+    /// ```
+    /// # use amity_ui::backend::Vector2;
+    /// assert_eq!(Vector2::new(3.0, 4.0).normalized(), Vector2::new(0.6, 0.8));
+    /// assert_eq!(Vector2::new(0.0, 0.0).normalized(), Vector2::new(0.0, 0.0));
+    /// ```
+    #[inline]
+    pub fn normalized(self) -> Self {
+        let length = self.length();
+        if length == 0.0 { return Self::default(); }
+
+        self / length
+    }
+
+    /// Dot product of two vectors.
+    #[inline]
+    pub const fn dot(self, other: Self) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// Linearly interpolate between two vectors.
+    ///
+    /// # Params
+    ///
+    /// - `other`: Vector to interpolate towards.
+    /// - `t`: Position between the vectors; `0` returns this vector, `1` returns `other`.
+    ///
+    /// ```
+    /// # use amity_ui::backend::Vector2;
+    /// let start = Vector2::new(10.0, -20.0);
+    /// let end = Vector2::new(30.0, 40.0);
+    /// assert_eq!(start.lerp(end, 0.0), start);
+    /// assert_eq!(start.lerp(end, 1.0), end);
+    /// assert_eq!(start.lerp(end, 0.5), Vector2::new(20.0, 10.0));
+    /// ```
+    #[inline]
+    pub const fn lerp(self, other: Self, t: f32) -> Self {
+        Self {
+            x: self.x + (other.x - self.x) * t,
+            y: self.y + (other.y - self.y) * t,
+        }
+    }
+
+    /// Clamp each component of the vector to the given range.
+    #[inline]
+    pub const fn clamp(self, min: Self, max: Self) -> Self {
+        Self {
+            x: self.x.clamp(min.x, max.x),
+            y: self.y.clamp(min.y, max.y),
+        }
+    }
+}
+
+const _: () = assert!(Vector2::new(1.0, 2.0).lerp(Vector2::new(3.0, -2.0), 0.0).x == 1.0);
+const _: () = assert!(Vector2::new(1.0, 2.0).lerp(Vector2::new(3.0, -2.0), 1.0).y == -2.0);
+const _: () = assert!(Vector2::new(5.0, -5.0).clamp(Vector2::new(0.0, 0.0), Vector2::new(1.0, 1.0)).x == 1.0);
+const _: () = assert!(Vector2::new(3.0, 4.0).length_squared() == 25.0);
+
+impl Add for Vector2 {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl AddAssign for Vector2 {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Vector2 {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl SubAssign for Vector2 {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
 }
 
-#[derive(Clone, Copy, PartialEq, Default)]
+impl Mul<f32> for Vector2 {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: f32) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Div<f32> for Vector2 {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, rhs: f32) -> Self::Output {
+        Self::new(self.x / rhs, self.y / rhs)
+    }
+}
+
+impl Neg for Vector2 {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub struct Rectangle {
     pub x: f32,
     pub y: f32,
@@ -25,9 +159,112 @@ impl Rectangle {
     pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
         Self { x, y, width, height }
     }
+
+    /// Create a rectangle from the position of its top-left corner and its size.
+    #[inline]
+    pub const fn from_parts(start: Vector2, size: Vector2) -> Self {
+        Self::new(start.x, start.y, size.x, size.y)
+    }
+
+    /// Position of the right edge.
+    #[inline]
+    pub const fn right(&self) -> f32 {
+        self.x + self.width
+    }
+
+    /// Position of the bottom edge.
+    #[inline]
+    pub const fn bottom(&self) -> f32 {
+        self.y + self.height
+    }
+
+    /// Position of the top-left corner.
+    #[inline]
+    pub const fn start(&self) -> Vector2 {
+        Vector2::new(self.x, self.y)
+    }
+
+    /// Width and height of the rectangle.
+    #[inline]
+    pub const fn size(&self) -> Vector2 {
+        Vector2::new(self.width, self.height)
+    }
+
+    /// Center of the rectangle.
+    #[inline]
+    pub const fn center(&self) -> Vector2 {
+        Vector2::new(self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    /// True if the rectangle has no area.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.width <= 0.0 || self.height <= 0.0
+    }
+
+    /// Check if the point is inside the rectangle. Points on the left and top edges are inside, points on the right
+    /// and bottom edges are not, so neighboring rectangles never both contain the same point.
+    #[inline]
+    pub const fn contains(&self, point: Vector2) -> bool {
+        point.x >= self.x && point.x < self.right()
+            && point.y >= self.y && point.y < self.bottom()
+    }
+
+    /// Get the area shared by two rectangles.
+    ///
+    /// # Returns
+    ///
+    /// The shared area, or [`None`] if the rectangles don't overlap. Rectangles only sharing an edge don't overlap.
+    ///
+    /// ```
+    /// # use amity_ui::backend::Rectangle;
+    /// let rect = Rectangle::new(0.0, 0.0, 100.0, 50.0);
+    ///
+    /// // shared edges and corners
+    /// assert_eq!(rect.intersection(&Rectangle::new(100.0, 0.0, 20.0, 50.0)), None);
+    /// assert_eq!(rect.intersection(&Rectangle::new(0.0, 50.0, 100.0, 20.0)), None);
+    /// assert_eq!(rect.intersection(&Rectangle::new(100.0, 50.0, 20.0, 20.0)), None);
+    ///
+    /// // fully contained, either way around
+    /// let inner = Rectangle::new(10.0, 10.0, 20.0, 20.0);
+    /// assert_eq!(rect.intersection(&inner), Some(inner));
+    /// assert_eq!(inner.intersection(&rect), Some(inner));
+    ///
+    /// // partial overlap
+    /// assert_eq!(
+    ///     rect.intersection(&Rectangle::new(90.0, 40.0, 20.0, 20.0)),
+    ///     Some(Rectangle::new(90.0, 40.0, 10.0, 10.0)),
+    /// );
+    /// ```
+    #[inline]
+    pub const fn intersection(&self, other: &Self) -> Option<Self> {
+        let left   = self.x.max(other.x);
+        let top    = self.y.max(other.y);
+        let right  = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+
+        let result = Self::new(left, top, right - left, bottom - top);
+        if result.is_empty() { None } else { Some(result) }
+    }
+
+    /// Get the smallest rectangle containing both rectangles.
+    #[inline]
+    pub const fn union(&self, other: &Self) -> Self {
+        let left   = self.x.min(other.x);
+        let top    = self.y.min(other.y);
+        let right  = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+
+        Self::new(left, top, right - left, bottom - top)
+    }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Default)]
+const _: () = assert!(Rectangle::new(0.0, 0.0, 10.0, 10.0).intersection(&Rectangle::new(10.0, 0.0, 10.0, 10.0)).is_none());
+const _: () = assert!(Rectangle::new(0.0, 0.0, 10.0, 10.0).intersection(&Rectangle::new(2.0, 2.0, 4.0, 4.0)).is_some());
+const _: () = assert!( Rectangle::new(0.0, 0.0, 10.0, 10.0).contains(Vector2::new(0.0, 0.0)));
+const _: () = assert!(!Rectangle::new(0.0, 0.0, 10.0, 10.0).contains(Vector2::new(10.0, 5.0)));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -39,6 +276,254 @@ impl Color {
     pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
         Self { r, g, b, a }
     }
+
+    /// Create an opaque color.
+    #[inline]
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::new(r, g, b, 255)
+    }
+
+    /// Parse a color from hex notation: `#RGB`, `#RRGGBB` or `#RRGGBBAA`. The `#` prefix is optional.
+    ///
+    /// # Returns
+    ///
+    /// The parsed color; colors without an alpha component are opaque.
+    pub fn from_hex(text: &str) -> Result<Self, ParseColorError> {
+        let digits = text.strip_prefix('#').unwrap_or(text);
+        if !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(ParseColorError::InvalidDigit);
+        }
+
+        // All digits are ASCII, so slicing by byte is safe from here on
+        let channel = |index: usize, width: usize| {
+            let value = u8::from_str_radix(&digits[index * width..(index + 1) * width], 16)
+                .expect("digits were checked");
+            if width == 1 { value * 0x11 } else { value }
+        };
+
+        match digits.len() {
+            3 => Ok(Self::rgb(channel(0, 1), channel(1, 1), channel(2, 1))),
+            6 => Ok(Self::rgb(channel(0, 2), channel(1, 2), channel(2, 2))),
+            8 => Ok(Self::new(channel(0, 2), channel(1, 2), channel(2, 2), channel(3, 2))),
+            _ => Err(ParseColorError::InvalidLength),
+        }
+    }
+
+    /// Get the same color with a different alpha.
+    #[inline]
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Self { a, ..self }
+    }
+
+    /// Linearly interpolate between two colors, including alpha.
+    ///
+    /// # Params
+    ///
+    /// - `other`: Color to interpolate towards.
+    /// - `t`: Position between the colors; `0` returns this color, `1` returns `other`.
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round().clamp(0.0, 255.0) as u8;
+
+        Self::new(
+            channel(self.r, other.r),
+            channel(self.g, other.g),
+            channel(self.b, other.b),
+            channel(self.a, other.a),
+        )
+    }
+
+    /// Multiply two colors component-wise, as done when applying a tint. Multiplying by white returns the same color.
+    #[inline]
+    pub const fn multiply(self, other: Self) -> Self {
+        // Rounded to nearest, so that multiplying by white is exact
+        const fn channel(a: u8, b: u8) -> u8 {
+            ((a as u16 * b as u16 + 127) / 255) as u8
+        }
+
+        Self::new(
+            channel(self.r, other.r),
+            channel(self.g, other.g),
+            channel(self.b, other.b),
+            channel(self.a, other.a),
+        )
+    }
+
+    /// Draw this color over another, using straight (non-premultiplied) alpha.
+    ///
+    /// # Params
+    ///
+    /// - `background`: Color underneath.
+    ///
+    /// # Returns
+    ///
+    /// Color visible after compositing.
+    pub fn blend_over(self, background: Self) -> Self {
+        let alpha = self.a as f32 / 255.0;
+        let background_alpha = background.a as f32 / 255.0 * (1.0 - alpha);
+        let result_alpha = alpha + background_alpha;
+
+        if result_alpha == 0.0 { return Self::default(); }
+
+        let channel = |a: u8, b: u8| {
+            ((a as f32 * alpha + b as f32 * background_alpha) / result_alpha).round() as u8
+        };
+
+        Self::new(
+            channel(self.r, background.r),
+            channel(self.g, background.g),
+            channel(self.b, background.b),
+            (result_alpha * 255.0).round() as u8,
+        )
+    }
+}
+
+const _: () = assert!(Color::new(12, 34, 56, 78).multiply(Color::new(255, 255, 255, 255)).a == 78);
+const _: () = assert!(Color::new(12, 34, 56, 255).multiply(Color::new(255, 255, 255, 128)).a == 128);
+
+/// Error returned by [`Color::from_hex`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseColorError {
+    /// The color doesn't have 3, 6 or 8 digits.
+    InvalidLength,
+    /// The text contains a character that isn't a hex digit.
+    InvalidDigit,
+}
+
+impl std::fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidLength => write!(f, "hex color must have 3, 6 or 8 digits"),
+            Self::InvalidDigit => write!(f, "hex color contains an invalid digit"),
+        }
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+/// Repeats held buttons, for backends whose platform doesn't, as with gamepad buttons in Raylib. A held button
+/// repeats for the first time after [`Self::delay`], and then every [`Self::interval`].
+///
+/// ```
+/// # use std::time::Duration;
+/// # use amity_ui::backend::ButtonRepeat;
+/// let mut repeat = ButtonRepeat::default();
+/// let frame = |repeat: &mut ButtonRepeat<u8>, millis, held: &[u8]| {
+///     repeat.update(Duration::from_millis(millis), held.iter().copied());
+///     repeat.is_repeated(&1)
+/// };
+///
+/// // Nothing repeats before the delay
+/// assert!(!frame(&mut repeat, 0, &[1]));
+/// assert!(!frame(&mut repeat, 399, &[1]));
+///
+/// // Then the button repeats once per interval
+/// assert!(frame(&mut repeat, 1, &[1]));
+/// assert!(!frame(&mut repeat, 59, &[1]));
+/// assert!(frame(&mut repeat, 1, &[1]));
+///
+/// // Checking doesn't advance the timer
+/// assert!(repeat.is_repeated(&1));
+/// assert!(repeat.is_repeated(&1));
+///
+/// // Releasing the button starts over
+/// assert!(!frame(&mut repeat, 16, &[]));
+/// assert!(!frame(&mut repeat, 0, &[1]));
+/// assert!(!frame(&mut repeat, 399, &[1]));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ButtonRepeat<K> {
+    /// Time a button has to be held for before it starts repeating.
+    pub delay: Duration,
+
+    /// Time between repeats of a held button.
+    pub interval: Duration,
+
+    /// Time each held button has been held for.
+    hold_times: HashMap<K, Duration>,
+
+    /// Buttons that repeated during the current frame.
+    repeated: HashSet<K>,
+}
+
+impl<K> ButtonRepeat<K> {
+    /// Default value for [`Self::delay`].
+    pub const DELAY: Duration = Duration::from_millis(400);
+
+    /// Default value for [`Self::interval`].
+    pub const INTERVAL: Duration = Duration::from_millis(60);
+}
+
+impl<K: Eq + Hash + Clone> Default for ButtonRepeat<K> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Self::DELAY, Self::INTERVAL)
+    }
+}
+
+impl<K: Eq + Hash + Clone> ButtonRepeat<K> {
+    /// Create a tracker with no buttons held.
+    ///
+    /// # Params
+    ///
+    /// - `delay`:    Value of [`Self::delay`].
+    /// - `interval`: Value of [`Self::interval`].
+    pub fn new(delay: Duration, interval: Duration) -> Self {
+        Self {
+            delay,
+            interval,
+            hold_times: HashMap::new(),
+            repeated: HashSet::new(),
+        }
+    }
+
+    /// Advance hold times by a frame. Must be called once per frame; buttons cannot repeat on the first frame they
+    /// are held.
+    ///
+    /// # Params
+    ///
+    /// - `delta_time`: Time elapsed since the last frame.
+    /// - `held`:       Buttons that are currently held down. Buttons not listed are released.
+    pub fn update(&mut self, delta_time: Duration, held: impl IntoIterator<Item = K>) {
+        let held: HashSet<K> = held.into_iter().collect();
+
+        self.hold_times.retain(|button, _| held.contains(button));
+        self.repeated.clear();
+
+        for button in held {
+            let Some(time) = self.hold_times.get_mut(&button) else {
+                self.hold_times.insert(button, Duration::ZERO);
+                continue;
+            };
+
+            let previous = *time;
+            *time += delta_time;
+            let current = *time;
+            if self.repeat_count(current) > self.repeat_count(previous) {
+                self.repeated.insert(button);
+            }
+        }
+    }
+
+    /// Check if the button repeated during the current frame.
+    #[inline]
+    pub fn is_repeated(&self, button: &K) -> bool {
+        self.repeated.contains(button)
+    }
+
+    /// Check if the button was held during the last update.
+    #[inline]
+    pub fn is_held(&self, button: &K) -> bool {
+        self.hold_times.contains_key(button)
+    }
+
+    /// Number of times a button held for the given time should have repeated.
+    fn repeat_count(&self, held: Duration) -> u128 {
+        let Some(repeating) = held.checked_sub(self.delay) else {
+            return 0;
+        };
+        // max(1) so a zero interval repeats every frame instead of dividing by zero
+        repeating.as_nanos() / self.interval.as_nanos().max(1) + 1
+    }
 }
 
 /// `Backend` is an interface making it possible to bind graphics to a library other than Raylib.
@@ -64,6 +549,11 @@ pub trait Backend {
         Duration::from_millis(500)
     }
 
+    /// Prepare the backend for a new frame. Called by [`crate::tree::LayoutTree::begin_frame`] once per frame,
+    /// before any input is read or anything is drawn. Does nothing by default.
+    #[inline]
+    fn begin_frame(&mut self) {}
+
     /// Check if the given mouse button has just been pressed.
     fn is_mouse_button_pressed(&self, button: Self::MouseButton) -> bool;
     /// Check if the given mouse button has just been released.
@@ -89,6 +579,20 @@ pub trait Backend {
     /// Return [`None`] if no character was pressed.
     fn input_character(&mut self) -> Option<char>;
 
+    /// Get text committed by the user since the last call, such as the result of an input method (IME) composition.
+    /// Unlike [`Self::input_character`], this can return text made of multiple characters, like emoji sequences,
+    /// which should be inserted as a whole.
+    ///
+    /// The default implementation collects every character queued for `input_character`.
+    ///
+    /// # Returns
+    ///
+    /// Committed text, or [`None`] if there is no text to insert.
+    fn input_composition(&mut self) -> Option<String> {
+        let text: String = std::iter::from_fn(|| self.input_character()).collect();
+        (!text.is_empty()).then_some(text)
+    }
+
     /// Check if the given gamepad button has been pressed on one of the connected gamepads.
     ///
     /// # Returns
@@ -126,6 +630,18 @@ pub trait Backend {
     /// Returns: 0 if no controller had a button repeat this frame, or number of the controller.
     fn is_gamepad_button_repeated(&self, gamepad: Self::GamepadID, button: Self::GamepadButton) -> bool;
 
+    /// Get the position of the left analog stick of a gamepad.
+    ///
+    /// The default implementation always returns zero, for backends without analog stick support.
+    ///
+    /// # Returns
+    ///
+    /// Tilt of the stick on both axes, from `-1.0` to `1.0`. Positive values point right and down.
+    #[inline]
+    fn gamepad_left_stick(&self, _gamepad: Self::GamepadID) -> Self::Vector2 {
+        self::Vector2::default().into()
+    }
+
     /// Set mouse position
     fn set_mouse_position(&mut self, value: Self::Vector2);
     /// Get mouse position
@@ -160,21 +676,27 @@ pub trait Backend {
     /// Get horizontal and vertical DPI of the window.
     fn dpi(&self) -> Self::Vector2;
 
-    /// Get the DPI value for the window as a scale relative to 96 DPI.
+    /// Get the DPI value for the window as a scale relative to 96 DPI. This is the number of dots on the screen that
+    /// make up a pixel, and may differ between the axes.
     ///
-    /// Suggested implementation
-    /// ```no_run
-    /// let dpi = self.dpi();
-    /// Vector2::new(dpi.x / 96.0, dpi.y / 96.0)
-    /// ```
-    fn hidpi_scale(&self) -> Self::Vector2;
+    /// Backends convert pixels to dots when drawing, and dots to pixels when reporting sizes and positions, such as
+    /// `window_size` or `mouse_position`.
+    #[inline]
+    fn hidpi_scale(&self) -> Self::Vector2 {
+        let dpi: self::Vector2 = self.dpi().into();
+        self::Vector2::new(dpi.x / 96.0, dpi.y / 96.0).into()
+    }
 
     /// Set area within the window items will be drawn to; any pixel drawn outside will be discarded.
+    ///
+    /// Calls may be nested; the area is then limited to its intersection with the area of the enclosing call. Each
+    /// call must be matched with a call to `restore_area`.
     fn set_area(&mut self, rect: Self::Rectangle);
     /// Area within the window items will be drawn to; any pixel drawn outside will be discarded.
     fn area(&self) -> Self::Rectangle;
 
-    /// Restore the capability to draw anywhere in the window.
+    /// Return to the area of the enclosing `set_area` call or, if there is none, restore the capability to draw
+    /// anywhere in the window.
     fn restore_area(&mut self);
 
     /// Set mouse cursor icon.
@@ -182,20 +704,50 @@ pub trait Backend {
     /// Get mouse cursor icon.
     fn mouse_cursor(&self) -> MouseCursor;
 
+    /// Create a transparent image in CPU memory, for example to draw text into.
+    ///
+    /// # Params
+    ///
+    /// - `width`:  Width of the image, in dots.
+    /// - `height`: Height of the image, in dots.
+    fn new_image(&self, width: i32, height: i32) -> Self::Image;
+
     /// Load a texture from memory.
-    unsafe fn load_texture_from_image(&mut self, image: Self::Image) -> Self::Texture;
+    ///
+    /// # Safety
+    ///
+    /// Must be called from the thread that owns the graphics context, once the context has been created. The
+    /// texture must be released with [`Self::unload_texture`] on the same backend.
+    unsafe fn load_texture_from_image(&mut self, image: &Self::Image) -> Self::Texture;
     /// Load a texture from file.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`Self::load_texture_from_image`]: call it from the thread owning the graphics context, and release
+    /// the texture with [`Self::unload_texture`].
     unsafe fn load_texture(&mut self, filename: &Path) -> Self::Texture;
 
-    /// Update a texture from an image. The texture must be valid and must be of the same size and format as the image.
-    unsafe fn update_texture(&mut self, texture: Self::Texture, image: Self::Image);
+    /// Update a texture from an image.
+    ///
+    /// # Safety
+    ///
+    /// `texture` must have been loaded by this backend and not unloaded yet, and it must have the same size and format
+    /// as `image`. Must be called from the thread that owns the graphics context.
+    unsafe fn update_texture(&mut self, texture: &mut Self::Texture, image: &Self::Image);
 
-    /// Destroy a texture created by this backend. Always use `texture.destroy()` to ensure thread safety.
+    /// Destroy a texture created by this backend.
     ///
     /// If the backend's textures are unloaded on drop, this can be implemented as
-    /// ```no_run
+    /// ```ignore
     /// _ = texture;
     /// ```
+    ///
+    /// # Safety
+    ///
+    /// `texture` must have been loaded by this backend, and must not be used after this call. This includes draws
+    /// that were already issued in the current frame: backends that batch draws, like raylib, only read textures once
+    /// the frame ends, so a texture drawn this frame may only be unloaded once the frame has been presented. Must be
+    /// called from the thread that owns the graphics context.
     unsafe fn unload_texture(&mut self, texture: Self::Texture);
 
     /// Set tint for all newly drawn shapes. The input color for every shape should be multiplied by this color.
@@ -205,28 +757,32 @@ pub trait Backend {
     fn tint(&self) -> Self::Color;
 
     /// Draw a line.
-    fn draw_line(start: Self::Vector2, end: Self::Vector2, color: Self::Color);
+    fn draw_line(&mut self, start: Self::Vector2, end: Self::Vector2, color: Self::Color);
 
     /// Draw a triangle, consisting of 3 vertices with counter-clockwise winding.
-    fn draw_triangle(a: Self::Vector2, b: Self::Vector2, c: Self::Vector2, color: Self::Color);
+    fn draw_triangle(&mut self, a: Self::Vector2, b: Self::Vector2, c: Self::Vector2, color: Self::Color);
 
     /// Draw a circle.
-    fn draw_circle(center: Self::Vector2, radius: f32, color: Self::Color);
+    ///
+    /// The radius is converted to dots using the horizontal scale of [`Self::hidpi_scale`]. If the DPI differs
+    /// between axes, the circle stays round rather than being stretched into an ellipse.
+    fn draw_circle(&mut self, center: Self::Vector2, radius: f32, color: Self::Color);
 
-    /// Draw a circle, but outline only.
-    fn draw_circle_outline(center: Self::Vector2, radius: f32, color: Self::Color);
+    /// Draw a circle, but outline only. The radius is scaled like in [`Self::draw_circle`].
+    fn draw_circle_outline(&mut self, center: Self::Vector2, radius: f32, color: Self::Color);
 
     /// Draw a rectangle.
-    fn draw_rectangle(rectangle: Self::Rectangle, color: Self::Color);
+    fn draw_rectangle(&mut self, rectangle: Self::Rectangle, color: Self::Color);
 
     /// Draw a texture.
-    fn draw_texture(texture: Self::Texture, rectangle: Self::Rectangle, tint: Self::Color);
+    fn draw_texture(&mut self, texture: &Self::Texture, rectangle: Self::Rectangle, tint: Self::Color);
 
     /// Draw a texture, but ensure it aligns with pixel boundaries, recommended for text.
-    fn draw_texture_align(texture: Self::Texture, rectangle: Self::Rectangle, tint: Self::Color);
+    fn draw_texture_align(&mut self, texture: &Self::Texture, rectangle: Self::Rectangle, tint: Self::Color);
 
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SystemCursors {
     // Default system cursor.
     SystemDefault,
@@ -252,23 +808,115 @@ pub enum SystemCursors {
     NotAllowed,
 }
 
+/// Image used as a mouse cursor. The pixels are shared, so cursors are cheap to clone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CursorImage {
+    pub width: i32,
+    pub height: i32,
+    /// Pixels of the image, row by row.
+    pub pixels: Rc<[Color]>,
+    /// Point within the image, in pixels from its top-left corner, that marks the position of the pointer.
+    pub hotspot: Vector2,
+}
+
+impl CursorImage {
+    /// Create a cursor image.
+    ///
+    /// ```
+    /// # use amity_ui::backend::{Color, CursorImage, CursorImageError, Vector2};
+    /// let pixels = [Color::new(0, 0, 0, 255); 4];
+    /// assert!(CursorImage::new(2, 2, pixels, Vector2::default()).is_ok());
+    /// assert_eq!(
+    ///     CursorImage::new(3, 2, pixels, Vector2::default()),
+    ///     Err(CursorImageError::PixelCountMismatch),
+    /// );
+    ///
+    /// // The size is computed without wrapping around, even where it doesn't fit in `usize`
+    /// assert!(CursorImage::new(i32::MAX, i32::MAX, pixels, Vector2::default()).is_err());
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// The image, or an error if the number of pixels doesn't match the size of the image.
+    pub fn new(
+        width: i32,
+        height: i32,
+        pixels: impl Into<Rc<[Color]>>,
+        hotspot: Vector2,
+    ) -> Result<Self, CursorImageError> {
+        let pixels = pixels.into();
+        let size = usize::try_from(width.max(0)).ok()
+            .zip(usize::try_from(height.max(0)).ok())
+            .and_then(|(width, height)| width.checked_mul(height))
+            .ok_or(CursorImageError::SizeOverflow)?;
+        if pixels.len() != size {
+            return Err(CursorImageError::PixelCountMismatch);
+        }
+
+        Ok(Self { width, height, pixels, hotspot })
+    }
+}
+
+/// Error returned by [`CursorImage::new`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorImageError {
+    /// The number of pixels in the image doesn't fit in `usize`.
+    SizeOverflow,
+    /// The number of pixels given doesn't match the size of the image.
+    PixelCountMismatch,
+}
+
+impl std::fmt::Display for CursorImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SizeOverflow => write!(f, "cursor image is too large"),
+            Self::PixelCountMismatch => write!(f, "pixel count doesn't match cursor image size"),
+        }
+    }
+}
+
+impl std::error::Error for CursorImageError {}
+
+/// Mouse cursor requested by a node, either provided by the system, or an image.
+///
+/// ```
+/// # use amity_ui::backend::{Backend, Color, CursorImage, MouseCursor, SystemCursors, Vector2, headless::HeadlessBackend};
+/// let image = CursorImage::new(2, 1, [Color::new(0, 0, 0, 255); 2], Vector2::new(1.0, 0.0))?;
+/// let mut backend = HeadlessBackend::new();
+/// backend.set_mouse_cursor(MouseCursor::image(image.clone()));
+///
+/// let cursor = backend.mouse_cursor();
+/// assert_eq!(cursor.image.as_ref(), Some(&image));
+/// assert_eq!(cursor.image.unwrap().hotspot, Vector2::new(1.0, 0.0));
+/// assert_eq!(cursor.system, SystemCursors::SystemDefault);
+/// # Ok::<(), amity_ui::backend::CursorImageError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
 pub struct MouseCursor {
-    /// Use a system-provided cursor.
+    /// Use a system-provided cursor. If an image is set, this is used as a fallback on platforms that can't display
+    /// image cursors.
     pub system: SystemCursors,
-    // TODO user-provided cursor image
+    /// Use an image as the cursor.
+    pub image: Option<CursorImage>,
 }
 
 #[allow(non_upper_case_globals)]
 impl MouseCursor {
-    pub const SystemDefault : Self = Self { system: SystemCursors::SystemDefault };
-    pub const None          : Self = Self { system: SystemCursors::None };
-    pub const Pointer       : Self = Self { system: SystemCursors::Pointer };
-    pub const Crosshair     : Self = Self { system: SystemCursors::Crosshair };
-    pub const Text          : Self = Self { system: SystemCursors::Text };
-    pub const AllScroll     : Self = Self { system: SystemCursors::AllScroll };
-    pub const ResizeEW      : Self = Self { system: SystemCursors::ResizeEW };
-    pub const ResizeNS      : Self = Self { system: SystemCursors::ResizeNS };
-    pub const ResizeNESW    : Self = Self { system: SystemCursors::ResizeNESW };
-    pub const ResizeNWSE    : Self = Self { system: SystemCursors::ResizeNWSE };
-    pub const NotAllowed    : Self = Self { system: SystemCursors::NotAllowed };
+    pub const SystemDefault : Self = Self { image: None, system: SystemCursors::SystemDefault };
+    pub const None          : Self = Self { image: None, system: SystemCursors::None };
+    pub const Pointer       : Self = Self { image: None, system: SystemCursors::Pointer };
+    pub const Crosshair     : Self = Self { image: None, system: SystemCursors::Crosshair };
+    pub const Text          : Self = Self { image: None, system: SystemCursors::Text };
+    pub const AllScroll     : Self = Self { image: None, system: SystemCursors::AllScroll };
+    pub const ResizeEW      : Self = Self { image: None, system: SystemCursors::ResizeEW };
+    pub const ResizeNS      : Self = Self { image: None, system: SystemCursors::ResizeNS };
+    pub const ResizeNESW    : Self = Self { image: None, system: SystemCursors::ResizeNESW };
+    pub const ResizeNWSE    : Self = Self { image: None, system: SystemCursors::ResizeNWSE };
+    pub const NotAllowed    : Self = Self { image: None, system: SystemCursors::NotAllowed };
+
+    /// Create an image cursor, falling back to [`SystemCursors::SystemDefault`] where image cursors aren't supported.
+    #[inline]
+    pub const fn image(image: CursorImage) -> Self {
+        Self { system: SystemCursors::SystemDefault, image: Some(image) }
+    }
 }
@@ -1,6 +1,7 @@
 use raylib::prelude::*;
-use std::{path::Path, time::Duration};
-use super::{Backend, MouseCursor};
+use std::{ffi::CString, path::Path, time::Duration};
+use crate::{action::{InputEventCode, ToEventCode}, context::IOID, input::{DefaultGamepadBindings, FluidInputAction, FromKeyName}, typeface::GlyphTarget};
+use super::{Backend, ButtonRepeat, MouseCursor, SystemCursors};
 
 impl From<super::Vector2> for Vector2 {
     fn from(value: super::Vector2) -> Self {
@@ -64,15 +65,373 @@ impl From<Color> for super::Color {
     }
 }
 
+/// Get the Raylib cursor closest to the given system cursor.
+///
+/// Returns [`None`] for [`SystemCursors::None`], which Raylib represents by hiding the cursor instead.
+fn raylib_cursor(cursor: SystemCursors) -> Option<raylib::consts::MouseCursor> {
+    use raylib::consts::MouseCursor::*;
+    Some(match cursor {
+        SystemCursors::SystemDefault => MOUSE_CURSOR_DEFAULT,
+        SystemCursors::None          => return None,
+        SystemCursors::Pointer       => MOUSE_CURSOR_POINTING_HAND,
+        SystemCursors::Crosshair     => MOUSE_CURSOR_CROSSHAIR,
+        SystemCursors::Text          => MOUSE_CURSOR_IBEAM,
+        SystemCursors::AllScroll     => MOUSE_CURSOR_RESIZE_ALL,
+        SystemCursors::ResizeEW      => MOUSE_CURSOR_RESIZE_EW,
+        SystemCursors::ResizeNS      => MOUSE_CURSOR_RESIZE_NS,
+        SystemCursors::ResizeNESW    => MOUSE_CURSOR_RESIZE_NESW,
+        SystemCursors::ResizeNWSE    => MOUSE_CURSOR_RESIZE_NWSE,
+        SystemCursors::NotAllowed    => MOUSE_CURSOR_NOT_ALLOWED,
+    })
+}
+
+/// Names of keyboard keys, as used by [`FromKeyName`]. Left modifier keys use the short modifier names.
+const KEY_NAMES: &[(KeyboardKey, &str)] = &[
+    (KeyboardKey::KEY_NULL, "null"),
+    (KeyboardKey::KEY_APOSTROPHE, "apostrophe"),
+    (KeyboardKey::KEY_COMMA, "comma"),
+    (KeyboardKey::KEY_MINUS, "minus"),
+    (KeyboardKey::KEY_PERIOD, "period"),
+    (KeyboardKey::KEY_SLASH, "slash"),
+    (KeyboardKey::KEY_ZERO, "0"),
+    (KeyboardKey::KEY_ONE, "1"),
+    (KeyboardKey::KEY_TWO, "2"),
+    (KeyboardKey::KEY_THREE, "3"),
+    (KeyboardKey::KEY_FOUR, "4"),
+    (KeyboardKey::KEY_FIVE, "5"),
+    (KeyboardKey::KEY_SIX, "6"),
+    (KeyboardKey::KEY_SEVEN, "7"),
+    (KeyboardKey::KEY_EIGHT, "8"),
+    (KeyboardKey::KEY_NINE, "9"),
+    (KeyboardKey::KEY_SEMICOLON, "semicolon"),
+    (KeyboardKey::KEY_EQUAL, "equal"),
+    (KeyboardKey::KEY_A, "a"),
+    (KeyboardKey::KEY_B, "b"),
+    (KeyboardKey::KEY_C, "c"),
+    (KeyboardKey::KEY_D, "d"),
+    (KeyboardKey::KEY_E, "e"),
+    (KeyboardKey::KEY_F, "f"),
+    (KeyboardKey::KEY_G, "g"),
+    (KeyboardKey::KEY_H, "h"),
+    (KeyboardKey::KEY_I, "i"),
+    (KeyboardKey::KEY_J, "j"),
+    (KeyboardKey::KEY_K, "k"),
+    (KeyboardKey::KEY_L, "l"),
+    (KeyboardKey::KEY_M, "m"),
+    (KeyboardKey::KEY_N, "n"),
+    (KeyboardKey::KEY_O, "o"),
+    (KeyboardKey::KEY_P, "p"),
+    (KeyboardKey::KEY_Q, "q"),
+    (KeyboardKey::KEY_R, "r"),
+    (KeyboardKey::KEY_S, "s"),
+    (KeyboardKey::KEY_T, "t"),
+    (KeyboardKey::KEY_U, "u"),
+    (KeyboardKey::KEY_V, "v"),
+    (KeyboardKey::KEY_W, "w"),
+    (KeyboardKey::KEY_X, "x"),
+    (KeyboardKey::KEY_Y, "y"),
+    (KeyboardKey::KEY_Z, "z"),
+    (KeyboardKey::KEY_LEFT_BRACKET, "left_bracket"),
+    (KeyboardKey::KEY_BACKSLASH, "backslash"),
+    (KeyboardKey::KEY_RIGHT_BRACKET, "right_bracket"),
+    (KeyboardKey::KEY_GRAVE, "grave"),
+    (KeyboardKey::KEY_SPACE, "space"),
+    (KeyboardKey::KEY_ESCAPE, "escape"),
+    (KeyboardKey::KEY_ENTER, "enter"),
+    (KeyboardKey::KEY_TAB, "tab"),
+    (KeyboardKey::KEY_BACKSPACE, "backspace"),
+    (KeyboardKey::KEY_INSERT, "insert"),
+    (KeyboardKey::KEY_DELETE, "delete"),
+    (KeyboardKey::KEY_RIGHT, "right"),
+    (KeyboardKey::KEY_LEFT, "left"),
+    (KeyboardKey::KEY_DOWN, "down"),
+    (KeyboardKey::KEY_UP, "up"),
+    (KeyboardKey::KEY_PAGE_UP, "page_up"),
+    (KeyboardKey::KEY_PAGE_DOWN, "page_down"),
+    (KeyboardKey::KEY_HOME, "home"),
+    (KeyboardKey::KEY_END, "end"),
+    (KeyboardKey::KEY_CAPS_LOCK, "caps_lock"),
+    (KeyboardKey::KEY_SCROLL_LOCK, "scroll_lock"),
+    (KeyboardKey::KEY_NUM_LOCK, "num_lock"),
+    (KeyboardKey::KEY_PRINT_SCREEN, "print_screen"),
+    (KeyboardKey::KEY_PAUSE, "pause"),
+    (KeyboardKey::KEY_F1, "f1"),
+    (KeyboardKey::KEY_F2, "f2"),
+    (KeyboardKey::KEY_F3, "f3"),
+    (KeyboardKey::KEY_F4, "f4"),
+    (KeyboardKey::KEY_F5, "f5"),
+    (KeyboardKey::KEY_F6, "f6"),
+    (KeyboardKey::KEY_F7, "f7"),
+    (KeyboardKey::KEY_F8, "f8"),
+    (KeyboardKey::KEY_F9, "f9"),
+    (KeyboardKey::KEY_F10, "f10"),
+    (KeyboardKey::KEY_F11, "f11"),
+    (KeyboardKey::KEY_F12, "f12"),
+    (KeyboardKey::KEY_LEFT_SHIFT, "shift"),
+    (KeyboardKey::KEY_LEFT_CONTROL, "ctrl"),
+    (KeyboardKey::KEY_LEFT_ALT, "alt"),
+    (KeyboardKey::KEY_LEFT_SUPER, "super"),
+    (KeyboardKey::KEY_RIGHT_SHIFT, "right_shift"),
+    (KeyboardKey::KEY_RIGHT_CONTROL, "right_control"),
+    (KeyboardKey::KEY_RIGHT_ALT, "right_alt"),
+    (KeyboardKey::KEY_RIGHT_SUPER, "right_super"),
+    (KeyboardKey::KEY_KB_MENU, "kb_menu"),
+    (KeyboardKey::KEY_KP_0, "kp_0"),
+    (KeyboardKey::KEY_KP_1, "kp_1"),
+    (KeyboardKey::KEY_KP_2, "kp_2"),
+    (KeyboardKey::KEY_KP_3, "kp_3"),
+    (KeyboardKey::KEY_KP_4, "kp_4"),
+    (KeyboardKey::KEY_KP_5, "kp_5"),
+    (KeyboardKey::KEY_KP_6, "kp_6"),
+    (KeyboardKey::KEY_KP_7, "kp_7"),
+    (KeyboardKey::KEY_KP_8, "kp_8"),
+    (KeyboardKey::KEY_KP_9, "kp_9"),
+    (KeyboardKey::KEY_KP_DECIMAL, "kp_decimal"),
+    (KeyboardKey::KEY_KP_DIVIDE, "kp_divide"),
+    (KeyboardKey::KEY_KP_MULTIPLY, "kp_multiply"),
+    (KeyboardKey::KEY_KP_SUBTRACT, "kp_subtract"),
+    (KeyboardKey::KEY_KP_ADD, "kp_add"),
+    (KeyboardKey::KEY_KP_ENTER, "kp_enter"),
+    (KeyboardKey::KEY_KP_EQUAL, "kp_equal"),
+    (KeyboardKey::KEY_BACK, "back"),
+    (KeyboardKey::KEY_MENU, "menu"),
+    (KeyboardKey::KEY_VOLUME_UP, "volume_up"),
+    (KeyboardKey::KEY_VOLUME_DOWN, "volume_down"),
+];
+
+/// Names of mouse buttons, as used by [`FromKeyName`].
+const MOUSE_BUTTON_NAMES: &[(MouseButton, &str)] = &[
+    (MouseButton::MOUSE_BUTTON_LEFT,    "mouse_left"),
+    (MouseButton::MOUSE_BUTTON_RIGHT,   "mouse_right"),
+    (MouseButton::MOUSE_BUTTON_MIDDLE,  "mouse_middle"),
+    (MouseButton::MOUSE_BUTTON_SIDE,    "mouse_side"),
+    (MouseButton::MOUSE_BUTTON_EXTRA,   "mouse_extra"),
+    (MouseButton::MOUSE_BUTTON_FORWARD, "mouse_forward"),
+    (MouseButton::MOUSE_BUTTON_BACK,    "mouse_back"),
+];
+
+impl FromKeyName for KeyboardKey {
+    fn from_key_name(name: &str) -> Option<Self> {
+        let name = match name {
+            "left_shift" => "shift",
+            "left_control" | "control" => "ctrl",
+            "left_alt" => "alt",
+            "left_super" => "super",
+            "esc" => "escape",
+            name => name,
+        };
+
+        KEY_NAMES.iter()
+            .find(|(_, key_name)| *key_name == name)
+            .map(|(key, _)| *key)
+    }
+
+    fn key_name(&self) -> &'static str {
+        KEY_NAMES.iter()
+            .find(|(key, _)| key == self)
+            .map_or("null", |(_, name)| name)
+    }
+}
+
+impl FromKeyName for MouseButton {
+    fn from_key_name(name: &str) -> Option<Self> {
+        MOUSE_BUTTON_NAMES.iter()
+            .find(|(_, button_name)| *button_name == name)
+            .map(|(button, _)| *button)
+    }
+
+    fn key_name(&self) -> &'static str {
+        MOUSE_BUTTON_NAMES.iter()
+            .find(|(button, _)| button == self)
+            .map_or("mouse_left", |(_, name)| name)
+    }
+}
+
+impl DefaultGamepadBindings for GamepadButton {
+    fn default_gamepad_bindings() -> Vec<(Self, FluidInputAction)> {
+        vec![
+            (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP,     FluidInputAction::FocusUp),
+            (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN,   FluidInputAction::FocusDown),
+            (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT,   FluidInputAction::FocusLeft),
+            (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT,  FluidInputAction::FocusRight),
+            (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN,  FluidInputAction::Press),
+            (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT, FluidInputAction::Cancel),
+            (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP,    FluidInputAction::ContextMenu),
+        ]
+    }
+}
+
+impl ToEventCode for KeyboardKey {
+    #[inline]
+    fn event_code(&self) -> InputEventCode {
+        InputEventCode { io_id: IOID::of::<KeyboardKey>(), event: *self as i32 }
+    }
+}
+
+impl ToEventCode for MouseButton {
+    #[inline]
+    fn event_code(&self) -> InputEventCode {
+        InputEventCode { io_id: IOID::of::<MouseButton>(), event: *self as i32 }
+    }
+}
+
+impl ToEventCode for GamepadButton {
+    #[inline]
+    fn event_code(&self) -> InputEventCode {
+        InputEventCode { io_id: IOID::of::<GamepadButton>(), event: *self as i32 }
+    }
+}
+
+impl GlyphTarget for Image {
+    #[inline]
+    fn width(&self) -> i32 {
+        Image::width(self)
+    }
+
+    #[inline]
+    fn height(&self) -> i32 {
+        Image::height(self)
+    }
+
+    /// Raylib images have no palette; glyphs are drawn in white, with coverage as alpha, so they can be tinted
+    /// when drawn.
+    fn blend_glyph_pixel(&mut self, x: i32, y: i32, coverage: u8, _palette_index: u8) {
+        let alpha = self.get_color(x, y).a.max(coverage);
+
+        self.draw_pixel(x, y, Color::new(0xff, 0xff, 0xff, alpha));
+    }
+}
+
+/// [`Backend`] implementation for Raylib.
+///
+/// Drawing functions issue Raylib draw calls directly, so they must only be used while a frame is being drawn,
+/// i.e. between `begin_drawing` and the end of its draw handle.
 pub struct RaylibBackend<'a> {
     pub rl: &'a mut RaylibHandle,
     pub thread: &'a RaylibThread,
+
+    /// Raylib doesn't repeat gamepad buttons, so held buttons are repeated by the backend. Updated by
+    /// [`Backend::begin_frame`].
+    pub gamepad_repeat: ButtonRepeat<(i32, GamepadButton)>,
+
     last_mouse_cursor: MouseCursor,
     draw_area: Rectangle,
+    /// Areas set by enclosing `set_area` calls, restored in reverse order by `restore_area`.
+    area_stack: Vec<Rectangle>,
     tint: Color,
     scale: f32,
 }
 
+impl<'a> RaylibBackend<'a> {
+    /// Number of gamepads Raylib supports.
+    const MAX_GAMEPADS: i32 = 4;
+
+    /// Gamepad buttons tracked for repeat.
+    const GAMEPAD_BUTTONS: [GamepadButton; 17] = {
+        use GamepadButton::*;
+        [
+            GAMEPAD_BUTTON_LEFT_FACE_UP,
+            GAMEPAD_BUTTON_LEFT_FACE_RIGHT,
+            GAMEPAD_BUTTON_LEFT_FACE_DOWN,
+            GAMEPAD_BUTTON_LEFT_FACE_LEFT,
+            GAMEPAD_BUTTON_RIGHT_FACE_UP,
+            GAMEPAD_BUTTON_RIGHT_FACE_RIGHT,
+            GAMEPAD_BUTTON_RIGHT_FACE_DOWN,
+            GAMEPAD_BUTTON_RIGHT_FACE_LEFT,
+            GAMEPAD_BUTTON_LEFT_TRIGGER_1,
+            GAMEPAD_BUTTON_LEFT_TRIGGER_2,
+            GAMEPAD_BUTTON_RIGHT_TRIGGER_1,
+            GAMEPAD_BUTTON_RIGHT_TRIGGER_2,
+            GAMEPAD_BUTTON_MIDDLE_LEFT,
+            GAMEPAD_BUTTON_MIDDLE,
+            GAMEPAD_BUTTON_MIDDLE_RIGHT,
+            GAMEPAD_BUTTON_LEFT_THUMB,
+            GAMEPAD_BUTTON_RIGHT_THUMB,
+        ]
+    };
+
+    /// Create a backend for the given Raylib window, with no scaling or tint.
+    pub fn new(rl: &'a mut RaylibHandle, thread: &'a RaylibThread) -> Self {
+        let draw_area = Rectangle::new(0.0, 0.0, rl.get_render_width() as f32, rl.get_render_height() as f32);
+        Self {
+            rl,
+            thread,
+            gamepad_repeat: ButtonRepeat::default(),
+            last_mouse_cursor: MouseCursor::SystemDefault,
+            draw_area,
+            area_stack: Vec::new(),
+            tint: Color::WHITE,
+            scale: 1.0,
+        }
+    }
+
+    /// Set the starting scale.
+    #[inline]
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Set the starting tint.
+    #[inline]
+    pub fn with_tint(mut self, tint: Color) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// Multiply the color by the current tint, component-wise.
+    #[inline]
+    fn tinted(&self, color: Color) -> Color {
+        super::Color::from(color).multiply(self.tint.into()).into()
+    }
+
+    /// Convert a position or size in pixels to dots on the screen.
+    fn to_dots(&self, value: Vector2) -> Vector2 {
+        let scale = self.hidpi_scale();
+        Vector2::new(value.x * scale.x, value.y * scale.y)
+    }
+
+    /// Convert a rectangle in pixels to dots on the screen.
+    fn rectangle_to_dots(&self, value: Rectangle) -> Rectangle {
+        let scale = self.hidpi_scale();
+        Rectangle::new(value.x * scale.x, value.y * scale.y, value.width * scale.x, value.height * scale.y)
+    }
+
+    /// Convert a position or size in dots on the screen to pixels.
+    fn to_pixels(&self, value: Vector2) -> Vector2 {
+        let scale = self.hidpi_scale();
+        Vector2::new(value.x / scale.x, value.y / scale.y)
+    }
+
+    /// Draw a texture to a rectangle given in dots.
+    fn draw_texture_dots(&self, texture: &Texture2D, rectangle: Rectangle, tint: Color) {
+        let source = Rectangle::new(0.0, 0.0, texture.width as f32, texture.height as f32);
+        unsafe {
+            raylib::ffi::DrawTexturePro(
+                **texture,
+                source.into(),
+                rectangle.into(),
+                Vector2::new(0.0, 0.0).into(),
+                0.0,
+                self.tinted(tint).into(),
+            );
+        }
+    }
+
+    /// Start scissor mode, limiting drawing to `draw_area`. Scissor mode works on whole dots, so partially
+    /// covered dots are included.
+    fn begin_scissor(&self) {
+        let area = self.rectangle_to_dots(self.draw_area);
+        let left   = area.x.floor();
+        let top    = area.y.floor();
+        let right  = (area.x + area.width).ceil();
+        let bottom = (area.y + area.height).ceil();
+        unsafe {
+            raylib::ffi::BeginScissorMode(left as i32, top as i32, (right - left) as i32, (bottom - top) as i32);
+        }
+    }
+}
+
 impl<'a> Backend for RaylibBackend<'a> {
     type MouseButton = MouseButton;
     type KeyboardKey = KeyboardKey;
@@ -84,6 +443,17 @@ impl<'a> Backend for RaylibBackend<'a> {
     type Image = Image;
     type Color = Color;
 
+    /// Update input state Raylib doesn't track on its own: held gamepad buttons are timed for repeat.
+    fn begin_frame(&mut self) {
+        let rl = &*self.rl;
+        let held = (0..Self::MAX_GAMEPADS)
+            .filter(|&gamepad| rl.is_gamepad_available(gamepad))
+            .flat_map(|gamepad| Self::GAMEPAD_BUTTONS.map(|button| (gamepad, button)))
+            .filter(|&(gamepad, button)| rl.is_gamepad_button_down(gamepad, button));
+
+        self.gamepad_repeat.update(Duration::from_secs_f32(rl.get_frame_time()), held);
+    }
+
     #[inline]
     fn is_mouse_button_pressed(&self, button: Self::MouseButton) -> bool {
         self.rl.is_mouse_button_pressed(button)
@@ -134,6 +504,20 @@ impl<'a> Backend for RaylibBackend<'a> {
         self.rl.get_char_pressed()
     }
 
+    /// Raylib queues committed text, including IME input, one character at a time; the whole queue is returned.
+    fn input_composition(&mut self) -> Option<String> {
+        let text: String = std::iter::from_fn(|| self.rl.get_char_pressed()).collect();
+        (!text.is_empty()).then_some(text)
+    }
+
+    #[inline]
+    fn gamepad_left_stick(&self, gamepad: Self::GamepadID) -> Self::Vector2 {
+        Vector2::new(
+            self.rl.get_gamepad_axis_movement(gamepad, GamepadAxis::GAMEPAD_AXIS_LEFT_X),
+            self.rl.get_gamepad_axis_movement(gamepad, GamepadAxis::GAMEPAD_AXIS_LEFT_Y),
+        )
+    }
+
     #[inline]
     fn is_gamepad_button_pressed(&self, gamepad: Self::GamepadID, button: Self::GamepadButton) -> bool {
         self.rl.is_gamepad_button_pressed(gamepad, button)
@@ -154,19 +538,21 @@ impl<'a> Backend for RaylibBackend<'a> {
         self.rl.is_gamepad_button_up(gamepad, button)
     }
 
+    /// Repeats are detected by [`Backend::begin_frame`], which the tree calls every frame.
     #[inline]
-    fn is_gamepad_button_repeated(&self, _gamepad: Self::GamepadID, _button: Self::GamepadButton) -> bool {
-        unimplemented!()
+    fn is_gamepad_button_repeated(&self, gamepad: Self::GamepadID, button: Self::GamepadButton) -> bool {
+        self.gamepad_repeat.is_repeated(&(gamepad, button))
     }
 
     #[inline]
     fn set_mouse_position(&mut self, value: Self::Vector2) {
+        let value = self.to_dots(value);
         self.rl.set_mouse_position(value);
     }
 
     #[inline]
     fn mouse_position(&self) -> Self::Vector2 {
-        self.rl.get_mouse_position()
+        self.to_pixels(self.rl.get_mouse_position())
     }
 
     #[inline]
@@ -201,7 +587,7 @@ impl<'a> Backend for RaylibBackend<'a> {
 
     #[inline]
     fn window_size(&self) -> Self::Vector2 {
-        Self::Vector2::new(self.rl.get_render_width() as f32, self.rl.get_render_height() as f32)
+        self.to_pixels(Vector2::new(self.rl.get_render_width() as f32, self.rl.get_render_height() as f32))
     }
 
     #[inline]
@@ -214,105 +600,166 @@ impl<'a> Backend for RaylibBackend<'a> {
         self.scale = value;
     }
 
+    /// Raylib reports DPI as a scale relative to 96 DPI. The result is multiplied by `scale`.
     #[inline]
     fn dpi(&self) -> Self::Vector2 {
-        self.rl.get_window_scale_dpi()
+        let scale = self.rl.get_window_scale_dpi();
+        Vector2::new(scale.x * 96.0 * self.scale, scale.y * 96.0 * self.scale)
     }
 
-    #[inline]
-    fn hidpi_scale(&self) -> Self::Vector2 {
-        const FRAC_1_96: f32 = 1.0 / 96.0;
-        let dpi = self.dpi();
-        Self::Vector2::new(dpi.x * FRAC_1_96, dpi.y * FRAC_1_96)
-    }
-
-    #[inline]
     fn set_area(&mut self, rect: Self::Rectangle) {
-        todo!("????")
+        let window = self.window_size();
+
+        // Crop the area to the enclosing area, or the window if this isn't nested
+        let bounds: super::Rectangle = if self.area_stack.is_empty() {
+            super::Rectangle::new(0.0, 0.0, window.x, window.y)
+        } else {
+            self.draw_area.into()
+        };
+        let area = super::Rectangle::from(rect).intersection(&bounds).unwrap_or_default();
+
+        self.area_stack.push(self.draw_area);
+        self.draw_area = area.into();
+        self.begin_scissor();
     }
 
     #[inline]
     fn area(&self) -> Self::Rectangle {
-        todo!("????")
+        self.draw_area
     }
 
-    #[inline]
     fn restore_area(&mut self) {
-        todo!()
+        // Return to the area of the enclosing `set_area` call, if there is one
+        if let Some(previous) = self.area_stack.pop() && !self.area_stack.is_empty() {
+            self.draw_area = previous;
+            self.begin_scissor();
+        } else {
+            self.area_stack.clear();
+            let window = self.window_size();
+            self.draw_area = Rectangle::new(0.0, 0.0, window.x, window.y);
+            unsafe { raylib::ffi::EndScissorMode(); }
+        }
     }
 
     #[inline]
     fn set_mouse_cursor(&mut self, value: MouseCursor) {
-        todo!()
+        // This is called every frame, avoid calling into Raylib if nothing changed
+        if value == self.last_mouse_cursor { return; }
+
+        // Raylib can't create cursors from images, so image cursors show their system fallback instead
+        match raylib_cursor(value.system) {
+            Some(cursor) => {
+                if self.last_mouse_cursor.system == SystemCursors::None {
+                    self.rl.show_cursor();
+                }
+                self.rl.set_mouse_cursor(cursor);
+            }
+            None => self.rl.hide_cursor(),
+        }
+
+        self.last_mouse_cursor = value;
     }
 
     #[inline]
     fn mouse_cursor(&self) -> MouseCursor {
-        todo!()
+        self.last_mouse_cursor.clone()
+    }
+
+    #[inline]
+    fn new_image(&self, width: i32, height: i32) -> Self::Image {
+        Image::gen_image_color(width, height, Color::BLANK)
     }
 
     #[inline]
-    unsafe fn load_texture_from_image(&mut self, image: Self::Image) -> Self::Texture {
-        todo!()
+    unsafe fn load_texture_from_image(&mut self, image: &Self::Image) -> Self::Texture {
+        // Like Raylib, represent a failed load with an invalid texture, which draws nothing
+        unsafe { Texture2D::from_raw(raylib::ffi::LoadTextureFromImage(**image)) }
     }
 
     #[inline]
     unsafe fn load_texture(&mut self, filename: &Path) -> Self::Texture {
-        todo!()
+        // A path with a null byte can't be loaded; the empty path fails the same way a missing file does
+        let filename = CString::new(filename.to_string_lossy().into_owned()).unwrap_or_default();
+        unsafe { Texture2D::from_raw(raylib::ffi::LoadTexture(filename.as_ptr())) }
     }
 
     #[inline]
-    unsafe fn update_texture(&mut self, texture: Self::Texture, image: Self::Image) {
-        todo!()
+    unsafe fn update_texture(&mut self, texture: &mut Self::Texture, image: &Self::Image) {
+        let is_matching = texture.width == image.width()
+            && texture.height == image.height()
+            && texture.format == image.format() as i32;
+
+        debug_assert!(is_matching, "update_texture: image size or format doesn't match the texture");
+        if !is_matching { return; }
+
+        unsafe { raylib::ffi::UpdateTexture(**texture, image.data()); }
     }
 
     #[inline]
     unsafe fn unload_texture(&mut self, texture: Self::Texture) {
-        todo!()
+        // Raylib textures are unloaded on drop
+        _ = texture;
     }
 
     #[inline]
     fn set_tint(&mut self, value: Self::Color) {
-        todo!()
+        self.tint = value;
     }
 
     #[inline]
     fn tint(&self) -> Self::Color {
-        todo!()
+        self.tint
     }
 
     #[inline]
-    fn draw_line(start: Self::Vector2, end: Self::Vector2, color: Self::Color) {
-        todo!()
+    fn draw_line(&mut self, start: Self::Vector2, end: Self::Vector2, color: Self::Color) {
+        let (start, end) = (self.to_dots(start), self.to_dots(end));
+        unsafe { raylib::ffi::DrawLineV(start.into(), end.into(), self.tinted(color).into()); }
     }
 
     #[inline]
-    fn draw_triangle(a: Self::Vector2, b: Self::Vector2, c: Self::Vector2, color: Self::Color) {
-        todo!()
+    fn draw_triangle(&mut self, a: Self::Vector2, mut b: Self::Vector2, mut c: Self::Vector2, color: Self::Color) {
+        // Raylib culls triangles that aren't wound counter-clockwise on screen. With y pointing down,
+        // counter-clockwise means a negative cross product; swap two vertices if the winding is reversed.
+        let cross = (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+        if cross > 0.0 {
+            std::mem::swap(&mut b, &mut c);
+        }
+        let (a, b, c) = (self.to_dots(a), self.to_dots(b), self.to_dots(c));
+        unsafe { raylib::ffi::DrawTriangle(a.into(), b.into(), c.into(), self.tinted(color).into()); }
     }
 
     #[inline]
-    fn draw_circle(center: Self::Vector2, radius: f32, color: Self::Color) {
-        todo!()
+    fn draw_circle(&mut self, center: Self::Vector2, radius: f32, color: Self::Color) {
+        let (center, radius) = (self.to_dots(center), radius * self.hidpi_scale().x);
+        unsafe { raylib::ffi::DrawCircleV(center.into(), radius, self.tinted(color).into()); }
     }
 
     #[inline]
-    fn draw_circle_outline(center: Self::Vector2, radius: f32, color: Self::Color) {
-        todo!()
+    fn draw_circle_outline(&mut self, center: Self::Vector2, radius: f32, color: Self::Color) {
+        let (center, radius) = (self.to_dots(center), radius * self.hidpi_scale().x);
+        unsafe { raylib::ffi::DrawCircleLinesV(center.into(), radius, self.tinted(color).into()); }
     }
 
     #[inline]
-    fn draw_rectangle(rectangle: Self::Rectangle, color: Self::Color) {
-        todo!()
+    fn draw_rectangle(&mut self, rectangle: Self::Rectangle, color: Self::Color) {
+        let rectangle = self.rectangle_to_dots(rectangle);
+        unsafe { raylib::ffi::DrawRectangleRec(rectangle.into(), self.tinted(color).into()); }
     }
 
     #[inline]
-    fn draw_texture(texture: Self::Texture, rectangle: Self::Rectangle, tint: Self::Color) {
-        todo!()
+    fn draw_texture(&mut self, texture: &Self::Texture, rectangle: Self::Rectangle, tint: Self::Color) {
+        self.draw_texture_dots(texture, self.rectangle_to_dots(rectangle), tint);
     }
 
     #[inline]
-    fn draw_texture_align(texture: Self::Texture, rectangle: Self::Rectangle, tint: Self::Color) {
-        todo!()
+    fn draw_texture_align(&mut self, texture: &Self::Texture, rectangle: Self::Rectangle, tint: Self::Color) {
+        // Snap each edge rather than the size, so adjacent textures still meet without a gap
+        let rectangle = self.rectangle_to_dots(rectangle);
+        let left   = rectangle.x.round();
+        let top    = rectangle.y.round();
+        let right  = (rectangle.x + rectangle.width).round();
+        let bottom = (rectangle.y + rectangle.height).round();
+        self.draw_texture_dots(texture, Rectangle::new(left, top, right - left, bottom - top), tint);
     }
 }
@@ -1,4 +1,4 @@
-use crate::{backend::{Backend, Rectangle}, style::{Side, SideArray}};
+use crate::{backend::{Backend, Color, Rectangle}, style::{Side, SideArray}};
 
 /// Interface for borders
 pub trait Border<B: Backend> {
@@ -43,3 +43,90 @@ pub trait Border<B: Backend> {
         }
     }
 }
+
+/// Border that draws nothing.
+#[derive(Clone, Copy, Default)]
+pub struct NoBorder;
+
+impl<B: Backend> Border<B> for NoBorder {
+    #[inline]
+    fn apply(&self, _backend: &mut B, _border_box: Rectangle, _size: SideArray<f32>) {}
+}
+
+/// Border filled with a single color.
+///
+/// Sides are drawn between the corners, and each corner is drawn separately, so no part of the border is drawn twice.
+///
+/// ```
+/// # use amity_ui::backend::{Color, Rectangle, headless::{DrawCommand, HeadlessBackend}};
+/// # use amity_ui::{border::{Border, SolidBorder}, style::SideArray};
+/// let mut backend = HeadlessBackend::new();
+/// let border = SolidBorder::new(Color::rgb(255, 0, 0));
+/// let border_box = Rectangle::new(10.0, 20.0, 100.0, 50.0);
+///
+/// // left, right, top, bottom
+/// border.apply(&mut backend, border_box, SideArray([1.0, 2.0, 3.0, 4.0]));
+///
+/// let rectangles = backend.draw_commands.iter()
+///     .map(|command| match command {
+///         DrawCommand::Rectangle { rectangle, .. } => *rectangle,
+///         _ => panic!("unexpected {command:?}"),
+///     })
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(rectangles, [
+///     // sides
+///     Rectangle::new( 10.0, 23.0,  1.0, 43.0),
+///     Rectangle::new(108.0, 23.0,  2.0, 43.0),
+///     Rectangle::new( 11.0, 20.0, 97.0,  3.0),
+///     Rectangle::new( 11.0, 66.0, 97.0,  4.0),
+///     // corners
+///     Rectangle::new( 10.0, 20.0,  1.0,  3.0),
+///     Rectangle::new(108.0, 20.0,  2.0,  3.0),
+///     Rectangle::new( 10.0, 66.0,  1.0,  4.0),
+///     Rectangle::new(108.0, 66.0,  2.0,  4.0),
+/// ]);
+///
+/// // sides of zero size are skipped along with their corners
+/// backend.draw_commands.clear();
+/// border.apply(&mut backend, border_box, SideArray([0.0, 2.0, 3.0, 4.0]));
+/// assert_eq!(backend.draw_commands.len(), 5);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolidBorder {
+    /// Color of every side of the border.
+    pub color: Color,
+}
+
+impl SolidBorder {
+    /// Create a border of the given color.
+    #[inline]
+    pub const fn new(color: Color) -> Self {
+        Self { color }
+    }
+}
+
+impl<B: Backend> Border<B> for SolidBorder {
+    fn apply(&self, backend: &mut B, border_box: Rectangle, size: SideArray<f32>) {
+        use Side::*;
+
+        let right = border_box.right() - size[Right];
+        let bottom = border_box.bottom() - size[Bottom];
+
+        // Sides don't include corners, so draw them separately to avoid overlap
+        let corners = [
+            Rectangle::new(border_box.x, border_box.y, size[Left],  size[Top]),
+            Rectangle::new(right,        border_box.y, size[Right], size[Top]),
+            Rectangle::new(border_box.x, bottom,       size[Left],  size[Bottom]),
+            Rectangle::new(right,        bottom,       size[Right], size[Bottom]),
+        ];
+        let sides = [Left, Right, Top, Bottom]
+            .map(|side| Border::<B>::side_rect(self, border_box, size, side));
+
+        for rectangle in sides.into_iter().chain(corners) {
+            if rectangle.is_empty() { continue; }
+
+            backend.draw_rectangle(rectangle.into(), self.color.into());
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{backend::Backend, context::IO};
+use crate::{backend::{Backend, Color, Rectangle, Vector2}, context::{HasContext, IO, TreeContext}, resource::{Resource, ResourceHandle, ResourceSpace}};
 
 /// I/O interface for canvas drawing functionality.
 ///
@@ -8,6 +8,199 @@ use crate::{backend::Backend, context::IO};
 ///
 /// The canvas should allow all inputs and never throw. If there's a defined boundary, the canvas should crop all
 /// geometry to fit.
+///
+/// Textures are drawn by the handle of a resource loaded into the canvas with [`IO::load_to`].
 pub trait CanvasIO<B: Backend>: IO<B> {
-    // todo
+    /// Boundary of the canvas, in canvas coordinates. Geometry outside of it is cropped.
+    fn bounds(&self) -> Rectangle;
+
+    /// Draw a filled rectangle.
+    fn draw_rectangle(&mut self, backend: &mut B, rectangle: Rectangle, color: Color);
+
+    /// Draw a line between two points.
+    fn draw_line(&mut self, backend: &mut B, start: Vector2, end: Vector2, color: Color);
+
+    /// Draw a filled circle.
+    fn draw_circle(&mut self, backend: &mut B, center: Vector2, radius: f32, color: Color);
+
+    /// Draw a texture stretched to fill the rectangle.
+    ///
+    /// # Params
+    ///
+    /// - `texture`:   Handle of a texture resource loaded with [`IO::load_to`]. Nothing is drawn if the handle is
+    ///   unknown to the canvas.
+    /// - `rectangle`: Box to fill with the texture.
+    /// - `tint`:      Color to multiply the texture by.
+    fn draw_texture(&mut self, backend: &mut B, texture: ResourceHandle, rectangle: Rectangle, tint: Color);
+}
+
+/// Reference [`CanvasIO`] implementation, drawing directly to the backend.
+///
+/// Canvas coordinates are pixels, so they are passed to the backend as-is, and the backend converts them to dots
+/// according to its [DPI](Backend::hidpi_scale). Rectangles and lines are cropped to the canvas bounds; other shapes
+/// are skipped if they lie completely outside, and otherwise drawn within a [scissor area](Backend::set_area).
+///
+/// ```
+/// # use amity_ui::{backend::{Color, Rectangle, Vector2, headless::{DrawCommand, HeadlessBackend}},
+/// #     canvas::{CanvasIO, CanvasSpace}};
+/// let mut backend = HeadlessBackend::new();
+/// let mut canvas = CanvasSpace::new(Rectangle::new(0.0, 0.0, 100.0, 100.0));
+/// let red = Color::new(255, 0, 0, 255);
+///
+/// // Partially visible geometry is cropped
+/// canvas.draw_rectangle(&mut backend, Rectangle::new(50.0, 50.0, 100.0, 100.0), red);
+/// canvas.draw_line(&mut backend, Vector2::new(-50.0, 50.0), Vector2::new(150.0, 50.0), red);
+///
+/// // Geometry outside of the canvas isn't drawn at all
+/// canvas.draw_rectangle(&mut backend, Rectangle::new(200.0, 0.0, 10.0, 10.0), red);
+/// canvas.draw_line(&mut backend, Vector2::new(0.0, -10.0), Vector2::new(100.0, -20.0), red);
+/// canvas.draw_circle(&mut backend, Vector2::new(-20.0, -20.0), 10.0, red);
+///
+/// assert_eq!(backend.draw_commands, [
+///     DrawCommand::Rectangle { rectangle: Rectangle::new(50.0, 50.0, 50.0, 50.0), color: red },
+///     DrawCommand::Line { start: Vector2::new(0.0, 50.0), end: Vector2::new(100.0, 50.0), color: red },
+/// ]);
+///
+/// // At 2x DPI, geometry is only scaled once, by the backend
+/// backend.next_frame();
+/// backend.set_dpi(Vector2::new(192.0, 192.0));
+/// canvas.draw_rectangle(&mut backend, Rectangle::new(50.0, 50.0, 100.0, 100.0), red);
+/// canvas.draw_line(&mut backend, Vector2::new(-50.0, 50.0), Vector2::new(150.0, 50.0), red);
+/// canvas.draw_circle(&mut backend, Vector2::new(20.0, 30.0), 10.0, red);
+///
+/// assert_eq!(backend.draw_commands, [
+///     DrawCommand::Rectangle { rectangle: Rectangle::new(100.0, 100.0, 100.0, 100.0), color: red },
+///     DrawCommand::Line { start: Vector2::new(0.0, 100.0), end: Vector2::new(200.0, 100.0), color: red },
+///     DrawCommand::Circle { center: Vector2::new(40.0, 60.0), radius: 20.0, color: red },
+/// ]);
+/// ```
+pub struct CanvasSpace<B: Backend> {
+    /// Boundary of the canvas, in canvas coordinates.
+    pub bounds: Rectangle,
+
+    /// Textures loaded into the canvas.
+    resources: ResourceSpace<B>,
+}
+
+impl<B: Backend> CanvasSpace<B> {
+    /// Create a canvas with the given boundary.
+    pub fn new(bounds: Rectangle) -> Self {
+        Self {
+            bounds,
+            resources: ResourceSpace::new(),
+        }
+    }
+
+    /// Resources loaded into the canvas.
+    #[inline]
+    pub fn resources(&self) -> &ResourceSpace<B> {
+        &self.resources
+    }
+
+    /// Draw within the canvas bounds using the backend's scissor area, unless the shape is fully inside.
+    ///
+    /// # Params
+    ///
+    /// - `shape`: Bounding box of the drawn shape, in canvas coordinates.
+    /// - `draw`:  Function drawing the shape.
+    fn draw_cropped(&self, backend: &mut B, shape: Rectangle, draw: impl FnOnce(&mut B)) {
+        let Some(visible) = shape.intersection(&self.bounds) else { return };
+
+        if visible == shape {
+            draw(backend);
+            return;
+        }
+
+        backend.set_area(self.bounds.into());
+        draw(backend);
+        backend.restore_area();
+    }
+}
+
+/// Clip a line to fit in a rectangle, using the Liang–Barsky algorithm.
+///
+/// # Returns
+///
+/// The visible part of the line, or `None` if the line is outside of the rectangle.
+fn clip_line(start: Vector2, end: Vector2, bounds: Rectangle) -> Option<(Vector2, Vector2)> {
+    let delta = end - start;
+    let mut t0: f32 = 0.0;
+    let mut t1: f32 = 1.0;
+
+    let edges = [
+        (-delta.x, start.x - bounds.x),
+        ( delta.x, bounds.right() - start.x),
+        (-delta.y, start.y - bounds.y),
+        ( delta.y, bounds.bottom() - start.y),
+    ];
+
+    for (p, q) in edges {
+        if p == 0.0 {
+            // Parallel to the edge; reject if outside
+            if q < 0.0 { return None; }
+            continue;
+        }
+
+        let t = q / p;
+        if p < 0.0 {
+            t0 = t0.max(t);
+        } else {
+            t1 = t1.min(t);
+        }
+        if t0 > t1 { return None; }
+    }
+
+    Some((start + delta * t0, start + delta * t1))
+}
+
+impl<B: Backend> HasContext<B> for CanvasSpace<B> {
+    #[inline]
+    fn tree_context(&self) -> TreeContext<'_, B> {
+        TreeContext { ptr: None }
+    }
+}
+
+impl<B: Backend> IO<B> for CanvasSpace<B> {
+    #[inline]
+    fn load_to(&self, resource: &mut dyn Resource<B>) {
+        self.resources.load_to(resource)
+    }
+}
+
+impl<B: Backend> CanvasIO<B> for CanvasSpace<B> {
+    #[inline]
+    fn bounds(&self) -> Rectangle {
+        self.bounds
+    }
+
+    fn draw_rectangle(&mut self, backend: &mut B, rectangle: Rectangle, color: Color) {
+        let Some(rectangle) = rectangle.intersection(&self.bounds) else { return };
+
+        backend.draw_rectangle(rectangle.into(), color.into());
+    }
+
+    fn draw_line(&mut self, backend: &mut B, start: Vector2, end: Vector2, color: Color) {
+        let Some((start, end)) = clip_line(start, end, self.bounds) else { return };
+
+        backend.draw_line(start.into(), end.into(), color.into());
+    }
+
+    fn draw_circle(&mut self, backend: &mut B, center: Vector2, radius: f32, color: Color) {
+        let shape = Rectangle::new(center.x - radius, center.y - radius, radius * 2.0, radius * 2.0);
+
+        self.draw_cropped(backend, shape, |backend| {
+            backend.draw_circle(center.into(), radius, color.into());
+        });
+    }
+
+    fn draw_texture(&mut self, backend: &mut B, texture: ResourceHandle, rectangle: Rectangle, tint: Color) {
+        // SAFETY: Drawing happens on the graphics thread
+        unsafe { self.resources.load_pending(backend) };
+
+        let Some(texture) = self.resources.texture(texture) else { return };
+
+        self.draw_cropped(backend, rectangle, |backend| {
+            backend.draw_texture(texture, rectangle.into(), tint.into());
+        });
+    }
 }
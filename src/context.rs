@@ -1,10 +1,19 @@
-use std::collections::BTreeSet;
-use crate::{backend::Backend, static_id::StaticID, tree::TreeAction};
+use std::{collections::BTreeMap, time::Duration};
+use crate::{action::{ActionCallback, ActionIO, InputEvent}, backend::{Backend, Color}, node::Node, resource::Resource, static_id::StaticID, tree::{TreeAction, TreeActionRef}};
 
 pub struct TreeContext<'a, B: Backend> {
     pub ptr: Option<&'a TreeContextData<B>>,
 }
 
+impl<B: Backend> Clone for TreeContext<'_, B> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<B: Backend> Copy for TreeContext<'_, B> {}
+
 pub struct TreeContextMut<'a, B: Backend> {
     pub ptr: Option<&'a mut TreeContextData<B>>,
 }
@@ -16,33 +25,72 @@ pub struct TreeContextData<B: Backend> {
     /// Manages and runs tree actions.
     pub actions: TreeActionContext<B>,
 
+    /// Number of active tint locks. While above zero, the tint can't be changed.
     lock_tint: i32,
 
-    tint: B::Color,
+    /// Tint applied to everything drawn, combining tints and opacity of every node on the current branch.
+    tint: Color,
 }
 
-pub struct IOInstance<B: Backend> {
-    pub id: IOID,
-    pub io: Box<dyn IO<B>>,
-}
+impl<B: Backend> TreeContextData<B> {
+    /// Create a context with no active I/O systems or actions.
+    pub fn new() -> Self {
+        Self {
+            io: TreeIOContext::new(),
+            actions: TreeActionContext::new(),
+            lock_tint: 0,
+            tint: Color::new(255, 255, 255, 255),
+        }
+    }
 
-impl<B: Backend> PartialEq for IOInstance<B> {
-    fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
+    /// Tint currently applied to drawn nodes. Nodes multiply this by their own tint and opacity while they are
+    /// drawn, and restore it afterwards.
+    #[inline]
+    pub const fn tint(&self) -> Color {
+        self.tint
     }
-}
 
-impl<B: Backend> Eq for IOInstance<B> {}
+    /// Change the tint. Does nothing if the tint is [locked](Self::lock_tint).
+    ///
+    /// # Returns
+    ///
+    /// True if the tint was changed, false if it is locked.
+    pub fn set_tint(&mut self, value: Color) -> bool {
+        if self.is_tint_locked() { return false; }
+
+        self.tint = value;
+        true
+    }
 
-impl<B: Backend> PartialOrd for IOInstance<B> {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+    /// Prevent the tint from being changed until a matching [`Self::unlock_tint`] call. Locks stack, so the tint
+    /// remains locked until every lock has been released.
+    #[inline]
+    pub fn lock_tint(&mut self) {
+        self.lock_tint += 1;
+    }
+
+    /// Release a lock previously placed with [`Self::lock_tint`].
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if the tint is not locked.
+    #[inline]
+    pub fn unlock_tint(&mut self) {
+        debug_assert!(self.lock_tint > 0, "tint is not locked");
+        self.lock_tint -= 1;
+    }
+
+    /// Check if the tint is locked.
+    #[inline]
+    pub const fn is_tint_locked(&self) -> bool {
+        self.lock_tint > 0
     }
 }
 
-impl<B: Backend> Ord for IOInstance<B> {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.id.cmp(&other.id)
+impl<B: Backend> Default for TreeContextData<B> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -51,40 +99,181 @@ impl<B: Backend> Ord for IOInstance<B> {
 /// I/O systems are changed by a replace operation. `replace` takes the new I/O systems, but returns the one set
 /// previously. This can be used to manage I/Os as a stack:
 ///
-/// ---
-/// auto previous = io.replace(id, this);
-/// scope (exit) io.replace(id, previous);
-/// ---
+/// ```
+/// # use amity_ui::backend::headless::HeadlessBackend;
+/// # use amity_ui::context::{HasContext, IO, IOID, TreeContext, TreeIOContext};
+/// # use amity_ui::resource::Resource;
+/// struct Named(&'static str);
+///
+/// impl HasContext<HeadlessBackend> for Named {
+///     fn tree_context(&self) -> TreeContext<'_, HeadlessBackend> {
+///         TreeContext { ptr: None }
+///     }
+/// }
+///
+/// impl IO<HeadlessBackend> for Named {
+///     fn load_to(&self, _: &mut dyn Resource<HeadlessBackend>) {}
+/// }
+///
+/// fn address(io: &dyn IO<HeadlessBackend>) -> *const () {
+///     (io as *const dyn IO<HeadlessBackend>).cast()
+/// }
+///
+/// let id = IOID::of::<Named>();
+/// let mut context = TreeIOContext::<HeadlessBackend>::new();
+/// let outer: Box<dyn IO<HeadlessBackend>> = Box::new(Named("outer"));
+/// let inner: Box<dyn IO<HeadlessBackend>> = Box::new(Named("inner"));
+/// let (outer_address, inner_address) = (address(&*outer), address(&*inner));
+///
+/// assert!(context.replace(id, outer).is_none());
+/// let previous = context.replace(id, inner);
+///
+/// // `replace` returns the previous system, while `get` returns the newest one
+/// assert_eq!(previous.as_deref().map(address), Some(outer_address));
+/// assert_eq!(context.get(id).map(address), Some(inner_address));
+///
+/// // ...draw nodes using `inner`...
+///
+/// context.restore(id, previous);
+/// assert_eq!(context.get(id).map(address), Some(outer_address));
+///
+/// context.restore(id, None);
+/// assert!(context.get(id).is_none());
+/// ```
 pub struct TreeIOContext<B: Backend> {
-    /// Key-value pairs of active I/O systems. Each pair contains the system and the ID of the interface
-    /// it implements. Pairs are sorted by the interface ID.
-    active_ios: BTreeSet<Vec<IOInstance<B>>>,
+    /// Active I/O systems, keyed and sorted by the ID of the interface they implement.
+    active_ios: BTreeMap<IOID, Box<dyn IO<B>>>,
 }
 
-pub trait HasContext<B: Backend> {
-    /// Returns the current tree context.
-    fn tree_context(&self) -> &TreeContext<B>;
+impl<B: Backend> TreeIOContext<B> {
+    /// Create a context with no active I/O systems.
+    #[inline]
+    pub fn new() -> Self {
+        Self { active_ios: BTreeMap::new() }
+    }
+
+    /// Set the active I/O system for an interface.
+    ///
+    /// # Params
+    ///
+    /// - `id`: ID of the interface the system implements.
+    /// - `io`: System to activate.
+    ///
+    /// # Returns
+    ///
+    /// The system that was active previously, if any.
+    #[inline]
+    pub fn replace(&mut self, id: IOID, io: Box<dyn IO<B>>) -> Option<Box<dyn IO<B>>> {
+        self.active_ios.insert(id, io)
+    }
+
+    /// Deactivate the I/O system for an interface.
+    ///
+    /// # Returns
+    ///
+    /// The system that was active, if any.
+    #[inline]
+    pub fn remove(&mut self, id: IOID) -> Option<Box<dyn IO<B>>> {
+        self.active_ios.remove(&id)
+    }
+
+    /// Restore a system returned by [`Self::replace`] or [`Self::remove`]. If `io` is `None`, the interface is left
+    /// with no active system.
+    pub fn restore(&mut self, id: IOID, io: Option<Box<dyn IO<B>>>) {
+        match io {
+            Some(io) => { self.active_ios.insert(id, io); }
+            None => { self.active_ios.remove(&id); }
+        }
+    }
+
+    /// Get the active I/O system for an interface.
+    #[inline]
+    pub fn get(&self, id: IOID) -> Option<&dyn IO<B>> {
+        self.active_ios.get(&id).map(|io| &**io)
+    }
+
+    /// Get the active I/O system for an interface.
+    #[inline]
+    pub fn get_mut(&mut self, id: IOID) -> Option<&mut (dyn IO<B> + 'static)> {
+        self.active_ios.get_mut(&id).map(|io| &mut **io)
+    }
+
+    /// Iterate over active I/O systems, sorted by interface ID.
+    pub fn iter(&self) -> impl Iterator<Item = (IOID, &dyn IO<B>)> {
+        self.active_ios.iter().map(|(id, io)| (*id, &**io))
+    }
+}
+
+impl<B: Backend> Default for TreeIOContext<B> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-    /// Returns the current tree context.
-    fn tree_context_mut(&mut self) -> &mut TreeContext<B>;
+pub trait HasContext<B: Backend> {
+    /// Returns the current tree context. The context is empty if the object isn't attached to a tree.
+    fn tree_context(&self) -> TreeContext<'_, B>;
 }
 
 pub trait IO<B: Backend>: HasContext<B> {
-    /// Load a resource by reference. This is the same as `Node.load`.
-    /// Params:
-    ///     resource = Resource to load. It will be updated with identifying information.
-    fn load_to(&self); // TODO
+    /// Load a resource by reference. Systems that draw or otherwise use resources, such as [`crate::canvas::CanvasIO`],
+    /// load them through this method. Systems that don't use resources may ignore the call.
+    ///
+    /// Loading may be deferred until the backend is available, but the resource is given its handle immediately.
+    /// Loading a resource that already holds a handle from this system should keep the handle.
+    ///
+    /// # Params
+    ///
+    /// - `resource`: Resource to load. It will be updated with identifying information.
+    ///
+    /// # See Also
+    ///
+    /// - [`crate::resource::ResourceSpace`] for a reference implementation.
+    fn load_to(&self, resource: &mut dyn Resource<B>);
 }
 
 /// ID for an I/O interface.
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct IOID {
     id: StaticID,
 }
 
+impl IOID {
+    /// Create an interface ID out of a static ID.
+    #[inline]
+    pub const fn new(id: StaticID) -> Self {
+        Self { id }
+    }
+
+    /// Get the ID of an interface type. Repeated calls for the same type return the same ID.
+    #[inline]
+    pub fn of<T: ?Sized + 'static>() -> Self {
+        Self::new(crate::static_id::of::<T>())
+    }
+
+    /// Get the underlying static ID.
+    #[inline]
+    pub const fn id(self) -> StaticID {
+        self.id
+    }
+}
+
 struct RunningAction<B: Backend> {
-    action: TreeAction<B>,
-    generation: i32,
+    action: TreeActionRef<B>,
+
+    /// Generation of the action at the time it was started, or `None` if it was started with `run_action`, which
+    /// doesn't track generations.
+    generation: Option<i32>,
+}
+
+impl<B: Backend> RunningAction<B> {
+    /// Check if the run is still valid: the action hasn't stopped, and hasn't been restarted since.
+    fn is_live(&self, action: &dyn TreeAction<B>) -> bool {
+        let data = action.action_data();
+
+        !data.to_stop && self.generation.is_none_or(|generation| generation == data.generation)
+    }
 }
 
 /// Keeps track of currently active actions.
@@ -99,3 +288,405 @@ pub struct TreeActionContext<B: Backend> {
     /// the action, and the drawn node activates another iterator.
     running_iterators: i32,
 }
+
+impl<B: Backend> TreeActionContext<B> {
+    /// Create a context with no running actions.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            actions: Vec::new(),
+            running_iterators: 0,
+        }
+    }
+
+    /// Number of running actions.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    /// Check if there are no running actions.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Run an action. It will receive callbacks starting with the next tree pass.
+    ///
+    /// If the action is already running, it will run twice; use [`Self::start_action`] to restart it instead.
+    pub fn run_action(&mut self, action: TreeActionRef<B>) {
+        self.actions.push(RunningAction { action, generation: None });
+    }
+
+    /// Start, or restart, an action. It will receive callbacks starting with the next tree pass.
+    ///
+    /// Starting the action bumps its generation. If the action was started before and is still running, the old run
+    /// is cancelled, so the action doesn't run twice at a time.
+    ///
+    /// ```
+    /// # use std::{cell::{Cell, RefCell}, rc::Rc};
+    /// # use amity_ui::{backend::headless::HeadlessBackend, context::TreeActionContext, node::{Node, label}};
+    /// # use amity_ui::tree::{TreeAction, TreeActionData};
+    /// type B = HeadlessBackend;
+    ///
+    /// // Counts the passes it started and finished
+    /// #[derive(Default)]
+    /// struct Counter { data: TreeActionData<B>, started: u32, ended: u32 }
+    ///
+    /// impl TreeAction<B> for Counter {
+    ///     fn action_data(&self) -> &TreeActionData<B> { &self.data }
+    ///     fn action_data_mut(&mut self) -> &mut TreeActionData<B> { &mut self.data }
+    ///     fn before_tree(&mut self, _root: &mut Node<B>) { self.started += 1; }
+    ///     fn after_tree(&mut self) { self.ended += 1; }
+    /// }
+    ///
+    /// let mut root = label((), "root");
+    /// let mut context = TreeActionContext::<B>::new();
+    /// let counter = Rc::new(RefCell::new(Counter::default()));
+    /// let finished = Rc::new(Cell::new(0));
+    /// counter.borrow_mut().data.then({
+    ///     let finished = finished.clone();
+    ///     move |_| finished.set(finished.get() + 1)
+    /// });
+    ///
+    /// context.start_action(counter.clone());
+    /// context.before_tree(&mut root);
+    ///
+    /// // Restart the action in the middle of a pass; the old run is dropped once the next pass starts
+    /// context.start_action(counter.clone());
+    /// assert_eq!(context.len(), 2);
+    /// context.before_tree(&mut root);
+    /// assert_eq!(context.len(), 1);
+    /// context.after_tree();
+    ///
+    /// let counter = counter.borrow();
+    /// assert_eq!((counter.started, counter.ended, finished.get()), (2, 1, 1));
+    /// assert!(context.is_empty());
+    /// ```
+    pub fn start_action(&mut self, action: TreeActionRef<B>) {
+        let generation = {
+            let mut action = action.borrow_mut();
+            let data = action.action_data_mut();
+            data.generation += 1;
+            data.to_stop = false;
+            data.in_tree = false;
+            data.in_start_node = false;
+            data.generation
+        };
+
+        self.actions.push(RunningAction { action, generation: Some(generation) });
+    }
+
+    /// Emit `before_tree` to every running action.
+    ///
+    /// # Params
+    ///
+    /// - `root`: Root node of the tree.
+    pub fn before_tree(&mut self, root: &mut Node<B>) {
+        self.for_each(|action| {
+            let data = action.action_data_mut();
+            data.in_tree = true;
+            data.in_start_node = data.start_node.is_none();
+
+            action.before_tree(root);
+        });
+    }
+
+    /// Emit `before_draw` to every running action that has descended into its start node.
+    ///
+    /// # Params
+    ///
+    /// - `node`: Node about to be drawn.
+    pub fn before_draw(&mut self, node: &mut Node<B>) {
+        self.for_each(|action| {
+            let data = action.action_data_mut();
+            if data.is_start_node(node) {
+                data.in_start_node = true;
+            }
+
+            if action.action_data().in_start_node {
+                action.before_draw(node);
+            }
+        });
+    }
+
+    /// Emit `after_draw` to every running action that is inside its start node.
+    ///
+    /// # Params
+    ///
+    /// - `node`: Node that has just been drawn, along with its children.
+    pub fn after_draw(&mut self, node: &mut Node<B>) {
+        self.for_each(|action| {
+            if action.action_data().in_start_node {
+                action.after_draw(node);
+            }
+
+            // Leaving the start node
+            let data = action.action_data_mut();
+            if data.is_start_node(node) {
+                data.in_start_node = false;
+            }
+        });
+    }
+
+    /// Emit `after_tree` to every running action, and stop them, as each action only runs for a single pass.
+    pub fn after_tree(&mut self) {
+        self.for_each(|action| {
+            action.action_data_mut().in_tree = false;
+            action.after_tree();
+            action.stop();
+        });
+    }
+
+    /// Run a callback for every action that hasn't stopped or been restarted. Afterwards, remove finished runs,
+    /// unless another iterator is running.
+    fn for_each(&mut self, mut callback: impl FnMut(&mut dyn TreeAction<B>)) {
+        self.running_iterators += 1;
+
+        for running in &self.actions {
+            let mut action = running.action.borrow_mut();
+            if !running.is_live(&*action) { continue; }
+
+            callback(&mut *action);
+        }
+
+        // Removing runs would break other iterators
+        if self.running_iterators == 1 {
+            self.actions.retain(|running| running.is_live(&*running.action.borrow()));
+        }
+
+        self.running_iterators -= 1;
+    }
+}
+
+impl<B: Backend> Default for TreeActionContext<B> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Input event saved by [`InputRecorder`].
+///
+/// Recorded events are plain data, so a log can be stored and fed back into an [`InputReplayer`] later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordedEvent {
+    /// Index of the frame the event was emitted in, counting from the start of the recording.
+    pub frame: u64,
+
+    /// Time between the start of the recording and the frame the event was emitted in.
+    pub time: Duration,
+
+    /// The emitted event.
+    pub event: InputEvent,
+
+    /// Number passed along with the event.
+    pub number: i32,
+}
+
+/// [`ActionIO`] wrapper recording every event passed through it, for use in tests, demos or macros.
+///
+/// Events are forwarded to the wrapped system as-is. Call [`Self::next_frame`] once per frame, passing
+/// [`Backend::delta_time`], to keep frame timestamps of the recording up to date. The recording can then be
+/// replayed with [`InputReplayer`].
+///
+/// ```
+/// # use std::time::Duration;
+/// # use raylib::consts::KeyboardKey;
+/// # use amity_ui::{action::*, backend::headless::HeadlessBackend, context::*, input::*};
+/// # use std::{cell::RefCell, rc::Rc};
+/// fn map() -> InputMapSpace<HeadlessBackend> {
+///     InputMapSpace::new(vec![InputLayer {
+///         modifiers: InputStroke { input: Vec::new() },
+///         bindings: [
+///             (FluidInputAction::Press, KeyboardKey::KEY_ENTER),
+///             (FluidInputAction::Cancel, KeyboardKey::KEY_ESCAPE),
+///         ]
+///             .map(|(action, key)| InputBinding { action: action.id(), trigger: InputStrokeItem::KeyboardKey(key) })
+///             .into(),
+///     }])
+/// }
+///
+/// let fired = Rc::new(RefCell::new(Vec::new()));
+/// let log = |fired: &Rc<RefCell<Vec<InputActionID>>>| -> ActionCallback {
+///     let fired = fired.clone();
+///     Box::new(move |action, _, _| { fired.borrow_mut().push(*action); true })
+/// };
+/// let press = |key: KeyboardKey| InputEvent { code: key.event_code(), is_active: true };
+///
+/// // Record two frames of input
+/// let mut recorder = InputRecorder::new(map());
+/// recorder.emit_event(press(KeyboardKey::KEY_ENTER), 0, log(&fired));
+/// recorder.inner_mut().after_draw();
+/// recorder.next_frame(Duration::from_millis(16));
+/// recorder.next_frame(Duration::from_millis(16));
+/// recorder.emit_event(press(KeyboardKey::KEY_ESCAPE), 0, log(&fired));
+/// recorder.inner_mut().after_draw();
+///
+/// let recorded = std::mem::take(&mut *fired.borrow_mut());
+/// assert_eq!(recorded, [FluidInputAction::Press.id(), FluidInputAction::Cancel.id()]);
+/// assert_eq!(recorder.events()[1].frame, 2);
+/// assert_eq!(recorder.events()[1].time, Duration::from_millis(32));
+///
+/// // Replaying fires the same actions on the same frames
+/// let mut replayer = InputReplayer::new(recorder.into_events());
+/// let mut io = map();
+/// let mut frames = Vec::new();
+/// while !replayer.is_finished() {
+///     replayer.replay_frame(&mut io, |_| log(&fired));
+///     io.after_draw();
+///     frames.push(fired.borrow().len());
+/// }
+/// assert_eq!(*fired.borrow(), recorded);
+/// assert_eq!(frames, [1, 1, 2]);
+/// ```
+pub struct InputRecorder<A> {
+    /// Wrapped system.
+    inner: A,
+
+    /// Events recorded so far.
+    events: Vec<RecordedEvent>,
+
+    /// Index of the current frame.
+    frame: u64,
+
+    /// Time since the start of the recording.
+    time: Duration,
+}
+
+impl<A> InputRecorder<A> {
+    /// Start recording events passed to the given system.
+    pub fn new(inner: A) -> Self {
+        Self {
+            inner,
+            events: Vec::new(),
+            frame: 0,
+            time: Duration::ZERO,
+        }
+    }
+
+    /// Get the wrapped system.
+    #[inline]
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// Get the wrapped system.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut A {
+        &mut self.inner
+    }
+
+    /// Events recorded so far, in the order they were emitted.
+    #[inline]
+    pub fn events(&self) -> &[RecordedEvent] {
+        &self.events
+    }
+
+    /// Stop recording.
+    ///
+    /// # Returns
+    ///
+    /// The recorded events.
+    #[inline]
+    pub fn into_events(self) -> Vec<RecordedEvent> {
+        self.events
+    }
+
+    /// Advance to the next frame. Events emitted afterwards will be stamped with the new frame.
+    ///
+    /// # Params
+    ///
+    /// - `delta`: Time the previous frame took, usually [`Backend::delta_time`].
+    pub fn next_frame(&mut self, delta: Duration) {
+        self.frame += 1;
+        self.time += delta;
+    }
+}
+
+impl<B: Backend, A: HasContext<B>> HasContext<B> for InputRecorder<A> {
+    #[inline]
+    fn tree_context(&self) -> TreeContext<'_, B> {
+        self.inner.tree_context()
+    }
+}
+
+impl<B: Backend, A: IO<B>> IO<B> for InputRecorder<A> {
+    #[inline]
+    fn load_to(&self, resource: &mut dyn Resource<B>) {
+        self.inner.load_to(resource)
+    }
+}
+
+impl<B: Backend, A: ActionIO<B>> ActionIO<B> for InputRecorder<A> {
+    fn emit_event(&mut self, event: InputEvent, number: i32, callback: ActionCallback) {
+        self.events.push(RecordedEvent { frame: self.frame, time: self.time, event, number });
+        self.inner.emit_event(event, number, callback);
+    }
+}
+
+/// Feeds events saved by [`InputRecorder`] back into an [`ActionIO`] system, on the same frames they were recorded in.
+///
+/// See [`InputRecorder`] for an example.
+pub struct InputReplayer {
+    /// Events to replay, sorted by frame.
+    events: Vec<RecordedEvent>,
+
+    /// Index of the next event to replay.
+    next: usize,
+
+    /// Index of the current frame.
+    frame: u64,
+}
+
+impl InputReplayer {
+    /// Prepare to replay a recording, starting from its first frame.
+    pub fn new(mut events: Vec<RecordedEvent>) -> Self {
+        events.sort_by_key(|event| event.frame);
+
+        Self {
+            events,
+            next: 0,
+            frame: 0,
+        }
+    }
+
+    /// Index of the frame that will be replayed next.
+    #[inline]
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Check if all events have been replayed.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.next == self.events.len()
+    }
+
+    /// Emit events recorded for the current frame, and advance to the next frame. Should be called once per frame,
+    /// before the system processes its events.
+    ///
+    /// # Params
+    ///
+    /// - `io`:       System to emit the events to.
+    /// - `callback`: Creates the callback to pass along with each event.
+    ///
+    /// # Returns
+    ///
+    /// Number of events emitted.
+    pub fn replay_frame<B: Backend>(
+        &mut self,
+        io: &mut dyn ActionIO<B>,
+        mut callback: impl FnMut(&RecordedEvent) -> ActionCallback,
+    ) -> usize {
+        let start = self.next;
+
+        while let Some(event) = self.events.get(self.next).filter(|event| event.frame <= self.frame) {
+            io.emit_event(event.event, event.number, callback(event));
+            self.next += 1;
+        }
+
+        self.frame += 1;
+        self.next - start
+    }
+}
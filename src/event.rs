@@ -1,7 +1,138 @@
-pub struct Event<T>(Box<dyn FnMut() -> T>);
+/// List of callbacks to run when something happens, for example when a button is pressed.
+///
+/// Subscribers receive the event's arguments by reference, and are called in the order they subscribed.
+pub struct Event<Args: ?Sized = (), T = ()> {
+    /// Subscribed callbacks along with their handles.
+    subscribers: Vec<(Subscription, EventCallback<Args, T>)>,
 
-impl<T> Event<T> {
-    pub fn dispatch(&mut self) -> T {
-        (self.0)()
+    /// ID to assign to the next subscriber.
+    next_id: u64,
+}
+
+/// Callback subscribed to an [`Event`].
+pub type EventCallback<Args, T> = Box<dyn FnMut(&Args) -> T>;
+
+/// Event that takes no arguments.
+pub type SimpleEvent<T = ()> = Event<(), T>;
+
+/// Handle to a subscriber of an [`Event`], used to unsubscribe it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Subscription {
+    id: u64,
+}
+
+impl<Args: ?Sized, T> Event<Args, T> {
+    /// Create an event with no subscribers.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            subscribers: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Add a callback to run whenever the event is dispatched.
+    ///
+    /// # Returns
+    ///
+    /// Handle that can be passed to [`Self::unsubscribe`] to remove the callback.
+    pub fn subscribe(&mut self, callback: impl FnMut(&Args) -> T + 'static) -> Subscription {
+        let subscription = Subscription { id: self.next_id };
+        self.next_id += 1;
+        self.subscribers.push((subscription, Box::new(callback)));
+        subscription
+    }
+
+    /// Remove a callback. Other callbacks keep their order.
+    ///
+    /// # Returns
+    ///
+    /// True if the callback was removed, false if it was not subscribed to this event.
+    ///
+    /// ```
+    /// # use amity_ui::event::Event;
+    /// let mut event = Event::<str, String>::new();
+    /// let first = event.subscribe(|name| format!("first {name}"));
+    /// let second = event.subscribe(|name| format!("second {name}"));
+    /// event.subscribe(|name| format!("third {name}"));
+    /// assert_eq!(event.dispatch("ok"), ["first ok", "second ok", "third ok"]);
+    ///
+    /// assert!(event.unsubscribe(second));
+    /// assert!(!event.unsubscribe(second));
+    /// event.subscribe(|name| format!("fourth {name}"));
+    /// assert_eq!(event.dispatch("ok"), ["first ok", "third ok", "fourth ok"]);
+    ///
+    /// assert!(event.unsubscribe(first));
+    /// assert_eq!(event.len(), 2);
+    /// ```
+    pub fn unsubscribe(&mut self, subscription: Subscription) -> bool {
+        let Some(index) = self.subscribers.iter().position(|(other, _)| *other == subscription) else {
+            return false;
+        };
+
+        self.subscribers.remove(index);
+        true
+    }
+
+    /// Run all subscribed callbacks.
+    ///
+    /// # Params
+    ///
+    /// - `args`: Arguments to pass to each callback.
+    ///
+    /// # Returns
+    ///
+    /// Results of the callbacks, in the order they were called.
+    ///
+    /// ```
+    /// # use std::{cell::Cell, rc::Rc};
+    /// # use amity_ui::event::Event;
+    /// let total = Rc::new(Cell::new(0));
+    /// let mut event = Event::<(i32, i32), i32>::new();
+    /// event.subscribe(|&(a, b)| a + b);
+    /// event.subscribe({
+    ///     let total = total.clone();
+    ///     move |&(a, b)| { total.set(total.get() + a * b); total.get() }
+    /// });
+    ///
+    /// assert_eq!(event.dispatch(&(2, 3)), [5, 6]);
+    /// assert_eq!(event.dispatch(&(4, 5)), [9, 26]);
+    /// assert_eq!(total.get(), 26);
+    /// ```
+    pub fn dispatch(&mut self, args: &Args) -> Vec<T> {
+        self.subscribers.iter_mut()
+            .map(|(_, callback)| callback(args))
+            .collect()
+    }
+
+    /// Number of subscribed callbacks.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.subscribers.len()
+    }
+
+    /// True if no callbacks are subscribed.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.subscribers.is_empty()
+    }
+}
+
+impl<T> Event<(), T> {
+    /// Run all subscribed callbacks of an event without arguments.
+    ///
+    /// # Returns
+    ///
+    /// Results of the callbacks, in the order they were called.
+    #[inline]
+    pub fn emit(&mut self) -> Vec<T> {
+        self.dispatch(&())
+    }
+}
+
+impl<Args: ?Sized, T> Default for Event<Args, T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
     }
 }
@@ -1,26 +1,185 @@
-use crate::{action::Actionable, backend::Backend};
+use std::{cell::RefCell, rc::{Rc, Weak}};
+
+use crate::{action::Actionable, backend::Backend, context::{HasContext, IO, TreeContext}, input::InputActionID, node::{Node, NodeVariant}, resource::Resource};
 
 /// Nodes implementing this interface can be focused by a `FocusIO` system.
 pub trait Focusable<B: Backend>: Actionable<B> {
     /// Handle input. Called each frame when focused.
     ///
-    /// This method should not be called if `blocksInput` is true.
+    /// This method should not be called if `blocks_input` is true.
+    ///
+    /// # Returns
     ///
-    /// Returns:
-    ///     True if focus input was handled, false if it was ignored.
+    /// True if focus input was handled, false if it was ignored.
     fn focus_impl(&mut self) -> bool;
 
     /// Set focus to this node.
     ///
-    /// Implementation would usually check `blocksInput` and call `focusIO.focus` on self for this to take effect.
-    /// A node may override this method to redirect the focus to another node (by calling its `focus()` method),
-    /// or ignore the request.
+    /// Implementation would usually check `blocks_input` and call [`FocusIO::set_focus`] on self for this to take
+    /// effect. A node may override this method to redirect the focus to another node (by calling its `focus()`
+    /// method), or ignore the request.
+    ///
+    /// Focus should do nothing if the node is disabled, or otherwise blocks input.
+    ///
+    /// # Params
+    ///
+    /// - `io`: Focus system to take focus in.
+    fn focus(&mut self, io: &mut dyn FocusIO<B>);
+
+    /// # Params
+    ///
+    /// - `io`: Focus system to check.
+    ///
+    /// # Returns
+    ///
+    /// True if this node has focus. Recommended implementation: `io.is_focused(self)`.
+    /// Proxy nodes, such as `FieldSlot` might choose to return the value of the node they hold.
+    fn is_focused(&self, io: &dyn FocusIO<B>) -> bool;
+}
+
+/// I/O interface for keyboard and gamepad focus. The system keeps track of a single focused node, which receives
+/// input from devices that don't point at the screen.
+pub trait FocusIO<B: Backend>: IO<B> {
+    /// Get the currently focused node.
     ///
-    /// Focus should do nothing if the node `isDisabled` is true or if
-    fn focus(&mut self);
+    /// # Returns
+    ///
+    /// The focused node, or `None` if no node is focused, or if the focused node no longer exists.
+    fn focus(&self) -> Option<Rc<RefCell<Node<B>>>>;
+
+    /// Change the focused node. This doesn't check if the node accepts focus; use [`Focusable::focus`] instead.
+    ///
+    /// # Params
+    ///
+    /// - `node`: Node to focus, or `None` to clear focus.
+    fn set_focus(&mut self, node: Option<&Rc<RefCell<Node<B>>>>);
+
+    /// Check if the given node is focused.
+    fn is_focused(&self, node: &Rc<RefCell<Node<B>>>) -> bool {
+        self.focus().is_some_and(|focus| Rc::ptr_eq(&focus, node))
+    }
+
+    /// Clear focus.
+    #[inline]
+    fn clear_focus(&mut self) {
+        self.set_focus(None);
+    }
+}
+
+/// Reference [`FocusIO`] implementation, holding focus for a whole tree or a part of it.
+///
+/// Nodes take focus through [`Focusable::focus`], which refuses focus for nodes that are disabled or hidden:
+///
+/// ```
+/// # use std::{cell::RefCell, rc::Rc};
+/// # use amity_ui::{backend::headless::HeadlessBackend, focus::{FocusIO, FocusSpace, Focusable}, node::button};
+/// let mut space = FocusSpace::<HeadlessBackend>::new();
+/// let mut enabled = Rc::new(RefCell::new(button((), "Enabled")));
+/// let mut disabled = Rc::new(RefCell::new(button((), "Disabled")));
+/// disabled.borrow_mut().data.set_disabled(true);
+///
+/// disabled.focus(&mut space);
+/// assert!(!disabled.is_focused(&space));
+/// assert!(space.focus().is_none());
+///
+/// enabled.focus(&mut space);
+/// assert!(enabled.is_focused(&space));
+///
+/// // Focus stays on the enabled node
+/// disabled.focus(&mut space);
+/// assert!(enabled.is_focused(&space));
+/// assert!(!disabled.is_focused(&space));
+///
+/// // Focus is released once the node is dropped
+/// drop(enabled);
+/// assert!(space.focus().is_none());
+/// ```
+pub struct FocusSpace<B: Backend> {
+    /// Currently focused node.
+    focus: Option<Weak<RefCell<Node<B>>>>,
+}
+
+impl<B: Backend> FocusSpace<B> {
+    /// Create a focus space with no node focused.
+    pub fn new() -> Self {
+        Self {
+            focus: None,
+        }
+    }
+}
+
+impl<B: Backend> Default for FocusSpace<B> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Backend> HasContext<B> for FocusSpace<B> {
+    #[inline]
+    fn tree_context(&self) -> TreeContext<'_, B> {
+        TreeContext { ptr: None }
+    }
+}
+
+impl<B: Backend> IO<B> for FocusSpace<B> {
+    #[inline]
+    fn load_to(&self, _resource: &mut dyn Resource<B>) {}
+}
+
+impl<B: Backend> FocusIO<B> for FocusSpace<B> {
+    #[inline]
+    fn focus(&self) -> Option<Rc<RefCell<Node<B>>>> {
+        self.focus.as_ref()?.upgrade()
+    }
+
+    #[inline]
+    fn set_focus(&mut self, node: Option<&Rc<RefCell<Node<B>>>>) {
+        self.focus = node.map(Rc::downgrade);
+    }
+}
+
+impl<B: Backend> Actionable<B> for Rc<RefCell<Node<B>>> {
+    /// A node blocks input if it's disabled or hidden.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently borrowed mutably.
+    #[inline]
+    fn blocks_input(&self) -> bool {
+        self.borrow().data.blocks_input()
+    }
+
+    /// Pass the action to the node's variant, if it handles input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is currently borrowed.
+    fn action_impl(&mut self, _io: Option<&mut dyn IO<B>>, _number: i32, action: &InputActionID, is_active: bool) -> bool {
+        let Node { data, variant } = &mut *self.borrow_mut();
+        match variant {
+            NodeVariant::Button(button) => button.action_impl(action, is_active),
+            NodeVariant::Checkbox(checkbox) => checkbox.action_impl(data, action, is_active),
+            NodeVariant::ScrollInput(scroll_input) => scroll_input.action_impl(action, is_active),
+            _ => false,
+        }
+    }
+}
+
+impl<B: Backend> Focusable<B> for Rc<RefCell<Node<B>>> {
+    #[inline]
+    fn focus_impl(&mut self) -> bool {
+        false
+    }
+
+    fn focus(&mut self, io: &mut dyn FocusIO<B>) {
+        if self.blocks_input() { return; }
+
+        io.set_focus(Some(self));
+    }
 
-    /// Returns:
-    ///     True if this node has focus. Recommended implementation: `return this == focusIO.focus`.
-    ///     Proxy nodes, such as `FieldSlot` might choose to return the value of the node they hold.
-    fn is_focused(&self) -> bool;
+    #[inline]
+    fn is_focused(&self, io: &dyn FocusIO<B>) -> bool {
+        io.is_focused(self)
+    }
 }
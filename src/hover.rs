@@ -1,4 +1,6 @@
-use crate::{backend::Backend, context::IO};
+use std::{cell::RefCell, rc::{Rc, Weak}, time::Duration};
+
+use crate::{backend::{Backend, Vector2}, context::{HasContext, IO, TreeContext}, node::Node, resource::Resource};
 
 /// `HoverIO` is an input handler system that reads events off devices with the ability to point at the screen,
 /// like mouses, touchpads or pens.
@@ -11,5 +13,285 @@ use crate::{backend::Backend, context::IO};
 /// two different nodes can be hovered by two different `HoverIO` systems, but a single `HoverIO` system can only
 /// hover a single node.
 pub trait HoverIO<B: Backend>: IO<B> {
-    // todo
+    /// Get the currently hovered node.
+    ///
+    /// # Returns
+    ///
+    /// The hovered node, or `None` if the pointer isn't over any node accepting hover.
+    fn hover(&self) -> Option<Rc<RefCell<Node<B>>>>;
+
+    /// Position of the pointer, as of the last update.
+    fn pointer(&self) -> Vector2;
+
+    /// Find the node under the pointer. Called once per frame, after the tree has been drawn, so node boxes are
+    /// up to date.
+    ///
+    /// # Params
+    ///
+    /// - `root`:  Root of the tree to search.
+    /// - `mouse`: Position of the pointer, in window coordinates.
+    fn update(&mut self, root: &Rc<RefCell<Node<B>>>, mouse: Vector2);
+
+    /// Handle input for the hovered node. Called each frame after [`Self::update`].
+    ///
+    /// # Returns
+    ///
+    /// True if a node is hovered and accepted the input, false if input was ignored.
+    fn hover_impl(&mut self) -> bool;
+}
+
+/// Find the topmost node at the given point.
+///
+/// Children are tested before their parent, and later children before earlier ones, as they are drawn on top. Hidden
+/// nodes and their children are skipped, and [`crate::node::HitPassthrough::in_children`] is used to skip branches.
+/// Nodes that block input can't be hovered, but still cover the nodes beneath them: if the topmost node blocks input,
+/// nothing is hovered.
+///
+/// # Params
+///
+/// - `node`:  Root of the branch to search.
+/// - `point`: Point to test, in window coordinates.
+///
+/// # Returns
+///
+/// The topmost node with the point in bounds, or `None` if there is none, or if it blocks input.
+pub fn find_hovered<B: Backend>(node: &Rc<RefCell<Node<B>>>, point: Vector2) -> Option<Rc<RefCell<Node<B>>>> {
+    hit_test(node, point).flatten()
+}
+
+/// Find the topmost node at the given point, including nodes that block input.
+///
+/// # Returns
+///
+/// `None` if no node was hit. Otherwise, the topmost node that was hit, or `Some(None)` if it blocks input.
+fn hit_test<B: Backend>(node: &Rc<RefCell<Node<B>>>, point: Vector2) -> Option<Option<Rc<RefCell<Node<B>>>>> {
+    let borrowed = node.borrow();
+    if borrowed.data.is_hidden() { return None; }
+
+    let hit = borrowed.in_bounds(point);
+
+    if hit.in_children() {
+        let child = borrowed.data.children.iter().rev()
+            .find_map(|child| hit_test(child, point));
+        if child.is_some() { return child; }
+    }
+
+    if hit.in_self() {
+        return Some((!borrowed.data.blocks_input()).then(|| node.clone()));
+    }
+
+    None
+}
+
+/// Hover the topmost node at the given point, updating the `is_hovered` flag of the previously and newly hovered
+/// nodes. Hover events are dispatched only if the hovered node changed.
+///
+/// # Params
+///
+/// - `hover`: Reference to the hovered node. Updated to the new one.
+/// - `root`:  Root of the tree to search.
+/// - `point`: Point to test, in window coordinates.
+pub(crate) fn update_hover<B: Backend>(hover: &mut Weak<RefCell<Node<B>>>, root: &Rc<RefCell<Node<B>>>, point: Vector2) {
+    let hovered = find_hovered(root, point);
+
+    if let Some(previous) = hover.upgrade()
+        && hovered.as_ref().is_none_or(|hovered| !Rc::ptr_eq(hovered, &previous))
+    {
+        previous.borrow_mut().data.set_hovered(false);
+    }
+    if let Some(hovered) = &hovered {
+        hovered.borrow_mut().data.set_hovered(true);
+    }
+
+    *hover = hovered.as_ref().map_or_else(Weak::new, Rc::downgrade);
+}
+
+/// Reference [`HoverIO`] implementation for a single mouse pointer.
+///
+/// ```
+/// # use std::{cell::RefCell, rc::Rc};
+/// # use amity_ui::{backend::{Vector2, headless::HeadlessBackend}, hover::{HoverIO, HoverSpace}, layout::Layout};
+/// # use amity_ui::{node::{HitPassthrough, label, vframe}, tree::LayoutTree};
+/// // `cover` is drawn over `under`, and both fill the window
+/// let cover = Rc::new(RefCell::new(label(Layout::fill(), "Cover")));
+/// let under = vframe(Layout::fill(), [cover.clone()]);
+/// let mut tree = LayoutTree::new(vframe((), [under.clone()]), HeadlessBackend::new());
+/// tree.draw();
+///
+/// let mut space = HoverSpace::new();
+/// let mut hovered = |space: &mut HoverSpace<_>| {
+///     space.update(&tree.root, Vector2::new(10.0, 10.0));
+///     space.hover().unwrap()
+/// };
+///
+/// // The opaque cover blocks the node beneath
+/// assert!(Rc::ptr_eq(&hovered(&mut space), &cover));
+/// assert!(cover.borrow().data.is_hovered());
+///
+/// // Hover passes through to the node beneath
+/// cover.borrow_mut().data.hit_passthrough = HitPassthrough::Passthrough;
+/// assert!(Rc::ptr_eq(&hovered(&mut space), &under));
+/// assert!(under.borrow().data.is_hovered() && !cover.borrow().data.is_hovered());
+///
+/// // The node beneath can also keep its children from being hovered
+/// cover.borrow_mut().data.hit_passthrough = HitPassthrough::Opaque;
+/// under.borrow_mut().data.hit_passthrough = HitPassthrough::PassthroughChildren;
+/// assert!(Rc::ptr_eq(&hovered(&mut space), &under));
+///
+/// // A disabled cover can't be hovered, but it still hides the node beneath
+/// under.borrow_mut().data.hit_passthrough = HitPassthrough::Opaque;
+/// cover.borrow_mut().data.set_disabled(true);
+/// space.update(&tree.root, Vector2::new(10.0, 10.0));
+/// assert!(space.hover().is_none());
+/// assert!(!under.borrow().data.is_hovered());
+/// ```
+pub struct HoverSpace<B: Backend> {
+    /// Currently hovered node.
+    hover: Weak<RefCell<Node<B>>>,
+
+    /// Position of the pointer.
+    pointer: Vector2,
+}
+
+impl<B: Backend> HoverSpace<B> {
+    /// Create a hover space with no node hovered.
+    pub fn new() -> Self {
+        Self {
+            hover: Weak::new(),
+            pointer: Vector2::default(),
+        }
+    }
+}
+
+impl<B: Backend> Default for HoverSpace<B> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Backend> HasContext<B> for HoverSpace<B> {
+    #[inline]
+    fn tree_context(&self) -> TreeContext<'_, B> {
+        TreeContext { ptr: None }
+    }
+}
+
+impl<B: Backend> IO<B> for HoverSpace<B> {
+    #[inline]
+    fn load_to(&self, _resource: &mut dyn Resource<B>) {}
+}
+
+impl<B: Backend> HoverIO<B> for HoverSpace<B> {
+    #[inline]
+    fn hover(&self) -> Option<Rc<RefCell<Node<B>>>> {
+        self.hover.upgrade()
+    }
+
+    #[inline]
+    fn pointer(&self) -> Vector2 {
+        self.pointer
+    }
+
+    fn update(&mut self, root: &Rc<RefCell<Node<B>>>, mouse: Vector2) {
+        self.pointer = mouse;
+        update_hover(&mut self.hover, root, mouse);
+    }
+
+    fn hover_impl(&mut self) -> bool {
+        self.hover().is_some_and(|node| !node.borrow().data.blocks_input())
+    }
+}
+
+/// Counts consecutive clicks to detect double and triple clicks.
+///
+/// A press continues the sequence if it happens within the double click time of the previous press, and within
+/// [`Self::MAX_DISTANCE`] of it. After a triple click, the sequence starts over.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use amity_ui::{backend::Vector2, hover::ClickTracker};
+/// let double_click_time = Duration::from_millis(500);
+/// let at = |millis| Duration::from_millis(millis);
+/// let position = Vector2::new(10.0, 10.0);
+///
+/// // Quick clicks
+/// let mut tracker = ClickTracker::new();
+/// assert_eq!(tracker.press(at(1000), position, double_click_time), 1);
+/// assert_eq!(tracker.press(at(1400), position, double_click_time), 2);
+/// assert_eq!(tracker.press(at(1800), Vector2::new(12.0, 13.0), double_click_time), 3);
+/// assert_eq!(tracker.press(at(2000), position, double_click_time), 1);
+///
+/// // Slow clicks
+/// tracker.reset();
+/// assert_eq!(tracker.press(at(1000), position, double_click_time), 1);
+/// assert_eq!(tracker.press(at(1501), position, double_click_time), 1);
+/// assert_eq!(tracker.click_count(), 1);
+///
+/// // Quick clicks too far apart
+/// assert_eq!(tracker.press(at(1600), Vector2::new(20.0, 10.0), double_click_time), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ClickTracker {
+    /// Time and position of the last press.
+    last_press: Option<(Duration, Vector2)>,
+
+    /// Number of presses in the current sequence.
+    count: u32,
+}
+
+impl ClickTracker {
+    /// Maximum distance between two presses of a multi-click, in pixels.
+    pub const MAX_DISTANCE: f32 = 4.0;
+
+    /// Highest click count reported before the sequence starts over.
+    pub const MAX_COUNT: u32 = 3;
+
+    /// Create a tracker with no recorded presses.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            last_press: None,
+            count: 0,
+        }
+    }
+
+    /// Record a mouse button press.
+    ///
+    /// # Params
+    ///
+    /// - `time`:              Time of the press, measured from any fixed point, such as the start of the program.
+    /// - `position`:          Position of the pointer.
+    /// - `double_click_time`: Maximum time between two presses of a multi-click; see [`Backend::double_click_time`].
+    ///
+    /// # Returns
+    ///
+    /// Click count after the press: 1 for a single click, 2 for a double click, 3 for a triple click.
+    pub fn press(&mut self, time: Duration, position: Vector2, double_click_time: Duration) -> u32 {
+        let continues = self.last_press.is_some_and(|(last_time, last_position)| {
+            time.saturating_sub(last_time) <= double_click_time
+                && (position - last_position).length_squared() <= Self::MAX_DISTANCE * Self::MAX_DISTANCE
+        });
+
+        self.count = if continues && self.count < Self::MAX_COUNT {
+            self.count + 1
+        } else {
+            1
+        };
+        self.last_press = Some((time, position));
+        self.count
+    }
+
+    /// Click count of the last press: 1 for a single click, 2 for a double click, 3 for a triple click. 0 if there
+    /// were no presses.
+    #[inline]
+    pub const fn click_count(&self) -> u32 {
+        self.count
+    }
+
+    /// Forget previous presses.
+    #[inline]
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
 }
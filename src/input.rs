@@ -1,6 +1,9 @@
-use crate::backend::Backend;
+use std::{fmt, time::Duration};
+
+use crate::backend::{Backend, ButtonRepeat};
 
 /// Default input actions one can listen to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum FluidInputAction {
     // Basic
     /// Press the input. Used for example to activate buttons.
@@ -127,24 +130,199 @@ pub enum FluidInputAction {
     PageDown,
 }
 
+impl FluidInputAction {
+    /// Get the ID of the action.
+    ///
+    /// The ID is the address of a static reserved for the action, so it is unique within the process and will never
+    /// collide with actions defined elsewhere using the same mechanism. IDs are stable within a run, but not across
+    /// runs, so they must not be saved.
+    ///
+    /// ```
+    /// # use amity_ui::input::FluidInputAction;
+    /// assert_ne!(FluidInputAction::Copy.id(), FluidInputAction::Paste.id());
+    /// assert_eq!(FluidInputAction::Copy.id(), FluidInputAction::Copy.id());
+    /// assert!(FluidInputAction::Copy.id().is(FluidInputAction::Copy));
+    /// assert!(!FluidInputAction::Copy.id().is(FluidInputAction::Paste));
+    /// ```
+    pub fn id(self) -> InputActionID {
+        static IDS: [u8; FluidInputAction::PageDown as usize + 1] = [0; FluidInputAction::PageDown as usize + 1];
+
+        InputActionID {
+            id: std::ptr::from_ref(&IDS[self as usize]) as usize,
+        }
+    }
+}
+
 /// ID of an input action.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct InputActionID {
     /// Unique ID of the action.
     pub id: usize,
 }
 
+impl InputActionID {
+    /// Check if this is the ID of the given action.
+    #[inline]
+    pub fn is(&self, action: FluidInputAction) -> bool {
+        *self == action.id()
+    }
+}
+
+impl From<FluidInputAction> for InputActionID {
+    #[inline]
+    fn from(value: FluidInputAction) -> Self {
+        value.id()
+    }
+}
+
 pub enum InputStrokeItem<B: Backend> {
     KeyboardKey(B::KeyboardKey),
     MouseButton(B::MouseButton),
     GamepadButton(B::GamepadButton),
 }
 
+impl<B: Backend> PartialEq for InputStrokeItem<B>
+where
+    B::KeyboardKey: PartialEq,
+    B::MouseButton: PartialEq,
+    B::GamepadButton: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::KeyboardKey(a), Self::KeyboardKey(b)) => a == b,
+            (Self::MouseButton(a), Self::MouseButton(b)) => a == b,
+            (Self::GamepadButton(a), Self::GamepadButton(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl<B: Backend> Clone for InputStrokeItem<B>
+where
+    B::KeyboardKey: Clone,
+    B::MouseButton: Clone,
+    B::GamepadButton: Clone,
+{
+    fn clone(&self) -> Self {
+        match self {
+            Self::KeyboardKey(key) => Self::KeyboardKey(key.clone()),
+            Self::MouseButton(button) => Self::MouseButton(button.clone()),
+            Self::GamepadButton(button) => Self::GamepadButton(button.clone()),
+        }
+    }
+}
+
+impl<B: Backend> InputStrokeItem<B>
+where
+    B::KeyboardKey: Copy,
+    B::MouseButton: Copy,
+{
+    /// Check if the key or button is held down.
+    ///
+    /// Gamepad buttons always return false, since the backend has to be told which gamepad to read from.
+    pub fn is_down(&self, backend: &B) -> bool {
+        match *self {
+            Self::KeyboardKey(key) => backend.is_key_down(key),
+            Self::MouseButton(button) => backend.is_mouse_button_down(button),
+            Self::GamepadButton(_) => false,
+        }
+    }
+
+    /// Check if the key or button has just been pressed. Keyboard keys also trigger when repeated.
+    ///
+    /// Gamepad buttons always return false, since the backend has to be told which gamepad to read from.
+    pub fn is_active(&self, backend: &B) -> bool {
+        match *self {
+            Self::KeyboardKey(key) => backend.is_key_pressed(key) || backend.is_key_repeated(key),
+            Self::MouseButton(button) => backend.is_mouse_button_pressed(button),
+            Self::GamepadButton(_) => false,
+        }
+    }
+}
+
 /// Represents a key or button input combination.
 pub struct InputStroke<B: Backend> {
     pub input: Vec<InputStrokeItem<B>>,
 }
 
+impl<B: Backend> InputStroke<B>
+where
+    B::KeyboardKey: FromKeyName,
+    B::MouseButton: FromKeyName,
+{
+    /// Parse a stroke from a human-readable form, such as `ctrl+shift+c` or `alt+mouse_left`.
+    ///
+    /// Items are separated by `+`. Whitespace around items is ignored, and names are case-insensitive. Modifiers are
+    /// named `ctrl`, `shift`, `alt` and `super`.
+    ///
+    /// # Params
+    ///
+    /// - `text`: Text to parse.
+    ///
+    /// # Returns
+    ///
+    /// The parsed stroke, with items in the order they were listed.
+    ///
+    /// # See Also
+    ///
+    /// - [`FromKeyName`] for the names of keys and buttons.
+    ///
+    /// ```
+    /// # use amity_ui::backend::raylib::RaylibBackend;
+    /// # use amity_ui::input::{FromKeyName, InputStroke, InputStrokeItem, ParseInputStrokeError};
+    /// # use raylib::prelude::{KeyboardKey, MouseButton};
+    /// let stroke = InputStroke::<RaylibBackend>::parse("ctrl+shift+c").unwrap();
+    /// assert!(matches!(stroke[..], [
+    ///     InputStrokeItem::KeyboardKey(KeyboardKey::KEY_LEFT_CONTROL),
+    ///     InputStrokeItem::KeyboardKey(KeyboardKey::KEY_LEFT_SHIFT),
+    ///     InputStrokeItem::KeyboardKey(KeyboardKey::KEY_C),
+    /// ]));
+    ///
+    /// let stroke = InputStroke::<RaylibBackend>::parse(" Alt + MOUSE_LEFT ").unwrap();
+    /// assert!(matches!(stroke[..], [
+    ///     InputStrokeItem::KeyboardKey(KeyboardKey::KEY_LEFT_ALT),
+    ///     InputStrokeItem::MouseButton(MouseButton::MOUSE_BUTTON_LEFT),
+    /// ]));
+    ///
+    /// // names round-trip through `key_name`
+    /// let name = stroke.iter()
+    ///     .map(|item| match item {
+    ///         InputStrokeItem::KeyboardKey(key) => key.key_name(),
+    ///         InputStrokeItem::MouseButton(button) => button.key_name(),
+    ///         InputStrokeItem::GamepadButton(_) => unreachable!(),
+    ///     })
+    ///     .collect::<Vec<_>>()
+    ///     .join("+");
+    /// assert_eq!(name, "alt+mouse_left");
+    ///
+    /// assert_eq!(
+    ///     InputStroke::<RaylibBackend>::parse("ctrl+banana").err(),
+    ///     Some(ParseInputStrokeError::UnknownName("banana".to_string())),
+    /// );
+    /// assert_eq!(InputStroke::<RaylibBackend>::parse("ctrl+").err(), Some(ParseInputStrokeError::EmptyItem));
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, ParseInputStrokeError> {
+        let input = text.split('+')
+            .map(|item| {
+                let name = item.trim().to_ascii_lowercase();
+                if name.is_empty() {
+                    return Err(ParseInputStrokeError::EmptyItem);
+                }
+
+                if let Some(key) = B::KeyboardKey::from_key_name(&name) {
+                    Ok(InputStrokeItem::KeyboardKey(key))
+                } else if let Some(button) = B::MouseButton::from_key_name(&name) {
+                    Ok(InputStrokeItem::MouseButton(button))
+                } else {
+                    Err(ParseInputStrokeError::UnknownName(name))
+                }
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { input })
+    }
+}
+
 impl<B: Backend> IntoIterator for InputStroke<B> {
     type Item = InputStrokeItem<B>;
     type IntoIter = <Vec<Self::Item> as IntoIterator>::IntoIter;
@@ -168,12 +346,61 @@ impl<B: Backend> std::ops::DerefMut for InputStroke<B> {
     }
 }
 
+/// Keys and buttons that can be referred to by name, used by [`InputStroke::parse`].
+///
+/// Names are lowercase, with words separated by underscores, for example `page_up`. Mouse buttons are prefixed with
+/// `mouse_`, for example `mouse_left`.
+pub trait FromKeyName: Sized {
+    /// Find a key by its name.
+    ///
+    /// # Returns
+    ///
+    /// The key, or [`None`] if no key has the given name.
+    fn from_key_name(name: &str) -> Option<Self>;
+
+    /// Get the name of the key. Passing the name to [`Self::from_key_name`] must return the same key.
+    fn key_name(&self) -> &'static str;
+}
+
+/// Error returned by [`InputStroke::parse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseInputStrokeError {
+    /// One of the items was empty, for example in `ctrl+` or `ctrl++c`.
+    EmptyItem,
+    /// No key or button has the given name.
+    UnknownName(String),
+}
+
+impl fmt::Display for ParseInputStrokeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyItem => write!(f, "input stroke contains an empty item"),
+            Self::UnknownName(name) => write!(f, "unknown key or button name `{name}`"),
+        }
+    }
+}
+
+impl std::error::Error for ParseInputStrokeError {}
+
 /// Binding of an input stroke to an input action.
 pub struct InputBinding<B: Backend> {
     pub action: InputActionID,
     pub trigger: InputStrokeItem<B>,
 }
 
+impl<B: Backend> Clone for InputBinding<B>
+where
+    InputStrokeItem<B>: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            action: self.action,
+            trigger: self.trigger.clone(),
+        }
+    }
+}
+
 /// A layer groups input bindings by common key modifiers.
 pub struct InputLayer<B: Backend> {
     pub modifiers: InputStroke<B>,
@@ -200,3 +427,147 @@ impl<B: Backend> Ord for InputLayer<B> {
         other.modifiers.len().cmp(&self.modifiers.len())
     }
 }
+
+/// Gamepad buttons with a default purpose in the UI, used by [`GamepadNavigation::new`].
+pub trait DefaultGamepadBindings: Sized {
+    /// Get the default bindings: the D-pad moves focus in its direction, and face buttons press, cancel, or open the
+    /// context menu.
+    fn default_gamepad_bindings() -> Vec<(Self, FluidInputAction)>;
+}
+
+/// Maps gamepad input to focus navigation and other input actions.
+///
+/// Bound buttons, along with the left stick, emit actions once when pushed. If held, directional focus actions
+/// repeat, so focus moves at a steady rate rather than flying across the UI. Buttons repeat as reported by
+/// [`Backend::is_gamepad_button_repeated`], and the stick with [`Self::stick_repeat`].
+///
+/// ```
+/// # use amity_ui::backend::{ButtonRepeat, Vector2, headless::HeadlessBackend};
+/// # use amity_ui::input::{FluidInputAction, GamepadNavigation};
+/// # use raylib::consts::GamepadButton;
+/// let mut backend = HeadlessBackend::new();
+/// let mut navigation = GamepadNavigation::new(0);
+/// let right = GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT;
+///
+/// backend.press_gamepad_button(0, right);
+/// assert_eq!(navigation.update(&backend), [FluidInputAction::FocusRight.id()]);
+///
+/// // Holding the button repeats the action whenever the backend repeats the button
+/// backend.next_frame();
+/// assert!(navigation.update(&backend).is_empty());
+/// backend.next_frame();
+/// backend.repeat_gamepad_button(0, right);
+/// assert_eq!(navigation.update(&backend), [FluidInputAction::FocusRight.id()]);
+///
+/// // Holding the stick repeats the action once the delay has passed
+/// backend.release_gamepad_button(0, right);
+/// backend.set_left_stick(0, Vector2::new(0.0, 1.0));
+/// backend.next_frame();
+/// assert_eq!(navigation.update(&backend), [FluidInputAction::FocusDown.id()]);
+///
+/// let frames = ButtonRepeat::<()>::DELAY.div_duration_f32(HeadlessBackend::DELTA_TIME).ceil() as usize;
+/// let mut actions = Vec::new();
+/// for _ in 1..frames {
+///     backend.next_frame();
+///     actions.extend(navigation.update(&backend));
+/// }
+/// assert!(actions.is_empty());
+///
+/// backend.next_frame();
+/// assert_eq!(navigation.update(&backend), [FluidInputAction::FocusDown.id()]);
+/// ```
+pub struct GamepadNavigation<B: Backend> {
+    /// Gamepad to read input from.
+    pub gamepad: B::GamepadID,
+
+    /// Buttons and the actions they emit.
+    pub bindings: Vec<(B::GamepadButton, InputActionID)>,
+
+    /// How far the left stick has to be tilted, from `0.0` to `1.0`, to count as held in that direction.
+    pub stick_threshold: f32,
+
+    /// Repeats focus actions while the stick is held in their direction. Buttons are repeated by the backend
+    /// instead.
+    pub stick_repeat: ButtonRepeat<InputActionID>,
+}
+
+impl<B: Backend> GamepadNavigation<B>
+where
+    B::GamepadID: Copy,
+    B::GamepadButton: Copy,
+{
+    /// Default value for `stick_threshold`.
+    pub const STICK_THRESHOLD: f32 = 0.5;
+
+    /// Create a mapping with default bindings for the given gamepad.
+    pub fn new(gamepad: B::GamepadID) -> Self
+    where
+        B::GamepadButton: DefaultGamepadBindings,
+    {
+        let bindings = B::GamepadButton::default_gamepad_bindings().into_iter()
+            .map(|(button, action)| (button, action.id()))
+            .collect();
+
+        Self::with_bindings(gamepad, bindings)
+    }
+
+    /// Create a mapping with custom bindings for the given gamepad.
+    pub fn with_bindings(gamepad: B::GamepadID, bindings: Vec<(B::GamepadButton, InputActionID)>) -> Self {
+        Self {
+            gamepad,
+            bindings,
+            stick_threshold: Self::STICK_THRESHOLD,
+            stick_repeat: ButtonRepeat::default(),
+        }
+    }
+
+    /// Read gamepad input. Should be called once every frame.
+    ///
+    /// # Returns
+    ///
+    /// Actions that have triggered this frame.
+    pub fn update(&mut self, backend: &B) -> Vec<InputActionID> {
+        let stick: crate::backend::Vector2 = backend.gamepad_left_stick(self.gamepad).into();
+        let stick_actions = [
+            (stick.x <= -self.stick_threshold, FluidInputAction::FocusLeft),
+            (stick.x >=  self.stick_threshold, FluidInputAction::FocusRight),
+            (stick.y <= -self.stick_threshold, FluidInputAction::FocusUp),
+            (stick.y >=  self.stick_threshold, FluidInputAction::FocusDown),
+        ];
+        let stick_down: Vec<InputActionID> = stick_actions.into_iter()
+            .filter(|(is_down, _)| *is_down)
+            .map(|(_, action)| action.id())
+            .collect();
+
+        // Directions pushed this frame trigger right away, and then repeat
+        let stick_pushed: Vec<bool> = stick_down.iter()
+            .map(|action| !self.stick_repeat.is_held(action))
+            .collect();
+        self.stick_repeat.update(backend.delta_time(), stick_down.iter().copied());
+
+        let buttons = self.bindings.iter()
+            .filter(|(button, action)| {
+                backend.is_gamepad_button_pressed(self.gamepad, *button)
+                    || is_repeatable(*action) && backend.is_gamepad_button_repeated(self.gamepad, *button)
+            })
+            .map(|(_, action)| *action);
+        let stick = stick_down.iter().zip(stick_pushed)
+            .filter(|&(action, is_pushed)| is_pushed || self.stick_repeat.is_repeated(action))
+            .map(|(action, _)| *action);
+
+        let mut triggered = Vec::new();
+        for action in buttons.chain(stick) {
+            if !triggered.contains(&action) {
+                triggered.push(action);
+            }
+        }
+        triggered
+    }
+}
+
+/// Check if the action should repeat while held: true for directional focus actions.
+fn is_repeatable(action: InputActionID) -> bool {
+    [FluidInputAction::FocusLeft, FluidInputAction::FocusRight, FluidInputAction::FocusUp, FluidInputAction::FocusDown]
+        .into_iter()
+        .any(|direction| action.is(direction))
+}
@@ -1,17 +1,486 @@
+use crate::backend::{Rectangle, Vector2};
+
+/// Alignment of a node within the space allocated for it, on a single axis.
+///
+/// Alignment is independent from [`Layout::expand`]: `expand` decides how much space a node is given along the
+/// direction its parent arranges children in, while `NodeAlign` decides where the node is placed within that space.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
 pub enum NodeAlign {
+    /// Keep the natural size and place the node at the start (left or top) of the space.
+    #[default]
     Start,
+    /// Keep the natural size and place the node in the middle of the space.
     Center,
+    /// Keep the natural size and place the node at the end (right or bottom) of the space.
     End,
+    /// Stretch the node to cover all of the space.
+    ///
+    /// In a [`LayoutMode::Flow`], the space given to a node along the flow direction is exactly its natural size,
+    /// unless it expands, so `Fill` only has a visible effect on the cross axis.
     Fill,
 }
 
+impl NodeAlign {
+    /// Place content within the space allocated for it, on a single axis.
+    ///
+    /// # Params
+    ///
+    /// - `start`: Start of the allocated space.
+    /// - `space`: Length of the allocated space.
+    /// - `size`:  Natural length of the content.
+    ///
+    /// # Returns
+    ///
+    /// Start and length of the content.
+    pub fn place(self, start: f32, space: f32, size: f32) -> (f32, f32) {
+        match self {
+            Self::Start  => (start, size),
+            Self::Center => (start + (space - size) / 2.0, size),
+            Self::End    => (start + space - size, size),
+            Self::Fill   => (start, space.max(size)),
+        }
+    }
+}
+
+/// Direction on a plane.
+///
+/// ```
+/// # use amity_ui::{backend::Vector2, layout::Axis};
+/// let vector = Vector2::new(1.0, 2.0);
+/// assert_eq!(Axis::Horizontal.main_of(vector), vector.x);
+/// assert_eq!(Axis::Horizontal.cross_of(vector), vector.y);
+/// assert_eq!(Axis::Vertical.main_of(vector), vector.y);
+///
+/// assert_eq!(Axis::Horizontal.opposite(), Axis::Vertical);
+/// assert_eq!(Axis::Horizontal.opposite().opposite(), Axis::Horizontal);
+/// assert_eq!(Axis::from(true), Axis::Horizontal);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Axis {
+    Horizontal,
+    #[default]
+    Vertical,
+}
+
+impl Axis {
+    /// Get the axis perpendicular to this one.
+    #[inline]
+    pub const fn opposite(self) -> Self {
+        match self {
+            Self::Horizontal => Self::Vertical,
+            Self::Vertical   => Self::Horizontal,
+        }
+    }
+
+    /// Check if this is the horizontal axis.
+    #[inline]
+    pub const fn is_horizontal(self) -> bool {
+        matches!(self, Self::Horizontal)
+    }
+
+    /// Length of the vector along this axis.
+    #[inline]
+    pub const fn main_of(self, vector: Vector2) -> f32 {
+        match self {
+            Self::Horizontal => vector.x,
+            Self::Vertical   => vector.y,
+        }
+    }
+
+    /// Length of the vector along the axis perpendicular to this one.
+    #[inline]
+    pub const fn cross_of(self, vector: Vector2) -> f32 {
+        self.opposite().main_of(vector)
+    }
+
+    /// Pick the item for this axis out of a `[horizontal, vertical]` pair, as used by [`Layout::node_align`] and
+    /// [`crate::style::Style::gap`].
+    #[inline]
+    pub const fn of<T: Copy>(self, pair: [T; 2]) -> T {
+        match self {
+            Self::Horizontal => pair[0],
+            Self::Vertical   => pair[1],
+        }
+    }
+
+    /// Create a vector out of its lengths along this axis and the perpendicular axis.
+    #[inline]
+    pub const fn vector(self, main: f32, cross: f32) -> Vector2 {
+        match self {
+            Self::Horizontal => Vector2::new(main, cross),
+            Self::Vertical   => Vector2::new(cross, main),
+        }
+    }
+}
+
+/// Convert an `is_horizontal` flag: true is horizontal, false is vertical.
+impl From<bool> for Axis {
+    #[inline]
+    fn from(is_horizontal: bool) -> Self {
+        if is_horizontal { Self::Horizontal } else { Self::Vertical }
+    }
+}
+
+/// Defines how a node arranges its children.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LayoutMode {
+    /// Place children one after another in a single row or column.
+    Flow {
+        direction: Axis,
+    },
+
+    /// Place children in a grid with a fixed number of columns. Rows are added as needed. All columns share the same
+    /// width, and each row is as tall as its tallest child.
+    Grid {
+        columns: u32,
+    },
+
+    /// Place children one after another along the axis, starting a new line whenever the next child wouldn't fit.
+    /// Each line is as thick as its thickest child.
+    Wrap {
+        axis: Axis,
+    },
+}
+
+impl Default for LayoutMode {
+    /// Children are placed in a column.
+    #[inline]
+    fn default() -> Self {
+        Self::Flow { direction: Axis::Vertical }
+    }
+}
+
+impl LayoutMode {
+    /// Measure space needed to fit the children.
+    ///
+    /// # Params
+    ///
+    /// - `children`:  Minimum size and layout of each child.
+    /// - `gap`:       Space left between neighboring columns (`x`) and rows (`y`).
+    /// - `available`: Space available for the children. Only used by [`Self::Wrap`] to decide where lines break.
+    ///
+    /// # Returns
+    ///
+    /// Minimum size of the content box.
+    pub fn measure(self, children: &[(Vector2, Layout)], gap: Vector2, available: Vector2) -> Vector2 {
+        match self {
+            Self::Flow { direction } => {
+                let gaps = children.len().saturating_sub(1) as f32 * direction.main_of(gap);
+                let main = children.iter().map(|(size, _)| direction.main_of(*size)).sum::<f32>() + gaps;
+                let cross = children.iter().map(|(size, _)| direction.cross_of(*size)).fold(0.0, f32::max);
+
+                direction.vector(main, cross)
+            }
+
+            Self::Grid { columns } => {
+                let columns = columns.max(1) as usize;
+                let column_count = children.len().min(columns);
+                let cell_width = children.iter().map(|(size, _)| size.x).fold(0.0, f32::max);
+                let rows = children.chunks(columns)
+                    .map(|row| row.iter().map(|(size, _)| size.y).fold(0.0, f32::max));
+                let row_count = children.len().div_ceil(columns);
+
+                Vector2::new(
+                    column_count as f32 * cell_width + column_count.saturating_sub(1) as f32 * gap.x,
+                    rows.sum::<f32>() + row_count.saturating_sub(1) as f32 * gap.y,
+                )
+            }
+
+            Self::Wrap { axis } => {
+                let lines = wrap_lines(children, axis, axis.main_of(gap), axis.main_of(available));
+                let main = lines.iter().map(|line| line.main).fold(0.0, f32::max);
+                let cross = lines.iter().map(|line| line.cross).sum::<f32>()
+                    + lines.len().saturating_sub(1) as f32 * axis.cross_of(gap);
+
+                axis.vector(main, cross)
+            }
+        }
+    }
+
+    /// Position children within the content box. Each child is given a cell, and then aligned within it according to
+    /// its [`Layout::node_align`].
+    ///
+    /// # Params
+    ///
+    /// - `children`:    Minimum size and layout of each child.
+    /// - `gap`:         Space left between neighboring columns (`x`) and rows (`y`).
+    /// - `content_box`: Box to place the children in.
+    ///
+    /// # Returns
+    ///
+    /// Box of each child, in the same order as `children`.
+    pub fn arrange(self, children: &[(Vector2, Layout)], gap: Vector2, content_box: Rectangle) -> Vec<Rectangle> {
+        let cells = match self {
+            Self::Flow { direction } => flow_cells(children, direction, gap, content_box),
+            Self::Grid { columns }   => grid_cells(children, columns, gap, content_box),
+            Self::Wrap { axis }      => wrap_cells(children, axis, gap, content_box),
+        };
+
+        cells.into_iter()
+            .zip(children)
+            .map(|(cell, (size, layout))| {
+                // Expanding nodes cover their cell along the flow direction, regardless of alignment
+                let size = match self {
+                    Self::Flow { direction } if layout.expand != 0 => {
+                        direction.vector(direction.main_of(cell.size()), direction.cross_of(*size))
+                    }
+                    _ => *size,
+                };
+
+                layout.place(cell, size)
+            })
+            .collect()
+    }
+}
+
 /// Node parameter for setting the node layout.
+///
+/// The default layout doesn't expand, aligns content to the start of both axes, and places children in a column.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
 pub struct Layout {
     /// Fraction of available space this node should occupy in the node direction.
     ///
     /// If set to `0`, the node doesn't have a strict size limit and has size based on content.
     pub expand: u32,
 
-    /// Align the content box to a side of the occupied space.
+    /// Align the content box to a side of the occupied space, or stretch it to cover it; horizontally and
+    /// vertically, respectively.
     pub node_align: [NodeAlign; 2],
+
+    /// Arrangement of the node's children.
+    pub mode: LayoutMode,
+}
+
+impl Layout {
+    /// Create a layout taking the given fraction of available space.
+    ///
+    /// # Params
+    ///
+    /// - `expand`: Value of [`Self::expand`].
+    #[inline]
+    pub const fn new(expand: u32) -> Self {
+        Self {
+            expand,
+            node_align: [NodeAlign::Start; 2],
+            mode: LayoutMode::Flow { direction: Axis::Vertical },
+        }
+    }
+
+    /// Create a layout that takes all available space, and fills it on both axes.
+    #[inline]
+    pub const fn fill() -> Self {
+        Self::new(1).align(NodeAlign::Fill, NodeAlign::Fill)
+    }
+
+    /// Set the alignment of the node, horizontally and vertically.
+    #[inline]
+    pub const fn align(mut self, x: NodeAlign, y: NodeAlign) -> Self {
+        self.node_align = [x, y];
+        self
+    }
+
+    /// Set the arrangement of the node's children.
+    #[inline]
+    pub const fn mode(mut self, mode: LayoutMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Place a node within the cell allocated for it, according to [`Self::node_align`].
+    ///
+    /// # Params
+    ///
+    /// - `cell`: Space allocated for the node.
+    /// - `size`: Minimum size of the node.
+    ///
+    /// ```
+    /// # use amity_ui::{backend::{Rectangle, Vector2}, layout::{Layout, NodeAlign}};
+    /// let cell = Rectangle::new(0.0, 0.0, 200.0, 100.0);
+    /// let size = Vector2::new(50.0, 20.0);
+    ///
+    /// // stretched horizontally, natural height centered vertically
+    /// let layout = Layout::default().align(NodeAlign::Fill, NodeAlign::Center);
+    /// assert_eq!(layout.place(cell, size), Rectangle::new(0.0, 40.0, 200.0, 20.0));
+    ///
+    /// let layout = Layout::default().align(NodeAlign::End, NodeAlign::Start);
+    /// assert_eq!(layout.place(cell, size), Rectangle::new(150.0, 0.0, 50.0, 20.0));
+    /// ```
+    ///
+    /// In a column, `Fill` stretches a node across the column without making it take more of its length, while
+    /// `expand` takes more of the column without stretching the node across:
+    ///
+    /// ```
+    /// # use amity_ui::{backend::{Rectangle, Vector2}, layout::{Layout, LayoutMode, NodeAlign}};
+    /// let children = [
+    ///     (Vector2::new(50.0, 20.0), Layout::default().align(NodeAlign::Fill, NodeAlign::Center)),
+    ///     (Vector2::new(50.0, 20.0), Layout::new(1)),
+    /// ];
+    /// let content_box = Rectangle::new(0.0, 0.0, 200.0, 100.0);
+    ///
+    /// assert_eq!(LayoutMode::default().arrange(&children, Vector2::default(), content_box), [
+    ///     Rectangle::new(0.0, 0.0, 200.0, 20.0),
+    ///     Rectangle::new(0.0, 20.0, 50.0, 80.0),
+    /// ]);
+    /// ```
+    pub fn place(&self, cell: Rectangle, size: Vector2) -> Rectangle {
+        let [(x, width), (y, height)] = [Axis::Horizontal, Axis::Vertical].map(|axis| {
+            self.align_of(axis).place(axis.main_of(cell.start()), axis.main_of(cell.size()), axis.main_of(size))
+        });
+
+        Rectangle::new(x, y, width, height)
+    }
+
+    /// Get the alignment of the node on the given axis.
+    #[inline]
+    pub const fn align_of(&self, axis: Axis) -> NodeAlign {
+        axis.of(self.node_align)
+    }
+}
+
+/// Create a [`Layout`] out of `key = value` pairs. Keys that aren't given keep their default value.
+///
+/// - `expand = n` sets [`Layout::expand`].
+/// - `align = (x, y)` sets alignment on both axes, and `align = x` only sets the horizontal alignment, leaving the
+///   vertical one at [`NodeAlign::Start`]. Alignment is given by the name of a [`NodeAlign`] variant.
+/// - `mode = mode` sets [`Layout::mode`].
+///
+/// ```
+/// # use amity_ui::{layout, layout::{Layout, NodeAlign}};
+/// assert!(layout!(expand = 2, align = (Center, Fill)) == Layout::new(2).align(NodeAlign::Center, NodeAlign::Fill));
+/// assert!(layout!(align = End).node_align == [NodeAlign::End, NodeAlign::Start]);
+/// assert!(layout!() == Layout::default());
+/// ```
+#[macro_export]
+macro_rules! layout {
+    (@set $layout:ident;) => {};
+    (@set $layout:ident; expand = $value:expr $(, $($rest:tt)*)?) => {
+        $layout.expand = $value;
+        $crate::layout!(@set $layout; $($($rest)*)?);
+    };
+    (@set $layout:ident; align = ($x:ident, $y:ident) $(, $($rest:tt)*)?) => {
+        $layout.node_align = [$crate::layout::NodeAlign::$x, $crate::layout::NodeAlign::$y];
+        $crate::layout!(@set $layout; $($($rest)*)?);
+    };
+    (@set $layout:ident; align = $x:ident $(, $($rest:tt)*)?) => {
+        $layout.node_align = [$crate::layout::NodeAlign::$x, $crate::layout::NodeAlign::Start];
+        $crate::layout!(@set $layout; $($($rest)*)?);
+    };
+    (@set $layout:ident; mode = $value:expr $(, $($rest:tt)*)?) => {
+        $layout.mode = $value;
+        $crate::layout!(@set $layout; $($($rest)*)?);
+    };
+    ($($args:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut layout = $crate::layout::Layout::default();
+        $crate::layout!(@set layout; $($args)*);
+        layout
+    }};
+}
+
+/// Cells of children placed in a single line. Expanding children share space left by the others, proportionally to
+/// their `expand` value.
+fn flow_cells(children: &[(Vector2, Layout)], direction: Axis, gap: Vector2, content_box: Rectangle) -> Vec<Rectangle> {
+    let gap = direction.main_of(gap);
+    let space = direction.main_of(content_box.size());
+    let cross = direction.cross_of(content_box.size());
+
+    let total_expand: u32 = children.iter().map(|(_, layout)| layout.expand).sum();
+    let fixed: f32 = children.iter()
+        .filter(|(_, layout)| layout.expand == 0)
+        .map(|(size, _)| direction.main_of(*size))
+        .sum();
+    let free_space = (space - fixed - children.len().saturating_sub(1) as f32 * gap).max(0.0);
+
+    let mut pen = 0.0;
+    children.iter()
+        .map(|(size, layout)| {
+            let length = if layout.expand == 0 {
+                direction.main_of(*size)
+            } else {
+                (free_space * layout.expand as f32 / total_expand as f32).max(direction.main_of(*size))
+            };
+            let cell = Rectangle::from_parts(content_box.start() + direction.vector(pen, 0.0), direction.vector(length, cross));
+
+            pen += length + gap;
+            cell
+        })
+        .collect()
+}
+
+/// Cells of children placed in a grid. Columns evenly divide the width of the content box.
+fn grid_cells(children: &[(Vector2, Layout)], columns: u32, gap: Vector2, content_box: Rectangle) -> Vec<Rectangle> {
+    let columns = columns.max(1) as usize;
+    let cell_width = ((content_box.width - (columns - 1) as f32 * gap.x) / columns as f32).max(0.0);
+    let mut cells = Vec::with_capacity(children.len());
+    let mut y = content_box.y;
+
+    for row in children.chunks(columns) {
+        let height = row.iter().map(|(size, _)| size.y).fold(0.0, f32::max);
+
+        for column in 0..row.len() {
+            let x = content_box.x + column as f32 * (cell_width + gap.x);
+            cells.push(Rectangle::new(x, y, cell_width, height));
+        }
+
+        y += height + gap.y;
+    }
+
+    cells
+}
+
+/// Cells of children placed in wrapped lines.
+fn wrap_cells(children: &[(Vector2, Layout)], axis: Axis, gap: Vector2, content_box: Rectangle) -> Vec<Rectangle> {
+    let main_gap = axis.main_of(gap);
+    let lines = wrap_lines(children, axis, main_gap, axis.main_of(content_box.size()));
+    let mut cells = Vec::with_capacity(children.len());
+    let mut cross_pen = 0.0;
+
+    for line in lines {
+        let mut pen = 0.0;
+
+        for (size, _) in &children[line.start..line.end] {
+            let main = axis.main_of(*size);
+            let start = content_box.start() + axis.vector(pen, cross_pen);
+
+            cells.push(Rectangle::from_parts(start, axis.vector(main, line.cross)));
+            pen += main + main_gap;
+        }
+
+        cross_pen += line.cross + axis.cross_of(gap);
+    }
+
+    cells
+}
+
+/// Line of children in [`LayoutMode::Wrap`].
+struct WrapLine {
+    /// Index of the first child in the line.
+    start: usize,
+    /// Index past the last child in the line.
+    end: usize,
+    /// Length of the line, including gaps.
+    main: f32,
+    /// Thickness of the line; thickness of the thickest child.
+    cross: f32,
+}
+
+/// Break children into lines no longer than `space`. Every line has at least one child, even if it doesn't fit.
+fn wrap_lines(children: &[(Vector2, Layout)], axis: Axis, gap: f32, space: f32) -> Vec<WrapLine> {
+    let mut lines: Vec<WrapLine> = Vec::new();
+
+    for (index, (size, _)) in children.iter().enumerate() {
+        let main = axis.main_of(*size);
+        let cross = axis.cross_of(*size);
+
+        if let Some(line) = lines.last_mut() && line.main + gap + main <= space {
+            line.end = index + 1;
+            line.main += gap + main;
+            line.cross = line.cross.max(cross);
+            continue;
+        }
+
+        lines.push(WrapLine { start: index, end: index + 1, main, cross });
+    }
+
+    lines
 }
@@ -11,15 +11,18 @@ pub mod hover;
 pub mod input;
 pub mod layout;
 pub mod node;
+pub mod resource;
 pub mod rope;
 pub mod scroll;
 pub mod scroll_input;
 pub mod static_id;
 pub mod style;
 pub mod tag_list;
+pub mod text;
 pub mod theme;
 pub mod tree;
 pub mod typeface;
+pub mod utils;
 
 pub mod prelude {
     pub use crate::{
@@ -34,14 +37,17 @@ pub mod prelude {
         hover,
         layout,
         node,
+        resource,
         rope,
         scroll,
         scroll_input,
         static_id,
         style,
         tag_list,
+        text,
         theme,
         tree,
         typeface,
+        utils,
     };
 }
@@ -1,6 +1,8 @@
+use std::{cell::RefCell, ops::Range, rc::{Rc, Weak}, time::Duration};
+
 use bitflags::bitflags;
 
-use crate::{backend::{Backend, Vector2}, layout::Layout, style::Style, theme::{Breadcrumbs, StyleDelegate, Theme}, tree::{LayoutTree, TreeAction}};
+use crate::{backend::{Backend, Rectangle, Vector2}, event::{Event, SimpleEvent}, input::{FluidInputAction, InputActionID}, layout::{Axis, Layout, LayoutMode}, resource::UnloadQueue, rope::Rope, scroll::Scrollable, scroll_input::ScrollInput, style::{SideArray, Style}, tag_list::{TagID, TagList}, text::{self, Caret, History, Selection, TextTexture}, theme::{Breadcrumbs, Rule, StyleDelegate, Theme}, tree::{LayoutTree, TreeActionRef}, typeface::{Typeface, wrap_lines}, utils::{NodeParam, simple_constructor}};
 
 bitflags! {
     /// This bitmask defines whether a node contains a point in its boundaries.
@@ -77,14 +79,13 @@ impl HitPassthrough {
     pub const fn filter(self, other: Self) -> Self {
         self.union(other)
     }
+}
 
+impl<B: Backend> NodeParam<B> for HitPassthrough {
     /// Set the node's opacity filter. This can be used as a node property - an opacity mask
     /// can be passed to a node builder.
-    ///
-    /// # Params
-    ///
-    /// - `node`: Node to change.
-    pub fn apply<B: Backend>(self, node: &mut Node<B>) {
+    #[inline]
+    fn apply(self, node: &mut Node<B>) {
         node.data.hit_passthrough = self;
     }
 }
@@ -109,7 +110,18 @@ pub struct NodeData<B: Backend> {
     /// Layout for this node.
     pub layout: Layout,
 
-    /// Breadcrumbs assigned and applicable to this node. Loaded every resize and every draw.
+    /// Children of this node, in drawing order. Nodes later in the list are drawn on top of earlier ones.
+    ///
+    /// Modified through [`ParentNode`], which keeps [`Self::parent`] of each child up to date.
+    pub(crate) children: Vec<Rc<RefCell<Node<B>>>>,
+
+    /// Node this node is a child of.
+    pub(crate) parent: Weak<RefCell<Node<B>>>,
+
+    /// Tags of the node, used by theme selectors.
+    pub tags: TagList,
+
+    /// Breadcrumbs assigned and applicable to this node. Assigned by the parent every resize.
     pub breadcrumbs: Breadcrumbs<B>,
 
     /// Filter to apply to every result of `in_bounds`, controlling how the node reacts to
@@ -122,22 +134,244 @@ pub struct NodeData<B: Backend> {
     /// The default value allows all events.
     pub hit_passthrough: HitPassthrough,
 
+    /// If true, input actions this node doesn't handle won't bubble up to its ancestors.
+    ///
+    /// # See Also
+    ///
+    /// - [`crate::action::bubble_action`]
+    pub stop_propagation: bool,
+
+    /// Position of the node in tab navigation order. Nodes with an index are visited first, in ascending order,
+    /// followed by nodes without one, in tree order. A negative index excludes the node from tab navigation; it can
+    /// still be focused directly, or with directional navigation.
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use amity_ui::{backend::headless::HeadlessBackend, focus::{FocusIO, Focusable}, input::*,
+    /// #     node::{Node, button, vframe}, tree::LayoutTree};
+    /// # use raylib::consts::KeyboardKey;
+    /// type Ref = Rc<RefCell<Node<HeadlessBackend>>>;
+    /// let new_button = |tab_index: i32| -> Ref {
+    ///     let node = Rc::new(RefCell::new(button((), "")));
+    ///     node.borrow_mut().data.tab_index = Some(tab_index);
+    ///     node
+    /// };
+    /// let [two, one, three, skipped] = [2, 1, 3, -1].map(new_button);
+    /// let root = vframe((), [two.clone(), one.clone(), skipped.clone(), three.clone()]);
+    ///
+    /// let mut tree = LayoutTree::new(root, HeadlessBackend::new());
+    /// tree.bound_inputs.push(InputLayer {
+    ///     modifiers: InputStroke { input: Vec::new() },
+    ///     bindings: vec![InputBinding {
+    ///         action: FluidInputAction::FocusNext.id(),
+    ///         trigger: InputStrokeItem::KeyboardKey(KeyboardKey::KEY_TAB),
+    ///     }],
+    /// });
+    /// tree.draw();
+    ///
+    /// let mut order = Vec::new();
+    /// for _ in 0..4 {
+    ///     tree.backend.press_key(KeyboardKey::KEY_TAB);
+    ///     tree.draw();
+    ///     tree.backend.release_key(KeyboardKey::KEY_TAB);
+    ///     tree.backend.next_frame();
+    ///     order.push(tree.focus.focus().unwrap().borrow().data.tab_index.unwrap());
+    /// }
+    /// assert_eq!(order, [1, 2, 3, 1]);
+    /// ```
+    pub tab_index: Option<i32>,
+
+    /// If true, the node is a focus trap: while the focused node is inside, keyboard and gamepad navigation can't move
+    /// focus out of it. Tab navigation wraps around to the first or last focusable node within the trap instead.
+    /// Useful for modal dialogs.
+    pub is_focus_trap: bool,
+
+    /// If true, the first focusable node inside this node is focused whenever it is shown, including the first time
+    /// it is drawn. Useful for dialogs. See [`LayoutTree::focus_first`].
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use amity_ui::{backend::headless::HeadlessBackend, focus::{FocusIO, Focusable},
+    /// #     node::{Node, button, label, vframe}, tree::LayoutTree};
+    /// type Ref = Rc<RefCell<Node<HeadlessBackend>>>;
+    /// let new_button = |text: &str| -> Ref { Rc::new(RefCell::new(button((), text))) };
+    /// let [open, first, second] = ["Open", "First", "Second"].map(new_button);
+    /// let title: Ref = Rc::new(RefCell::new(label((), "Dialog")));
+    /// let dialog = vframe((), [title, first.clone(), second.clone()]);
+    /// dialog.borrow_mut().data.auto_focus = true;
+    /// dialog.borrow_mut().data.set_hidden(true);
+    /// let root = vframe((), [open.clone(), dialog.clone()]);
+    ///
+    /// let mut tree = LayoutTree::new(root, HeadlessBackend::new());
+    /// open.clone().focus(&mut tree.focus);
+    /// tree.draw();
+    /// assert!(tree.focus.is_focused(&open));
+    ///
+    /// // Showing the dialog focuses its first field
+    /// dialog.borrow_mut().data.set_hidden(false);
+    /// tree.draw();
+    /// assert!(tree.focus.is_focused(&first));
+    ///
+    /// // It only happens once, when the dialog is shown
+    /// second.clone().focus(&mut tree.focus);
+    /// tree.draw();
+    /// assert!(tree.focus.is_focused(&second));
+    /// ```
+    pub auto_focus: bool,
+
+    /// True if the node has been shown since it was last drawn, and should be focused if [`Self::auto_focus`] is set.
+    pub(crate) is_auto_focus_pending: bool,
+
+    /// If true, the node hides itself when it receives [`FluidInputAction::Cancel`], either from a child or while
+    /// focused, and dispatches [`Self::on_dismiss`]. If focus was inside, it returns to [`Self::opener`]. Useful for
+    /// dialogs, menus and popovers. Only the innermost such node closes, since it handles the action.
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use amity_ui::{backend::headless::HeadlessBackend, focus::{FocusIO, Focusable}, input::*,
+    /// #     node::{Node, button, vframe}, tree::LayoutTree};
+    /// # use raylib::consts::KeyboardKey;
+    /// type Ref = Rc<RefCell<Node<HeadlessBackend>>>;
+    /// let new_button = |text: &str| -> Ref { Rc::new(RefCell::new(button((), text))) };
+    /// let [open, first_field, second_field] = ["Open", "First", "Second"].map(new_button);
+    /// let dismissed = Rc::new(RefCell::new(Vec::new()));
+    /// let overlay = |name: &'static str, field: &Ref| {
+    ///     let node = vframe((), [field.clone()]);
+    ///     {
+    ///         let data = &mut node.borrow_mut().data;
+    ///         data.auto_focus = true;
+    ///         data.dismiss_on_cancel = true;
+    ///         data.set_hidden(true);
+    ///         let dismissed = dismissed.clone();
+    ///         data.on_dismiss.subscribe(move |_| dismissed.borrow_mut().push(name));
+    ///     }
+    ///     node
+    /// };
+    /// let [first, second] = [overlay("first", &first_field), overlay("second", &second_field)];
+    /// let root = vframe((), [open.clone(), first.clone(), second.clone()]);
+    ///
+    /// let mut tree = LayoutTree::new(root, HeadlessBackend::new());
+    /// tree.bound_inputs.push(InputLayer {
+    ///     modifiers: InputStroke { input: Vec::new() },
+    ///     bindings: vec![InputBinding {
+    ///         action: FluidInputAction::Cancel.id(),
+    ///         trigger: InputStrokeItem::KeyboardKey(KeyboardKey::KEY_ESCAPE),
+    ///     }],
+    /// });
+    /// let escape = |tree: &mut LayoutTree<HeadlessBackend>| {
+    ///     tree.backend.press_key(KeyboardKey::KEY_ESCAPE);
+    ///     tree.draw();
+    ///     tree.backend.release_key(KeyboardKey::KEY_ESCAPE);
+    ///     tree.backend.next_frame();
+    /// };
+    /// open.clone().focus(&mut tree.focus);
+    /// tree.draw();
+    ///
+    /// // Open both overlays, one over the other
+    /// first.borrow_mut().data.set_hidden(false);
+    /// tree.draw();
+    /// second.borrow_mut().data.set_hidden(false);
+    /// tree.draw();
+    /// assert!(tree.focus.is_focused(&second_field));
+    ///
+    /// // Escape only closes the top overlay, and returns focus to where it was when the overlay opened
+    /// escape(&mut tree);
+    /// assert!(second.borrow().data.is_hidden() && !first.borrow().data.is_hidden());
+    /// assert!(tree.focus.is_focused(&first_field));
+    ///
+    /// escape(&mut tree);
+    /// assert!(first.borrow().data.is_hidden());
+    /// assert!(tree.focus.is_focused(&open));
+    /// assert_eq!(*dismissed.borrow(), ["second", "first"]);
+    /// ```
+    pub dismiss_on_cancel: bool,
+
+    /// Dispatched when the node is dismissed with [`FluidInputAction::Cancel`]; see [`Self::dismiss_on_cancel`].
+    pub on_dismiss: SimpleEvent,
+
+    /// Node focus returns to when this node is dismissed. Set to the previously focused node when
+    /// [`Self::auto_focus`] moves focus into this node.
+    pub opener: Weak<RefCell<Node<B>>>,
+
+    /// If true, children of this node are only drawn within its padding box.
+    pub clip_children: bool,
+
+    /// Opacity of the node and its children, in range `0.0..=1.0`, multiplied into the tint alpha when drawing.
+    opacity: f32,
+
     /// Minimum size of the node.
     pub(crate) min_size: Vector2,
 
+    /// Margin box of the node, in window coordinates, as of the last draw.
+    pub(crate) margin_box: Rectangle,
+
+    /// Padding box of the node, in window coordinates, as of the last draw.
+    pub(crate) padding_box: Rectangle,
+
+    /// Content box of the node, in window coordinates, as of the last draw.
+    pub(crate) content_box: Rectangle,
+
     /// If true, this node must update its size.
     is_resize_pending: bool,
 
     /// If true, this node is hidden and won't be rendered.
     is_hidden: bool,
 
+    /// Dispatched when the node is shown or hidden, either directly or because an ancestor was. The argument is true
+    /// if the node became visible.
+    ///
+    /// # See Also
+    ///
+    /// - [`Self::is_effectively_visible`]
+    pub on_visibility_changed: Event<bool>,
+
     /// If true, this node is currently hovered.
     is_hovered: bool,
 
+    /// Dispatched when the mouse starts hovering the node.
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use amity_ui::{backend::{Backend, Vector2, headless::HeadlessBackend}, layout::Layout, node::{Node, button, vframe},
+    /// #     style::Style, tree::LayoutTree};
+    /// let style = Style::default().with_padding([10.0; 4]);
+    /// let node: Rc<RefCell<Node<HeadlessBackend>>> = Rc::new(RefCell::new(button(style, "")));
+    /// let root = vframe(Layout::fill(), [node.clone()]);
+    /// let events = Rc::new(RefCell::new(Vec::new()));
+    /// {
+    ///     let data = &mut node.borrow_mut().data;
+    ///     let enter = events.clone();
+    ///     data.on_hover_enter.subscribe(move |_| enter.borrow_mut().push("enter"));
+    ///     let leave = events.clone();
+    ///     data.on_hover_leave.subscribe(move |_| leave.borrow_mut().push("leave"));
+    /// }
+    ///
+    /// let mut backend = HeadlessBackend::new();
+    /// backend.set_window_size(Vector2::new(100.0, 100.0));
+    /// let mut tree = LayoutTree::new(root, backend);
+    /// tree.draw();
+    /// let node_box = node.borrow().data.padding_box();
+    ///
+    /// // Staying over the node doesn't fire the event again
+    /// for position in [node_box.center(), node_box.center(), Vector2::new(90.0, 90.0), Vector2::new(95.0, 95.0)] {
+    ///     tree.backend.set_mouse_position(position);
+    ///     tree.draw();
+    /// }
+    /// assert_eq!(*events.borrow(), ["enter", "leave"]);
+    /// ```
+    pub on_hover_enter: SimpleEvent,
+
+    /// Dispatched when the mouse stops hovering the node, either because it moved away, or because the node was
+    /// removed from the tree.
+    pub on_hover_leave: SimpleEvent,
+
     /// If true, this node is currently disabled.
     is_disabled: bool,
     /// Check if this node is disabled, or has inherited the status.
-    is_disabled_inherited: bool,
+    pub(crate) is_disabled_inherited: bool,
+
+    /// True if the node is in a subtree drawn by a [`Portal`]. See [`Self::is_mirrored`].
+    pub(crate) is_mirrored: bool,
 
     /// If true, this node will be removed from the tree on the next draw.
     to_remove: bool,
@@ -150,17 +384,26 @@ pub struct NodeData<B: Backend> {
     /// This can be set to false to reset the theme.
     pub is_theme_explicit: bool,
 
+    /// Style set with [`Self::set_style`]. Rules of the theme are applied on top of it.
+    base_style: Style<B>,
+
     /// Cached style for this node.
     style: Style<B>,
 
     /// Attached styling delegates.
     style_delegates: Vec<StyleDelegate<B>>,
 
+    /// Rules returned by style delegates during the last draw. They're applied last when the style is reloaded.
+    delegate_rules: Vec<Rule<B>>,
+
+    /// Texture the node's text is drawn into, created on the first draw. See [`Self::text_texture`].
+    text_texture: Option<TextTexture<B>>,
+
     /// Actions queued for this node; only used for queueing actions before the first `resize`; afterwards, all
     /// actions are queued directly into the tree.
     ///
     /// Queues into `TreeContext`.
-    queued_actions: Vec<TreeAction<B>>,
+    queued_actions: Vec<TreeActionRef<B>>,
 }
 
 impl<B: Backend> NodeData<B> {
@@ -173,40 +416,314 @@ impl<B: Backend> NodeData<B> {
     ///
     /// # See Also
     /// - [`crate::utils::simple_constructor`]
-    pub const fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             tree: None,
-            layout: todo!(),
-            breadcrumbs: todo!(),
-            hit_passthrough: todo!(),
+            layout: Layout::default(),
+            children: Vec::new(),
+            parent: Weak::new(),
+            tags: TagList::default(),
+            breadcrumbs: Breadcrumbs::default(),
+            hit_passthrough: HitPassthrough::Opaque,
+            stop_propagation: false,
+            tab_index: None,
+            is_focus_trap: false,
+            auto_focus: false,
+            is_auto_focus_pending: true,
+            dismiss_on_cancel: false,
+            on_dismiss: Event::new(),
+            opener: Weak::new(),
+            clip_children: false,
+            opacity: 1.0,
             min_size: Vector2::default(),
+            margin_box: Rectangle::default(),
+            padding_box: Rectangle::default(),
+            content_box: Rectangle::default(),
             is_resize_pending: true,
             is_hidden: false,
+            on_visibility_changed: Event::new(),
             is_hovered: false,
+            on_hover_enter: Event::new(),
+            on_hover_leave: Event::new(),
             is_disabled: false,
             is_disabled_inherited: false,
+            is_mirrored: false,
             to_remove: false,
             theme: Theme::new(),
             is_theme_explicit: false,
-            style: todo!(),
+            base_style: Style::default(),
+            style: Style::default(),
             style_delegates: Vec::new(),
+            delegate_rules: Vec::new(),
+            text_texture: None,
             queued_actions: Vec::new(),
         }
     }
 
+    /// Children of this node, in drawing order.
+    #[inline]
+    pub fn children(&self) -> &[Rc<RefCell<Node<B>>>] {
+        &self.children
+    }
+
+    /// # Returns
+    ///
+    /// The node this node is a child of, or `None` if it's the root node, or hasn't been added to a parent.
+    #[inline]
+    pub fn parent(&self) -> Option<Rc<RefCell<Node<B>>>> {
+        self.parent.upgrade()
+    }
+
     /// Check if the node is hidden.
     #[inline]
     pub const fn is_hidden(&self) -> bool {
         self.is_hidden || self.to_remove
     }
 
-    /// Set the visibility
+    /// Check if the node is disabled, either explicitly or because one of its ancestors is.
+    #[inline]
+    pub const fn is_disabled(&self) -> bool {
+        self.is_disabled || self.is_disabled_inherited
+    }
+
+    /// Check if the node is in a subtree drawn by a [`Portal`] during the last frame.
+    #[inline]
+    pub const fn is_mirrored(&self) -> bool {
+        self.is_mirrored
+    }
+
+    /// Check if the node blocks input: it is disabled, hidden, or mirrored by a portal. Nodes that block input can't
+    /// take focus or be hovered, and don't receive input actions.
+    #[inline]
+    pub const fn blocks_input(&self) -> bool {
+        self.is_disabled() || self.is_hidden() || self.is_mirrored
+    }
+
+    /// Check if the node has been marked for removal with [`Self::remove`].
+    #[inline]
+    pub const fn is_removed(&self) -> bool {
+        self.to_remove
+    }
+
+    /// Remove the node from the tree before the next draw. The node is hidden immediately.
+    #[inline]
+    pub fn remove(&mut self) {
+        self.to_remove = true;
+        self.update_size();
+    }
+
+    /// Disable or enable the node. Disabled nodes, and their children, don't take input.
+    ///
+    /// Children inherit the status immediately; the inherited status is also refreshed every time the tree is drawn.
+    ///
+    /// # Params
+    ///
+    /// - `value`: True to disable the node.
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use amity_ui::{action::Actionable, backend::headless::HeadlessBackend, node::{button, vframe}};
+    /// let new_button = |text: &str| Rc::new(RefCell::new(button::<HeadlessBackend>((), text)));
+    /// let [first, second] = ["First", "Second"].map(new_button);
+    /// second.borrow_mut().data.set_disabled(true);
+    /// let container = vframe((), [first.clone(), second.clone()]);
+    /// assert!(!first.blocks_input());
+    ///
+    /// // Children inherit the disabled status
+    /// container.borrow_mut().data.set_disabled(true);
+    /// assert!(first.blocks_input() && second.blocks_input());
+    ///
+    /// // Enabling the container only enables children that weren't disabled on their own
+    /// container.borrow_mut().data.set_disabled(false);
+    /// assert!(!first.blocks_input());
+    /// assert!(second.blocks_input());
+    /// ```
+    pub fn set_disabled(&mut self, value: bool) {
+        self.is_disabled = value;
+        self.propagate_disabled();
+    }
+
+    /// Set the disabled status inherited from the parent, and pass it on to the children.
+    ///
+    /// # Params
+    ///
+    /// - `value`: True if any ancestor of the node is disabled.
+    pub(crate) fn inherit_disabled(&mut self, value: bool) {
+        self.is_disabled_inherited = value;
+        self.propagate_disabled();
+    }
+
+    /// Pass the disabled status of this node on to its children.
+    fn propagate_disabled(&self) {
+        let value = self.is_disabled();
+
+        for child in &self.children {
+            child.borrow_mut().data.inherit_disabled(value);
+        }
+    }
+
+    /// Check if the node is hovered by the mouse.
+    #[inline]
+    pub const fn is_hovered(&self) -> bool {
+        self.is_hovered
+    }
+
+    /// Change the hover status, dispatching [`Self::on_hover_enter`] or [`Self::on_hover_leave`] if it changed. Called
+    /// by the hover system.
+    pub(crate) fn set_hovered(&mut self, value: bool) {
+        if self.is_hovered == value { return; }
+
+        self.is_hovered = value;
+        if value {
+            self.on_hover_enter.dispatch(&());
+        } else {
+            self.on_hover_leave.dispatch(&());
+        }
+    }
+
+    /// Opacity of the node, in range `0.0..=1.0`.
+    #[inline]
+    pub const fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    /// Change the opacity of the node. Opacity multiplies the alpha of everything drawn by the node and its
+    /// descendants; opacity of nested nodes stacks.
+    ///
+    /// Opacity is applied through the tree's tint, so it has no effect while the tint is
+    /// [locked](crate::context::TreeContextData::lock_tint).
+    ///
+    /// # Params
+    ///
+    /// - `value`: New opacity, clamped to `0.0..=1.0`. NaN is treated as fully opaque.
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use amity_ui::{backend::{Color, headless::{DrawCommand, HeadlessBackend}}, node::{Node, NodeVariant, vframe},
+    /// #     style::Style, tree::LayoutTree, utils::simple_constructor};
+    /// let red = Color::new(255, 0, 0, 255);
+    /// let child: Rc<RefCell<Node<HeadlessBackend>>> = Rc::new(RefCell::new(simple_constructor(
+    ///     NodeVariant::Space,
+    ///     Style::default().with_background_color(red),
+    /// )));
+    /// let root = vframe((), [child]);
+    /// root.borrow_mut().data.set_opacity(0.5);
+    ///
+    /// let mut tree = LayoutTree::new(root, HeadlessBackend::new());
+    /// tree.draw();
+    ///
+    /// let colors: Vec<_> = tree.backend.draw_commands.iter()
+    ///     .filter_map(|command| match command {
+    ///         DrawCommand::Rectangle { color, .. } if color.r == 255 => Some(*color),
+    ///         _ => None,
+    ///     })
+    ///     .collect();
+    /// assert_eq!(colors, [Color::new(255, 0, 0, 128)]);
+    /// ```
+    #[inline]
+    pub fn set_opacity(&mut self, value: f32) {
+        self.opacity = if value.is_nan() { 1.0 } else { value.clamp(0.0, 1.0) };
+    }
+
+    /// Style computed for this node during the last resize: the style set with [`Self::set_style`], changed by rules
+    /// of the node's theme.
+    ///
+    /// # See Also
+    ///
+    /// - [`Node::reload_style`]
+    #[inline]
+    pub fn style(&self) -> &Style<B> {
+        &self.style
+    }
+
+    /// Style of the node along with the texture its text is drawn into, for nodes that draw text. The texture is
+    /// created on first use.
+    ///
+    /// # Params
+    ///
+    /// - `unload_queue`: Queue the texture passes replaced textures to.
+    pub(crate) fn text_texture(&mut self, unload_queue: &UnloadQueue<B>) -> (&Style<B>, &mut TextTexture<B>) {
+        let texture = self.text_texture.get_or_insert_with(|| TextTexture::new(unload_queue));
+        (&self.style, texture)
+    }
+
+    /// Replace the style of the node. The style is used as-is until the next resize, when rules of the theme are
+    /// applied on top of it.
+    #[inline]
+    pub fn set_style(&mut self, value: Style<B>) {
+        self.base_style = value.clone();
+        self.style = value;
+        self.update_size();
+    }
+
+    /// Attach a style delegate to the node. Delegates are called every frame, in the order they were added, after
+    /// delegates of the theme's rules.
+    ///
+    /// # See Also
+    ///
+    /// - [`Node::update_style`]
     #[inline]
+    pub fn add_style_delegate(&mut self, delegate: StyleDelegate<B>) {
+        self.style_delegates.push(delegate);
+    }
+
+    /// Check if the node is visible: neither the node nor any of its ancestors are hidden.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an ancestor of the node is currently borrowed mutably.
+    pub fn is_effectively_visible(&self) -> bool {
+        !self.is_hidden() && self.parent().is_none_or(|parent| parent.borrow().data.is_effectively_visible())
+    }
+
+    /// Set the visibility. If the node's effective visibility changes, [`Self::on_visibility_changed`] is dispatched
+    /// for the node and for every descendant that isn't hidden on its own.
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use amity_ui::{backend::headless::HeadlessBackend, node::{Node, label, vframe}};
+    /// let child: Rc<RefCell<Node<HeadlessBackend>>> = Rc::new(RefCell::new(label((), "Child")));
+    /// let parent = vframe((), [child.clone()]);
+    /// let events = Rc::new(RefCell::new(Vec::new()));
+    /// let child_events = events.clone();
+    /// child.borrow_mut().data.on_visibility_changed.subscribe(move |value| child_events.borrow_mut().push(*value));
+    ///
+    /// parent.borrow_mut().data.set_hidden(true);
+    /// assert!(!child.borrow().data.is_hidden());
+    /// assert!(!child.borrow().data.is_effectively_visible());
+    ///
+    /// parent.borrow_mut().data.set_hidden(false);
+    /// assert!(child.borrow().data.is_effectively_visible());
+    /// assert_eq!(*events.borrow(), [false, true]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if an ancestor or a descendant of the node is currently borrowed mutably.
     pub fn set_hidden(&mut self, value: bool) {
-        // If changed, trigger resize
-        if self.is_hidden != value { self.update_size(); }
+        if self.is_hidden == value { return; }
 
+        let was_hidden = self.is_hidden();
         self.is_hidden = value;
+        self.update_size();
+
+        let is_parent_visible = self.parent().is_none_or(|parent| parent.borrow().data.is_effectively_visible());
+        if is_parent_visible && was_hidden != self.is_hidden() {
+            self.dispatch_visibility_changed(!self.is_hidden());
+        }
+    }
+
+    /// Dispatch [`Self::on_visibility_changed`] for this node, and for descendants that aren't hidden on their own.
+    fn dispatch_visibility_changed(&mut self, is_visible: bool) {
+        self.is_auto_focus_pending = is_visible;
+        self.on_visibility_changed.dispatch(&is_visible);
+
+        for child in &self.children {
+            let mut child = child.borrow_mut();
+            if child.data.is_hidden() { continue; }
+
+            child.data.dispatch_visibility_changed(is_visible);
+        }
     }
 
     /// The theme defines how the node will appear to the user.
@@ -261,9 +778,73 @@ impl<B: Backend> NodeData<B> {
         self.update_size();
     }
 
-    /// Recalculate the window size before next draw.
+    /// Check if the node's size has to be recalculated.
+    #[inline]
+    pub const fn is_resize_pending(&self) -> bool {
+        self.is_resize_pending
+    }
+
+    /// Minimum size of the node, including margin, border and padding, as of the last resize.
+    #[inline]
+    pub const fn min_size(&self) -> Vector2 {
+        self.min_size
+    }
+
+    /// Space allocated for the node by its parent, in window coordinates, as of the last draw. Empty if the node
+    /// hasn't been drawn yet.
+    #[inline]
+    pub const fn margin_box(&self) -> Rectangle {
+        self.margin_box
+    }
+
+    /// Box of the node inside its border, in window coordinates, as of the last draw. Empty if the node hasn't been
+    /// drawn yet.
+    #[inline]
+    pub const fn padding_box(&self) -> Rectangle {
+        self.padding_box
+    }
+
+    /// Box of the node's content and children, excluding padding, in window coordinates, as of the last draw. Empty
+    /// if the node hasn't been drawn yet.
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use amity_ui::{backend::{Rectangle, headless::HeadlessBackend}, node::{Node, NodeVariant, vframe},
+    /// #     style::Style, tree::LayoutTree, utils::simple_constructor};
+    /// let child: Rc<RefCell<Node<HeadlessBackend>>> = Rc::new(RefCell::new(simple_constructor(NodeVariant::Space, ())));
+    /// let root = vframe(Style::default().with_padding([10.0, 20.0, 30.0, 40.0]), [child.clone()]);
+    ///
+    /// let mut tree = LayoutTree::new(root.clone(), HeadlessBackend::new());
+    /// tree.draw();
+    ///
+    /// let padding_box = root.borrow().data.padding_box();
+    /// let content_box = child.borrow().data.content_box();
+    /// assert_eq!(padding_box.start(), Default::default());
+    /// assert_eq!((content_box.x, content_box.y), (10.0, 30.0));
+    /// ```
     #[inline]
+    pub const fn content_box(&self) -> Rectangle {
+        self.content_box
+    }
+
+    /// Recalculate the window size before next draw. The node and all of its ancestors are marked for resize.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an ancestor of the node is currently borrowed.
     pub fn update_size(&mut self) {
+        self.is_resize_pending = true;
+
+        // Ancestors of a pending node are already pending
+        let mut parent = self.parent();
+        while let Some(node) = parent {
+            let mut node = node.borrow_mut();
+            if node.data.is_resize_pending { break; }
+
+            node.data.is_resize_pending = true;
+            parent = node.data.parent();
+        }
+
         if let Some(tree) = &mut self.tree {
             tree.root.borrow_mut().data.is_resize_pending = true;
         }
@@ -271,21 +852,1630 @@ impl<B: Backend> NodeData<B> {
     }
 }
 
-pub type NodeType = std::mem::Discriminant<NodeVariant>;
+impl<B: Backend> Default for NodeData<B> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Kind of a node, regardless of its content. Themes use this to select nodes to apply rules to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NodeType {
+    Frame,
+    Label,
+    Button,
+    TextInput,
+    ScrollInput,
+
+    /// Handle of a [`ScrollInput`]. Handles aren't nodes, but their style is resolved from the theme of the
+    /// scrollbar, using rules of this type.
+    ScrollHandle,
+    Scrollable,
+    Tooltip,
+    Checkbox,
+    Portal,
+    Space,
+}
+
+impl NodeType {
+    /// Every node type.
+    pub const ALL: [Self; 11] = [
+        Self::Frame,
+        Self::Label,
+        Self::Button,
+        Self::TextInput,
+        Self::ScrollInput,
+        Self::ScrollHandle,
+        Self::Scrollable,
+        Self::Tooltip,
+        Self::Checkbox,
+        Self::Portal,
+        Self::Space,
+    ];
+
+    /// Name of the node type, as used to refer to it in selectors.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Frame        => "frame",
+            Self::Label        => "label",
+            Self::Button       => "button",
+            Self::TextInput    => "text_input",
+            Self::ScrollInput  => "scroll_input",
+            Self::ScrollHandle => "scroll_handle",
+            Self::Scrollable   => "scrollable",
+            Self::Tooltip      => "tooltip",
+            Self::Checkbox     => "checkbox",
+            Self::Portal       => "portal",
+            Self::Space        => "space",
+        }
+    }
+
+    /// Find a node type by its name.
+    ///
+    /// # Returns
+    ///
+    /// The node type, or [`None`] if no type has the given name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|node_type| node_type.name() == name)
+    }
+}
 
-pub enum NodeVariant {
-    // todo
+/// Container node, arranging its children in a column or a row.
+///
+/// Children are placed one after another, separated by the vertical or horizontal [gap](Style::with_gap). Children
+/// that [expand](Layout::expand) share the space left by the others, proportionally to their `expand` value, and each
+/// child is aligned on the other axis according to its [`Layout::node_align`]. If the children don't fit, they are
+/// clipped to the frame's content box.
+///
+/// ```
+/// # use std::{cell::RefCell, rc::Rc};
+/// # use amity_ui::{backend::{Backend, Rectangle, Vector2, headless::{DrawCommand, HeadlessBackend}}, layout::Layout,
+/// #     node::{Node, NodeVariant, hframe, vframe}, style::Style, theme::Breadcrumbs, tree::LayoutTree, utils::*};
+/// fn space(params: impl NodeParam<HeadlessBackend>) -> Rc<RefCell<Node<HeadlessBackend>>> {
+///     Rc::new(RefCell::new(simple_constructor(NodeVariant::Space, params)))
+/// }
+///
+/// // Expanding children split the free space
+/// let root = hframe(Layout::fill(), [space(Layout::new(1)), space(Layout::new(2))]);
+/// let mut backend = HeadlessBackend::new();
+/// backend.set_window_size(Vector2::new(300.0, 100.0));
+/// let mut tree = LayoutTree::new(root, backend);
+/// tree.draw();
+///
+/// let boxes: Vec<_> = tree.backend.draw_commands.iter()
+///     .filter_map(|command| match command {
+///         DrawCommand::Rectangle { rectangle, .. } => Some(*rectangle),
+///         _ => None,
+///     })
+///     .collect();
+/// assert_eq!(boxes[1..], [Rectangle::new(0.0, 0.0, 100.0, 0.0), Rectangle::new(100.0, 0.0, 200.0, 0.0)]);
+///
+/// // Gap is added between children
+/// let padded = || Style::default().with_padding([10.0; 4]);
+/// let root = vframe(Style::default().with_gap([0.0, 5.0]), [space(padded()), space(padded())]);
+/// root.borrow_mut().resize(Vector2::new(300.0, 300.0), Vector2::new(96.0, 96.0), &mut Breadcrumbs::default());
+/// assert_eq!(root.borrow().data.min_size(), Vector2::new(20.0, 45.0));
+/// ```
+pub struct Frame {
+    /// If true, children are placed in a row instead of a column.
+    pub is_horizontal: bool,
 }
 
-impl NodeVariant {
+impl Frame {
+    /// Create a frame placing children in a row if `is_horizontal` is true, or in a column otherwise.
     #[inline]
-    #[must_use]
-    pub const fn node_type(&self) -> NodeType {
-        std::mem::discriminant(self)
+    pub const fn new(is_horizontal: bool) -> Self {
+        Self { is_horizontal }
+    }
+
+    /// Axis children are placed along.
+    #[inline]
+    pub const fn direction(&self) -> Axis {
+        if self.is_horizontal { Axis::Horizontal } else { Axis::Vertical }
     }
 }
 
-pub struct Node<B: Backend> {
-    pub data: NodeData<B>,
-    pub variant: NodeVariant,
+/// Create a [`Frame`] node with the given children.
+///
+/// The node's layout mode is set to flow along the frame's direction, after params are applied.
+///
+/// # Params
+///
+/// - `is_horizontal`: Value of [`Frame::is_horizontal`].
+/// - `params`:        Properties to set on the node, see [`NodeParam`].
+/// - `children`:      Nodes to place inside the frame. Nodes that already have a parent are moved.
+pub fn frame<B: Backend>(
+    is_horizontal: bool,
+    params: impl NodeParam<B>,
+    children: impl IntoIterator<Item = Rc<RefCell<Node<B>>>>,
+) -> Rc<RefCell<Node<B>>> {
+    let frame = Frame::new(is_horizontal);
+    let direction = frame.direction();
+    let mut node = simple_constructor(NodeVariant::Frame(frame), params);
+    node.data.layout.mode = LayoutMode::Flow { direction };
+
+    let node = Rc::new(RefCell::new(node));
+    for child in children {
+        node.append_child(child);
+    }
+    node
+}
+
+/// Create a [`Frame`] placing its children in a column.
+///
+/// # See Also
+///
+/// - [`frame`]
+#[inline]
+pub fn vframe<B: Backend>(
+    params: impl NodeParam<B>,
+    children: impl IntoIterator<Item = Rc<RefCell<Node<B>>>>,
+) -> Rc<RefCell<Node<B>>> {
+    frame(false, params, children)
+}
+
+/// Create a [`Frame`] placing its children in a row.
+///
+/// # See Also
+///
+/// - [`frame`]
+#[inline]
+pub fn hframe<B: Backend>(
+    params: impl NodeParam<B>,
+    children: impl IntoIterator<Item = Rc<RefCell<Node<B>>>>,
+) -> Rc<RefCell<Node<B>>> {
+    frame(true, params, children)
+}
+
+/// Node displaying text. Text is wrapped to fit the width of the node, breaking lines between words where possible.
+///
+/// The label is measured during resize, so changing [`Self::text`] requires a call to [`NodeData::update_size`].
+///
+/// ```
+/// # use std::{cell::{Cell, RefCell}, rc::Rc};
+/// # use amity_ui::{backend::{Backend, Vector2, headless::{DrawCommand, HeadlessBackend}}, node::{NodeVariant, label},
+/// #     rope::Rope, style::Style, typeface::Typeface, utils::NodeParam};
+/// # struct Monospace(Rc<Cell<usize>>);
+/// # impl Typeface<HeadlessBackend> for Monospace {
+/// #     fn glyph_count(&self) -> usize { 0 }
+/// #     fn pen_position(&self) -> Vector2 { Vector2::default() }
+/// #     fn line_height(&self) -> i32 { 20 }
+/// #     fn indent_width(&self) -> &i32 { &0 }
+/// #     fn indent_width_mut(&mut self) -> &mut i32 { unimplemented!() }
+/// #     fn advance(&mut self, _glyph: char) -> Vector2 { Vector2::new(10.0, 0.0) }
+/// #     fn dpi(&self) -> Vector2 { Vector2::new(96.0, 96.0) }
+/// #     fn set_size(&mut self, _dpi: Vector2, _size: f32) {}
+/// #     fn draw_line(&self, _: &mut <HeadlessBackend as Backend>::Image, _: &mut Vector2, _: Rope, _: u8) {
+/// #         self.0.set(self.0.get() + 1);
+/// #     }
+/// # }
+/// let lines_drawn = Rc::new(Cell::new(0));
+/// let style = Style::default().with_typeface(Monospace(lines_drawn.clone()));
+/// let node = Rc::new(RefCell::new(label::<HeadlessBackend>(style, "one two three four five")));
+///
+/// // Each character is 10 pixels wide, so only about 10 fit in a line
+/// let mut backend = HeadlessBackend::new();
+/// backend.set_window_size(Vector2::new(100.0, 600.0));
+/// let mut tree = amity_ui::tree::LayoutTree::new(node.clone(), backend);
+/// tree.draw();
+///
+/// assert_eq!(node.borrow().data.min_size().y, 60.0);
+/// assert_eq!(lines_drawn.get(), 3);
+/// let Some(DrawCommand::Texture { rectangle, .. }) = tree.backend.draw_commands.last() else { panic!() };
+/// assert_eq!(rectangle.height, 60.0);
+/// ```
+pub struct Label {
+    /// Text to display.
+    pub text: Rope,
+
+    /// Text split into lines, as of the last wrap.
+    lines: Vec<Rope>,
+
+    /// Text the lines were created from.
+    wrapped_text: Rope,
+
+    /// Width, in dots, the lines were wrapped to.
+    wrap_width: f32,
+}
+
+impl Label {
+    /// Create a label displaying the given text.
+    pub fn new(text: Rope) -> Self {
+        Self {
+            text,
+            lines: Vec::new(),
+            wrapped_text: Rope::new(),
+            wrap_width: f32::NAN,
+        }
+    }
+
+    /// Split the text into lines that fit in the given width. Lines are only wrapped again if the text or the width
+    /// changed since the last call.
+    ///
+    /// # Params
+    ///
+    /// - `typeface`: Typeface to measure the text with.
+    /// - `width`:    Maximum width of a line, in dots.
+    ///
+    /// # Returns
+    ///
+    /// The lines, in order. There's always at least one line.
+    pub fn wrap<B: Backend>(&mut self, typeface: &mut dyn Typeface<B>, width: f32) -> &[Rope] {
+        if width != self.wrap_width || self.text != self.wrapped_text {
+            self.lines = wrap_lines(typeface, self.text.clone(), width);
+            self.wrapped_text = self.text.clone();
+            self.wrap_width = width;
+        }
+
+        &self.lines
+    }
+
+    /// Measure the text wrapped to the given width.
+    ///
+    /// # Params
+    ///
+    /// - `typeface`: Typeface to measure the text with, with its size set for the window's DPI.
+    /// - `width`:    Available width, in pixels.
+    ///
+    /// # Returns
+    ///
+    /// Width of the longest line and height of all lines, in pixels.
+    pub fn measure<B: Backend>(&mut self, typeface: &mut dyn Typeface<B>, width: f32) -> Vector2 {
+        let scale = text::dot_scale(typeface);
+        let lines = self.wrap(typeface, width / scale.x);
+        let width = lines.iter()
+            .map(|line| typeface.measure_line(line.clone()).x)
+            .fold(0.0, f32::max);
+
+        Vector2::new(width * scale.x, (lines.len() as i32 * typeface.line_height()) as f32 * scale.y)
+    }
+
+    /// Draw the text, wrapped to the width of the box, using the style's typeface and `text_color`.
+    ///
+    /// # Params
+    ///
+    /// - `backend`:     Backend to draw with.
+    /// - `style`:       Style of the node.
+    /// - `content_box`: Box to draw the text in.
+    /// - `texture`:     Texture of the node to draw the text into. It is only rendered again if the text changes.
+    pub fn draw<B: Backend>(
+        &mut self,
+        backend: &mut B,
+        style: &Style<B>,
+        content_box: Rectangle,
+        texture: &mut TextTexture<B>,
+    ) {
+        let mut typeface = style.typeface.borrow_mut();
+        let scale = text::dot_scale(&*typeface);
+        let lines = self.wrap(&mut *typeface, content_box.width / scale.x);
+        drop(typeface);
+
+        texture.draw(backend, style, lines, content_box);
+    }
+}
+
+/// Create a [`Label`] node.
+///
+/// ```
+/// # use amity_ui::{backend::headless::HeadlessBackend, layout::Layout, node::{NodeVariant, label}};
+/// let node = label::<HeadlessBackend>((), "hi");
+///
+/// assert!(matches!(&node.variant, NodeVariant::Label(label) if label.text.value() == "hi"));
+/// assert!(node.data.layout == Layout::default());
+/// ```
+///
+/// # Params
+///
+/// - `params`: Properties to set on the node, see [`NodeParam`].
+/// - `text`:   Text to display.
+pub fn label<B: Backend>(params: impl NodeParam<B>, text: &str) -> Node<B> {
+    simple_constructor(NodeVariant::Label(Label::new(Rope::from_str(text))), params)
+}
+
+/// Bubble of text shown next to another node, the anchor, once the mouse has rested over it for a while.
+///
+/// The tooltip doesn't take any space in the layout. Once shown, it is drawn below the anchor, or above it if there
+/// is no room below, and is kept within the window. It is hidden as soon as the anchor is no longer hovered, or when
+/// the mouse moves further than [`Self::movement_threshold`], after which the delay starts over.
+///
+/// The bubble is drawn using the tooltip node's style, including its background, border and padding. Since it is
+/// drawn along with the tooltip node, the tooltip should be placed near the end of the tree so it covers other
+/// nodes, and mustn't be placed inside its anchor.
+///
+/// ```
+/// # use std::{cell::RefCell, rc::Rc, time::Duration};
+/// # use amity_ui::{backend::{Backend, Vector2, headless::HeadlessBackend}, layout::Layout,
+/// #     node::{Node, NodeVariant, button, tooltip, vframe}, style::Style, tree::LayoutTree};
+/// let anchor: Rc<RefCell<Node<HeadlessBackend>>> =
+///     Rc::new(RefCell::new(button(Style::default().with_padding([10.0; 4]), "")));
+/// let tip = Rc::new(RefCell::new(tooltip((), &anchor, "Help")));
+/// let root = vframe(Layout::fill(), [anchor.clone(), tip.clone()]);
+/// let is_shown = || match &tip.borrow().variant {
+///     NodeVariant::Tooltip(tooltip) => tooltip.is_shown(),
+///     _ => unreachable!(),
+/// };
+///
+/// let mut tree = LayoutTree::new(root, HeadlessBackend::new());
+/// tree.backend.set_delta_time(Duration::from_millis(100));
+/// tree.draw();
+/// tree.backend.set_mouse_position(anchor.borrow().data.padding_box().center());
+///
+/// // The tooltip only shows up after the mouse rests on the anchor for 500 ms
+/// for _ in 0..4 {
+///     tree.draw();
+///     assert!(!is_shown());
+/// }
+/// tree.draw();
+/// assert!(is_shown());
+///
+/// // Leaving the anchor hides it
+/// tree.backend.set_mouse_position(Vector2::new(400.0, 400.0));
+/// tree.draw();
+/// assert!(!is_shown());
+/// ```
+pub struct Tooltip<B: Backend> {
+    /// Text of the tooltip.
+    pub label: Label,
+
+    /// Node the tooltip describes.
+    pub anchor: Weak<RefCell<Node<B>>>,
+
+    /// Time the mouse has to rest over the anchor before the tooltip is shown.
+    pub delay: Duration,
+
+    /// Distance, in pixels, the mouse can move without hiding the tooltip or restarting the delay.
+    pub movement_threshold: f32,
+
+    /// Time the mouse has spent resting over the anchor.
+    hover_time: Duration,
+
+    /// Position of the mouse when it came to rest.
+    rest_position: Vector2,
+
+    /// If true, the tooltip is currently visible.
+    is_shown: bool,
+}
+
+impl<B: Backend> Tooltip<B> {
+    /// Default value for `delay`.
+    pub const DELAY: Duration = Duration::from_millis(500);
+
+    /// Default value for `movement_threshold`.
+    pub const MOVEMENT_THRESHOLD: f32 = 8.0;
+
+    /// Distance between the anchor and the bubble, in pixels.
+    pub const OFFSET: f32 = 4.0;
+
+    /// Create a tooltip for the given anchor.
+    pub fn new(text: Rope, anchor: &Rc<RefCell<Node<B>>>) -> Self {
+        Self {
+            label: Label::new(text),
+            anchor: Rc::downgrade(anchor),
+            delay: Self::DELAY,
+            movement_threshold: Self::MOVEMENT_THRESHOLD,
+            hover_time: Duration::ZERO,
+            rest_position: Vector2::default(),
+            is_shown: false,
+        }
+    }
+
+    /// Check if the tooltip is visible.
+    #[inline]
+    pub const fn is_shown(&self) -> bool {
+        self.is_shown
+    }
+
+    /// Hide the tooltip and restart the delay.
+    #[inline]
+    pub fn hide(&mut self) {
+        self.is_shown = false;
+        self.hover_time = Duration::ZERO;
+    }
+
+    /// Advance the hover delay, showing or hiding the tooltip.
+    ///
+    /// # Params
+    ///
+    /// - `is_hovered`: True if the anchor is hovered.
+    /// - `mouse`:      Position of the mouse.
+    /// - `delta_time`: Time since the last update.
+    pub fn update(&mut self, is_hovered: bool, mouse: Vector2, delta_time: Duration) {
+        if !is_hovered {
+            self.hide();
+            return;
+        }
+
+        if self.hover_time.is_zero() || (mouse - self.rest_position).length() > self.movement_threshold {
+            self.hide();
+            self.rest_position = mouse;
+        }
+
+        self.hover_time += delta_time;
+        if self.hover_time >= self.delay {
+            self.is_shown = true;
+        }
+    }
+
+    /// Find the position of the bubble: below the anchor if it fits, otherwise above the anchor if it fits there.
+    /// The bubble is then moved to stay within the window, if possible.
+    ///
+    /// ```
+    /// # use amity_ui::{backend::{Rectangle, Vector2, headless::HeadlessBackend}, node::Tooltip};
+    /// let window = Vector2::new(800.0, 600.0);
+    /// let size = Vector2::new(100.0, 30.0);
+    /// let place = |anchor| Tooltip::<HeadlessBackend>::place(anchor, size, window);
+    ///
+    /// // Below the anchor if there's room
+    /// assert_eq!(place(Rectangle::new(10.0, 10.0, 50.0, 20.0)), Rectangle::new(10.0, 34.0, 100.0, 30.0));
+    ///
+    /// // Above the anchor at the bottom of the window, and moved left to fit
+    /// assert_eq!(place(Rectangle::new(750.0, 560.0, 50.0, 20.0)), Rectangle::new(700.0, 526.0, 100.0, 30.0));
+    /// ```
+    ///
+    /// # Params
+    ///
+    /// - `anchor`: Box of the anchor.
+    /// - `size`:   Size of the bubble.
+    /// - `window`: Size of the window.
+    pub fn place(anchor: Rectangle, size: Vector2, window: Vector2) -> Rectangle {
+        let below = anchor.bottom() + Self::OFFSET;
+        let above = anchor.y - Self::OFFSET - size.y;
+        let y = if below + size.y > window.y && above >= 0.0 { above } else { below };
+
+        let x = anchor.x.min(window.x - size.x).max(0.0);
+        let y = y.min(window.y - size.y).max(0.0);
+
+        Rectangle::from_parts(Vector2::new(x, y), size)
+    }
+
+    /// Update the tooltip, and draw it if it is shown.
+    ///
+    /// # Params
+    ///
+    /// - `backend`: Backend to draw with.
+    /// - `style`:   Style of the tooltip node.
+    /// - `texture`: Texture of the tooltip node to draw the text into.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the anchor is borrowed mutably, which happens if the tooltip is drawn inside its anchor.
+    pub fn draw(&mut self, backend: &mut B, style: &Style<B>, texture: &mut TextTexture<B>) {
+        let anchor = self.anchor.upgrade();
+        let anchor = anchor.as_ref().map(|anchor| anchor.borrow());
+        let is_hovered = anchor.as_ref().is_some_and(|anchor| anchor.data.is_hovered());
+
+        self.update(is_hovered, backend.mouse_position().into(), backend.delta_time());
+
+        let Some(anchor) = anchor else { return };
+        if !self.is_shown { return; }
+
+        let window: Vector2 = backend.window_size().into();
+        let spacing = style.spacing();
+        let content = self.label.measure(&mut *style.typeface.borrow_mut(), (window.x - spacing.x).max(0.0));
+        let margin_box = Self::place(anchor.data.padding_box(), content + spacing, window);
+
+        let border_box = style.border_box(margin_box);
+        backend.draw_rectangle(border_box.into(), style.background_color.into());
+        style.border_style.apply(backend, border_box, SideArray(style.border));
+        self.label.draw(backend, style, style.content_box(style.padding_box(border_box)), texture);
+    }
+}
+
+/// Create a [`Tooltip`] node.
+///
+/// # Params
+///
+/// - `params`: Properties to set on the node, see [`NodeParam`].
+/// - `anchor`: Node the tooltip describes.
+/// - `text`:   Text to display.
+pub fn tooltip<B: Backend>(params: impl NodeParam<B>, anchor: &Rc<RefCell<Node<B>>>, text: &str) -> Node<B> {
+    simple_constructor(NodeVariant::Tooltip(Tooltip::new(Rope::from_str(text), anchor)), params)
+}
+
+/// Node that can be pressed to perform an action.
+///
+/// The button is pressed with [`FluidInputAction::Press`] while focused. Like other nodes, it ignores input while
+/// disabled. To style hovered or focused buttons differently, a theme's style delegate can check
+/// [`NodeData::is_hovered`] or the focus of the tree.
+///
+/// ```
+/// # use std::{cell::{Cell, RefCell}, rc::Rc};
+/// # use amity_ui::{backend::headless::HeadlessBackend, focus::Focusable, input::*, node::{NodeVariant, button},
+/// #     tree::LayoutTree};
+/// # use raylib::consts::KeyboardKey;
+/// let node = Rc::new(RefCell::new(button::<HeadlessBackend>((), "Click me")));
+/// let presses = Rc::new(Cell::new(0));
+/// if let NodeVariant::Button(button) = &mut node.borrow_mut().variant {
+///     let presses = presses.clone();
+///     button.on_press.subscribe(move |_| presses.set(presses.get() + 1));
+/// }
+///
+/// let mut tree = LayoutTree::new(node.clone(), HeadlessBackend::new());
+/// tree.bound_inputs.push(InputLayer {
+///     modifiers: InputStroke { input: Vec::new() },
+///     bindings: vec![InputBinding {
+///         action: FluidInputAction::Press.id(),
+///         trigger: InputStrokeItem::KeyboardKey(KeyboardKey::KEY_ENTER),
+///     }],
+/// });
+/// node.clone().focus(&mut tree.focus);
+///
+/// // Holding the key presses the button once
+/// tree.backend.press_key(KeyboardKey::KEY_ENTER);
+/// tree.draw();
+/// tree.backend.next_frame();
+/// tree.draw();
+/// assert_eq!(presses.get(), 1);
+///
+/// // Disabled buttons ignore input
+/// node.borrow_mut().data.set_disabled(true);
+/// tree.backend.release_key(KeyboardKey::KEY_ENTER);
+/// tree.backend.next_frame();
+/// tree.backend.press_key(KeyboardKey::KEY_ENTER);
+/// tree.draw();
+/// assert_eq!(presses.get(), 1);
+/// ```
+///
+/// The text is measured and drawn like a [`Label`]:
+///
+/// ```
+/// # use std::{cell::RefCell, rc::Rc};
+/// # use amity_ui::{backend::{Backend, Vector2, headless::{DrawCommand, HeadlessBackend}}, node::button, rope::Rope,
+/// #     style::Style, tree::LayoutTree, typeface::Typeface};
+/// # struct Monospace;
+/// # impl Typeface<HeadlessBackend> for Monospace {
+/// #     fn glyph_count(&self) -> usize { 0 }
+/// #     fn pen_position(&self) -> Vector2 { Vector2::default() }
+/// #     fn line_height(&self) -> i32 { 20 }
+/// #     fn indent_width(&self) -> &i32 { &0 }
+/// #     fn indent_width_mut(&mut self) -> &mut i32 { unimplemented!() }
+/// #     fn advance(&mut self, _glyph: char) -> Vector2 { Vector2::new(10.0, 0.0) }
+/// #     fn dpi(&self) -> Vector2 { Vector2::new(96.0, 96.0) }
+/// #     fn set_size(&mut self, _dpi: Vector2, _size: f32) {}
+/// #     fn draw_line(&self, _: &mut <HeadlessBackend as Backend>::Image, _: &mut Vector2, _: Rope, _: u8) {}
+/// # }
+/// let style = Style::default().with_typeface(Monospace).with_padding([5.0; 4]);
+/// let node = Rc::new(RefCell::new(button::<HeadlessBackend>(style, "Click me")));
+/// let mut tree = LayoutTree::new(node.clone(), HeadlessBackend::new());
+/// tree.draw();
+///
+/// // 8 characters, 10 pixels wide each, and the padding
+/// assert_eq!(node.borrow().data.min_size(), Vector2::new(90.0, 30.0));
+/// let Some(DrawCommand::Texture { rectangle, .. }) = tree.backend.draw_commands.last() else { panic!() };
+/// assert_eq!((rectangle.x, rectangle.y, rectangle.width), (5.0, 5.0, 80.0));
+/// ```
+pub struct Button {
+    /// Text displayed on the button. Like a [`Label`], it is measured during resize, and wrapped to fit the button.
+    pub label: Label,
+
+    /// Dispatched when the button is pressed.
+    pub on_press: SimpleEvent,
+}
+
+impl Button {
+    /// Create a button displaying the given text.
+    pub fn new(text: Rope) -> Self {
+        Self {
+            label: Label::new(text),
+            on_press: SimpleEvent::new(),
+        }
+    }
+
+    /// Press the button, dispatching [`Self::on_press`].
+    #[inline]
+    pub fn press(&mut self) {
+        self.on_press.dispatch(&());
+    }
+
+    /// Handle an input action. The button is pressed by an active [`FluidInputAction::Press`].
+    ///
+    /// # Returns
+    ///
+    /// True if the action was handled.
+    pub fn action_impl(&mut self, action: &InputActionID, is_active: bool) -> bool {
+        if !is_active || !action.is(FluidInputAction::Press) { return false; }
+
+        self.press();
+        true
+    }
+}
+
+/// Create a [`Button`] node.
+///
+/// # Params
+///
+/// - `params`: Properties to set on the node, see [`NodeParam`].
+/// - `text`:   Text displayed on the button.
+pub fn button<B: Backend>(params: impl NodeParam<B>, text: &str) -> Node<B> {
+    simple_constructor(NodeVariant::Button(Button::new(Rope::from_str(text))), params)
+}
+
+/// Box that can be checked and unchecked.
+///
+/// The checkbox is toggled with [`FluidInputAction::Press`] while focused. While checked, the node has the
+/// [`Checkbox::checked_tag`], so themes can style checked boxes with the `checkbox.checked` selector. The box is
+/// drawn with the style's `line_color`, and so is the checkmark.
+///
+/// ```
+/// # use std::{cell::RefCell, rc::Rc};
+/// # use amity_ui::{backend::{Color, headless::HeadlessBackend}, focus::Focusable, input::*,
+/// #     node::{Checkbox, NodeVariant, checkbox}, theme::parse_stylesheet, tree::LayoutTree};
+/// # use raylib::consts::KeyboardKey;
+/// let theme = parse_stylesheet("checkbox.checked { line-color: #2277cc; }").unwrap();
+/// let node = Rc::new(RefCell::new(checkbox::<HeadlessBackend>(theme, false)));
+/// let toggles = Rc::new(RefCell::new(Vec::new()));
+/// if let NodeVariant::Checkbox(checkbox) = &mut node.borrow_mut().variant {
+///     let toggles = toggles.clone();
+///     checkbox.on_toggle.subscribe(move |value| toggles.borrow_mut().push(*value));
+/// }
+/// let state = || {
+///     let node = node.borrow();
+///     let NodeVariant::Checkbox(checkbox) = &node.variant else { unreachable!() };
+///     let is_themed = node.data.style().line_color() == Color::rgb(0x22, 0x77, 0xcc);
+///     (checkbox.is_checked(), node.data.tags.contains(Checkbox::checked_tag()), is_themed)
+/// };
+///
+/// let mut tree = LayoutTree::new(node.clone(), HeadlessBackend::new());
+/// tree.bound_inputs.push(InputLayer {
+///     modifiers: InputStroke { input: Vec::new() },
+///     bindings: vec![InputBinding {
+///         action: FluidInputAction::Press.id(),
+///         trigger: InputStrokeItem::KeyboardKey(KeyboardKey::KEY_ENTER),
+///     }],
+/// });
+/// node.clone().focus(&mut tree.focus);
+/// tree.draw();
+/// assert_eq!(state(), (false, false, false));
+///
+/// for is_checked in [true, false] {
+///     tree.backend.press_key(KeyboardKey::KEY_ENTER);
+///     tree.draw();
+///     tree.backend.release_key(KeyboardKey::KEY_ENTER);
+///     tree.backend.next_frame();
+///
+///     // The style is reloaded when the tree is resized, on the next frame
+///     tree.draw();
+///     assert_eq!(state(), (is_checked, is_checked, is_checked));
+/// }
+/// assert_eq!(*toggles.borrow(), [true, false]);
+/// ```
+pub struct Checkbox {
+    /// True if the box is checked.
+    is_checked: bool,
+
+    /// Dispatched when the checkbox is checked or unchecked. The argument is the new state.
+    pub on_toggle: Event<bool>,
+}
+
+impl Checkbox {
+    /// Default size of the box, in pixels.
+    pub const SIZE: f32 = 16.0;
+
+    /// Create a checkbox. Use [`checkbox`] to create a node, so that the node is tagged to match the state.
+    pub fn new(is_checked: bool) -> Self {
+        Self {
+            is_checked,
+            on_toggle: Event::new(),
+        }
+    }
+
+    /// Tag present on checked checkboxes.
+    #[inline]
+    pub fn checked_tag() -> TagID {
+        TagID::named("checked")
+    }
+
+    /// Check if the box is checked.
+    #[inline]
+    pub const fn is_checked(&self) -> bool {
+        self.is_checked
+    }
+
+    /// Check or uncheck the box. If the state changed, the node's tags are updated and [`Self::on_toggle`] is
+    /// dispatched.
+    ///
+    /// # Params
+    ///
+    /// - `data`:  Data of the checkbox node.
+    /// - `value`: True to check the box.
+    pub fn set_checked<B: Backend>(&mut self, data: &mut NodeData<B>, value: bool) {
+        if self.is_checked == value { return; }
+
+        self.is_checked = value;
+        self.update_tags(data);
+        self.on_toggle.dispatch(&value);
+    }
+
+    /// Flip the state of the checkbox. See [`Self::set_checked`].
+    #[inline]
+    pub fn toggle<B: Backend>(&mut self, data: &mut NodeData<B>) {
+        self.set_checked(data, !self.is_checked);
+    }
+
+    /// Add or remove the [`Self::checked_tag`] to match the state. The node is marked for resize, so its style is
+    /// reloaded before the next frame is drawn.
+    pub fn update_tags<B: Backend>(&self, data: &mut NodeData<B>) {
+        let is_changed = if self.is_checked {
+            data.tags.insert(Self::checked_tag())
+        } else {
+            data.tags.remove(Self::checked_tag())
+        };
+
+        if is_changed {
+            data.update_size();
+        }
+    }
+
+    /// Handle an input action. The checkbox is toggled by an active [`FluidInputAction::Press`].
+    ///
+    /// # Returns
+    ///
+    /// True if the action was handled.
+    pub fn action_impl<B: Backend>(&mut self, data: &mut NodeData<B>, action: &InputActionID, is_active: bool) -> bool {
+        if !is_active || !action.is(FluidInputAction::Press) { return false; }
+
+        self.toggle(data);
+        true
+    }
+
+    /// Draw the box and, if checked, the checkmark, in the largest square that fits in the content box.
+    ///
+    /// # Params
+    ///
+    /// - `backend`:     Backend to draw with.
+    /// - `style`:       Style of the node.
+    /// - `content_box`: Box to draw the checkbox in.
+    pub fn draw<B: Backend>(&self, backend: &mut B, style: &Style<B>, content_box: Rectangle) {
+        let size = content_box.width.min(content_box.height);
+        let start = content_box.start();
+        let point = |x: f32, y: f32| (start + Vector2::new(x, y) * size).into();
+        let color = || style.line_color.into();
+
+        let corners = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+        for (index, &(x, y)) in corners.iter().enumerate() {
+            let (next_x, next_y) = corners[(index + 1) % corners.len()];
+            backend.draw_line(point(x, y), point(next_x, next_y), color());
+        }
+
+        if !self.is_checked { return; }
+
+        backend.draw_line(point(0.2, 0.5), point(0.4, 0.75), color());
+        backend.draw_line(point(0.4, 0.75), point(0.8, 0.25), color());
+    }
+}
+
+/// Create a [`Checkbox`] node.
+///
+/// # Params
+///
+/// - `params`:     Properties to set on the node, see [`NodeParam`].
+/// - `is_checked`: Initial state of the checkbox.
+pub fn checkbox<B: Backend>(params: impl NodeParam<B>, is_checked: bool) -> Node<B> {
+    let mut node = simple_constructor(NodeVariant::Checkbox(Checkbox::new(is_checked)), params);
+    if let NodeVariant::Checkbox(checkbox) = &node.variant {
+        checkbox.update_tags(&mut node.data);
+    }
+    node
+}
+
+/// Node drawing another subtree, the target, in its content box, without taking the target out of its place in the
+/// tree. Useful for minimaps or drag previews.
+///
+/// The target isn't resized again, nor is its layout changed: it is drawn with the layout it was given during its own
+/// last draw, moved to the portal's content box and scaled by [`Self::scale`]. The portal takes the space of the scaled
+/// target, as measured before the portal was resized. The mirrored content doesn't react to input, and is not clipped
+/// to the portal. While the target is mirrored, the original blocks input; see [`NodeData::is_mirrored`].
+///
+/// Portals inside a mirrored subtree are not drawn, and neither is a portal's own ancestor.
+///
+/// ```
+/// # use std::{cell::{Cell, RefCell}, rc::Rc};
+/// # use amity_ui::{backend::{Backend, Color, Rectangle, Vector2, headless::{DrawCommand, HeadlessBackend}},
+/// #     node::{Node, label, portal, vframe}, rope::Rope, style::Style, tree::LayoutTree, typeface::Typeface};
+/// # struct Monospace(i32, Rc<Cell<usize>>);
+/// # impl Typeface<HeadlessBackend> for Monospace {
+/// #     fn glyph_count(&self) -> usize { 0 }
+/// #     fn pen_position(&self) -> Vector2 { Vector2::default() }
+/// #     fn line_height(&self) -> i32 { 20 }
+/// #     fn indent_width(&self) -> &i32 { &self.0 }
+/// #     fn indent_width_mut(&mut self) -> &mut i32 { &mut self.0 }
+/// #     fn advance(&mut self, _glyph: char) -> Vector2 { Vector2::new(10.0, 0.0) }
+/// #     fn dpi(&self) -> Vector2 { Vector2::new(96.0, 96.0) }
+/// #     fn set_size(&mut self, _dpi: Vector2, _size: f32) { self.1.set(self.1.get() + 1); }
+/// #     fn draw_line(&self, _: &mut <HeadlessBackend as Backend>::Image, _: &mut Vector2, _: Rope, _: u8) {}
+/// # }
+/// // The typeface counts how many times the label is resized
+/// let resizes = Rc::new(Cell::new(0));
+/// let red = Color::rgb(255, 0, 0);
+/// let style = Style::default()
+///     .with_typeface(Monospace(0, resizes.clone()))
+///     .with_background_color(red)
+///     .with_padding([5.0; 4]);
+/// let original: Rc<RefCell<Node<HeadlessBackend>>> = Rc::new(RefCell::new(label(style, "Hi")));
+/// let [mirror, minimap] = [1.0, 0.5].map(|scale| Rc::new(RefCell::new(portal((), &original, scale))));
+/// let root = vframe((), [original.clone(), mirror.clone(), minimap.clone()]);
+/// let mut tree = LayoutTree::new(root, HeadlessBackend::new());
+/// tree.draw();
+///
+/// // The label is drawn in place, then by each portal, at the portal's position and scale
+/// let commands = &tree.backend.draw_commands;
+/// let backgrounds: Vec<_> = commands.iter().filter_map(|command| match command {
+///     DrawCommand::Rectangle { rectangle, color } if *color == red => Some(*rectangle),
+///     _ => None,
+/// }).collect();
+/// let texts: Vec<_> = commands.iter().filter_map(|command| match command {
+///     DrawCommand::Texture { rectangle, .. } => Some(*rectangle),
+///     _ => None,
+/// }).collect();
+/// assert_eq!(backgrounds, [
+///     Rectangle::new(0.0,  0.0, 30.0, 30.0),
+///     Rectangle::new(0.0, 30.0, 30.0, 30.0),
+///     Rectangle::new(0.0, 60.0, 15.0, 15.0),
+/// ]);
+/// // Text is aligned to whole pixels
+/// assert_eq!(texts, [
+///     Rectangle::new(5.0,  5.0, 20.0, 20.0),
+///     Rectangle::new(5.0, 35.0, 20.0, 20.0),
+///     Rectangle::new(3.0, 63.0, 10.0, 10.0),
+/// ]);
+///
+/// // The label was only laid out once, and it isn't laid out again on later frames
+/// assert_eq!(original.borrow().data.padding_box(), Rectangle::new(0.0, 0.0, 30.0, 30.0));
+/// tree.draw();
+/// assert_eq!(resizes.get(), 1);
+///
+/// // The original blocks input while it is mirrored
+/// assert!(original.borrow().data.is_mirrored() && original.borrow().data.blocks_input());
+/// mirror.borrow_mut().data.set_hidden(true);
+/// minimap.borrow_mut().data.set_hidden(true);
+/// tree.draw();
+/// assert!(!original.borrow().data.blocks_input());
+/// ```
+pub struct Portal<B: Backend> {
+    /// Subtree drawn by the portal. The target has to be placed in the tree elsewhere, where it is resized and laid
+    /// out.
+    pub target: Weak<RefCell<Node<B>>>,
+
+    /// Scale the target is drawn at, relative to its size in the tree.
+    ///
+    /// Changing the scale requires a resize.
+    pub scale: f32,
+}
+
+impl<B: Backend> Portal<B> {
+    /// Create a portal drawing the given subtree. Use [`portal`] to create a node.
+    ///
+    /// # Params
+    ///
+    /// - `target`: Subtree to draw.
+    /// - `scale`:  Scale to draw the subtree at.
+    pub fn new(target: &Rc<RefCell<Node<B>>>, scale: f32) -> Self {
+        Self {
+            target: Rc::downgrade(target),
+            scale,
+        }
+    }
+
+    /// Size of the scaled target, as measured during its last resize, or zero if the target is gone or being
+    /// resized.
+    pub fn measure(&self) -> Vector2 {
+        self.target.upgrade()
+            .and_then(|target| target.try_borrow().ok().map(|target| target.data.min_size * self.scale))
+            .unwrap_or_default()
+    }
+}
+
+/// Create a [`Portal`] node.
+///
+/// # Params
+///
+/// - `params`: Properties to set on the node, see [`NodeParam`].
+/// - `target`: Subtree to draw.
+/// - `scale`:  Scale to draw the subtree at.
+pub fn portal<B: Backend>(params: impl NodeParam<B>, target: &Rc<RefCell<Node<B>>>, scale: f32) -> Node<B> {
+    simple_constructor(NodeVariant::Portal(Portal::new(target, scale)), params)
+}
+
+/// Node for editing text.
+///
+/// While focused, the input inserts typed text, and responds to text navigation, selection, editing, clipboard and
+/// undo actions. Every change to the text is recorded in [`Self::history`] and dispatches [`Self::on_change`].
+///
+/// ```
+/// # use std::{cell::{Cell, RefCell}, rc::Rc};
+/// # use amity_ui::{backend::headless::HeadlessBackend, focus::Focusable, input::*, node::{NodeVariant, TextInput},
+/// #     rope::Rope, tree::LayoutTree, utils::simple_constructor};
+/// # use raylib::consts::KeyboardKey;
+/// let input = TextInput::new(Rope::new());
+/// let node = Rc::new(RefCell::new(simple_constructor::<HeadlessBackend>(NodeVariant::TextInput(input), ())));
+/// let changes = Rc::new(Cell::new(0));
+/// if let NodeVariant::TextInput(input) = &mut node.borrow_mut().variant {
+///     let changes = changes.clone();
+///     input.on_change.subscribe(move |_| changes.set(changes.get() + 1));
+/// }
+///
+/// let binding = |action: FluidInputAction, key| {
+///     InputBinding { action: action.id(), trigger: InputStrokeItem::KeyboardKey(key) }
+/// };
+/// let mut tree = LayoutTree::new(node.clone(), HeadlessBackend::new());
+/// tree.bound_inputs.push(InputLayer {
+///     modifiers: InputStroke { input: vec![InputStrokeItem::KeyboardKey(KeyboardKey::KEY_LEFT_CONTROL)] },
+///     bindings: vec![binding(FluidInputAction::Undo, KeyboardKey::KEY_Z)],
+/// });
+/// tree.bound_inputs.push(InputLayer {
+///     modifiers: InputStroke { input: Vec::new() },
+///     bindings: vec![binding(FluidInputAction::Backspace, KeyboardKey::KEY_BACKSPACE)],
+/// });
+/// node.clone().focus(&mut tree.focus);
+/// let value = || match &node.borrow().variant {
+///     NodeVariant::TextInput(input) => input.value.to_string(),
+///     _ => unreachable!(),
+/// };
+///
+/// // Type "abc"
+/// for character in "abc".chars() {
+///     tree.backend.push_character(character);
+///     tree.draw();
+///     tree.backend.next_frame();
+/// }
+/// assert_eq!(value(), "abc");
+///
+/// // Erase the last character
+/// tree.backend.press_key(KeyboardKey::KEY_BACKSPACE);
+/// tree.draw();
+/// tree.backend.next_frame();
+/// tree.backend.release_key(KeyboardKey::KEY_BACKSPACE);
+/// assert_eq!(value(), "ab");
+///
+/// // Undo the erase
+/// tree.backend.press_key(KeyboardKey::KEY_LEFT_CONTROL);
+/// tree.backend.press_key(KeyboardKey::KEY_Z);
+/// tree.draw();
+/// assert_eq!(value(), "abc");
+/// assert_eq!(changes.get(), 5);
+/// ```
+pub struct TextInput {
+    /// Text currently in the input.
+    pub value: Rope,
+
+    /// Selected text, or position of the caret if the selection is empty.
+    pub selection: Selection,
+
+    /// Blink state of the caret.
+    pub caret: Caret,
+
+    /// Undo history of the text.
+    pub history: History,
+
+    /// Dispatched whenever the text changes, whether because of user input, or because of an undo.
+    pub on_change: SimpleEvent,
+}
+
+impl TextInput {
+    /// Create a text input holding the given text, with the caret at the end.
+    pub fn new(value: Rope) -> Self {
+        let selection = Selection::new(value.len());
+
+        Self {
+            history: History::new(value.clone()),
+            value,
+            selection,
+            caret: Caret::new(),
+            on_change: SimpleEvent::new(),
+        }
+    }
+
+    /// Range of selected text, in UTF-8 bytes. Empty if nothing is selected.
+    #[inline]
+    pub fn selection(&self) -> Range<usize> {
+        self.selection.range()
+    }
+
+    /// Selected text.
+    #[inline]
+    pub fn selected_text(&self) -> Rope {
+        let selection = self.selection();
+        self.value.slice(selection.start, selection.len())
+    }
+
+    /// Replace the selected text, or insert text at the caret if nothing is selected. The caret is placed after the
+    /// new text, and the selection is cleared.
+    ///
+    /// # Params
+    ///
+    /// - `text`: Text to insert.
+    pub fn replace_selection(&mut self, text: &str) {
+        let selection = self.selection();
+        if selection.is_empty() && text.is_empty() { return; }
+
+        let is_insertion = selection.is_empty() && text.chars().count() == 1;
+        self.value = self.value.replace(selection.clone(), text);
+        self.selection = Selection::new(selection.start + text.len());
+        self.history.push(self.value.clone(), is_insertion);
+        self.changed();
+    }
+
+    /// Erase the selected text. If nothing is selected, erase text next to the caret instead.
+    ///
+    /// # Params
+    ///
+    /// - `action`: Selection action picking the text to erase if nothing is selected, for example
+    ///   [`FluidInputAction::SelectPreviousChar`] to erase the character before the caret.
+    pub fn erase(&mut self, action: FluidInputAction) {
+        if self.selection.is_empty() {
+            self.selection.action(&self.value, &action.id());
+        }
+
+        self.replace_selection("");
+    }
+
+    /// Revert the last edit, if any. The caret is kept in place if possible.
+    ///
+    /// # Returns
+    ///
+    /// True if there was an edit to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(value) = self.history.undo() else { return false };
+
+        self.restore(value);
+        true
+    }
+
+    /// Redo the last undone edit, if any. The caret is kept in place if possible.
+    ///
+    /// # Returns
+    ///
+    /// True if there was an edit to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(value) = self.history.redo() else { return false };
+
+        self.restore(value);
+        true
+    }
+
+    /// Replace the text with a snapshot from the history.
+    fn restore(&mut self, value: Rope) {
+        let caret = self.selection.caret;
+
+        self.selection = Selection::new(if value.is_char_boundary(caret) { caret } else { value.len() });
+        self.value = value;
+        self.changed();
+    }
+
+    /// Show the caret and dispatch [`Self::on_change`] after the text has changed.
+    fn changed(&mut self) {
+        self.caret.reset();
+        self.on_change.dispatch(&());
+    }
+
+    /// Copy the selected text to the clipboard. Does nothing if nothing is selected.
+    pub fn copy<B: Backend>(&self, backend: &mut B) {
+        if self.selection().is_empty() { return; }
+
+        backend.set_clipboard(&self.selected_text().value());
+    }
+
+    /// Copy the selected text to the clipboard, and remove it from the input. Does nothing if nothing is selected.
+    pub fn cut<B: Backend>(&mut self, backend: &mut B) {
+        if self.selection().is_empty() { return; }
+
+        self.copy(backend);
+        self.replace_selection("");
+    }
+
+    /// Insert text from the clipboard at the caret, replacing the selection.
+    ///
+    /// ```
+    /// # use amity_ui::{backend::headless::HeadlessBackend, node::TextInput, rope::Rope, text::Selection};
+    /// let mut backend = HeadlessBackend::new();
+    /// let mut input = TextInput::new(Rope::from_str("żółw "));
+    /// input.selection.anchor = 0;
+    /// input.copy(&mut backend);
+    /// input.selection = Selection::new(input.value.len());
+    /// input.paste(&backend);
+    ///
+    /// assert_eq!(input.value.value(), "żółw żółw ");
+    /// ```
+    pub fn paste<B: Backend>(&mut self, backend: &B) {
+        self.replace_selection(&backend.clipboard());
+    }
+
+    /// Insert text typed by the user in place of the selection. Prefers [`Backend::input_composition`], so composed
+    /// text, such as IME input or emoji sequences, is inserted as a whole.
+    ///
+    /// ```
+    /// # use amity_ui::{backend::headless::HeadlessBackend, node::TextInput, rope::Rope};
+    /// let mut backend = HeadlessBackend::new();
+    /// let mut input = TextInput::new(Rope::from_str("Hi "));
+    /// backend.push_composition("👩‍💻");
+    /// backend.push_text("!");
+    ///
+    /// assert!(input.input_text(&mut backend));
+    /// assert_eq!(input.value.value(), "Hi 👩‍💻");
+    /// assert_eq!(input.selection(), input.value.len()..input.value.len());
+    ///
+    /// assert!(input.input_text(&mut backend));
+    /// assert!(!input.input_text(&mut backend));
+    /// assert_eq!(input.value.value(), "Hi 👩‍💻!");
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// True if any text was inserted.
+    pub fn input_text<B: Backend>(&mut self, backend: &mut B) -> bool {
+        let Some(text) = backend.input_composition() else { return false };
+
+        self.replace_selection(&text);
+        true
+    }
+
+    /// Run a clipboard action: [`FluidInputAction::Copy`], [`FluidInputAction::Cut`] or
+    /// [`FluidInputAction::Paste`].
+    ///
+    /// # Returns
+    ///
+    /// True if the action is a clipboard action, and was handled.
+    pub fn clipboard_action<B: Backend>(&mut self, backend: &mut B, action: &InputActionID) -> bool {
+        if action.is(FluidInputAction::Copy) {
+            self.copy(backend);
+        } else if action.is(FluidInputAction::Cut) {
+            self.cut(backend);
+        } else if action.is(FluidInputAction::Paste) {
+            self.paste(backend);
+        } else {
+            return false;
+        }
+
+        true
+    }
+
+    /// Handle an input action: text navigation and selection, editing, clipboard, undo and redo. Only active actions
+    /// are handled.
+    ///
+    /// # Params
+    ///
+    /// - `backend`:   Backend to access the clipboard through.
+    /// - `action`:    Action to handle.
+    /// - `is_active`: True if the action has just been triggered.
+    ///
+    /// # Returns
+    ///
+    /// True if the action was handled.
+    pub fn action_impl<B: Backend>(&mut self, backend: &mut B, action: &InputActionID, is_active: bool) -> bool {
+        use FluidInputAction::*;
+
+        if !is_active { return false; }
+        if self.selection.action(&self.value, action) || self.clipboard_action(backend, action) { return true; }
+
+        if action.is(Backspace) {
+            self.erase(SelectPreviousChar);
+        } else if action.is(BackspaceWord) {
+            self.erase(SelectPreviousWord);
+        } else if action.is(DeleteChar) {
+            self.erase(SelectNextChar);
+        } else if action.is(DeleteWord) {
+            self.erase(SelectNextWord);
+        } else if action.is(Undo) {
+            self.undo();
+        } else if action.is(Redo) {
+            self.redo();
+        } else {
+            return false;
+        }
+
+        true
+    }
+
+    /// Advance the caret and history timers. Done by the tree every frame, before the input is drawn.
+    ///
+    /// # Params
+    ///
+    /// - `delta_time`: Time elapsed since the last frame, as returned by [`Backend::delta_time`].
+    /// - `is_focused`: True if the input is focused. The caret is only shown while focused.
+    pub fn update(&mut self, delta_time: Duration, is_focused: bool) {
+        self.history.advance(delta_time);
+        self.caret.update(delta_time, self.selection.caret, is_focused);
+    }
+
+    /// Draw the text, selection and caret.
+    ///
+    /// # Params
+    ///
+    /// - `backend`:     Backend to draw with.
+    /// - `style`:       Style of the node.
+    /// - `content_box`: Box to draw the text in.
+    /// - `texture`:     Texture of the node to draw the text into. It is only rendered again if the text changes.
+    pub fn draw<B: Backend>(
+        &mut self,
+        backend: &mut B,
+        style: &Style<B>,
+        content_box: Rectangle,
+        texture: &mut TextTexture<B>,
+    ) {
+        let mut typeface = style.typeface.borrow_mut();
+        let lines = wrap_lines(&mut *typeface, self.value.clone(), f32::INFINITY);
+        let selection = self.selection();
+
+        text::draw_selection(backend, &mut *typeface, &self.value, selection, content_box,
+            style.selection_background_color);
+        drop(typeface);
+        texture.draw(backend, style, &lines, content_box);
+        self.caret.draw(backend, &mut *style.typeface.borrow_mut(), &self.value, content_box, style.text_color);
+    }
+}
+
+/// Content specific to each kind of node.
+pub enum NodeVariant<B: Backend> {
+    Frame(Frame),
+    Label(Label),
+    Button(Button),
+    TextInput(TextInput),
+    ScrollInput(ScrollInput<B>),
+    Scrollable(Scrollable<B>),
+    Tooltip(Tooltip<B>),
+    Checkbox(Checkbox),
+    Portal(Portal<B>),
+    /// Node with no content, used to take up space.
+    Space,
+}
+
+impl<B: Backend> NodeVariant<B> {
+    /// Check if nodes of this variant take input, and can be reached with focus navigation.
+    #[inline]
+    pub const fn is_focusable(&self) -> bool {
+        matches!(self, Self::Button(_) | Self::TextInput(_) | Self::ScrollInput(_) | Self::Checkbox(_))
+    }
+
+    /// Get the type of the node, which identifies the variant regardless of its data. Themes key their rules by it.
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use amity_ui::{backend::headless::HeadlessBackend, node::{Button, Label, NodeType, NodeVariant}, rope::Rope};
+    /// type Variant = NodeVariant<HeadlessBackend>;
+    /// let label = |text: &str| Variant::Label(Label::new(Rope::from_str(text)));
+    /// let button = Variant::Button(Button::new(Rope::from_str("one")));
+    ///
+    /// assert_eq!(label("one").node_type(), label("two").node_type());
+    /// assert_ne!(label("one").node_type(), button.node_type());
+    ///
+    /// // Node types can be used as map keys
+    /// let rules = HashMap::from([(NodeType::Label, "label rules"), (NodeType::Button, "button rules")]);
+    /// assert_eq!(rules[&label("three").node_type()], "label rules");
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn node_type(&self) -> NodeType {
+        match self {
+            Self::Frame(_)       => NodeType::Frame,
+            Self::Label(_)       => NodeType::Label,
+            Self::Button(_)      => NodeType::Button,
+            Self::TextInput(_)   => NodeType::TextInput,
+            Self::ScrollInput(_) => NodeType::ScrollInput,
+            Self::Scrollable(_)  => NodeType::Scrollable,
+            Self::Tooltip(_)     => NodeType::Tooltip,
+            Self::Checkbox(_)    => NodeType::Checkbox,
+            Self::Portal(_)      => NodeType::Portal,
+            Self::Space          => NodeType::Space,
+        }
+    }
+}
+
+pub struct Node<B: Backend> {
+    pub data: NodeData<B>,
+    pub variant: NodeVariant<B>,
+}
+
+impl<B: Backend> Node<B> {
+    /// Test if the given point is within the node's bounds. The node's padding box, as of the last draw, is used as
+    /// the boundary, and the result is filtered through [`NodeData::hit_passthrough`].
+    ///
+    /// Because the box is only known after the node has been drawn, points will always be out of bounds for nodes
+    /// that haven't been drawn yet.
+    ///
+    /// Children of a [`Scrollable`] can only be hit within its viewport.
+    ///
+    /// # Params
+    ///
+    /// - `point`: Point to test, in window coordinates.
+    ///
+    /// # Returns
+    ///
+    /// [`HitPassthrough::Opaque`] if the point is inside the node's box, [`HitPassthrough::Passthrough`] otherwise,
+    /// combined with the node's `hit_passthrough` mask.
+    ///
+    /// # See Also
+    ///
+    /// - [`HitPassthrough::filter`]
+    pub fn in_bounds(&self, point: Vector2) -> HitPassthrough {
+        let mut hit = if self.data.padding_box.contains(point) {
+            HitPassthrough::Opaque
+        } else {
+            HitPassthrough::Passthrough
+        };
+
+        // Content of a scrollable is only visible within the viewport
+        if let NodeVariant::Scrollable(scrollable) = &self.variant
+            && !scrollable.viewport(self.data.padding_box).contains(point)
+        {
+            hit = hit.filter(HitPassthrough::PassthroughChildren);
+        }
+
+        hit.filter(self.data.hit_passthrough)
+    }
+
+    /// Compute [`NodeData::style`]: the style set with [`NodeData::set_style`], changed by every rule of the node's
+    /// theme that matches the node, and then by matching rules of the node's [breadcrumbs](NodeData::breadcrumbs).
+    /// Rules returned by style delegates during the last [`Self::update_style`] call are applied last. Rules are
+    /// applied in order, so later rules override fields set by earlier ones.
+    ///
+    /// This is done for every node during resize.
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use amity_ui::{backend::{Color, headless::HeadlessBackend}, node::{NodeType, label}, style::Style, tags,
+    /// #     tag_list::TagList, theme::{Breadcrumbs, Rule, Selector, StyleTemplate, Theme}};
+    /// let rule = |tags: TagList, fields: StyleTemplate<HeadlessBackend>| Rc::new(Rule {
+    ///     selector: Selector { node_type: NodeType::Label, tags, reject_all: false },
+    ///     fields,
+    ///     style_delegate: None,
+    ///     breadcrumbs: Breadcrumbs::default(),
+    /// });
+    /// let blue = Color::new(0, 0, 255, 255);
+    /// let mut theme = Theme::new();
+    /// theme.rules.insert(NodeType::Label, vec![
+    ///     rule(TagList::new(), StyleTemplate { font_size: Some(20.0), ..Default::default() }),
+    ///     rule(tags!(heading), StyleTemplate { text_color: Some(blue), ..Default::default() }),
+    /// ]);
+    ///
+    /// let mut heading = label((theme.clone(), Style::default().with_padding([4.0; 4]), tags!(heading)), "Title");
+    /// let mut body = label((theme, Style::default()), "Text");
+    /// heading.reload_style();
+    /// body.reload_style();
+    ///
+    /// assert_eq!(heading.data.style().font_size(), 20.0);
+    /// assert_eq!(heading.data.style().text_color(), blue);
+    /// assert_eq!(heading.data.style().padding(), [4.0; 4]);
+    /// assert_eq!(body.data.style().font_size(), 20.0);
+    /// assert_ne!(body.data.style().text_color(), blue);
+    /// ```
+    pub fn reload_style(&mut self) {
+        let node_type = self.variant.node_type();
+        let data = &mut self.data;
+        let mut style = data.base_style.clone();
+
+        let crumbs = data.breadcrumbs.load().filter(|rule| rule.selector.test(node_type, &data.tags));
+        for rule in data.theme.select(node_type, &data.tags).chain(crumbs) {
+            rule.apply(&mut style);
+        }
+        for rule in &data.delegate_rules {
+            rule.apply(&mut style);
+        }
+
+        data.style = style;
+    }
+
+    /// Call style delegates of the node, and of the rules matching it, and reload the style with the rules they
+    /// return. Called by the tree every frame, before the node is drawn.
+    ///
+    /// # Params
+    ///
+    /// - `backend`: Backend passed to the delegates.
+    ///
+    /// # Returns
+    ///
+    /// True if the new style changes the size of the node, so the node has to be resized.
+    pub fn update_style(&mut self, backend: &B) -> bool {
+        let node_type = self.variant.node_type();
+        let data = &self.data;
+        let rules: Vec<Rc<Rule<B>>> = data.theme.select(node_type, &data.tags)
+            .chain(data.breadcrumbs.load().filter(|rule| rule.selector.test(node_type, &data.tags)))
+            .filter(|rule| rule.style_delegate.is_some())
+            .cloned()
+            .collect();
+
+        if rules.is_empty() && self.data.style_delegates.is_empty() { return false; }
+
+        // Delegates are taken out of the node while they run, so they can be given the node
+        let delegates = std::mem::take(&mut self.data.style_delegates);
+        let delegate_rules = rules.iter()
+            .filter_map(|rule| rule.style_delegate.as_ref())
+            .chain(&delegates)
+            .map(|delegate| delegate(self, backend))
+            .collect();
+        self.data.style_delegates = delegates;
+        self.data.delegate_rules = delegate_rules;
+
+        let previous = self.data.style.clone();
+        self.reload_style();
+        !self.data.style.is_layout_equal(&previous)
+    }
+
+    /// Recalculate the minimum size of the node and its children, and clear [`NodeData::is_resize_pending`].
+    ///
+    /// The node's style is reloaded before measuring, and children inherit the node's theme, unless they have their
+    /// own. Breadcrumbs introduced by the node's style are pushed while the children are resized, so rules with
+    /// tree-aware selectors reach them. Children are measured next, then combined according to the node's
+    /// [`Layout::mode`]; margin, border and padding are added on top. Hidden nodes take no space. Nodes displaying text
+    /// set the size of their typeface for the given DPI.
+    ///
+    /// # Params
+    ///
+    /// - `available`:   Space available to the node, including its margin.
+    /// - `dpi`:         DPI of the window, as returned by [`Backend::dpi`].
+    /// - `breadcrumbs`: Breadcrumbs active in the branch, assigned to the node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the children, or the typeface of the node, is currently borrowed.
+    pub fn resize(&mut self, available: Vector2, dpi: Vector2, breadcrumbs: &mut Breadcrumbs<B>) {
+        self.data.is_resize_pending = false;
+
+        if self.data.is_hidden() {
+            self.data.min_size = Vector2::default();
+            return;
+        }
+
+        self.data.breadcrumbs = breadcrumbs.clone();
+        self.reload_style();
+        let data = &mut self.data;
+        let mut breadcrumbs = breadcrumbs.push_all(&data.style.breadcrumbs);
+
+        if let NodeVariant::Label(_) | NodeVariant::Button(_) | NodeVariant::TextInput(_) | NodeVariant::Tooltip(_)
+            = self.variant
+        {
+            data.style.typeface.borrow_mut().set_size(dpi, data.style.font_size);
+        }
+
+        // Tooltips float over other nodes, so they don't take up space
+        if let NodeVariant::Tooltip(_) = self.variant {
+            data.min_size = Vector2::default();
+            return;
+        }
+
+        let spacing = data.style.spacing();
+        let [gap_x, gap_y] = data.style.gap;
+        let available = Vector2::new((available.x - spacing.x).max(0.0), (available.y - spacing.y).max(0.0));
+
+        let content = match &mut self.variant {
+            NodeVariant::Label(label) | NodeVariant::Button(Button { label, .. }) => {
+                label.measure(&mut *data.style.typeface.borrow_mut(), available.x)
+            }
+            NodeVariant::Checkbox(_) => Vector2::new(Checkbox::SIZE, Checkbox::SIZE),
+            NodeVariant::Portal(portal) => portal.measure(),
+            _ => Vector2::default(),
+        };
+
+        let children: Vec<_> = data.children.iter()
+            .map(|child| {
+                let mut child = child.borrow_mut();
+                if !child.data.is_theme_explicit {
+                    child.data.theme = data.theme.clone();
+                }
+                child.resize(available, dpi, &mut breadcrumbs);
+                (child.data.min_size, child.data.layout)
+            })
+            .collect();
+
+        let mut children = data.layout.mode.measure(&children, Vector2::new(gap_x, gap_y), available);
+
+        // Scrollables only need space for the content on axes they can't scroll
+        if let NodeVariant::Scrollable(scrollable) = &mut self.variant {
+            scrollable.reload_style(&data.theme, &data.style);
+            if scrollable.horizontal.is_some() { children.x = 0.0; }
+            if scrollable.vertical.is_some() { children.y = 0.0; }
+            children += scrollable.scroll_bar_size();
+        }
+
+        data.min_size = Vector2::new(children.x.max(content.x), children.y.max(content.y)) + spacing;
+    }
+}
+
+/// Editing the children of a node. Implemented for shared node references, so that children can refer back to their
+/// parent.
+///
+/// ```
+/// # use std::{cell::RefCell, rc::Rc};
+/// # use amity_ui::{backend::headless::HeadlessBackend, node::{Node, ParentNode, label, vframe}};
+/// type Ref = Rc<RefCell<Node<HeadlessBackend>>>;
+/// let new_label = |text: &str| -> Ref { Rc::new(RefCell::new(label((), text))) };
+/// let [first, second, third] = ["First", "Second", "Third"].map(new_label);
+/// let [parent, other] = [vframe((), []), vframe((), [])];
+/// let is_child = |node: &Ref, parent: &Ref| {
+///     node.borrow().data.parent().is_some_and(|found| Rc::ptr_eq(&found, parent))
+/// };
+/// let children = |parent: &Ref| parent.borrow().data.children().to_vec();
+///
+/// parent.append_child(first.clone());
+/// parent.append_child(third.clone());
+/// parent.insert_child(1, second.clone());
+/// assert!(children(&parent).iter().zip([&first, &second, &third]).all(|(child, node)| Rc::ptr_eq(child, node)));
+/// assert!([&first, &second, &third].iter().all(|node| is_child(node, &parent)));
+///
+/// let removed = parent.remove_child(0);
+/// assert!(Rc::ptr_eq(&removed, &first));
+/// assert!(first.borrow().data.parent().is_none());
+///
+/// // Adding a node to another parent moves it
+/// other.append_child(second.clone());
+/// assert!(is_child(&second, &other));
+/// assert_eq!(children(&parent).len(), 1);
+/// assert!(Rc::ptr_eq(&children(&parent)[0], &third) && is_child(&third, &parent));
+/// ```
+pub trait ParentNode<B: Backend> {
+    /// Add a child at the end of the node's children. If the child already has a parent, it is moved.
+    ///
+    /// # Params
+    ///
+    /// - `child`: Node to add.
+    fn append_child(&self, child: Rc<RefCell<Node<B>>>);
+
+    /// Add a child at the given position among the node's children. If the child already has a parent, it is moved.
+    ///
+    /// # Params
+    ///
+    /// - `index`: Position to place the child at. Children at or after this index are moved back.
+    /// - `child`: Node to add.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of children.
+    fn insert_child(&self, index: usize, child: Rc<RefCell<Node<B>>>);
+
+    /// Remove a child from the node.
+    ///
+    /// # Params
+    ///
+    /// - `index`: Position of the child to remove.
+    ///
+    /// # Returns
+    ///
+    /// The removed child, now without a parent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    fn remove_child(&self, index: usize) -> Rc<RefCell<Node<B>>>;
+}
+
+impl<B: Backend> ParentNode<B> for Rc<RefCell<Node<B>>> {
+    #[inline]
+    fn append_child(&self, child: Rc<RefCell<Node<B>>>) {
+        detach(&child);
+
+        let index = self.borrow().data.children.len();
+        self.insert_child(index, child);
+    }
+
+    fn insert_child(&self, index: usize, child: Rc<RefCell<Node<B>>>) {
+        detach(&child);
+
+        let mut node = self.borrow_mut();
+        {
+            let mut child = child.borrow_mut();
+            child.data.parent = Rc::downgrade(self);
+            child.data.inherit_disabled(node.data.is_disabled());
+        }
+        node.data.children.insert(index, child);
+        node.data.update_size();
+    }
+
+    fn remove_child(&self, index: usize) -> Rc<RefCell<Node<B>>> {
+        let mut node = self.borrow_mut();
+        let child = node.data.children.remove(index);
+        node.data.update_size();
+
+        child.borrow_mut().data.parent = Weak::new();
+        child
+    }
+}
+
+/// Remove the node from its current parent, if it has one.
+fn detach<B: Backend>(node: &Rc<RefCell<Node<B>>>) {
+    let Some(parent) = node.borrow().data.parent() else { return };
+
+    let index = parent.borrow().data.children.iter().position(|child| Rc::ptr_eq(child, node));
+    if let Some(index) = index {
+        parent.remove_child(index);
+    }
 }
@@ -0,0 +1,274 @@
+use std::{cell::{Cell, RefCell}, collections::HashMap, path::PathBuf, rc::Rc};
+
+use crate::{backend::Backend, context::{HasContext, IO, TreeContext}};
+
+/// Identifies a resource loaded through an I/O system with [`IO::load_to`]. Handles are issued by, and only
+/// meaningful to, the system that loaded the resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ResourceHandle {
+    id: u32,
+}
+
+impl ResourceHandle {
+    /// Create a handle from its numeric ID.
+    #[inline]
+    pub const fn new(id: u32) -> Self {
+        Self { id }
+    }
+
+    /// Numeric ID of the handle.
+    #[inline]
+    pub const fn id(self) -> u32 {
+        self.id
+    }
+}
+
+/// Deferred operation loading a resource into a backend.
+///
+/// # Safety
+///
+/// Loaders may create textures, so they must be called from the thread that owns the graphics context.
+pub type ResourceLoader<B> = Box<dyn FnOnce(&mut B) -> <B as Backend>::Texture>;
+
+/// Data that has to be loaded by the backend before it can be used, such as a texture or a font. Resources are passed
+/// to [`IO::load_to`], which stamps them with a [`ResourceHandle`] identifying the loaded data.
+pub trait Resource<B: Backend> {
+    /// # Returns
+    ///
+    /// Handle assigned to the resource when it was last loaded, or `None` if it hasn't been loaded yet.
+    fn handle(&self) -> Option<ResourceHandle>;
+
+    /// Assign a handle to the resource. Called by the I/O system loading the resource.
+    fn set_handle(&mut self, handle: ResourceHandle);
+
+    /// Prepare the operation loading the resource into the backend. The I/O system will run it once the backend is
+    /// available.
+    fn loader(&self) -> ResourceLoader<B>;
+}
+
+/// Texture loaded from a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextureResource {
+    /// File to load the texture from.
+    pub path: PathBuf,
+
+    /// Handle of the loaded texture.
+    handle: Option<ResourceHandle>,
+}
+
+impl TextureResource {
+    /// Create a texture resource pointing at the given file. The file isn't read until the texture is loaded.
+    #[inline]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            handle: None,
+        }
+    }
+}
+
+impl<B: Backend> Resource<B> for TextureResource {
+    #[inline]
+    fn handle(&self) -> Option<ResourceHandle> {
+        self.handle
+    }
+
+    #[inline]
+    fn set_handle(&mut self, handle: ResourceHandle) {
+        self.handle = Some(handle);
+    }
+
+    fn loader(&self) -> ResourceLoader<B> {
+        let path = self.path.clone();
+
+        // SAFETY: Running the loader is restricted to the graphics thread by ResourceLoader's contract
+        Box::new(move |backend: &mut B| unsafe { backend.load_texture(&path) })
+    }
+}
+
+/// Reference [`IO`] implementation for loading resources. Resources passed to [`IO::load_to`] are given a handle
+/// immediately, and are loaded into the backend on the next call to [`Self::load_pending`]. Each resource is only
+/// loaded once; passing it again keeps its handle.
+///
+/// ```
+/// # use std::path::Path;
+/// # use amity_ui::{backend::headless::HeadlessBackend, context::IO, resource::{Resource, ResourceSpace, TextureResource}};
+/// let mut backend = HeadlessBackend::new();
+/// let mut space = ResourceSpace::new();
+/// let mut icon = TextureResource::new("icon.png");
+///
+/// space.load_to(&mut icon);
+/// let handle = Resource::<HeadlessBackend>::handle(&icon).unwrap();
+/// assert!(space.texture(handle).is_none());
+///
+/// unsafe { space.load_pending(&mut backend) };
+/// assert_eq!(space.texture(handle).unwrap().path.as_deref(), Some(Path::new("icon.png")));
+///
+/// // Loading again keeps the handle
+/// space.load_to(&mut icon);
+/// assert_eq!(Resource::<HeadlessBackend>::handle(&icon), Some(handle));
+/// ```
+pub struct ResourceSpace<B: Backend> {
+    /// ID of the next issued handle.
+    next_id: Cell<u32>,
+
+    /// Resources waiting for the backend.
+    pending: RefCell<Vec<(ResourceHandle, ResourceLoader<B>)>>,
+
+    /// Textures loaded so far.
+    textures: HashMap<ResourceHandle, B::Texture>,
+}
+
+impl<B: Backend> ResourceSpace<B> {
+    /// Create a space with no resources loaded.
+    pub fn new() -> Self {
+        Self {
+            next_id: Cell::new(0),
+            pending: RefCell::new(Vec::new()),
+            textures: HashMap::new(),
+        }
+    }
+
+    /// Check if the handle refers to a resource that is loaded, or waiting to be loaded, by this space.
+    fn owns(&self, handle: ResourceHandle) -> bool {
+        self.textures.contains_key(&handle)
+            || self.pending.borrow().iter().any(|(pending, _)| *pending == handle)
+    }
+
+    /// Get a loaded texture.
+    ///
+    /// # Returns
+    ///
+    /// The texture, or `None` if the handle wasn't issued by this space, or the texture hasn't been loaded yet.
+    #[inline]
+    pub fn texture(&self, handle: ResourceHandle) -> Option<&B::Texture> {
+        self.textures.get(&handle)
+    }
+
+    /// Load every resource passed to [`IO::load_to`] since the last call.
+    ///
+    /// # Safety
+    ///
+    /// Textures are loaded with `backend`, so the requirements of [`Backend::load_texture_from_image`] apply. The
+    /// textures must be released with [`Self::unload`], using the same backend.
+    pub unsafe fn load_pending(&mut self, backend: &mut B) {
+        for (handle, loader) in self.pending.get_mut().drain(..) {
+            self.textures.insert(handle, loader(backend));
+        }
+    }
+
+    /// Unload every texture loaded by this space. Handles issued so far become invalid; resources passed to
+    /// [`IO::load_to`] afterwards are given new handles.
+    ///
+    /// # Safety
+    ///
+    /// `backend` must be the backend passed to [`Self::load_pending`]. Textures are unloaded with
+    /// [`Backend::unload_texture`], so none of them may still be waiting to be drawn.
+    pub unsafe fn unload(&mut self, backend: &mut B) {
+        for (_, texture) in self.textures.drain() {
+            unsafe { backend.unload_texture(texture) };
+        }
+        self.pending.get_mut().clear();
+    }
+}
+
+impl<B: Backend> Default for ResourceSpace<B> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Backend> HasContext<B> for ResourceSpace<B> {
+    #[inline]
+    fn tree_context(&self) -> TreeContext<'_, B> {
+        TreeContext { ptr: None }
+    }
+}
+
+impl<B: Backend> IO<B> for ResourceSpace<B> {
+    fn load_to(&self, resource: &mut dyn Resource<B>) {
+        if resource.handle().is_some_and(|handle| self.owns(handle)) { return; }
+
+        let handle = ResourceHandle::new(self.next_id.get());
+        self.next_id.set(handle.id + 1);
+        self.pending.borrow_mut().push((handle, resource.loader()));
+        resource.set_handle(handle);
+    }
+}
+
+/// Textures waiting to be unloaded. A texture drawn during a frame can't be unloaded until the frame ends, since
+/// backends may only read it once the frame is presented. Such textures are pushed here instead, and unloaded by
+/// [`crate::tree::LayoutTree::begin_frame`] when the next frame starts.
+///
+/// Clones share the same queue.
+///
+/// ```
+/// # use amity_ui::{backend::{Backend, headless::HeadlessBackend}, resource::UnloadQueue};
+/// let mut backend = HeadlessBackend::new();
+/// let queue = UnloadQueue::new();
+/// let image = backend.new_image(4, 4);
+/// queue.clone().push(unsafe { backend.load_texture_from_image(&image) });
+/// assert_eq!(queue.len(), 1);
+///
+/// unsafe { queue.unload_all(&mut backend) };
+/// assert!(queue.is_empty());
+/// ```
+pub struct UnloadQueue<B: Backend> {
+    textures: Rc<RefCell<Vec<B::Texture>>>,
+}
+
+impl<B: Backend> UnloadQueue<B> {
+    /// Create an empty queue.
+    pub fn new() -> Self {
+        Self {
+            textures: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    /// Queue a texture to be unloaded.
+    #[inline]
+    pub fn push(&self, texture: B::Texture) {
+        self.textures.borrow_mut().push(texture);
+    }
+
+    /// Number of textures in the queue.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.textures.borrow().len()
+    }
+
+    /// Check if the queue is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.textures.borrow().is_empty()
+    }
+
+    /// Unload every queued texture.
+    ///
+    /// # Safety
+    ///
+    /// Textures are unloaded with [`Backend::unload_texture`], so the frame they were last drawn in must have ended,
+    /// and `backend` must be the backend that loaded them.
+    pub unsafe fn unload_all(&self, backend: &mut B) {
+        for texture in self.textures.borrow_mut().drain(..) {
+            unsafe { backend.unload_texture(texture) };
+        }
+    }
+}
+
+impl<B: Backend> Clone for UnloadQueue<B> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            textures: Rc::clone(&self.textures),
+        }
+    }
+}
+
+impl<B: Backend> Default for UnloadQueue<B> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,21 +1,50 @@
-use std::{cell::RefCell, rc::Weak};
+use std::{borrow::Cow, collections::VecDeque, fmt, ops::Range, rc::Rc};
 
 /// Rope implementation, providing more efficient modification if there's lots of text.
 ///
 /// The `Rope` structure acts as a slice, a view into the rope's contents. If additional text is added to a node stored
 /// inside, the change will not be reflected by the rope.
 ///
-/// `rope.init` is guaranteed to be valid and empty.
+/// `Rope::new()` is guaranteed to be valid and empty.
+///
+/// Ropes compare and format by their text, regardless of how it is split into nodes:
+///
+/// ```
+/// # use amity_ui::rope::Rope;
+/// let flat = Rope::from_str("Hello, world!");
+/// let tree = Rope::concat(Rope::from_str("Hello, "), Rope::from_str("world!"));
+/// assert_eq!(format!("{tree}"), "Hello, world!");
+/// assert_eq!(flat, tree);
+/// assert_eq!(tree, "Hello, world!");
+/// assert_ne!(tree.slice(0, 5), tree);
+/// ```
+///
+/// A rope owns its nodes, so it stays valid after the ropes it was built from are dropped:
+///
+/// ```
+/// # use amity_ui::rope::Rope;
+/// fn build() -> Rope {
+///     let hello = Rope::from_str("Hello, ");
+///     let world = Rope::from_str("world!");
+///     Rope::concat(hello, world).insert(5, " there")
+/// }
+///
+/// let rope = build();
+/// assert_eq!(rope.value(), "Hello there, world!");
+/// assert_eq!(rope.slice(6, 5), "there");
+/// ```
 ///
 /// See_Also: https://en.wikipedia.org/wiki/Rope_(data_structure)
+#[derive(Clone)]
 pub struct Rope {
-    /// Content of the rope, if it contains children.
-    node: Weak<RefCell<RopeNode>>,
+    /// Content of the rope, if it contains children. Nodes are immutable, so they are shared between ropes created
+    /// from each other.
+    node: Option<Rc<RopeNode>>,
 
     /// Content of the rope if it's a leaf. Not sliced; to get the text with the slice applied, use `value`.
     ///
     /// This must be a fully valid string. Content may not be split in the middle of a codepoint.
-    leaf_text: Box<[u8]>,
+    leaf_text: Rc<str>,
 
     /// Start and length of the rope, in UTF-8 bytes.
     start: usize,
@@ -25,10 +54,1267 @@ pub struct Rope {
     depth: i32,
 }
 
+impl Rope {
+    /// Maximum difference in depth between the two sides of a concatenation. If exceeded, the deeper side is rotated
+    /// into the other to keep the tree balanced.
+    pub const MAX_DEPTH_DIFFERENCE: i32 = 2;
+
+    /// Maximum depth of a rope. Concatenation rebalances ropes that would exceed it.
+    ///
+    /// # See Also
+    ///
+    /// - [`Self::rebalance`]
+    pub const MAX_DEPTH: i32 = 64;
+
+    /// Number of codepoints included in the [`fmt::Debug`] output of a rope.
+    pub const DEBUG_PREVIEW: usize = 32;
+
+    /// Create an empty rope.
+    pub fn new() -> Self {
+        Self::from_str("")
+    }
+
+    /// Create a leaf rope holding the given text.
+    #[allow(clippy::should_implement_trait, reason = "construction is infallible, `FromStr` would only add an unwrap")]
+    pub fn from_str(text: &str) -> Self {
+        Self {
+            node: None,
+            leaf_text: text.into(),
+            start: 0,
+            length: text.len(),
+            depth: 1,
+        }
+    }
+
+    /// Length of the rope, in UTF-8 bytes.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.length
+    }
+
+    /// True if the rope contains no text.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Depth of the rope's tree. Leaves have a depth of `1`.
+    #[inline]
+    pub const fn depth(&self) -> i32 {
+        self.depth
+    }
+
+    /// True if the rope is a leaf, with text stored directly rather than in children nodes.
+    #[inline]
+    pub fn is_leaf(&self) -> bool {
+        self.node.is_none()
+    }
+
+    /// Get the node holding the rope's children.
+    ///
+    /// The children are stored whole; the slice of this rope is not applied to them.
+    ///
+    /// # Returns
+    ///
+    /// The node of the rope, or [`None`] if the rope is a leaf.
+    #[inline]
+    pub fn node(&self) -> Option<&RopeNode> {
+        self.node.as_deref()
+    }
+
+    /// Concatenate two ropes.
+    ///
+    /// Neither rope is copied; the result holds both of them as children. If either rope is empty, the other one is
+    /// returned unchanged.
+    ///
+    /// Repeated concatenation keeps the depth logarithmic in the length of the rope:
+    ///
+    /// ```
+    /// # use amity_ui::rope::Rope;
+    /// let mut rope = Rope::new();
+    /// for _ in 0..10_000 {
+    ///     rope = Rope::concat(rope, Rope::from_str("a"));
+    /// }
+    /// assert_eq!(rope.len(), 10_000);
+    /// assert!(rope.chars().all(|char| char == 'a'));
+    ///
+    /// // 10,000 leaves fit in a tree 14 levels deep, and rotations keep the rope within a small factor of that
+    /// let limit = 2 * 10_000f64.log2().ceil() as i32;
+    /// assert!(rope.depth() <= limit, "depth {} exceeds {limit}", rope.depth());
+    ///
+    /// // Concatenating with an empty rope doesn't add a level
+    /// assert_eq!(Rope::concat(rope.clone(), Rope::new()).depth(), rope.depth());
+    /// assert_eq!(Rope::concat(Rope::new(), rope.clone()).depth(), rope.depth());
+    /// ```
+    ///
+    /// # Params
+    ///
+    /// - `left`: Rope to place first.
+    /// - `right`: Rope to place after `left`.
+    ///
+    /// # Returns
+    ///
+    /// A rope holding the text of both ropes. If it would be deeper than [`Self::MAX_DEPTH`], it is rebalanced.
+    pub fn concat(left: Rope, right: Rope) -> Rope {
+        if left.is_empty() { return right; }
+        if right.is_empty() { return left; }
+
+        // Rotate the deeper side into the other one to keep the tree balanced
+        if left.depth > right.depth + Self::MAX_DEPTH_DIFFERENCE
+            && let Some((inner_left, inner_right)) = left.children()
+        {
+            return Self::concat(inner_left, Self::concat(inner_right, right));
+        }
+        if right.depth > left.depth + Self::MAX_DEPTH_DIFFERENCE
+            && let Some((inner_left, inner_right)) = right.children()
+        {
+            return Self::concat(Self::concat(left, inner_left), inner_right);
+        }
+
+        let result = Self::join(left, right);
+        if result.depth > Self::MAX_DEPTH {
+            return result.rebalance();
+        }
+        result
+    }
+
+    /// Create a node holding both ropes, without checking if they are empty or balanced.
+    fn join(left: Rope, right: Rope) -> Rope {
+        let length = left.length + right.length;
+        let depth = left.depth.max(right.depth) + 1;
+
+        Self {
+            node: Some(Rc::new(RopeNode::new(left, right))),
+            leaf_text: "".into(),
+            start: 0,
+            length,
+            depth,
+        }
+    }
+
+    /// Rebuild the rope so its depth is close to the lowest possible for its length, using the Fibonacci-bucket
+    /// algorithm described by Boehm, Atkinson and Plass.
+    ///
+    /// The leaves are kept as they are, and the text of the rope doesn't change. Only the nodes above the leaves are
+    /// recreated.
+    ///
+    /// ```
+    /// # use amity_ui::rope::Rope;
+    /// let mut rope = Rope::new();
+    /// for index in 0..5000 {
+    ///     rope = rope.insert(rope.len(), if index % 2 == 0 { "a" } else { "b" });
+    /// }
+    ///
+    /// let balanced = rope.rebalance();
+    /// assert_eq!(balanced, rope);
+    /// assert_eq!(balanced.slice(1000, 4).value(), "abab");
+    ///
+    /// // A balanced rope of depth `d` holds at least `fibonacci(d + 1)` leaves
+    /// let (mut previous, mut fibonacci, mut depth) = (0, 1, 1);
+    /// while fibonacci <= 5000 {
+    ///     (previous, fibonacci) = (fibonacci, previous + fibonacci);
+    ///     depth += 1;
+    /// }
+    /// assert!(balanced.depth() <= depth);
+    /// assert!(rope.depth() <= depth);
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// A balanced rope with the same text.
+    pub fn rebalance(&self) -> Rope {
+        let mut leaves = Vec::new();
+        self.push_leaves(&mut leaves);
+
+        // Slot `i` holds a rope with length in `fibonacci(i + 2)..fibonacci(i + 3)`. Slots with higher indices hold
+        // text that comes earlier.
+        let mut slots: Vec<Option<Rope>> = Vec::new();
+
+        for mut rope in leaves {
+            let mut index = 0;
+
+            loop {
+                // Absorb the text on the left until the rope fits in the slot
+                while rope.length >= fibonacci(index + 3) {
+                    if let Some(slot) = slots.get_mut(index).and_then(Option::take) {
+                        rope = Self::join(slot, rope);
+                    }
+                    index += 1;
+                }
+
+                if slots.len() <= index {
+                    slots.resize(index + 1, None);
+                }
+
+                match slots[index].take() {
+                    Some(slot) => rope = Self::join(slot, rope),
+                    None => {
+                        slots[index] = Some(rope);
+                        break;
+                    }
+                }
+            }
+        }
+
+        slots.into_iter()
+            .flatten()
+            .reduce(|right, left| Self::join(left, right))
+            .unwrap_or_default()
+    }
+
+    /// Collect the non-empty leaves of the rope, with the slice applied, in order.
+    fn push_leaves(&self, out: &mut Vec<Rope>) {
+        match self.children() {
+            Some((left, right)) => {
+                left.push_leaves(out);
+                right.push_leaves(out);
+            }
+            None if !self.is_empty() => out.push(self.clone()),
+            None => {}
+        }
+    }
+
+    /// Get both children of the rope, with the slice applied.
+    ///
+    /// # Returns
+    ///
+    /// Left and right children of the rope, or [`None`] if the rope is a leaf.
+    fn children(&self) -> Option<(Rope, Rope)> {
+        let node = self.node.as_deref()?;
+        let left_length = node.left.length;
+        let start = self.start;
+        let end = self.start + self.length;
+
+        let left = node.left.clip(start.min(left_length)..end.min(left_length));
+        let right = node.right.clip(start.max(left_length) - left_length..end.max(left_length) - left_length);
+        Some((left, right))
+    }
+
+    /// Count codepoints in the rope.
+    ///
+    /// Counts are cached in nodes, so this only needs to scan the leaves at the edges of the slice.
+    pub fn char_len(&self) -> usize {
+        if let Some(count) = self.cached(|node| node.char_count) {
+            return count;
+        }
+        match self.children() {
+            Some((left, right)) => left.char_len() + right.char_len(),
+            None => self.leaf_value().chars().count(),
+        }
+    }
+
+    /// Count lines in the rope.
+    ///
+    /// Lines are separated with `\n`; a `\r\n` sequence counts as a single line break. A line break at the end of
+    /// the rope ends the last line rather than starting a new one, and an empty rope has no lines.
+    ///
+    /// ```
+    /// # use amity_ui::rope::Rope;
+    /// let empty = Rope::new();
+    /// assert_eq!(empty.line_count(), 0);
+    /// assert_eq!(empty.byte_to_line(0), 0);
+    /// assert_eq!(empty.line_to_byte(0), 0);
+    /// assert_eq!(empty.line_to_byte(1), 0);
+    ///
+    /// // No trailing line break
+    /// let rope = Rope::concat(Rope::from_str("one\ntw"), Rope::from_str("o\nthree"));
+    /// assert_eq!(rope.line_count(), 3);
+    /// assert_eq!(rope.byte_to_line(3), 0);
+    /// assert_eq!(rope.byte_to_line(4), 1);
+    /// assert_eq!(rope.byte_to_line(rope.len()), 2);
+    /// assert_eq!(rope.line_to_byte(1), 4);
+    /// assert_eq!(rope.line_to_byte(2), 8);
+    /// assert_eq!(rope.line_to_byte(3), rope.len());
+    ///
+    /// // A trailing line break ends the last line
+    /// let rope = Rope::from_str("one\ntwo\n");
+    /// assert_eq!(rope.line_count(), 2);
+    /// assert_eq!(rope.byte_to_line(rope.len()), 2);
+    /// assert_eq!(rope.line_to_byte(2), rope.len());
+    ///
+    /// // `\r\n` is a single line break
+    /// let rope = Rope::concat(Rope::from_str("one\r"), Rope::from_str("\ntwo\r\n"));
+    /// assert_eq!(rope.line_count(), 2);
+    /// assert_eq!(rope.byte_to_line(4), 0);
+    /// assert_eq!(rope.byte_to_line(5), 1);
+    /// assert_eq!(rope.line_to_byte(1), 5);
+    /// ```
+    pub fn line_count(&self) -> usize {
+        if self.is_empty() { return 0; }
+
+        let ends_with_break = self.byte_at(self.length - 1) == b'\n';
+        self.line_breaks() + usize::from(!ends_with_break)
+    }
+
+    /// Find the line containing the given byte.
+    ///
+    /// # Params
+    ///
+    /// - `index`: Index of the byte, in UTF-8 bytes. May be equal to the length of the rope.
+    ///
+    /// # Returns
+    ///
+    /// Index of the line, starting from `0`.
+    ///
+    /// # Panics
+    ///
+    /// If the index is out of bounds.
+    pub fn byte_to_line(&self, index: usize) -> usize {
+        assert!(index <= self.length, "index {index} is out of bounds of rope of length {}", self.length);
+
+        self.sub(0..index).line_breaks()
+    }
+
+    /// Find the first byte of the given line.
+    ///
+    /// # Params
+    ///
+    /// - `line`: Index of the line, starting from `0`.
+    ///
+    /// # Returns
+    ///
+    /// Index of the first byte of the line, in UTF-8 bytes. If the rope has fewer lines, the length of the rope.
+    pub fn line_to_byte(&self, line: usize) -> usize {
+        if line == 0 { return 0; }
+        if line > self.line_breaks() { return self.length; }
+
+        self.line_break_end(line)
+    }
+
+    /// Count `\n` bytes in the rope.
+    fn line_breaks(&self) -> usize {
+        if let Some(count) = self.cached(|node| node.line_breaks) {
+            return count;
+        }
+        match self.children() {
+            Some((left, right)) => left.line_breaks() + right.line_breaks(),
+            None => self.leaf_value().bytes().filter(|&byte| byte == b'\n').count(),
+        }
+    }
+
+    /// Find the end of the given line break.
+    ///
+    /// # Params
+    ///
+    /// - `line`: Number of the line break, starting from `1`. The rope must have at least this many line breaks.
+    ///
+    /// # Returns
+    ///
+    /// Index of the byte right after the line break.
+    fn line_break_end(&self, line: usize) -> usize {
+        match self.children() {
+            Some((left, right)) => {
+                let left_breaks = left.line_breaks();
+                if line <= left_breaks {
+                    left.line_break_end(line)
+                } else {
+                    left.length + right.line_break_end(line - left_breaks)
+                }
+            }
+            None => {
+                let (index, _) = self.leaf_value().match_indices('\n')
+                    .nth(line - 1)
+                    .expect("rope should have enough line breaks");
+                index + 1
+            }
+        }
+    }
+
+    /// Get a byte of the rope.
+    ///
+    /// # Params
+    ///
+    /// - `index`: Index of the byte. Must be in bounds.
+    fn byte_at(&self, index: usize) -> u8 {
+        match self.children() {
+            Some((left, _)) if index < left.length => left.byte_at(index),
+            Some((left, right)) => right.byte_at(index - left.length),
+            None => self.leaf_value().as_bytes()[index],
+        }
+    }
+
+    /// Read a value cached in the node, if the rope spans the whole node.
+    fn cached(&self, value: impl FnOnce(&RopeNode) -> usize) -> Option<usize> {
+        let node = self.node.as_deref()?;
+        let is_whole = self.start == 0 && self.length == node.left.length + node.right.length;
+
+        is_whole.then(|| value(node))
+    }
+
+    /// Get the text of a leaf, with the slice applied. Returns an empty string for nodes.
+    fn leaf_value(&self) -> &str {
+        if !self.is_leaf() { return ""; }
+
+        &self.leaf_text[self.start..self.start + self.length]
+    }
+
+    /// Get the bytes of a leaf, with the slice applied. Unlike [`Self::leaf_value`], this works even if the slice
+    /// splits a codepoint. Returns an empty slice for nodes.
+    fn leaf_bytes(&self) -> &[u8] {
+        if !self.is_leaf() { return &[]; }
+
+        &self.leaf_text.as_bytes()[self.start..self.start + self.length]
+    }
+
+    /// Get a part of the rope. No text is copied; the result shares its nodes with this rope.
+    ///
+    /// ```
+    /// # use amity_ui::rope::Rope;
+    /// let rope = Rope::concat(Rope::from_str("Hello, "), Rope::from_str("wörld"));
+    ///
+    /// // Slices can span both sides of a node
+    /// assert_eq!(rope.slice(3, 7).to_string(), "lo, wö");
+    /// assert_eq!(rope.slice(7, 6).to_string(), "wörld");
+    /// assert_eq!(rope.slice(0, 7).slice(5, 2).to_string(), ", ");
+    /// ```
+    ///
+    /// Slicing in the middle of a codepoint panics:
+    ///
+    /// ```should_panic
+    /// # use amity_ui::rope::Rope;
+    /// Rope::from_str("wörld").slice(0, 2);
+    /// ```
+    ///
+    /// # Params
+    ///
+    /// - `start`: Index of the first byte to include, in UTF-8 bytes.
+    /// - `len`: Length of the slice, in UTF-8 bytes.
+    ///
+    /// # Returns
+    ///
+    /// A rope holding the given part of the text.
+    ///
+    /// # Panics
+    ///
+    /// If the range is out of bounds, or if either end of it lands in the middle of a codepoint.
+    pub fn slice(&self, start: usize, len: usize) -> Rope {
+        let end = start.checked_add(len)
+            .filter(|&end| end <= self.length)
+            .unwrap_or_else(|| panic!("range {start}..{start}+{len} is out of bounds of rope of length {}", self.length));
+
+        assert!(
+            self.is_char_boundary(start) && self.is_char_boundary(end),
+            "range {start}..{end} splits a codepoint",
+        );
+
+        self.sub(start..end)
+    }
+
+    /// Insert text into the rope. The original rope is left unchanged; the result shares its nodes with it.
+    ///
+    /// Only the nodes along the path to the insertion point are created anew, so inserting into a large rope touches
+    /// `O(log n)` nodes:
+    ///
+    /// ```
+    /// # use std::collections::HashSet;
+    /// # use amity_ui::rope::{Rope, RopeNode};
+    /// fn collect(rope: &Rope, out: &mut HashSet<*const RopeNode>) {
+    ///     if let Some(node) = rope.node() && out.insert(node) {
+    ///         collect(&node.left, out);
+    ///         collect(&node.right, out);
+    ///     }
+    /// }
+    ///
+    /// let mut rope = Rope::new();
+    /// for index in 0..10_000 {
+    ///     rope = Rope::concat(rope, Rope::from_str(if index % 2 == 0 { "ab" } else { "cd" }));
+    /// }
+    /// let inserted = rope.insert(rope.len() / 2, "middle");
+    /// assert_eq!(inserted.slice(10_000, 8).value(), "middleab");
+    ///
+    /// let mut old_nodes = HashSet::new();
+    /// let mut new_nodes = HashSet::new();
+    /// collect(&rope, &mut old_nodes);
+    /// collect(&inserted, &mut new_nodes);
+    ///
+    /// let created = new_nodes.difference(&old_nodes).count();
+    /// assert!(created <= 4 * rope.depth() as usize, "{created} nodes created for depth {}", rope.depth());
+    /// assert!(old_nodes.len() >= 9_999);
+    /// ```
+    ///
+    /// # Params
+    ///
+    /// - `at`: Index to insert the text at, in UTF-8 bytes.
+    /// - `text`: Text to insert.
+    ///
+    /// # Returns
+    ///
+    /// A rope with the text inserted.
+    ///
+    /// # Panics
+    ///
+    /// If `at` is out of bounds, or lands in the middle of a codepoint.
+    pub fn insert(&self, at: usize, text: &str) -> Rope {
+        self.replace(at..at, text)
+    }
+
+    /// Remove a part of the rope. The original rope is left unchanged; the result shares its nodes with it.
+    ///
+    /// ```
+    /// # use amity_ui::rope::Rope;
+    /// let rope = Rope::concat(Rope::from_str("héllo, "), Rope::from_str("wörld🌍"));
+    /// assert_eq!(rope.delete(1..8).value(), "hwörld🌍");
+    ///
+    /// // Deleting the entire range collapses back to an empty leaf
+    /// let empty = rope.delete(0..rope.len());
+    /// assert!(empty.is_empty());
+    /// assert!(empty.is_leaf());
+    /// assert_eq!(empty.depth(), 1);
+    /// assert_eq!(empty, Rope::new());
+    /// assert_eq!(empty.line_count(), 0);
+    /// ```
+    ///
+    /// # Params
+    ///
+    /// - `range`: Range to remove, in UTF-8 bytes.
+    ///
+    /// # Returns
+    ///
+    /// A rope with the range removed.
+    ///
+    /// # Panics
+    ///
+    /// If the range is out of bounds, or if either end of it lands in the middle of a codepoint.
+    pub fn delete(&self, range: Range<usize>) -> Rope {
+        self.replace(range, "")
+    }
+
+    /// Replace a part of the rope with new text. The original rope is left unchanged; the result shares its nodes
+    /// with it.
+    ///
+    /// # Params
+    ///
+    /// - `range`: Range to replace, in UTF-8 bytes.
+    /// - `text`: Text to place in the range.
+    ///
+    /// # Returns
+    ///
+    /// A rope with the range replaced.
+    ///
+    /// # Panics
+    ///
+    /// If the range is out of bounds, or if either end of it lands in the middle of a codepoint.
+    pub fn replace(&self, range: Range<usize>, text: &str) -> Rope {
+        assert!(
+            range.start <= range.end && range.end <= self.length,
+            "range {range:?} is out of bounds of rope of length {}", self.length,
+        );
+        assert!(
+            self.is_char_boundary(range.start) && self.is_char_boundary(range.end),
+            "range {range:?} splits a codepoint",
+        );
+
+        let left = self.sub(0..range.start);
+        let right = self.sub(range.end..self.length);
+
+        Rope::concat(Rope::concat(left, Rope::from_str(text)), right)
+    }
+
+    /// Check if the given index is on a codepoint boundary. The start and end of the rope are boundaries.
+    ///
+    /// # Params
+    ///
+    /// - `index`: Index to check, in UTF-8 bytes.
+    ///
+    /// # Returns
+    ///
+    /// True if the index is the start or the end of a codepoint, false if it splits one or is out of bounds.
+    pub fn is_char_boundary(&self, index: usize) -> bool {
+        if index == 0 || index == self.length { return true; }
+        if index > self.length { return false; }
+
+        match self.children() {
+            Some((left, _)) if index < left.length => left.is_char_boundary(index),
+            Some((left, right)) => right.is_char_boundary(index - left.length),
+            None => self.leaf_text.is_char_boundary(self.start + index),
+        }
+    }
+
+    /// Cut a part of the rope, descending into children if the part fits within one. Codepoint boundaries are not
+    /// checked.
+    ///
+    /// # Params
+    ///
+    /// - `range`: Range to keep, in UTF-8 bytes, relative to the start of the rope.
+    fn sub(&self, range: Range<usize>) -> Rope {
+        if range.is_empty() { return Rope::new(); }
+
+        if let Some((left, right)) = self.children() {
+            if range.end <= left.length {
+                return left.sub(range);
+            }
+            if range.start >= left.length {
+                return right.sub(range.start - left.length..range.end - left.length);
+            }
+        }
+
+        self.clip(range)
+    }
+
+    /// Cut a part of the rope without checking for codepoint boundaries.
+    ///
+    /// # Params
+    ///
+    /// - `range`: Range to keep, in UTF-8 bytes, relative to the start of the rope.
+    fn clip(&self, range: Range<usize>) -> Rope {
+        Self {
+            start: self.start + range.start,
+            length: range.len(),
+            ..self.clone()
+        }
+    }
+
+    /// Iterate over the codepoints in the rope. The tree is walked lazily, without collecting the text into a string.
+    ///
+    /// ```
+    /// # use amity_ui::rope::Rope;
+    /// let greeting = Rope::concat(Rope::from_str("héllo, "), Rope::from_str("wörld"));
+    /// let rope = Rope::concat(
+    ///     Rope::concat(greeting.slice(1, 10), Rope::from_str("🌍\n")),
+    ///     Rope::concat(Rope::from_str("ünïcode").slice(2, 6), greeting.slice(9, 5)),
+    /// );
+    /// let value = rope.value();
+    /// assert_eq!(value, "éllo, wö🌍\nnïcodörld");
+    ///
+    /// assert!(rope.chars().eq(value.chars()));
+    /// assert!(rope.chars().rev().eq(value.chars().rev()));
+    /// assert!(rope.bytes().eq(value.bytes()));
+    /// assert!(rope.bytes().rev().eq(value.bytes().rev()));
+    ///
+    /// // Both ends can be consumed at once
+    /// let mut chars = rope.chars();
+    /// assert_eq!((chars.next(), chars.next_back()), (Some('é'), Some('d')));
+    /// assert_eq!(chars.collect::<String>(), value[2..value.len() - 1]);
+    /// ```
+    pub fn chars(&self) -> Chars {
+        Chars(Edges::new(self))
+    }
+
+    /// Iterate over the UTF-8 bytes in the rope. The tree is walked lazily, without collecting the text into a string.
+    pub fn bytes(&self) -> Bytes {
+        Bytes(Edges::new(self))
+    }
+
+    /// Find the first occurrence of the given text in the rope.
+    ///
+    /// The rope is searched leaf by leaf, without collecting its text, so matches spanning multiple leaves are found
+    /// too.
+    ///
+    /// ```
+    /// # use amity_ui::rope::Rope;
+    /// let rope = Rope::concat(Rope::from_str("Hello, wo"), Rope::from_str("rld! Hello!"));
+    /// assert_eq!(rope.find("world"), Some(7));
+    /// assert_eq!(rope.find("Hello"), Some(0));
+    /// assert_eq!(rope.find("hello"), None);
+    /// assert_eq!(rope.rfind("Hello"), Some(14));
+    /// assert_eq!(rope.rfind("o, wor"), Some(4));
+    /// assert_eq!(rope.rfind("planet"), None);
+    /// ```
+    ///
+    /// # Params
+    ///
+    /// - `needle`: Text to search for.
+    ///
+    /// # Returns
+    ///
+    /// Start of the first match, in UTF-8 bytes, or [`None`] if the text doesn't occur in the rope. An empty needle
+    /// matches at the start of the rope.
+    #[inline]
+    pub fn find(&self, needle: &str) -> Option<usize> {
+        Matches::new(self.bytes(), needle.bytes()).next()
+    }
+
+    /// Find the last occurrence of the given text in the rope. Like [`Self::find`], matches spanning multiple leaves
+    /// are found.
+    ///
+    /// # Params
+    ///
+    /// - `needle`: Text to search for.
+    ///
+    /// # Returns
+    ///
+    /// Start of the last match, in UTF-8 bytes, or [`None`] if the text doesn't occur in the rope. An empty needle
+    /// matches at the end of the rope.
+    pub fn rfind(&self, needle: &str) -> Option<usize> {
+        let end = Matches::new(self.bytes().rev(), needle.bytes().rev()).next()?;
+        Some(self.length - end - needle.len())
+    }
+
+    /// Find all occurrences of the given text in the rope. Overlapping occurrences are all included.
+    ///
+    /// ```
+    /// # use amity_ui::rope::Rope;
+    /// let rope = Rope::concat(Rope::from_str("aaba"), Rope::from_str("aa"));
+    /// assert_eq!(rope.find_all("aa"), [0, 3, 4]);
+    /// assert_eq!(rope.find_all("a"), [0, 1, 3, 4, 5]);
+    /// assert_eq!(Rope::from_str("aaaa").find_all("aa"), [0, 1, 2]);
+    /// assert_eq!(rope.find_all("c"), []);
+    /// ```
+    ///
+    /// # Params
+    ///
+    /// - `needle`: Text to search for.
+    ///
+    /// # Returns
+    ///
+    /// Starts of every match, in UTF-8 bytes, in ascending order. An empty needle matches at every codepoint
+    /// boundary.
+    pub fn find_all(&self, needle: &str) -> Vec<usize> {
+        if needle.is_empty() {
+            return (0..=self.length).filter(|&index| self.is_char_boundary(index)).collect();
+        }
+
+        Matches::new(self.bytes(), needle.bytes()).collect()
+    }
+
+    /// Find the first occurrence of the given text in the rope, treating ASCII letters of either case as equal.
+    ///
+    /// Only ASCII letters are folded, the same way as [`str::eq_ignore_ascii_case`] does it, so offsets of the match
+    /// are the same as in the original text.
+    ///
+    /// ```
+    /// # use amity_ui::rope::Rope;
+    /// let rope = Rope::concat(Rope::from_str("Hello, Wo"), Rope::from_str("RLD!"));
+    /// assert_eq!(rope.find_ignore_ascii_case("world"), Some(7));
+    /// assert_eq!(rope.find_all_ignore_ascii_case("L"), [2, 3, 10]);
+    /// ```
+    ///
+    /// # Params
+    ///
+    /// - `needle`: Text to search for.
+    ///
+    /// # Returns
+    ///
+    /// Start of the first match, in UTF-8 bytes, or [`None`] if the text doesn't occur in the rope.
+    #[inline]
+    pub fn find_ignore_ascii_case(&self, needle: &str) -> Option<usize> {
+        let haystack = self.bytes().map(|byte| byte.to_ascii_lowercase());
+        Matches::new(haystack, needle.bytes().map(|byte| byte.to_ascii_lowercase())).next()
+    }
+
+    /// Find all occurrences of the given text in the rope, treating ASCII letters of either case as equal.
+    ///
+    /// # Params
+    ///
+    /// - `needle`: Text to search for.
+    ///
+    /// # Returns
+    ///
+    /// Starts of every match, in UTF-8 bytes, in ascending order. An empty needle matches at every codepoint
+    /// boundary.
+    ///
+    /// # See Also
+    ///
+    /// - [`Self::find_ignore_ascii_case`]
+    /// - [`Self::find_all`]
+    pub fn find_all_ignore_ascii_case(&self, needle: &str) -> Vec<usize> {
+        if needle.is_empty() {
+            return self.find_all(needle);
+        }
+
+        let haystack = self.bytes().map(|byte| byte.to_ascii_lowercase());
+        Matches::new(haystack, needle.bytes().map(|byte| byte.to_ascii_lowercase())).collect()
+    }
+
+    /// Remove the first codepoint from a leaf.
+    fn pop_front_char(&mut self) -> Option<char> {
+        let char = self.leaf_value().chars().next()?;
+        self.start += char.len_utf8();
+        self.length -= char.len_utf8();
+        Some(char)
+    }
+
+    /// Remove the last codepoint from a leaf.
+    fn pop_back_char(&mut self) -> Option<char> {
+        let char = self.leaf_value().chars().next_back()?;
+        self.length -= char.len_utf8();
+        Some(char)
+    }
+
+    /// Remove the first byte from a leaf.
+    fn pop_front_byte(&mut self) -> Option<u8> {
+        let byte = *self.leaf_bytes().first()?;
+        self.start += 1;
+        self.length -= 1;
+        Some(byte)
+    }
+
+    /// Remove the last byte from a leaf.
+    fn pop_back_byte(&mut self) -> Option<u8> {
+        let byte = *self.leaf_bytes().last()?;
+        self.length -= 1;
+        Some(byte)
+    }
+
+    /// Get the text of the rope, with the slice applied.
+    ///
+    /// ```
+    /// # use amity_ui::rope::Rope;
+    /// let text = "héllo🌍";
+    /// assert_eq!(Rope::from_str(text).value(), text);
+    ///
+    /// // Text is reconstructed across leaf boundaries, including ones right next to multibyte codepoints
+    /// let rope = Rope::concat(Rope::from_str("hé"), Rope::from_str("llo🌍"));
+    /// assert!(!rope.is_leaf());
+    /// assert_eq!(rope.value(), text);
+    /// assert_eq!(rope.slice(1, 3).value(), "él");
+    /// assert_eq!(rope.slice(6, 4).value(), "🌍");
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// Borrowed text if the rope is a leaf, otherwise text collected from all of the rope's leaves.
+    pub fn value(&self) -> Cow<'_, str> {
+        if self.is_leaf() {
+            return Cow::Borrowed(self.leaf_value());
+        }
+
+        let mut result = String::with_capacity(self.length);
+        self.push_range(0..self.length, &mut result);
+        Cow::Owned(result)
+    }
+
+    /// Write the given range of the rope's text into a string.
+    ///
+    /// # Params
+    ///
+    /// - `range`: Range to write, in UTF-8 bytes, relative to the start of the rope.
+    /// - `out`: String to write to.
+    fn push_range(&self, range: Range<usize>, out: &mut String) {
+        let Some(node) = &self.node else {
+            out.push_str(&self.leaf_text[self.start + range.start..self.start + range.end]);
+            return;
+        };
+
+        // Translate the range to the node's coordinates
+        let start = self.start + range.start;
+        let end = self.start + range.end;
+        let left_length = node.left.length;
+
+        if start < left_length {
+            node.left.push_range(start..end.min(left_length), out);
+        }
+        if end > left_length {
+            node.right.push_range(start.max(left_length) - left_length..end - left_length, out);
+        }
+    }
+}
+
+impl Default for Rope {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for Rope {
+    /// Write the text of the rope, leaf by leaf.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.children() {
+            Some((left, right)) => {
+                left.fmt(f)?;
+                right.fmt(f)
+            }
+            None => f.write_str(self.leaf_value()),
+        }
+    }
+}
+
+impl fmt::Debug for Rope {
+    /// Show the depth and length of the rope, along with the first [`Rope::DEBUG_PREVIEW`] codepoints of its text.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut preview: String = self.chars().take(Self::DEBUG_PREVIEW).collect();
+        if preview.len() < self.length {
+            preview.push('…');
+        }
+
+        f.debug_struct("Rope")
+            .field("depth", &self.depth)
+            .field("length", &self.length)
+            .field("text", &preview)
+            .finish()
+    }
+}
+
+impl PartialEq for Rope {
+    fn eq(&self, other: &Self) -> bool {
+        self.length == other.length && self.bytes().eq(other.bytes())
+    }
+}
+
+impl Eq for Rope {}
+
+impl PartialEq<str> for Rope {
+    fn eq(&self, other: &str) -> bool {
+        self.length == other.len() && self.bytes().eq(other.bytes())
+    }
+}
+
+impl PartialEq<&str> for Rope {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+impl IntoIterator for &Rope {
+    type Item = char;
+    type IntoIter = Chars;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.chars()
+    }
+}
+
+/// Kind of a character, used to find word boundaries. A word is a run of characters of the same kind, except for
+/// ideographs, each of which is a word on its own.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Whitespace,
+    Alphanumeric,
+    Punctuation,
+    Ideograph,
+}
+
+impl CharClass {
+    fn of(c: char) -> Self {
+        if c.is_whitespace() {
+            Self::Whitespace
+        } else if is_ideograph(c) {
+            Self::Ideograph
+        } else if c.is_alphanumeric() || c == '_' {
+            Self::Alphanumeric
+        } else {
+            Self::Punctuation
+        }
+    }
+}
+
+/// Check if the character is a CJK ideograph.
+fn is_ideograph(c: char) -> bool {
+    matches!(c,
+        '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{20000}'..='\u{3FFFF}'
+    )
+}
+
+/// Length in bytes of the word at the start of the iterator, along with any whitespace preceding it.
+fn word_length(mut chars: impl Iterator<Item = char>) -> usize {
+    let mut length = 0;
+    let mut class = None;
+
+    for c in chars.by_ref() {
+        length += c.len_utf8();
+        class = Some(CharClass::of(c));
+        if class != Some(CharClass::Whitespace) { break; }
+    }
+
+    if class == Some(CharClass::Ideograph) { return length; }
+
+    for c in chars {
+        if Some(CharClass::of(c)) != class { break; }
+        length += c.len_utf8();
+    }
+
+    length
+}
+
+/// Get a number of the Fibonacci sequence, starting with `fibonacci(0) == 0` and `fibonacci(1) == 1`. Saturates
+/// instead of overflowing.
+fn fibonacci(index: usize) -> usize {
+    let (mut current, mut next) = (0usize, 1usize);
+    for _ in 0..index {
+        (current, next) = (next, current.saturating_add(next));
+    }
+    current
+}
+
+/// Find the start of the word before the given position. Whitespace between the word and the position is skipped.
+///
+/// Words are runs of letters and digits, or runs of punctuation. Each CJK ideograph is a word of its own.
+///
+/// ```
+/// # use amity_ui::rope::{Rope, word_boundary_before};
+/// let rope = Rope::from_str("foo.bar  漢字");
+/// assert_eq!(word_boundary_before(&rope, rope.len()), 12);
+/// assert_eq!(word_boundary_before(&rope, 12), 9);
+/// assert_eq!(word_boundary_before(&rope, 9), 4);
+/// assert_eq!(word_boundary_before(&rope, 4), 3);
+/// assert_eq!(word_boundary_before(&rope, 3), 0);
+/// assert_eq!(word_boundary_before(&rope, 0), 0);
+/// ```
+///
+/// # Params
+///
+/// - `rope`: Text to search.
+/// - `byte`: Position to start from, in UTF-8 bytes.
+///
+/// # Returns
+///
+/// Position of the boundary, in UTF-8 bytes. Zero if there is no word before the position.
+///
+/// # Panics
+///
+/// If `byte` is out of bounds, or lands in the middle of a codepoint.
+pub fn word_boundary_before(rope: &Rope, byte: usize) -> usize {
+    byte - word_length(rope.slice(0, byte).chars().rev())
+}
+
+/// Find the end of the word after the given position. Whitespace between the position and the word is skipped.
+///
+/// Words are runs of letters and digits, or runs of punctuation. Each CJK ideograph is a word of its own.
+///
+/// ```
+/// # use amity_ui::rope::{Rope, word_boundary_after};
+/// let rope = Rope::from_str("foo.bar  漢字");
+/// assert_eq!(word_boundary_after(&rope, 0), 3);
+/// assert_eq!(word_boundary_after(&rope, 3), 4);
+/// assert_eq!(word_boundary_after(&rope, 4), 7);
+/// assert_eq!(word_boundary_after(&rope, 7), 12);
+/// assert_eq!(word_boundary_after(&rope, 12), 15);
+/// assert_eq!(word_boundary_after(&rope, 15), 15);
+/// ```
+///
+/// # Params
+///
+/// - `rope`: Text to search.
+/// - `byte`: Position to start from, in UTF-8 bytes.
+///
+/// # Returns
+///
+/// Position of the boundary, in UTF-8 bytes. The length of the rope if there is no word after the position.
+///
+/// # Panics
+///
+/// If `byte` is out of bounds, or lands in the middle of a codepoint.
+pub fn word_boundary_after(rope: &Rope, byte: usize) -> usize {
+    byte + word_length(rope.slice(byte, rope.len() - byte).chars())
+}
+
+/// Iterator over the codepoints of a [`Rope`].
+///
+/// # See Also
+///
+/// - [`Rope::chars`]
+#[derive(Clone)]
+pub struct Chars(Edges);
+
+impl Iterator for Chars {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_with(Rope::pop_front_char)
+    }
+}
+
+impl DoubleEndedIterator for Chars {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back_with(Rope::pop_back_char)
+    }
+}
+
+/// Iterator over the UTF-8 bytes of a [`Rope`].
+///
+/// # See Also
+///
+/// - [`Rope::bytes`]
+#[derive(Clone)]
+pub struct Bytes(Edges);
+
+impl Iterator for Bytes {
+    type Item = u8;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_with(Rope::pop_front_byte)
+    }
+}
+
+impl DoubleEndedIterator for Bytes {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back_with(Rope::pop_back_byte)
+    }
+}
+
+/// Streaming substring search, using the Knuth-Morris-Pratt algorithm. Yields the start of each match, including
+/// overlapping ones, in the order they are found.
+struct Matches<I> {
+    /// Bytes left to search.
+    haystack: I,
+
+    /// Bytes to search for.
+    needle: Vec<u8>,
+
+    /// For each prefix of the needle, length of its longest proper prefix that is also its suffix.
+    fallback: Vec<usize>,
+
+    /// Length of the needle prefix matched so far.
+    matched: usize,
+
+    /// Number of bytes read from the haystack.
+    position: usize,
+
+    /// Set once an empty needle has matched, as it only matches once.
+    done: bool,
+}
+
+impl<I: Iterator<Item = u8>> Matches<I> {
+    fn new(haystack: I, needle: impl Iterator<Item = u8>) -> Self {
+        let needle: Vec<u8> = needle.collect();
+        let mut fallback = vec![0; needle.len()];
+        let mut length = 0;
+
+        for index in 1..needle.len() {
+            while length > 0 && needle[index] != needle[length] {
+                length = fallback[length - 1];
+            }
+            if needle[index] == needle[length] {
+                length += 1;
+            }
+            fallback[index] = length;
+        }
+
+        Self {
+            haystack,
+            needle,
+            fallback,
+            matched: 0,
+            position: 0,
+            done: false,
+        }
+    }
+}
+
+impl<I: Iterator<Item = u8>> Iterator for Matches<I> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.needle.is_empty() {
+            return (!std::mem::replace(&mut self.done, true)).then_some(0);
+        }
+
+        for byte in self.haystack.by_ref() {
+            self.position += 1;
+
+            while self.matched > 0 && self.needle[self.matched] != byte {
+                self.matched = self.fallback[self.matched - 1];
+            }
+            if self.needle[self.matched] == byte {
+                self.matched += 1;
+            }
+            if self.matched == self.needle.len() {
+                self.matched = self.fallback[self.matched - 1];
+                return Some(self.position - self.needle.len());
+            }
+        }
+
+        None
+    }
+}
+
+/// Walks the leaves of a rope from both ends, keeping the leaves currently read from each end.
+#[derive(Clone)]
+struct Edges {
+    /// Parts of the rope that haven't been reached from either end yet.
+    pending: VecDeque<Rope>,
+
+    /// Remaining part of the leaf read from the front.
+    front: Rope,
+
+    /// Remaining part of the leaf read from the back.
+    back: Rope,
+}
+
+impl Edges {
+    fn new(rope: &Rope) -> Self {
+        Self {
+            pending: VecDeque::from([rope.clone()]),
+            front: Rope::new(),
+            back: Rope::new(),
+        }
+    }
+
+    /// Take the next item from the front.
+    ///
+    /// # Params
+    ///
+    /// - `take`: Function removing an item from the front of a leaf.
+    fn next_with<T>(&mut self, mut take: impl FnMut(&mut Rope) -> Option<T>) -> Option<T> {
+        loop {
+            if let Some(item) = take(&mut self.front) {
+                return Some(item);
+            }
+
+            let Some(rope) = self.pending.pop_front() else {
+                // Nothing left in the middle; continue into the leaf read from the back
+                return take(&mut self.back);
+            };
+
+            match rope.children() {
+                Some((left, right)) => {
+                    self.pending.push_front(right);
+                    self.pending.push_front(left);
+                }
+                None => self.front = rope,
+            }
+        }
+    }
+
+    /// Take the next item from the back.
+    ///
+    /// # Params
+    ///
+    /// - `take`: Function removing an item from the back of a leaf.
+    fn next_back_with<T>(&mut self, mut take: impl FnMut(&mut Rope) -> Option<T>) -> Option<T> {
+        loop {
+            if let Some(item) = take(&mut self.back) {
+                return Some(item);
+            }
+
+            let Some(rope) = self.pending.pop_back() else {
+                // Nothing left in the middle; continue into the leaf read from the front
+                return take(&mut self.front);
+            };
+
+            match rope.children() {
+                Some((left, right)) => {
+                    self.pending.push_back(left);
+                    self.pending.push_back(right);
+                }
+                None => self.back = rope,
+            }
+        }
+    }
+}
+
 pub struct RopeNode {
     /// Left child of this node.
     pub left: Rope,
 
     /// Right child of this node.
     pub right: Rope,
+
+    /// Number of line breaks in both children.
+    line_breaks: usize,
+
+    /// Number of codepoints in both children.
+    char_count: usize,
+}
+
+impl RopeNode {
+    /// Create a node out of two ropes, counting lines and characters in them.
+    pub fn new(left: Rope, right: Rope) -> Self {
+        Self {
+            line_breaks: left.line_breaks() + right.line_breaks(),
+            char_count: left.char_len() + right.char_len(),
+            left,
+            right,
+        }
+    }
 }
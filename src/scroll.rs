@@ -1,16 +1,392 @@
-use crate::{backend::{Backend, Vector2}, hover::HoverIO, node::Node, scroll_input::ScrollInput};
+use std::{cell::RefCell, rc::Rc, time::Duration};
 
-/// Implement scrolling for the given node.
+use crate::{backend::{Backend, Rectangle, Vector2}, layout::{Axis, LayoutMode}, node::{Node, NodeVariant, ParentNode}, scroll_input::ScrollInput, style::Style, theme::Theme, utils::{NodeParam, simple_constructor}};
+
+/// Implement scrolling for a [`NodeVariant::Scrollable`] node. The node's children are its content: they are placed
+/// in the scrolled content box, and clipped to the viewport.
 ///
-/// This only supports scrolling in one axis.
+/// Each axis can be scrolled independently, by assigning a scrollbar to it. If both axes have a scrollbar, the
+/// bottom-right corner where the scrollbars meet is left empty.
 pub struct Scrollable<B: Backend> {
-    pub node: Node<B>,
-
-    pub hover_io: Box<dyn HoverIO<B>>,
+    /// Horizontal scrollbar for the frame, if scrolling horizontally is possible. Can be replaced with a customized
+    /// one.
+    pub horizontal: Option<ScrollInput<B>>,
 
-    /// Scrollbar for the frame. Can be replaced with a customized one.
-    pub scroll_bar: ScrollInput<B>,
+    /// Vertical scrollbar for the frame, if scrolling vertically is possible. Can be replaced with a customized one.
+    pub vertical: Option<ScrollInput<B>>,
 
     /// minSize including the padding.
     padding_box_size: Vector2,
+
+    /// Area content was visible in during the last draw, in window coordinates.
+    last_viewport: Rectangle,
+}
+
+impl<B: Backend> Scrollable<B> {
+    /// Create a scrollable node variant. Either scrollbar can be `None` to disable scrolling on its axis.
+    ///
+    /// # Params
+    ///
+    /// - `horizontal`: Value of [`Self::horizontal`].
+    /// - `vertical`:   Value of [`Self::vertical`].
+    pub fn new(horizontal: Option<ScrollInput<B>>, vertical: Option<ScrollInput<B>>) -> Self {
+        Self {
+            horizontal,
+            vertical,
+            padding_box_size: Vector2::default(),
+            last_viewport: Rectangle::default(),
+        }
+    }
+
+    /// Current scroll position on both axes, in pixels.
+    pub fn scroll(&self) -> Vector2 {
+        Vector2::new(
+            self.horizontal.as_ref().map_or(0.0, |bar| bar.position),
+            self.vertical.as_ref().map_or(0.0, |bar| bar.position),
+        )
+    }
+
+    /// Space taken by the scrollbars; the width of the vertical scrollbar, and the height of the horizontal one.
+    pub fn scroll_bar_size(&self) -> Vector2 {
+        Vector2::new(
+            self.vertical.as_ref().map_or(0.0, |bar| bar.width),
+            self.horizontal.as_ref().map_or(0.0, |bar| bar.width),
+        )
+    }
+
+    /// Update the scrollable space after the content has been resized.
+    ///
+    /// # Params
+    ///
+    /// - `content_size`: Size of the content, including the padding.
+    /// - `outer_size`:   Size of the box the content is displayed in, including the scrollbars.
+    pub fn resize_content(&mut self, content_size: Vector2, outer_size: Vector2) {
+        self.padding_box_size = content_size;
+
+        let viewport = outer_size - self.scroll_bar_size();
+
+        if let Some(bar) = &mut self.horizontal {
+            bar.available_space = (content_size.x - viewport.x).max(0.0);
+            bar.set_position(bar.position);
+        }
+        if let Some(bar) = &mut self.vertical {
+            bar.available_space = (content_size.y - viewport.y).max(0.0);
+            bar.set_position(bar.position);
+        }
+    }
+
+    /// Scroll in response to mouse wheel motion, as returned by [`Backend::scroll`]. Horizontal motion only affects
+    /// the horizontal scrollbar, and vertical motion only affects the vertical scrollbar.
+    ///
+    /// Scrollbars in smooth mode are pushed instead of moved; see [`ScrollInput::push`].
+    ///
+    /// ```
+    /// # use amity_ui::{backend::{Rectangle, Vector2, headless::HeadlessBackend}, canvas::CanvasSpace, hover::HoverSpace,
+    /// #     scroll::Scrollable, scroll_input::{ScrollInput, ScrollInputHandle}};
+    /// # let bar = |is_horizontal: bool| {
+    /// #     let canvas = || Box::new(CanvasSpace::new(Rectangle::default()));
+    /// #     ScrollInput::new(is_horizontal, canvas(), ScrollInputHandle::new(Box::new(HoverSpace::new()), canvas()))
+    /// # };
+    /// let mut scrollable = Scrollable::<HeadlessBackend>::new(Some(bar(true)), Some(bar(false)));
+    /// scrollable.resize_content(Vector2::new(1000.0, 1000.0), Vector2::new(200.0, 200.0));
+    ///
+    /// // Vertical motion only moves the vertical scrollbar
+    /// scrollable.scroll_wheel(Vector2::new(0.0, -1.0));
+    /// assert_eq!(scrollable.scroll(), Vector2::new(0.0, 60.0));
+    ///
+    /// // Horizontal motion only moves the horizontal one
+    /// scrollable.scroll_wheel(Vector2::new(-2.0, 0.0));
+    /// assert_eq!(scrollable.scroll(), Vector2::new(120.0, 60.0));
+    /// ```
+    ///
+    /// # Params
+    ///
+    /// - `motion`: Wheel motion on each axis. Positive values scroll up (or left).
+    pub fn scroll_wheel(&mut self, motion: Vector2) {
+        if let Some(bar) = &mut self.horizontal && motion.x != 0.0 {
+            bar.push(-motion.x * ScrollInput::<B>::SCROLL_SPEED);
+        }
+        if let Some(bar) = &mut self.vertical && motion.y != 0.0 {
+            bar.push(-motion.y * ScrollInput::<B>::SCROLL_SPEED);
+        }
+    }
+
+    /// Advance smooth scrolling of both scrollbars by a frame.
+    ///
+    /// # Params
+    ///
+    /// - `delta_time`: Time elapsed since the last frame, as returned by [`Backend::delta_time`].
+    pub fn update_momentum(&mut self, delta_time: Duration) {
+        for bar in [&mut self.horizontal, &mut self.vertical].into_iter().flatten() {
+            bar.update_momentum(delta_time);
+        }
+    }
+
+    /// Area content is visible in, excluding the scrollbars.
+    ///
+    /// # Params
+    ///
+    /// - `outer`: Box of the scrollable node, including the scrollbars.
+    pub fn viewport(&self, outer: Rectangle) -> Rectangle {
+        let size = self.scroll_bar_size();
+
+        Rectangle::new(outer.x, outer.y, (outer.width - size.x).max(0.0), (outer.height - size.y).max(0.0))
+    }
+
+    /// Box the content should be drawn in. It is offset by the scroll position, so it will usually exceed the
+    /// viewport; use [`Self::draw_content`] to clip it.
+    ///
+    /// # Params
+    ///
+    /// - `outer`: Box of the scrollable node, including the scrollbars.
+    pub fn content_box(&self, outer: Rectangle) -> Rectangle {
+        let viewport = self.viewport(outer);
+        let start = viewport.start() - self.scroll();
+
+        Rectangle::new(
+            start.x,
+            start.y,
+            self.padding_box_size.x.max(viewport.width),
+            self.padding_box_size.y.max(viewport.height),
+        )
+    }
+
+    /// Get the boxes of the scrollbars.
+    ///
+    /// # Params
+    ///
+    /// - `outer`: Box of the scrollable node, including the scrollbars.
+    ///
+    /// # Returns
+    ///
+    /// Boxes of the horizontal and vertical scrollbar, respectively, if present. Neither covers the corner between
+    /// the two.
+    pub fn scroll_bar_boxes(&self, outer: Rectangle) -> [Option<Rectangle>; 2] {
+        let viewport = self.viewport(outer);
+
+        [
+            self.horizontal.as_ref().map(|bar| Rectangle::new(viewport.x, viewport.bottom(), viewport.width, bar.width)),
+            self.vertical.as_ref().map(|bar| Rectangle::new(viewport.right(), viewport.y, bar.width, viewport.height)),
+        ]
+    }
+
+    /// Corner where both scrollbars meet. The corner belongs to neither scrollbar.
+    ///
+    /// ```
+    /// # use amity_ui::{backend::{Rectangle, Vector2, headless::HeadlessBackend}, canvas::CanvasSpace, hover::HoverSpace,
+    /// #     scroll::Scrollable, scroll_input::{ScrollInput, ScrollInputHandle}};
+    /// # let bar = |is_horizontal: bool| {
+    /// #     let canvas = || Box::new(CanvasSpace::new(Rectangle::default()));
+    /// #     ScrollInput::new(is_horizontal, canvas(), ScrollInputHandle::new(Box::new(HoverSpace::new()), canvas()))
+    /// # };
+    /// let mut scrollable = Scrollable::<HeadlessBackend>::new(Some(bar(true)), Some(bar(false)));
+    /// let outer = Rectangle::new(0.0, 0.0, 200.0, 100.0);
+    /// assert_eq!(scrollable.corner_box(outer), Some(Rectangle::new(190.0, 90.0, 10.0, 10.0)));
+    ///
+    /// // Neither scrollbar covers the corner
+    /// let [horizontal, vertical] = scrollable.scroll_bar_boxes(outer);
+    /// assert_eq!(horizontal, Some(Rectangle::new(0.0, 90.0, 190.0, 10.0)));
+    /// assert_eq!(vertical, Some(Rectangle::new(190.0, 0.0, 10.0, 90.0)));
+    ///
+    /// // There is no corner with a single scrollbar
+    /// scrollable.horizontal = None;
+    /// assert_eq!(scrollable.corner_box(outer), None);
+    /// ```
+    ///
+    /// # Params
+    ///
+    /// - `outer`: Box of the scrollable node, including the scrollbars.
+    ///
+    /// # Returns
+    ///
+    /// Box of the corner, or `None` if there are less than two scrollbars.
+    pub fn corner_box(&self, outer: Rectangle) -> Option<Rectangle> {
+        let (Some(horizontal), Some(vertical)) = (&self.horizontal, &self.vertical) else { return None; };
+        let viewport = self.viewport(outer);
+
+        Some(Rectangle::new(viewport.right(), viewport.bottom(), vertical.width, horizontal.width))
+    }
+
+    /// Draw the content, clipped to the viewport.
+    ///
+    /// # Params
+    ///
+    /// - `backend`: Backend to draw with.
+    /// - `outer`:   Box of the scrollable node, including the scrollbars.
+    /// - `draw`:    Callback drawing the content, given the box returned by [`Self::content_box`].
+    pub fn draw_content(&mut self, backend: &mut B, outer: Rectangle, draw: impl FnOnce(&mut B, Rectangle)) {
+        let viewport = self.update_viewport(outer);
+        backend.set_area(viewport.into());
+        draw(backend, self.content_box(outer));
+        backend.restore_area();
+    }
+
+    /// Record the viewport for the current frame, to be used by [`Self::scroll_to`]. Done by
+    /// [`Self::draw_content`].
+    ///
+    /// # Params
+    ///
+    /// - `outer`: Box of the scrollable node, including the scrollbars.
+    ///
+    /// # Returns
+    ///
+    /// The viewport; see [`Self::viewport`].
+    pub fn update_viewport(&mut self, outer: Rectangle) -> Rectangle {
+        self.last_viewport = self.viewport(outer);
+        self.last_viewport
+    }
+
+    /// Scroll just enough to make the target fully visible. If the target is larger than the viewport, its start is
+    /// revealed. Nothing changes if the target is already visible.
+    ///
+    /// # Params
+    ///
+    /// - `target`: Box to reveal, in window coordinates, as of the last draw.
+    pub fn scroll_to(&mut self, target: Rectangle) {
+        let viewport = self.last_viewport;
+
+        if let Some(bar) = &mut self.horizontal {
+            bar.scroll_by(scroll_into_view(viewport.x, viewport.width, target.x, target.width));
+        }
+        if let Some(bar) = &mut self.vertical {
+            bar.scroll_by(scroll_into_view(viewport.y, viewport.height, target.y, target.height));
+        }
+    }
+
+    /// Draw both scrollbars next to the viewport. Metrics of visually hidden scrollbars are updated too, so they
+    /// remain usable.
+    ///
+    /// # Params
+    ///
+    /// - `backend`: Backend to draw with.
+    /// - `outer`:   Box of the scrollable node, including the scrollbars.
+    /// - `style`:   Style of the scrollable node.
+    /// - `theme`:   Theme of the scrollable node, styling the scrollbars.
+    pub fn draw_scroll_bars(&mut self, backend: &mut B, outer: Rectangle, style: &Style<B>, theme: &Theme<B>) {
+        let [horizontal_box, vertical_box] = self.scroll_bar_boxes(outer);
+
+        if let (Some(bar), Some(outer)) = (&mut self.horizontal, horizontal_box) {
+            bar.draw(backend, outer, style, theme);
+        }
+        if let (Some(bar), Some(outer)) = (&mut self.vertical, vertical_box) {
+            bar.draw(backend, outer, style, theme);
+        }
+    }
+
+    /// Resolve the style of both scrollbars, updating their width. Done when the node is resized. See
+    /// [`ScrollInput::reload_style`].
+    ///
+    /// # Params
+    ///
+    /// - `theme`: Theme of the scrollable node.
+    /// - `style`: Style of the scrollable node.
+    pub fn reload_style(&mut self, theme: &Theme<B>, style: &Style<B>) {
+        for bar in [&mut self.horizontal, &mut self.vertical].into_iter().flatten() {
+            bar.reload_style(theme, style);
+        }
+    }
+}
+
+/// Create a [`Scrollable`] node with the given children, placed in a column.
+///
+/// The content is drawn offset by the scroll position and clipped to the viewport, followed by the scrollbars. When
+/// the node is hovered, or any of its children are, it scrolls with the mouse wheel. When focus moves to one of its
+/// children, it scrolls to reveal the focused node; see [`crate::tree::LayoutTree::scroll_to_focus`].
+///
+/// ```
+/// # use std::{cell::RefCell, rc::Rc};
+/// # use amity_ui::{backend::{Backend, Rectangle, Vector2, headless::HeadlessBackend}, canvas::CanvasSpace,
+/// #     focus::Focusable, hover::HoverSpace, node::{NodeVariant, checkbox}, scroll::{Scrollable, scroll_frame},
+/// #     scroll_input::{ScrollInput, ScrollInputHandle}, style::Style, tree::LayoutTree};
+/// # let bar = |is_horizontal: bool| {
+/// #     let canvas = || Box::new(CanvasSpace::new(Rectangle::default()));
+/// #     ScrollInput::new(is_horizontal, canvas(), ScrollInputHandle::new(Box::new(HoverSpace::new()), canvas()))
+/// # };
+/// // 20 checkboxes, 50 pixels tall each, in a 600 pixel tall window
+/// let boxes: Vec<_> = (0..20)
+///     .map(|_| Rc::new(RefCell::new(checkbox(Style::default().with_padding([17.0; 4]), false))))
+///     .collect();
+/// let root = scroll_frame(Scrollable::<HeadlessBackend>::new(None, Some(bar(false))), (), boxes.clone());
+/// let mut tree = LayoutTree::new(root.clone(), HeadlessBackend::new());
+/// let scroll = || match &root.borrow().variant {
+///     NodeVariant::Scrollable(scrollable) => scrollable.scroll(),
+///     _ => unreachable!(),
+/// };
+/// tree.draw();
+///
+/// // Focusing a visible node doesn't scroll
+/// boxes[3].clone().focus(&mut tree.focus);
+/// tree.draw();
+/// assert_eq!(scroll(), Vector2::new(0.0, 0.0));
+///
+/// // Focusing a node below the viewport scrolls just enough to reveal it
+/// boxes[14].clone().focus(&mut tree.focus);
+/// tree.draw();
+/// assert_eq!(scroll(), Vector2::new(0.0, 150.0));
+///
+/// // Wheel motion scrolls the hovered scrollable
+/// tree.backend.next_frame();
+/// tree.backend.set_mouse_position(Vector2::new(10.0, 10.0));
+/// tree.backend.set_scroll(Vector2::new(0.0, -1.0));
+/// tree.draw();
+/// assert_eq!(scroll(), Vector2::new(0.0, 210.0));
+/// assert!(tree.scroll.upgrade().is_some_and(|scroll| Rc::ptr_eq(&scroll, &root)));
+/// ```
+///
+/// # Params
+///
+/// - `scrollable`: Scrolling state of the node, with the scrollbars to use.
+/// - `params`:     Properties to set on the node, see [`NodeParam`].
+/// - `children`:   Nodes to place inside. Nodes that already have a parent are moved.
+pub fn scroll_frame<B: Backend>(
+    scrollable: Scrollable<B>,
+    params: impl NodeParam<B>,
+    children: impl IntoIterator<Item = Rc<RefCell<Node<B>>>>,
+) -> Rc<RefCell<Node<B>>> {
+    let mut node = simple_constructor(NodeVariant::Scrollable(scrollable), params);
+    node.data.layout.mode = LayoutMode::Flow { direction: Axis::Vertical };
+
+    let node = Rc::new(RefCell::new(node));
+    for child in children {
+        node.append_child(child);
+    }
+    node
+}
+
+/// Find the smallest scroll distance, on a single axis, that makes the target fully visible. If the target is larger
+/// than the viewport, its start is revealed instead.
+///
+/// ```
+/// # use amity_ui::scroll::scroll_into_view;
+/// // Target below the viewport: scroll down just enough to reveal its bottom edge
+/// assert_eq!(scroll_into_view(0.0, 100.0, 150.0, 30.0), 80.0);
+///
+/// // Target above the viewport: scroll up to its top edge
+/// assert_eq!(scroll_into_view(200.0, 100.0, 150.0, 30.0), -50.0);
+///
+/// // Target already visible
+/// assert_eq!(scroll_into_view(0.0, 100.0, 10.0, 30.0), 0.0);
+/// ```
+///
+/// # Params
+///
+/// - `viewport_start`:  Start of the visible area.
+/// - `viewport_length`: Length of the visible area.
+/// - `target_start`:    Start of the target.
+/// - `target_length`:   Length of the target.
+///
+/// # Returns
+///
+/// Distance to scroll by. Positive values scroll down (or right).
+pub fn scroll_into_view(viewport_start: f32, viewport_length: f32, target_start: f32, target_length: f32) -> f32 {
+    let before = target_start - viewport_start;
+    let after = (target_start + target_length) - (viewport_start + viewport_length);
+
+    if before < 0.0 {
+        before
+    } else if after > 0.0 {
+        after.min(before)
+    } else {
+        0.0
+    }
 }
@@ -1,17 +1,39 @@
-use std::{cell::RefCell, rc::Weak};
+use std::{cell::RefCell, rc::Weak, time::Duration};
 
-use crate::{backend::Backend, canvas::CanvasIO, hover::HoverIO};
+use crate::{backend::{Backend, Color, Rectangle, Vector2}, canvas::CanvasIO, hover::HoverIO, input::{FluidInputAction, InputActionID}, layout::Axis, node::NodeType, style::Style, tag_list::{TagID, TagList}, theme::Theme};
 
+/// Scrollbar, controlling the scroll position of a [`crate::scroll::Scrollable`] on one axis.
 ///
+/// The scrollbar responds to scroll actions along its axis, such as [`FluidInputAction::ScrollDown`], and to page
+/// actions, such as [`FluidInputAction::PageDown`], which scroll by the visible length.
+///
+/// ```
+/// # use amity_ui::{backend::{Rectangle, headless::HeadlessBackend}, canvas::CanvasSpace, hover::HoverSpace,
+/// #     input::FluidInputAction, scroll_input::{ScrollInput, ScrollInputHandle}};
+/// # let canvas = || Box::new(CanvasSpace::new(Rectangle::new(0.0, 0.0, 800.0, 600.0)));
+/// let handle = ScrollInputHandle::new(Box::new(HoverSpace::new()), canvas());
+/// let mut scroll = ScrollInput::<HeadlessBackend>::new(false, canvas(), handle);
+/// scroll.available_space = 500.0;
+/// scroll.update_metrics(Rectangle::new(0.0, 0.0, 10.0, 200.0));
+///
+/// let page = 200.0 - ScrollInput::<HeadlessBackend>::PAGE_OVERLAP;
+/// assert!(scroll.action_impl(&FluidInputAction::PageDown.id(), true));
+/// assert_eq!(scroll.position, page);
+///
+/// // Scrolling stops at the end
+/// scroll.action_impl(&FluidInputAction::PageDown.id(), true);
+/// scroll.action_impl(&FluidInputAction::PageDown.id(), true);
+/// assert_eq!(scroll.position, 500.0);
+///
+/// // A vertical scrollbar ignores horizontal actions
+/// assert!(!scroll.action_impl(&FluidInputAction::PageLeft.id(), true));
+/// assert_eq!(scroll.position, 500.0);
+/// ```
 pub struct ScrollInput<B: Backend> {
-
-    // TODO Hiding a scrollbar makes it completely unusable, since it cannot scan the viewport. Perhaps override
-    // `isHidden` to virtually hide the scrollbar, and keep it always "visible" as such?
-
     pub canvas_io: Box<dyn CanvasIO<B>>,
 
-    /// If true, the scrollbar will be horizontal.
-    pub is_horizontal: bool,
+    /// Axis the scrollbar scrolls along.
+    pub axis: Axis,
 
     /// Amount of pixels the page is scrolled down.
     pub position: f32,
@@ -22,15 +44,35 @@ pub struct ScrollInput<B: Backend> {
     /// scrollbar.
     pub available_space: f32,
 
-    /// Width of the scrollbar.
-    pub width: f32, // 10
+    /// Width of the scrollbar. Set to the [scrollbar width](Style::scrollbar_width) of the track whenever the style is
+    /// reloaded.
+    pub width: f32,
+
+    /// If true, the track and the handle are not drawn, but the scrollbar keeps working.
+    ///
+    /// Hiding the node would make the scrollbar unusable, as its metrics are only updated while it is drawn. Use
+    /// this instead to remove the scrollbar from view while keeping wheel and keyboard scrolling functional.
+    pub is_visually_hidden: bool,
+
+    /// If true, scrolling is animated: scroll events push the scrollbar, which then slows down over time, instead
+    /// of jumping to the new position instantly. Disabled by default.
+    pub is_smooth: bool,
+
+    /// Current scrolling speed in smooth mode, in pixels per second.
+    pub(crate) velocity: f32,
 
     /// Handle of the scrollbar.
     pub handle: Box<ScrollInputHandle<B>>,
 
+    /// Style of the track, resolved by [`Self::reload_style`].
+    style: Box<Style<B>>,
+
     /// True if the scrollbar is pressed.
     pub(crate) is_pressed: bool,
 
+    /// True if the scrollbar is hovered.
+    pub(crate) is_hovered: bool,
+
     /// If true, the inner part of the scrollbar is hovered.
     pub(crate) inner_hovered: bool,
 
@@ -48,6 +90,462 @@ impl<B: Backend> ScrollInput<B> {
 
     /// Keyboard/gamepad scroll speed in pixels per event.
     pub const ACTION_SCROLL_SPEED: f32 = 60.0;
+
+    /// Rate at which velocity decays in smooth mode, per second. Higher values stop the scrollbar sooner.
+    pub const MOMENTUM_DECAY: f32 = 10.0;
+
+    /// Velocity, in pixels per second, below which smooth scrolling stops.
+    pub const MINIMUM_VELOCITY: f32 = 1.0;
+
+    /// Length, in pixels, of the part of the previous page that stays visible after scrolling by a page.
+    pub const PAGE_OVERLAP: f32 = 20.0;
+
+    /// Default value for `width`.
+    pub const WIDTH: f32 = 10.0;
+
+    /// Create a scrollbar with no space to scroll.
+    ///
+    /// # Params
+    ///
+    /// - `axis`:      Axis to scroll along. `true` can be passed for a horizontal scrollbar, and `false` for a
+    ///   vertical one.
+    /// - `canvas_io`: Canvas to draw the scrollbar on.
+    /// - `handle`:    Handle of the scrollbar.
+    pub fn new(axis: impl Into<Axis>, canvas_io: Box<dyn CanvasIO<B>>, handle: ScrollInputHandle<B>) -> Self {
+        Self {
+            canvas_io,
+            axis: axis.into(),
+            position: 0.0,
+            available_space: 0.0,
+            width: Self::WIDTH,
+            is_visually_hidden: false,
+            is_smooth: false,
+            velocity: 0.0,
+            handle: Box::new(handle),
+            style: Box::default(),
+            is_pressed: false,
+            is_hovered: false,
+            inner_hovered: false,
+            page_length: 0.0,
+            length: 0.0,
+        }
+    }
+
+    /// Tag present on the track while the scrollbar is hovered, and on the handle while the handle is hovered.
+    #[inline]
+    pub fn hovered_tag() -> TagID {
+        TagID::named("hovered")
+    }
+
+    /// Tag present on the track and the handle while the handle is dragged.
+    #[inline]
+    pub fn pressed_tag() -> TagID {
+        TagID::named("pressed")
+    }
+
+    /// Style of the track, as resolved by the last call to [`Self::reload_style`].
+    #[inline]
+    pub fn style(&self) -> &Style<B> {
+        &self.style
+    }
+
+    /// Check if the scrollbar is horizontal.
+    #[inline]
+    pub const fn is_horizontal(&self) -> bool {
+        self.axis.is_horizontal()
+    }
+
+    /// Set the scroll position, keeping it within `[0, available_space]`.
+    #[inline]
+    pub fn set_position(&mut self, value: f32) {
+        self.position = value.clamp(0.0, self.available_space.max(0.0));
+    }
+
+    /// Scroll by the given amount of pixels. Positive values scroll down (or right).
+    #[inline]
+    pub fn scroll_by(&mut self, delta: f32) {
+        self.set_position(self.position + delta);
+    }
+
+    /// Scroll in response to a scroll event. In smooth mode, this pushes the scrollbar so it travels the given
+    /// distance over the next frames; otherwise, this is the same as [`Self::scroll_by`].
+    ///
+    /// # Params
+    ///
+    /// - `delta`: Distance to scroll, in pixels. Positive values scroll down (or right).
+    pub fn push(&mut self, delta: f32) {
+        if !self.is_smooth {
+            self.scroll_by(delta);
+            return;
+        }
+
+        // With exponential decay, total distance travelled is velocity / decay
+        self.velocity += delta * Self::MOMENTUM_DECAY;
+    }
+
+    /// Distance scrolled by page actions: the length of the scrollbar, which matches the visible length of the
+    /// content, minus [`Self::PAGE_OVERLAP`].
+    #[inline]
+    pub fn page_step(&self) -> f32 {
+        (self.length as f32 - Self::PAGE_OVERLAP).max(0.0)
+    }
+
+    /// Handle an input action. Only actions along the scrollbar's axis are handled: [`FluidInputAction::ScrollUp`],
+    /// [`FluidInputAction::ScrollDown`], [`FluidInputAction::PageUp`] and [`FluidInputAction::PageDown`] for vertical
+    /// scrollbars, and their left and right counterparts for horizontal ones.
+    ///
+    /// # Returns
+    ///
+    /// True if the action was handled.
+    pub fn action_impl(&mut self, action: &InputActionID, is_active: bool) -> bool {
+        use FluidInputAction::*;
+
+        if !is_active { return false; }
+
+        let [back, forward, page_back, page_forward] = match self.axis {
+            Axis::Horizontal => [ScrollLeft, ScrollRight, PageLeft, PageRight],
+            Axis::Vertical   => [ScrollUp, ScrollDown, PageUp, PageDown],
+        };
+
+        let delta = if action.is(back) {
+            -Self::ACTION_SCROLL_SPEED
+        } else if action.is(forward) {
+            Self::ACTION_SCROLL_SPEED
+        } else if action.is(page_back) {
+            -self.page_step()
+        } else if action.is(page_forward) {
+            self.page_step()
+        } else {
+            return false;
+        };
+
+        self.push(delta);
+        true
+    }
+
+    /// Update page and scrollbar length to match the box the scrollbar occupies. Done by [`Self::draw`], even if
+    /// the scrollbar is visually hidden.
+    ///
+    /// # Params
+    ///
+    /// - `outer`: Box of the scrollbar.
+    pub fn update_metrics(&mut self, outer: Rectangle) {
+        let length = self.axis.main_of(outer.size());
+
+        self.length = length.max(0.0) as f64;
+        self.page_length = self.length + self.available_space.max(0.0) as f64;
+
+        // Keep the handle large enough to grab, unless the scrollbar itself is smaller
+        let handle_length = if self.page_length > 0.0 {
+            self.length * self.length / self.page_length
+        } else {
+            self.length
+        };
+        self.handle.length = handle_length
+            .max(ScrollInputHandle::<B>::MINIMUM_LENGTH as f64)
+            .min(self.length);
+    }
+
+    /// Space the handle can move in; the length of the scrollbar excluding the handle.
+    #[inline]
+    fn free_space(&self) -> f32 {
+        (self.length - self.handle.length).max(0.0) as f32
+    }
+
+    /// Start dragging the handle.
+    ///
+    /// # Params
+    ///
+    /// - `mouse_position`: Position of the pointer grabbing the handle.
+    pub fn start_drag(&mut self, mouse_position: Vector2) {
+        self.handle.start_mouse_position = mouse_position;
+        self.handle.start_scroll_position = self.position;
+        self.handle.just_pressed = true;
+        self.handle.is_pressed = true;
+    }
+
+    /// Move the handle along with the pointer, after [`Self::start_drag`].
+    ///
+    /// Moving the handle from one end of the scrollbar to the other spans the entire scroll range, even if the
+    /// handle has been enlarged to [`ScrollInputHandle::MINIMUM_LENGTH`].
+    ///
+    /// ```
+    /// # use amity_ui::{backend::{Rectangle, Vector2, headless::HeadlessBackend}, canvas::CanvasSpace,
+    /// #     hover::HoverSpace, scroll_input::{ScrollInput, ScrollInputHandle}};
+    /// # let canvas = || Box::new(CanvasSpace::new(Rectangle::default()));
+    /// # let handle = ScrollInputHandle::new(Box::new(HoverSpace::new()), canvas());
+    /// let mut scroll = ScrollInput::<HeadlessBackend>::new(false, canvas(), handle);
+    /// let outer = Rectangle::new(0.0, 0.0, 10.0, 200.0);
+    ///
+    /// // With a lot of content, the handle is kept at its minimum length
+    /// scroll.available_space = 1_000_000.0;
+    /// scroll.update_metrics(outer);
+    /// assert_eq!(scroll.handle_box(outer).height, ScrollInputHandle::<HeadlessBackend>::MINIMUM_LENGTH as f32);
+    ///
+    /// // Dragging it across the rest of the track reaches the end
+    /// scroll.start_drag(Vector2::new(5.0, 10.0));
+    /// scroll.drag(Vector2::new(5.0, 160.0));
+    /// scroll.end_drag();
+    /// assert_eq!(scroll.position, 1_000_000.0);
+    /// assert_eq!(scroll.handle_box(outer), Rectangle::new(0.0, 150.0, 10.0, 50.0));
+    /// ```
+    ///
+    /// # Params
+    ///
+    /// - `mouse_position`: Current position of the pointer.
+    pub fn drag(&mut self, mouse_position: Vector2) {
+        if !self.handle.is_pressed { return; }
+
+        let motion = self.axis.main_of(mouse_position - self.handle.start_mouse_position);
+        let free_space = self.free_space();
+
+        self.handle.just_pressed = false;
+
+        if free_space <= 0.0 { return; }
+
+        self.set_position(self.handle.start_scroll_position + motion * self.available_space / free_space);
+    }
+
+    /// Release the handle.
+    #[inline]
+    pub fn end_drag(&mut self) {
+        self.handle.is_pressed = false;
+        self.handle.just_pressed = false;
+    }
+
+    /// Get the box of the handle, as of the last call to [`Self::update_metrics`].
+    ///
+    /// # Params
+    ///
+    /// - `outer`: Box of the scrollbar.
+    pub fn handle_box(&self, outer: Rectangle) -> Rectangle {
+        let free_space = self.free_space();
+        let offset = if self.available_space > 0.0 {
+            free_space * self.position / self.available_space
+        } else {
+            0.0
+        };
+        let length = self.handle.length as f32;
+
+        Rectangle::from_parts(
+            outer.start() + self.axis.vector(offset, 0.0),
+            self.axis.vector(length, self.axis.cross_of(outer.size())),
+        )
+    }
+
+    /// Update hover state of the scrollbar and its handle. Done by [`Self::draw`].
+    ///
+    /// # Params
+    ///
+    /// - `outer`: Box of the scrollbar.
+    /// - `point`: Position of the pointer.
+    pub fn update_hover(&mut self, outer: Rectangle, point: Vector2) {
+        self.is_hovered = outer.contains(point);
+        self.inner_hovered = self.handle_box(outer).contains(point);
+    }
+
+    /// Resolve the style of the track and the handle from a theme, and update [`Self::width`] to match. Done by
+    /// [`Self::draw`], and when the node holding the scrollbar is resized.
+    ///
+    /// The track starts from the given style, and takes rules of the theme for [`NodeType::ScrollInput`]. The handle
+    /// starts from the track, filled with its line color, and takes rules for [`NodeType::ScrollHandle`]. Both are
+    /// tagged with [`Self::pressed_tag`] while the handle is dragged, and with [`Self::hovered_tag`] while hovered.
+    ///
+    /// ```
+    /// # use amity_ui::{backend::{Backend, Color, Rectangle, Vector2, headless::{DrawCommand, HeadlessBackend}},
+    /// #     canvas::CanvasSpace, hover::HoverSpace, scroll_input::{ScrollInput, ScrollInputHandle}, style::Style,
+    /// #     theme::parse_stylesheet};
+    /// # let canvas = || Box::new(CanvasSpace::new(Rectangle::default()));
+    /// # let handle = ScrollInputHandle::new(Box::new(HoverSpace::new()), canvas());
+    /// let theme = parse_stylesheet("
+    ///     scroll_input { background-color: #eeeeee; scrollbar-width: 12 }
+    ///     scroll_handle { background-color: #888888; corner-radius: 4 }
+    ///     scroll_handle.hovered { background-color: #555555 }
+    ///     scroll_handle.pressed { background-color: #222222 }
+    /// ")?;
+    /// let mut scroll = ScrollInput::new(false, canvas(), handle);
+    /// let mut backend = HeadlessBackend::new();
+    /// let outer = Rectangle::new(0.0, 0.0, 12.0, 200.0);
+    /// let style = Style::default();
+    /// let handle_colors = |backend: &HeadlessBackend| -> Vec<Color> {
+    ///     backend.draw_commands.iter()
+    ///         .filter_map(|command| match command {
+    ///             DrawCommand::Circle { color, .. } => Some(*color),
+    ///             _ => None,
+    ///         })
+    ///         .collect()
+    /// };
+    /// scroll.available_space = 200.0;
+    ///
+    /// backend.set_mouse_position(Vector2::new(100.0, 100.0));
+    /// scroll.draw(&mut backend, outer, &style, &theme);
+    /// assert_eq!(scroll.width, 12.0);
+    /// assert_eq!(scroll.style().background_color(), Color::rgb(0xee, 0xee, 0xee));
+    /// assert_eq!(scroll.handle.style().background_color(), Color::rgb(0x88, 0x88, 0x88));
+    /// assert_eq!(handle_colors(&backend), [Color::rgb(0x88, 0x88, 0x88); 4]);
+    ///
+    /// // Hovering the handle gives it a different color
+    /// backend.next_frame();
+    /// backend.set_mouse_position(Vector2::new(5.0, 10.0));
+    /// scroll.draw(&mut backend, outer, &style, &theme);
+    /// assert_eq!(scroll.handle.style().background_color(), Color::rgb(0x55, 0x55, 0x55));
+    /// assert_eq!(handle_colors(&backend), [Color::rgb(0x55, 0x55, 0x55); 4]);
+    ///
+    /// // And so does dragging it
+    /// scroll.start_drag(Vector2::new(5.0, 10.0));
+    /// scroll.reload_style(&theme, &style);
+    /// assert_eq!(scroll.handle.style().background_color(), Color::rgb(0x22, 0x22, 0x22));
+    /// # Ok::<(), amity_ui::theme::ParseStylesheetError>(())
+    /// ```
+    ///
+    /// # Params
+    ///
+    /// - `theme`: Theme of the node holding the scrollbar.
+    /// - `base`:  Style of the node holding the scrollbar.
+    pub fn reload_style(&mut self, theme: &Theme<B>, base: &Style<B>) {
+        let state_tags = |is_hovered: bool| -> TagList {
+            [(is_hovered, Self::hovered_tag()), (self.handle.is_pressed, Self::pressed_tag())].into_iter()
+                .filter_map(|(is_set, tag)| is_set.then_some(tag))
+                .collect()
+        };
+
+        let mut track = base.clone();
+        for rule in theme.select(NodeType::ScrollInput, &state_tags(self.is_hovered)) {
+            rule.fields.apply(&mut track);
+        }
+
+        let mut handle = track.clone();
+        handle.background_color = track.line_color;
+        for rule in theme.select(NodeType::ScrollHandle, &state_tags(self.inner_hovered)) {
+            rule.fields.apply(&mut handle);
+        }
+
+        self.width = track.scrollbar_width;
+        *self.style = track;
+        self.handle.style = handle;
+    }
+
+    /// Update the metrics, hover state and style, and draw the scrollbar. The track is filled with the background
+    /// color of its style, and the handle with the background color of the handle's style; both are rounded by their
+    /// corner radius. See [`Self::reload_style`].
+    ///
+    /// Nothing is drawn if the scrollbar is visually hidden, but the metrics are still updated.
+    ///
+    /// ```
+    /// # use amity_ui::{backend::{Backend, Rectangle, Vector2, headless::HeadlessBackend}, canvas::CanvasSpace,
+    /// #     hover::HoverSpace, scroll::Scrollable, scroll_input::{ScrollInput, ScrollInputHandle}, style::Style,
+    /// #     theme::Theme};
+    /// # let canvas = || Box::new(CanvasSpace::new(Rectangle::default()));
+    /// # let handle = ScrollInputHandle::new(Box::new(HoverSpace::new()), canvas());
+    /// let mut scroll = ScrollInput::<HeadlessBackend>::new(false, canvas(), handle);
+    /// let mut backend = HeadlessBackend::new();
+    /// let outer = Rectangle::new(0.0, 0.0, 10.0, 200.0);
+    /// let style = Style::default();
+    /// let theme = Theme::new();
+    ///
+    /// // A hidden scrollbar draws nothing, but follows changes to the available space
+    /// scroll.is_visually_hidden = true;
+    /// scroll.available_space = 200.0;
+    /// scroll.draw(&mut backend, outer, &style, &theme);
+    /// assert!(backend.draw_commands.is_empty());
+    /// assert_eq!(scroll.handle_box(outer).height, 100.0);
+    ///
+    /// scroll.available_space = 300.0;
+    /// scroll.draw(&mut backend, outer, &style, &theme);
+    /// assert!(backend.draw_commands.is_empty());
+    /// assert_eq!(scroll.handle_box(outer).height, 80.0);
+    ///
+    /// // Once shown again, the track and the up to date handle are drawn
+    /// scroll.is_visually_hidden = false;
+    /// scroll.draw(&mut backend, outer, &style, &theme);
+    /// assert_eq!(backend.draw_commands.len(), 2);
+    ///
+    /// // Wheel scrolling works while the scrollbar is hidden
+    /// scroll.is_visually_hidden = true;
+    /// let mut scrollable = Scrollable::new(None, Some(scroll));
+    /// scrollable.resize_content(Vector2::new(10.0, 500.0), Vector2::new(10.0, 200.0));
+    /// scrollable.scroll_wheel(Vector2::new(0.0, -1.0));
+    /// assert_eq!(scrollable.scroll().y, ScrollInput::<HeadlessBackend>::SCROLL_SPEED);
+    /// ```
+    ///
+    /// # Params
+    ///
+    /// - `backend`: Backend to draw with.
+    /// - `outer`:   Box of the scrollbar.
+    /// - `style`:   Style of the node holding the scrollbar.
+    /// - `theme`:   Theme of the node holding the scrollbar.
+    pub fn draw(&mut self, backend: &mut B, outer: Rectangle, style: &Style<B>, theme: &Theme<B>) {
+        self.update_metrics(outer);
+        self.update_hover(outer, backend.mouse_position().into());
+        self.reload_style(theme, style);
+
+        if self.is_visually_hidden { return; }
+
+        let handle = &self.handle.style;
+        draw_rounded_rectangle(backend, outer, self.style.corner_radius, self.style.background_color);
+        draw_rounded_rectangle(backend, self.handle_box(outer), handle.corner_radius, handle.background_color);
+    }
+
+    /// Check if the scrollbar is still moving in smooth mode.
+    #[inline]
+    pub fn is_moving(&self) -> bool {
+        self.velocity != 0.0
+    }
+
+    /// Advance smooth scrolling by a frame. Does nothing if the scrollbar isn't moving.
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use amity_ui::{backend::{Rectangle, headless::HeadlessBackend}, canvas::CanvasSpace, hover::HoverSpace,
+    /// #     scroll_input::{ScrollInput, ScrollInputHandle}};
+    /// # let canvas = || Box::new(CanvasSpace::new(Rectangle::default()));
+    /// # let handle = ScrollInputHandle::new(Box::new(HoverSpace::new()), canvas());
+    /// let mut scroll = ScrollInput::<HeadlessBackend>::new(false, canvas(), handle);
+    /// scroll.available_space = 1000.0;
+    /// scroll.is_smooth = true;
+    ///
+    /// // A push doesn't move the scrollbar right away
+    /// scroll.push(100.0);
+    /// assert_eq!(scroll.position, 0.0);
+    /// assert!(scroll.is_moving());
+    ///
+    /// // It moves over the next frames, slowing down
+    /// let frame = Duration::from_millis(16);
+    /// let mut steps = Vec::new();
+    /// for _ in 0..5 {
+    ///     let previous = scroll.position;
+    ///     scroll.update_momentum(frame);
+    ///     steps.push(scroll.position - previous);
+    /// }
+    /// assert!(steps.windows(2).all(|pair| pair[0] > pair[1] && pair[1] > 0.0));
+    ///
+    /// // Then comes to rest, having travelled the pushed distance
+    /// for _ in 0..100 {
+    ///     scroll.update_momentum(frame);
+    /// }
+    /// assert!(!scroll.is_moving());
+    /// assert!((scroll.position - 100.0).abs() < 0.5);
+    /// ```
+    ///
+    /// # Params
+    ///
+    /// - `delta_time`: Time elapsed since the last frame, as returned by [`Backend::delta_time`].
+    pub fn update_momentum(&mut self, delta_time: Duration) {
+        if self.velocity == 0.0 { return; }
+
+        let decay = (-Self::MOMENTUM_DECAY * delta_time.as_secs_f32()).exp();
+        let distance = self.velocity / Self::MOMENTUM_DECAY * (1.0 - decay);
+
+        self.velocity *= decay;
+        self.scroll_by(distance);
+
+        // Stop once slow enough, or after hitting either end
+        let at_edge = (self.position <= 0.0 && self.velocity < 0.0)
+            || (self.position >= self.available_space && self.velocity > 0.0);
+        if at_edge || self.velocity.abs() < Self::MINIMUM_VELOCITY {
+            self.velocity = 0.0;
+        }
+    }
 }
 
 // : Node, FluidHoverable, Hoverable
@@ -64,14 +562,66 @@ pub struct ScrollInputHandle<B: Backend> {
     pub(crate) just_pressed: bool,
 
     /// Position of the mouse when dragging started.
-    pub(crate) start_mouse_position: B::Vector2,
+    pub(crate) start_mouse_position: Vector2,
 
     /// Scroll value when dragging started.
     pub(crate) start_scroll_position: f32,
 
+    /// Style of the handle, resolved by [`ScrollInput::reload_style`].
+    style: Style<B>,
+
     is_pressed: bool,
 }
 
 impl<B: Backend> ScrollInputHandle<B> {
+    /// Minimum length of the handle, in pixels. The handle is only shorter if the scrollbar itself is.
     pub const MINIMUM_LENGTH: u32 = 50;
+
+    /// Create a handle, not attached to a scrollbar.
+    ///
+    /// # Params
+    ///
+    /// - `hover_io`:  Hover system the handle can be grabbed through.
+    /// - `canvas_io`: Canvas to draw the handle on.
+    pub fn new(hover_io: Box<dyn HoverIO<B>>, canvas_io: Box<dyn CanvasIO<B>>) -> Self {
+        Self {
+            hover_io,
+            canvas_io,
+            parent: Weak::new(),
+            length: 0.0,
+            just_pressed: false,
+            start_mouse_position: Vector2::default(),
+            start_scroll_position: 0.0,
+            style: Style::default(),
+            is_pressed: false,
+        }
+    }
+
+    /// Style of the handle, as resolved by the last call to [`ScrollInput::reload_style`].
+    #[inline]
+    pub fn style(&self) -> &Style<B> {
+        &self.style
+    }
+}
+
+/// Fill a rectangle with rounded corners. The radius is limited to half of the shorter side. Corners are drawn as
+/// circles overlapping the rest of the rectangle, so translucent colors appear darker there.
+fn draw_rounded_rectangle<B: Backend>(backend: &mut B, rectangle: Rectangle, radius: f32, color: Color) {
+    let radius = radius.min(rectangle.width / 2.0).min(rectangle.height / 2.0);
+    if radius <= 0.0 {
+        backend.draw_rectangle(rectangle.into(), color.into());
+        return;
+    }
+
+    let [left, top] = [rectangle.x + radius, rectangle.y + radius];
+    let [right, bottom] = [rectangle.right() - radius, rectangle.bottom() - radius];
+    let middle = Rectangle::new(left, rectangle.y, right - left, rectangle.height);
+    let sides = [rectangle.x, right].map(|x| Rectangle::new(x, top, radius, bottom - top));
+
+    for part in [middle].into_iter().chain(sides) {
+        backend.draw_rectangle(part.into(), color.into());
+    }
+    for center in [(left, top), (right, top), (left, bottom), (right, bottom)] {
+        backend.draw_circle(Vector2::new(center.0, center.1).into(), radius, color.into());
+    }
 }
@@ -1,8 +1,63 @@
+use std::{any::TypeId, collections::HashMap, sync::{Mutex, OnceLock, atomic::{AtomicUsize, Ordering}}};
+
 /// Unique ID generated from a symbol.
 ///
-/// See `staticID` for generating static IDs.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// See [`next`] and [`of`] for generating static IDs. IDs are unique within the process, but they are not stable
+/// across runs, so they must not be saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct StaticID {
     /// The ID.
     id: usize,
 }
+
+impl StaticID {
+    /// Get the numeric value of the ID.
+    #[inline]
+    pub const fn id(self) -> usize {
+        self.id
+    }
+}
+
+/// Generate a new, unique ID. Safe to call from multiple threads at once.
+///
+/// ```
+/// # use amity_ui::static_id;
+/// let handles = (0..4)
+///     .map(|_| std::thread::spawn(|| (0..1000).map(|_| static_id::next()).collect::<Vec<_>>()))
+///     .collect::<Vec<_>>();
+///
+/// let mut ids = handles.into_iter()
+///     .flat_map(|handle| handle.join().unwrap())
+///     .collect::<Vec<_>>();
+/// ids.sort();
+/// ids.dedup();
+/// assert_eq!(ids.len(), 4000);
+/// ```
+pub fn next() -> StaticID {
+    static COUNTER: AtomicUsize = AtomicUsize::new(1);
+
+    StaticID {
+        id: COUNTER.fetch_add(1, Ordering::Relaxed),
+    }
+}
+
+/// Get the ID of a type. The first call for a type generates a new ID with [`next`]; later calls return the same ID.
+///
+/// ```
+/// # use amity_ui::static_id;
+/// struct Primary;
+/// struct Secondary;
+///
+/// assert_eq!(static_id::of::<Primary>(), static_id::of::<Primary>());
+/// assert_ne!(static_id::of::<Primary>(), static_id::of::<Secondary>());
+/// assert_ne!(static_id::of::<Vec<u8>>(), static_id::of::<Vec<u16>>());
+/// ```
+pub fn of<T: ?Sized + 'static>() -> StaticID {
+    static IDS: OnceLock<Mutex<HashMap<TypeId, StaticID>>> = OnceLock::new();
+
+    let mut ids = IDS.get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|error| error.into_inner());
+
+    *ids.entry(TypeId::of::<T>()).or_insert_with(next)
+}
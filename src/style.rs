@@ -1,4 +1,6 @@
-use crate::{backend::{Backend, Color, MouseCursor}, border::Border, theme::Breadcrumbs, typeface::Typeface};
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{backend::{Backend, Color, MouseCursor, Rectangle, Vector2}, border::{Border, NoBorder}, theme::Breadcrumbs, typeface::{EmptyTypeface, Typeface}};
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Side {
@@ -9,13 +11,13 @@ pub enum Side {
 }
 
 /// Side array is a static array defining a property separately for each side of a box, for example margin and border
-/// size. Order is as follows: `[left, right, top, bottom]`. You can use `Style.Side` to index this array with an enum.
+/// size. Order is as follows: `[left, right, top, bottom]`. You can use [`Side`] to index this array with an enum.
 ///
-/// Because of the default behavior of static arrays, one can set the value for all sides to be equal with a simple
-/// assignment: `array = 8`. Additionally, to make it easier to manipulate the box, one may use the `sideX` and `sideY`
-/// functions to get a `float[2]` array of the values corresponding to the given axis (which can also be assigned like
-/// `array.sideX = 8`) or the `sideLeft`, `sideRight`, `sideTop` and `sideBottom` functions corresponding to the given
-/// sides.
+/// Use [`SideArray::uniform`] to set the value for all sides to be equal. Additionally, to make it easier to manipulate
+/// the box, one may use the [`SideArray::side_x`] and [`SideArray::side_y`] functions to get the pair of values
+/// corresponding to the given axis (or their `_mut` variants to assign both at once) or the `side_left`, `side_right`,
+/// `side_top` and `side_bottom` functions corresponding to the given sides.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub struct SideArray<T>(pub [T; 4]);
 
 impl<T> std::ops::Index<Side> for SideArray<T> {
@@ -44,6 +46,98 @@ impl<T> SideArray<T> {
     pub const fn side_mut(&mut self, side: Side) -> &mut T {
         &mut self.0[side as usize]
     }
+
+    /// Create a side array with the same value on all sides.
+    #[inline]
+    pub fn uniform(value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self([value.clone(), value.clone(), value.clone(), value])
+    }
+
+    /// Get the values for the horizontal axis: `[left, right]`.
+    #[inline]
+    pub const fn side_x(&self) -> [&T; 2] {
+        let [left, right, _, _] = &self.0;
+        [left, right]
+    }
+
+    /// Get the values for the vertical axis: `[top, bottom]`.
+    #[inline]
+    pub const fn side_y(&self) -> [&T; 2] {
+        let [_, _, top, bottom] = &self.0;
+        [top, bottom]
+    }
+
+    /// Get mutable references to the values for the horizontal axis: `[left, right]`.
+    #[inline]
+    pub const fn side_x_mut(&mut self) -> [&mut T; 2] {
+        let [left, right, _, _] = &mut self.0;
+        [left, right]
+    }
+
+    /// Get mutable references to the values for the vertical axis: `[top, bottom]`.
+    #[inline]
+    pub const fn side_y_mut(&mut self) -> [&mut T; 2] {
+        let [_, _, top, bottom] = &mut self.0;
+        [top, bottom]
+    }
+
+    #[inline]
+    pub const fn side_left(&self) -> &T {
+        self.side(Side::Left)
+    }
+
+    #[inline]
+    pub const fn side_right(&self) -> &T {
+        self.side(Side::Right)
+    }
+
+    #[inline]
+    pub const fn side_top(&self) -> &T {
+        self.side(Side::Top)
+    }
+
+    #[inline]
+    pub const fn side_bottom(&self) -> &T {
+        self.side(Side::Bottom)
+    }
+
+    #[inline]
+    pub const fn side_left_mut(&mut self) -> &mut T {
+        self.side_mut(Side::Left)
+    }
+
+    #[inline]
+    pub const fn side_right_mut(&mut self) -> &mut T {
+        self.side_mut(Side::Right)
+    }
+
+    #[inline]
+    pub const fn side_top_mut(&mut self) -> &mut T {
+        self.side_mut(Side::Top)
+    }
+
+    #[inline]
+    pub const fn side_bottom_mut(&mut self) -> &mut T {
+        self.side_mut(Side::Bottom)
+    }
+}
+
+/// Shrink a box by the given amount on each side. The resulting box never has a negative size.
+///
+/// # Params
+///
+/// - `rect`:  Box to shrink.
+/// - `sides`: Amount to take from each side, `[left, right, top, bottom]`.
+pub fn crop_box(rect: Rectangle, [left, right, top, bottom]: [f32; 4]) -> Rectangle {
+    Rectangle::new(
+        rect.x + left,
+        rect.y + top,
+        (rect.width - left - right).max(0.0),
+        (rect.height - top - bottom).max(0.0),
+    )
 }
 
 /// Contains the style for a node.
@@ -53,27 +147,30 @@ pub struct Style<B: Backend> {
     /// Main typeface to be used for text.
     ///
     /// Changing the typeface requires a resize.
-    typeface: Box<dyn Typeface<B>>,
+    pub(crate) typeface: Rc<RefCell<dyn Typeface<B>>>,
 
     /// Size of the font in use, in pixels.
     ///
     /// Changing the size requires a resize.
-    font_size: f32,
+    pub(crate) font_size: f32,
 
     /// Text color.
-    text_color: Color,
+    pub(crate) text_color: Color,
 
 
     // Background & content
 
     /// Color of lines belonging to the node, especially important to separators and sliders.
-    line_color: Color,
+    pub(crate) line_color: Color,
 
     /// Background color of the node.
-    background_color: Color,
+    pub(crate) background_color: Color,
 
     /// Background color for selected text.
-    selection_background_color: Color,
+    pub(crate) selection_background_color: Color,
+
+    /// Radius of rounded corners, in pixels. Used by scrollbar tracks and handles.
+    pub(crate) corner_radius: f32,
 
     // Spacing
 
@@ -82,43 +179,408 @@ pub struct Style<B: Backend> {
     /// Updating margins requires a resize.
     ///
     /// See: `is_side_array`.
-    margin: [f32; 4],
+    pub(crate) margin: [f32; 4],
 
     /// Border size, placed between margin and padding. `[left, right, top, bottom]`.
     ///
     /// Updating border requires a resize.
     ///
     /// See: `is_side_array`
-    border: [f32; 4],
+    pub(crate) border: [f32; 4],
 
     /// Padding (inner margin) of the node. `[left, right, top, bottom]`.
     ///
     /// Updating padding requires a resize.
     ///
     /// See: `is_side_array`
-    padding: [f32; 4],
+    pub(crate) padding: [f32; 4],
 
     /// Margin/gap between two neighboring elements; for container nodes that support it.
     ///
     /// Updating the gap requires a resize.
-    gap: [f32; 2],
+    pub(crate) gap: [f32; 2],
+
+    /// Width of scrollbars, measured across the axis they scroll along.
+    ///
+    /// Updating the width requires a resize.
+    pub(crate) scrollbar_width: f32,
 
     /// Border style to use.
     ///
     /// Updating border requires a resize.
-    border_style: Box<dyn Border<B>>,
+    pub(crate) border_style: Rc<dyn Border<B>>,
 
     // Misc
 
     /// Apply tint to all node contents, including children.
-    tint: Color,
+    pub(crate) tint: Color,
 
     /// Cursor icon to use while this node is hovered.
     ///
-    /// Custom image cursors are not supported yet.
-    mouse_cursor: MouseCursor,
+    /// Image cursors are given with [`MouseCursor::image`]; backends that can't display them fall back to the
+    /// cursor's system icon.
+    pub(crate) mouse_cursor: MouseCursor,
 
     /// Breadcrumbs associated with this style. Used to keep track of tree-aware theme selectors, such as
     /// `children`. Does not include breadcrumbs loaded by parent nodes.
-    breadcrumbs: Breadcrumbs<B>,
+    pub(crate) breadcrumbs: Breadcrumbs<B>,
+}
+
+impl<B: Backend> Clone for Style<B> {
+    fn clone(&self) -> Self {
+        Self {
+            typeface: self.typeface.clone(),
+            font_size: self.font_size,
+            text_color: self.text_color,
+            line_color: self.line_color,
+            background_color: self.background_color,
+            selection_background_color: self.selection_background_color,
+            corner_radius: self.corner_radius,
+            margin: self.margin,
+            border: self.border,
+            padding: self.padding,
+            gap: self.gap,
+            scrollbar_width: self.scrollbar_width,
+            border_style: self.border_style.clone(),
+            tint: self.tint,
+            mouse_cursor: self.mouse_cursor.clone(),
+            breadcrumbs: self.breadcrumbs.clone(),
+        }
+    }
+}
+
+impl<B: Backend> PartialEq for Style<B> {
+    /// Compare the fields of two styles. Typefaces and border styles are compared by address, and breadcrumbs are
+    /// ignored.
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.typeface, &other.typeface)
+            && self.font_size == other.font_size
+            && self.text_color == other.text_color
+            && self.line_color == other.line_color
+            && self.background_color == other.background_color
+            && self.selection_background_color == other.selection_background_color
+            && self.corner_radius == other.corner_radius
+            && self.margin == other.margin
+            && self.border == other.border
+            && self.padding == other.padding
+            && self.gap == other.gap
+            && self.scrollbar_width == other.scrollbar_width
+            && Rc::ptr_eq(&self.border_style, &other.border_style)
+            && self.tint == other.tint
+            && self.mouse_cursor == other.mouse_cursor
+    }
+}
+
+impl<B: Backend> Default for Style<B> {
+    /// Unstyled look: black text on a transparent background, with no spacing or border, and no font loaded.
+    fn default() -> Self {
+        Self {
+            typeface: Rc::new(RefCell::new(EmptyTypeface::default())),
+            font_size: 14.0,
+            text_color: Color::new(0, 0, 0, 255),
+            line_color: Color::new(0, 0, 0, 255),
+            background_color: Color::new(0, 0, 0, 0),
+            selection_background_color: Color::new(0x55, 0xb9, 0xff, 255),
+            corner_radius: 0.0,
+            margin: [0.0; 4],
+            border: [0.0; 4],
+            padding: [0.0; 4],
+            gap: [0.0; 2],
+            scrollbar_width: 10.0,
+            border_style: Rc::new(NoBorder),
+            tint: Color::new(255, 255, 255, 255),
+            mouse_cursor: MouseCursor::SystemDefault,
+            breadcrumbs: Breadcrumbs::default(),
+        }
+    }
+}
+
+impl<B: Backend> Style<B> {
+    /// Set the main typeface to be used for text.
+    #[inline]
+    pub fn with_typeface(mut self, value: impl Typeface<B> + 'static) -> Self {
+        self.typeface = Rc::new(RefCell::new(value));
+        self
+    }
+
+    /// Set the size of the font, in pixels.
+    #[inline]
+    pub fn with_font_size(mut self, value: f32) -> Self {
+        self.font_size = value;
+        self
+    }
+
+    /// Set the text color.
+    #[inline]
+    pub fn with_text_color(mut self, value: Color) -> Self {
+        self.text_color = value;
+        self
+    }
+
+    /// Set the color of lines belonging to the node.
+    #[inline]
+    pub fn with_line_color(mut self, value: Color) -> Self {
+        self.line_color = value;
+        self
+    }
+
+    /// Set the background color of the node.
+    #[inline]
+    pub fn with_background_color(mut self, value: Color) -> Self {
+        self.background_color = value;
+        self
+    }
+
+    /// Set the background color for selected text.
+    #[inline]
+    pub fn with_selection_background_color(mut self, value: Color) -> Self {
+        self.selection_background_color = value;
+        self
+    }
+
+    /// Set the radius of rounded corners, in pixels.
+    #[inline]
+    pub fn with_corner_radius(mut self, value: f32) -> Self {
+        self.corner_radius = value;
+        self
+    }
+
+    /// Set the margin of the node, `[left, right, top, bottom]`.
+    #[inline]
+    pub fn with_margin(mut self, value: [f32; 4]) -> Self {
+        self.margin = value;
+        self
+    }
+
+    /// Set the border size, `[left, right, top, bottom]`.
+    #[inline]
+    pub fn with_border(mut self, value: [f32; 4]) -> Self {
+        self.border = value;
+        self
+    }
+
+    /// Set the padding of the node, `[left, right, top, bottom]`.
+    #[inline]
+    pub fn with_padding(mut self, value: [f32; 4]) -> Self {
+        self.padding = value;
+        self
+    }
+
+    /// Set the gap between neighboring elements.
+    #[inline]
+    pub fn with_gap(mut self, value: [f32; 2]) -> Self {
+        self.gap = value;
+        self
+    }
+
+    /// Set the width of scrollbars.
+    #[inline]
+    pub fn with_scrollbar_width(mut self, value: f32) -> Self {
+        self.scrollbar_width = value;
+        self
+    }
+
+    /// Set the border style.
+    #[inline]
+    pub fn with_border_style(mut self, value: impl Border<B> + 'static) -> Self {
+        self.border_style = Rc::new(value);
+        self
+    }
+
+    /// Set the tint applied to all node contents, including children.
+    #[inline]
+    pub fn with_tint(mut self, value: Color) -> Self {
+        self.tint = value;
+        self
+    }
+
+    /// Set the cursor icon to use while the node is hovered.
+    #[inline]
+    pub fn with_mouse_cursor(mut self, value: MouseCursor) -> Self {
+        self.mouse_cursor = value;
+        self
+    }
+
+    /// Main typeface to be used for text.
+    #[inline]
+    pub fn typeface(&self) -> &Rc<RefCell<dyn Typeface<B>>> {
+        &self.typeface
+    }
+
+    /// Size of the font, in pixels.
+    #[inline]
+    pub const fn font_size(&self) -> f32 {
+        self.font_size
+    }
+
+    /// Text color.
+    #[inline]
+    pub const fn text_color(&self) -> Color {
+        self.text_color
+    }
+
+    /// Color of lines belonging to the node.
+    #[inline]
+    pub const fn line_color(&self) -> Color {
+        self.line_color
+    }
+
+    /// Background color of the node.
+    #[inline]
+    pub const fn background_color(&self) -> Color {
+        self.background_color
+    }
+
+    /// Background color for selected text.
+    #[inline]
+    pub const fn selection_background_color(&self) -> Color {
+        self.selection_background_color
+    }
+
+    /// Radius of rounded corners, in pixels.
+    #[inline]
+    pub const fn corner_radius(&self) -> f32 {
+        self.corner_radius
+    }
+
+    /// Margin of the node, `[left, right, top, bottom]`.
+    #[inline]
+    pub const fn margin(&self) -> [f32; 4] {
+        self.margin
+    }
+
+    /// Border size, `[left, right, top, bottom]`.
+    #[inline]
+    pub const fn border(&self) -> [f32; 4] {
+        self.border
+    }
+
+    /// Padding of the node, `[left, right, top, bottom]`.
+    #[inline]
+    pub const fn padding(&self) -> [f32; 4] {
+        self.padding
+    }
+
+    /// Gap between neighboring elements.
+    #[inline]
+    pub const fn gap(&self) -> [f32; 2] {
+        self.gap
+    }
+
+    /// Width of scrollbars.
+    #[inline]
+    pub const fn scrollbar_width(&self) -> f32 {
+        self.scrollbar_width
+    }
+
+    /// Border style.
+    #[inline]
+    pub fn border_style(&self) -> &Rc<dyn Border<B>> {
+        &self.border_style
+    }
+
+    /// Tint applied to all node contents, including children.
+    #[inline]
+    pub const fn tint(&self) -> Color {
+        self.tint
+    }
+
+    /// Cursor icon to use while the node is hovered.
+    #[inline]
+    pub const fn mouse_cursor(&self) -> &MouseCursor {
+        &self.mouse_cursor
+    }
+
+    /// Interpolate between two styles. Sizes and colors change gradually, while fields that can't be interpolated,
+    /// such as the typeface, border style or mouse cursor, switch to the other style halfway through.
+    ///
+    /// # Params
+    ///
+    /// - `other`: Style to interpolate towards.
+    /// - `t`:     Position between the styles; `0` returns this style, `1` returns `other`. Clamped to `[0, 1]`.
+    ///
+    /// ```
+    /// # use amity_ui::{backend::{Color, MouseCursor, headless::HeadlessBackend}, style::Style};
+    /// let from = Style::<HeadlessBackend>::default()
+    ///     .with_padding([0.0, 10.0, 20.0, 40.0])
+    ///     .with_background_color(Color::new(0, 100, 200, 0));
+    /// let to = Style::default()
+    ///     .with_padding([10.0, 10.0, 0.0, 20.0])
+    ///     .with_background_color(Color::new(200, 100, 0, 255))
+    ///     .with_mouse_cursor(MouseCursor::Pointer);
+    ///
+    /// let middle = from.lerp(&to, 0.5);
+    /// assert_eq!(middle.padding(), [5.0, 10.0, 10.0, 30.0]);
+    /// assert_eq!(middle.background_color(), Color::new(100, 100, 100, 128));
+    /// assert_eq!(*middle.mouse_cursor(), MouseCursor::Pointer);
+    /// assert_eq!(*from.lerp(&to, 0.49).mouse_cursor(), MouseCursor::SystemDefault);
+    ///
+    /// // `t` is clamped
+    /// assert!(from.lerp(&to, -1.0) == from);
+    /// assert!(from.lerp(&to, 2.0) == to);
+    /// ```
+    pub fn lerp(&self, other: &Style<B>, t: f32) -> Style<B> {
+        let t = t.clamp(0.0, 1.0);
+        let snapped = if t >= 0.5 { other } else { self };
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        let lerp_sides = |a: [f32; 4], b: [f32; 4]| std::array::from_fn(|side| lerp(a[side], b[side]));
+
+        Style {
+            typeface: snapped.typeface.clone(),
+            font_size: lerp(self.font_size, other.font_size),
+            text_color: self.text_color.lerp(other.text_color, t),
+            line_color: self.line_color.lerp(other.line_color, t),
+            background_color: self.background_color.lerp(other.background_color, t),
+            selection_background_color: self.selection_background_color.lerp(other.selection_background_color, t),
+            corner_radius: lerp(self.corner_radius, other.corner_radius),
+            margin: lerp_sides(self.margin, other.margin),
+            border: lerp_sides(self.border, other.border),
+            padding: lerp_sides(self.padding, other.padding),
+            gap: [lerp(self.gap[0], other.gap[0]), lerp(self.gap[1], other.gap[1])],
+            scrollbar_width: lerp(self.scrollbar_width, other.scrollbar_width),
+            border_style: snapped.border_style.clone(),
+            tint: self.tint.lerp(other.tint, t),
+            mouse_cursor: snapped.mouse_cursor.clone(),
+            breadcrumbs: snapped.breadcrumbs.clone(),
+        }
+    }
+
+    /// Check if two styles give the node the same size: their spacing, scrollbar width, font size and typeface are
+    /// the same.
+    pub(crate) fn is_layout_equal(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.typeface, &other.typeface)
+            && self.font_size == other.font_size
+            && self.margin == other.margin
+            && self.border == other.border
+            && self.padding == other.padding
+            && self.gap == other.gap
+            && self.scrollbar_width == other.scrollbar_width
+    }
+
+    /// Border box of a node: its margin box with the margin removed.
+    #[inline]
+    pub fn border_box(&self, margin_box: Rectangle) -> Rectangle {
+        crop_box(margin_box, self.margin)
+    }
+
+    /// Padding box of a node: its border box with the border removed.
+    #[inline]
+    pub fn padding_box(&self, border_box: Rectangle) -> Rectangle {
+        crop_box(border_box, self.border)
+    }
+
+    /// Content box of a node: its padding box with the padding removed.
+    #[inline]
+    pub fn content_box(&self, padding_box: Rectangle) -> Rectangle {
+        crop_box(padding_box, self.padding)
+    }
+
+    /// Space taken by margin, border and padding combined, horizontally and vertically.
+    pub fn spacing(&self) -> Vector2 {
+        let [left, right, top, bottom] = std::array::from_fn(|side| {
+            self.margin[side] + self.border[side] + self.padding[side]
+        });
+
+        Vector2::new(left + right, top + bottom)
+    }
 }
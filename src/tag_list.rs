@@ -1,11 +1,136 @@
-use std::{collections::BTreeSet, num::NonZeroI64};
+use std::{collections::{BTreeSet, HashMap}, num::NonZeroI64, sync::{Mutex, OnceLock, atomic::{AtomicI64, Ordering}}};
 
 /// Node parameter assigning a new set of tags to a node.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct TagList(BTreeSet<TagID>);
 
+impl TagList {
+    /// Create an empty tag list.
+    #[inline]
+    pub const fn new() -> Self {
+        Self(BTreeSet::new())
+    }
+
+    /// Create a tag list holding the given tags.
+    #[inline]
+    pub fn with(tags: impl IntoIterator<Item = TagID>) -> Self {
+        Self(tags.into_iter().collect())
+    }
+
+    /// Add a tag to the list.
+    ///
+    /// # Returns
+    ///
+    /// True if the tag was added, false if it was already present.
+    #[inline]
+    pub fn insert(&mut self, tag: TagID) -> bool {
+        self.0.insert(tag)
+    }
+
+    /// Remove a tag from the list.
+    ///
+    /// # Returns
+    ///
+    /// True if the tag was removed, false if it wasn't present.
+    #[inline]
+    pub fn remove(&mut self, tag: TagID) -> bool {
+        self.0.remove(&tag)
+    }
+
+    /// Check if the list has the given tag.
+    #[inline]
+    pub fn contains(&self, tag: TagID) -> bool {
+        self.0.contains(&tag)
+    }
+
+    /// Check if every tag in this list is also present in the other list. Selectors use this to match nodes; an empty
+    /// list is a subset of any list.
+    #[inline]
+    pub fn is_subset(&self, other: &TagList) -> bool {
+        self.0.is_subset(&other.0)
+    }
+
+    /// True if the list has no tags.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over the tags, in order of their IDs.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = TagID> {
+        self.0.iter().copied()
+    }
+}
+
+impl FromIterator<TagID> for TagList {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = TagID>>(iter: T) -> Self {
+        Self::with(iter)
+    }
+}
+
 /// Unique ID of a node tag.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct TagID {
     /// Unique ID of the tag.
     id: NonZeroI64,
 }
+
+impl TagID {
+    /// Create a new, unique tag. Safe to call from multiple threads at once.
+    pub fn new() -> Self {
+        static COUNTER: AtomicI64 = AtomicI64::new(1);
+
+        let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+        Self {
+            id: NonZeroI64::new(id).expect("tag IDs should never run out"),
+        }
+    }
+
+    /// Get the tag assigned to the given name. The first call for a name creates a new tag; later calls return the
+    /// same tag.
+    ///
+    /// # See Also
+    ///
+    /// - [`tags`] for creating tag lists out of names.
+    pub fn named(name: &str) -> Self {
+        static TAGS: OnceLock<Mutex<HashMap<String, TagID>>> = OnceLock::new();
+
+        let mut tags = TAGS.get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+
+        if let Some(tag) = tags.get(name) {
+            return *tag;
+        }
+
+        let tag = Self::new();
+        tags.insert(name.to_owned(), tag);
+        tag
+    }
+}
+
+impl Default for TagID {
+    /// Create a new, unique tag.
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Create a [`TagList`] out of tag names. Each name always maps to the same tag, as given by [`TagID::named`].
+///
+/// ```
+/// # use amity_ui::{tags, tag_list::TagID};
+/// let list = tags![primary, large];
+/// assert!(list.contains(TagID::named("primary")));
+/// ```
+#[macro_export]
+macro_rules! tags {
+    ($($name:ident),* $(,)?) => {
+        $crate::tag_list::TagList::with([
+            $($crate::tag_list::TagID::named(::std::stringify!($name))),*
+        ])
+    };
+}
@@ -0,0 +1,706 @@
+use std::{cell::RefCell, collections::VecDeque, ops::Range, rc::{Rc, Weak}, time::Duration};
+use crate::{
+    backend::{Backend, Color, Rectangle, Vector2},
+    input::{FluidInputAction, InputActionID},
+    resource::UnloadQueue,
+    rope::{Rope, word_boundary_after, word_boundary_before},
+    style::Style,
+    typeface::Typeface,
+};
+
+/// Undo history of a text field. Keeps a bounded list of snapshots of the text, along with a position in the list;
+/// undo and redo move the position back and forth.
+///
+/// Consecutive single-character insertions made in quick succession are merged into one step, so undo removes a
+/// whole typed word rather than a single letter. Time is measured by calling [`Self::advance`] every frame.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use amity_ui::{rope::Rope, text::History};
+/// let mut history = History::new(Rope::from_str(""));
+/// history.push(Rope::from_str("a"), true);
+/// history.push(Rope::from_str("ab"), true);
+/// history.advance(Duration::from_secs(2));
+/// history.push(Rope::from_str("abc"), true);
+///
+/// assert_eq!(history.undo().unwrap().value(), "ab");
+/// assert_eq!(history.undo().unwrap().value(), "");
+/// assert_eq!(history.redo().unwrap().value(), "ab");
+///
+/// // Editing after undo discards steps that could be redone
+/// history.push(Rope::from_str("ab!"), false);
+/// assert!(history.redo().is_none());
+///
+/// // Only the newest snapshots are kept
+/// history.capacity = 2;
+/// history.push(Rope::from_str("ab?"), false);
+/// assert_eq!(history.undo().unwrap().value(), "ab!");
+/// assert!(history.undo().is_none());
+/// ```
+pub struct History {
+    /// Snapshots of the text, oldest first.
+    entries: VecDeque<Rope>,
+
+    /// Index of the snapshot holding the current text.
+    position: usize,
+
+    /// Maximum number of snapshots to keep, including the current one. Once exceeded, the oldest snapshot is
+    /// removed.
+    pub capacity: usize,
+
+    /// Time measured with `advance`.
+    time: Duration,
+
+    /// Time of the last push, if it was a single-character insertion that later insertions can merge into.
+    last_insertion: Option<Duration>,
+}
+
+impl History {
+    /// Default value for `capacity`.
+    pub const CAPACITY: usize = 256;
+
+    /// Insertions made within this time of each other are merged into a single step.
+    pub const COALESCE_TIME: Duration = Duration::from_secs(1);
+
+    /// Create a history starting with the given text.
+    pub fn new(value: Rope) -> Self {
+        Self {
+            entries: VecDeque::from([value]),
+            position: 0,
+            capacity: Self::CAPACITY,
+            time: Duration::ZERO,
+            last_insertion: None,
+        }
+    }
+
+    /// Advance time, used to decide if insertions should be merged.
+    ///
+    /// # Params
+    ///
+    /// - `delta_time`: Time elapsed since the last frame, as returned by [`crate::backend::Backend::delta_time`].
+    #[inline]
+    pub fn advance(&mut self, delta_time: Duration) {
+        self.time += delta_time;
+    }
+
+    /// Record an edit. Steps that could be redone are discarded.
+    ///
+    /// # Params
+    ///
+    /// - `value`:        Text after the edit.
+    /// - `is_insertion`: True if the edit inserted a single character. Insertions are merged with the previous step
+    ///   if it was an insertion too, and it happened within [`Self::COALESCE_TIME`].
+    pub fn push(&mut self, value: Rope, is_insertion: bool) {
+        let can_redo = self.position + 1 < self.entries.len();
+        let is_coalesced = !can_redo
+            && is_insertion
+            && self.last_insertion.is_some_and(|time| self.time - time <= Self::COALESCE_TIME);
+
+        self.entries.truncate(self.position + 1);
+        self.last_insertion = is_insertion.then_some(self.time);
+
+        if is_coalesced {
+            self.entries[self.position] = value;
+            return;
+        }
+
+        self.entries.push_back(value);
+        self.position += 1;
+
+        while self.entries.len() > self.capacity.max(1) {
+            self.entries.pop_front();
+            self.position -= 1;
+        }
+    }
+
+    /// Go back one step.
+    ///
+    /// # Returns
+    ///
+    /// Text before the last edit, or `None` if there is nothing to undo.
+    pub fn undo(&mut self) -> Option<Rope> {
+        if self.position == 0 { return None; }
+
+        self.position -= 1;
+        self.last_insertion = None;
+        Some(self.entries[self.position].clone())
+    }
+
+    /// Go forward one step, reverting the last undo.
+    ///
+    /// # Returns
+    ///
+    /// Text after the undone edit, or `None` if there is nothing to redo.
+    pub fn redo(&mut self) -> Option<Rope> {
+        if self.position + 1 >= self.entries.len() { return None; }
+
+        self.position += 1;
+        self.last_insertion = None;
+        Some(self.entries[self.position].clone())
+    }
+
+    /// Check if there is an edit to undo.
+    #[inline]
+    pub fn can_undo(&self) -> bool {
+        self.position > 0
+    }
+
+    /// Check if there is an undone edit to redo.
+    #[inline]
+    pub fn can_redo(&self) -> bool {
+        self.position + 1 < self.entries.len()
+    }
+}
+
+/// Selected range of text. The selection spans from the anchor, where it was started, to the caret, which moves as
+/// the selection is changed. Both are byte offsets into a [`Rope`], and the selection is empty if they're equal.
+///
+/// Movement [actions](FluidInputAction) move the caret. Plain movement, like `NextWord`, collapses the selection,
+/// while the `Select*` variants, like `SelectNextWord`, keep the anchor in place, extending the selection.
+///
+/// ```
+/// # use amity_ui::{input::FluidInputAction, rope::Rope, text::Selection};
+/// let rope = Rope::from_str("hello world");
+/// let mut selection = Selection::new(0);
+///
+/// selection.action(&rope, &FluidInputAction::SelectNextWord.id());
+/// assert_eq!(selection.range(), 0..5);
+///
+/// selection.action(&rope, &FluidInputAction::SelectAll.id());
+/// assert_eq!(selection.range(), 0..rope.len());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Selection {
+    /// Position the selection was started at, in UTF-8 bytes.
+    pub anchor: usize,
+
+    /// Position of the caret, in UTF-8 bytes.
+    pub caret: usize,
+}
+
+impl Selection {
+    /// Create an empty selection with the caret at the given position.
+    #[inline]
+    pub const fn new(caret: usize) -> Self {
+        Self { anchor: caret, caret }
+    }
+
+    /// Selected range, in UTF-8 bytes, from the lower position to the higher one.
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.anchor.min(self.caret)..self.anchor.max(self.caret)
+    }
+
+    /// Check if no text is selected.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.anchor == self.caret
+    }
+
+    /// Get the same selection with the anchor placed before the caret.
+    #[inline]
+    pub fn normalized(&self) -> Self {
+        let range = self.range();
+        Self { anchor: range.start, caret: range.end }
+    }
+
+    /// Move the caret.
+    ///
+    /// # Params
+    ///
+    /// - `caret`:  New position of the caret.
+    /// - `extend`: If true, the anchor is left in place, extending or shrinking the selection. Otherwise, the
+    ///   selection is collapsed to the caret.
+    #[inline]
+    pub fn move_to(&mut self, caret: usize, extend: bool) {
+        self.caret = caret;
+        if !extend {
+            self.anchor = caret;
+        }
+    }
+
+    /// Run a text navigation or selection action.
+    ///
+    /// Moving by character with a non-empty selection collapses it to its start or end instead of moving the caret.
+    /// Moving to the previous or next line keeps the caret in the same column, counted in characters, if the line is
+    /// long enough.
+    ///
+    /// # Params
+    ///
+    /// - `rope`:   Text the selection is in.
+    /// - `action`: Action to run.
+    ///
+    /// # Returns
+    ///
+    /// True if the action moves the selection, and was handled.
+    pub fn action(&mut self, rope: &Rope, action: &InputActionID) -> bool {
+        use FluidInputAction::*;
+
+        let Some(action) = [
+            PreviousChar, NextChar, PreviousWord, NextWord, PreviousLine, NextLine, ToLineStart, ToLineEnd, ToStart,
+            ToEnd, SelectPreviousChar, SelectNextChar, SelectPreviousWord, SelectNextWord, SelectPreviousLine,
+            SelectNextLine, SelectAll, SelectToLineStart, SelectToLineEnd, SelectToStart, SelectToEnd,
+        ].into_iter().find(|candidate| action.is(*candidate)) else {
+            return false;
+        };
+
+        let caret = self.caret;
+        match action {
+            PreviousChar if !self.is_empty() => *self = Self::new(self.range().start),
+            NextChar if !self.is_empty() => *self = Self::new(self.range().end),
+            PreviousChar       => self.move_to(previous_char(rope, caret), false),
+            NextChar           => self.move_to(next_char(rope, caret), false),
+            PreviousWord       => self.move_to(word_boundary_before(rope, caret), false),
+            NextWord           => self.move_to(word_boundary_after(rope, caret), false),
+            PreviousLine       => self.move_to(line_offset(rope, caret, -1), false),
+            NextLine           => self.move_to(line_offset(rope, caret, 1), false),
+            ToLineStart        => self.move_to(line_start(rope, caret), false),
+            ToLineEnd          => self.move_to(line_end(rope, caret), false),
+            ToStart            => self.move_to(0, false),
+            ToEnd              => self.move_to(rope.len(), false),
+            SelectPreviousChar => self.move_to(previous_char(rope, caret), true),
+            SelectNextChar     => self.move_to(next_char(rope, caret), true),
+            SelectPreviousWord => self.move_to(word_boundary_before(rope, caret), true),
+            SelectNextWord     => self.move_to(word_boundary_after(rope, caret), true),
+            SelectPreviousLine => self.move_to(line_offset(rope, caret, -1), true),
+            SelectNextLine     => self.move_to(line_offset(rope, caret, 1), true),
+            SelectAll          => *self = Self { anchor: 0, caret: rope.len() },
+            SelectToLineStart  => self.move_to(line_start(rope, caret), true),
+            SelectToLineEnd    => self.move_to(line_end(rope, caret), true),
+            SelectToStart      => self.move_to(0, true),
+            SelectToEnd        => self.move_to(rope.len(), true),
+            _ => unreachable!(),
+        }
+
+        true
+    }
+}
+
+/// Position of the character before the given position, or `0` if there is none.
+fn previous_char(rope: &Rope, byte: usize) -> usize {
+    byte - rope.slice(0, byte).chars().next_back().map_or(0, char::len_utf8)
+}
+
+/// Position after the character at the given position, or the length of the rope if there is none.
+fn next_char(rope: &Rope, byte: usize) -> usize {
+    byte + rope.slice(byte, rope.len() - byte).chars().next().map_or(0, char::len_utf8)
+}
+
+/// Position of the start of the line containing the given position.
+fn line_start(rope: &Rope, byte: usize) -> usize {
+    rope.line_to_byte(rope.byte_to_line(byte))
+}
+
+/// Position of the end of the line containing the given position, before the line break.
+fn line_end(rope: &Rope, byte: usize) -> usize {
+    let start = line_start(rope, byte);
+    let next = rope.line_to_byte(rope.byte_to_line(byte) + 1);
+    let mut line = rope.slice(start, next - start).chars();
+
+    let mut end = next;
+    if line.next_back() == Some('\n') {
+        end -= 1;
+        if line.next_back() == Some('\r') {
+            end -= 1;
+        }
+    }
+    end
+}
+
+/// Move the position by the given number of lines, keeping the column if the target line is long enough.
+fn line_offset(rope: &Rope, byte: usize, offset: isize) -> usize {
+    let line = rope.byte_to_line(byte);
+    let Some(target) = line.checked_add_signed(offset) else { return 0 };
+    if target > rope.byte_to_line(rope.len()) { return rope.len(); }
+
+    let start = line_start(rope, byte);
+    let column = rope.slice(start, byte - start).char_len();
+
+    let target_start = rope.line_to_byte(target);
+    let target_end = line_end(rope, target_start);
+    rope.slice(target_start, target_end - target_start).chars()
+        .take(column)
+        .fold(target_start, |position, c| position + c.len_utf8())
+}
+
+/// Fill the background behind selected text. Should be called before the text is drawn.
+///
+/// Lines of text are separated with `\n`. The first line of a multi-line selection is filled from the start of the
+/// selection to the right edge of the box, lines in between are filled across the whole box, and the last line is
+/// filled from the left edge up to the end of the selection.
+///
+/// ```
+/// # use amity_ui::{backend::{Backend, Color, Rectangle, Vector2, headless::{DrawCommand, HeadlessBackend}},
+/// #     rope::Rope, text::draw_selection, typeface::Typeface};
+/// # struct Monospace(i32);
+/// # impl Typeface<HeadlessBackend> for Monospace {
+/// #     fn glyph_count(&self) -> usize { 0 }
+/// #     fn pen_position(&self) -> Vector2 { Vector2::default() }
+/// #     fn line_height(&self) -> i32 { 20 }
+/// #     fn indent_width(&self) -> &i32 { &self.0 }
+/// #     fn indent_width_mut(&mut self) -> &mut i32 { &mut self.0 }
+/// #     fn advance(&mut self, _glyph: char) -> Vector2 { Vector2::new(10.0, 0.0) }
+/// #     fn dpi(&self) -> Vector2 { Vector2::new(96.0, 96.0) }
+/// #     fn set_size(&mut self, _dpi: Vector2, _size: f32) {}
+/// #     fn draw_line(&self, _: &mut <HeadlessBackend as Backend>::Image, _: &mut Vector2, _: Rope, _: u8) {}
+/// # }
+/// let mut backend = HeadlessBackend::new();
+/// let text = Rope::from_str("abc\ndef");
+/// let color = Color::new(0x55, 0xb9, 0xff, 255);
+///
+/// // Select from "bc" to "de"
+/// draw_selection(&mut backend, &mut Monospace(0), &text, 1..6, Rectangle::new(0.0, 0.0, 100.0, 40.0), color);
+///
+/// assert_eq!(backend.draw_commands, [
+///     DrawCommand::Rectangle { rectangle: Rectangle::new(10.0, 0.0, 90.0, 20.0), color },
+///     DrawCommand::Rectangle { rectangle: Rectangle::new(0.0, 20.0, 20.0, 20.0), color },
+/// ]);
+/// ```
+///
+/// # Params
+///
+/// - `backend`:     Backend to draw with.
+/// - `typeface`:    Typeface the text is drawn with, used to measure it.
+/// - `text`:        Text the selection is in.
+/// - `selection`:   Selected range, in UTF-8 bytes. Nothing is drawn if it is empty.
+/// - `content_box`: Box the text is drawn in, starting at the top-left corner.
+/// - `color`:       Color to fill the selection with, usually the `selection_background_color` of the node's style.
+pub fn draw_selection<B: Backend>(
+    backend: &mut B,
+    typeface: &mut dyn Typeface<B>,
+    text: &Rope,
+    selection: Range<usize>,
+    content_box: Rectangle,
+    color: Color,
+) {
+    if selection.is_empty() { return; }
+
+    let line_height = line_height(typeface);
+    let start = caret_position(typeface, text, selection.start);
+    let end = caret_position(typeface, text, selection.end);
+    let first_line = text.byte_to_line(selection.start);
+    let last_line = text.byte_to_line(selection.end);
+
+    for line in first_line..=last_line {
+        let left = if line == first_line { start.x } else { 0.0 };
+        let right = if line == last_line { end.x } else { content_box.width };
+        if right <= left { continue; }
+
+        let rectangle = Rectangle::new(
+            content_box.x + left,
+            content_box.y + line as f32 * line_height,
+            right - left,
+            line_height,
+        );
+        backend.draw_rectangle(rectangle.into(), color.into());
+    }
+}
+
+/// Texture holding lines of text, kept between frames. The lines are drawn into an image with
+/// [`Typeface::draw_line`], which is uploaded as a texture and drawn at the top-left corner of the box. The texture is
+/// only rendered again when the lines, the typeface, its font size or the DPI change.
+///
+/// Replaced and dropped textures may still be waiting to be drawn, so they are passed to an [`UnloadQueue`] rather than
+/// unloaded immediately.
+///
+/// ```
+/// # use amity_ui::{backend::{Backend, Rectangle, Vector2, headless::{DrawCommand, HeadlessBackend}},
+/// #     resource::UnloadQueue, rope::Rope, style::Style, text::TextTexture, typeface::Typeface};
+/// # struct Monospace(i32);
+/// # impl Typeface<HeadlessBackend> for Monospace {
+/// #     fn glyph_count(&self) -> usize { 0 }
+/// #     fn pen_position(&self) -> Vector2 { Vector2::default() }
+/// #     fn line_height(&self) -> i32 { 20 }
+/// #     fn indent_width(&self) -> &i32 { &self.0 }
+/// #     fn indent_width_mut(&mut self) -> &mut i32 { &mut self.0 }
+/// #     fn advance(&mut self, _glyph: char) -> Vector2 { Vector2::new(10.0, 0.0) }
+/// #     fn dpi(&self) -> Vector2 { Vector2::new(96.0, 96.0) }
+/// #     fn set_size(&mut self, _dpi: Vector2, _size: f32) {}
+/// #     fn draw_line(&self, _: &mut <HeadlessBackend as Backend>::Image, _: &mut Vector2, _: Rope, _: u8) {}
+/// # }
+/// let mut backend = HeadlessBackend::new();
+/// let queue = UnloadQueue::new();
+/// let mut texture = TextTexture::new(&queue);
+/// let style = Style::default().with_typeface(Monospace(0));
+/// let content_box = Rectangle::new(0.0, 0.0, 100.0, 20.0);
+/// let texture_ids = |backend: &HeadlessBackend| -> Vec<u32> {
+///     backend.draw_commands.iter()
+///         .filter_map(|command| match command {
+///             DrawCommand::Texture { texture, .. } => Some(*texture),
+///             _ => None,
+///         })
+///         .collect()
+/// };
+///
+/// texture.draw(&mut backend, &style, &[Rope::from_str("Hello")], content_box);
+/// let first = texture_ids(&backend);
+/// backend.next_frame();
+///
+/// // The same text reuses the texture
+/// texture.draw(&mut backend, &style, &[Rope::from_str("Hello")], content_box);
+/// assert_eq!(texture_ids(&backend), first);
+/// assert!(queue.is_empty());
+/// backend.next_frame();
+///
+/// // Changing the text renders it again, and queues the old texture
+/// texture.draw(&mut backend, &style, &[Rope::from_str("World")], content_box);
+/// assert_ne!(texture_ids(&backend), first);
+/// assert_eq!(queue.len(), 1);
+///
+/// drop(texture);
+/// assert_eq!(queue.len(), 2);
+/// ```
+pub struct TextTexture<B: Backend> {
+    /// Rendered texture, or `None` if there was nothing to draw.
+    texture: Option<B::Texture>,
+
+    /// Size of the texture, in dots.
+    size: Vector2,
+
+    /// Lines drawn into the texture.
+    lines: Vec<Rope>,
+
+    /// Typeface the lines were drawn with.
+    typeface: Option<Weak<RefCell<dyn Typeface<B>>>>,
+
+    /// Font size the lines were drawn at.
+    font_size: f32,
+
+    /// DPI the lines were drawn at.
+    dpi: Vector2,
+
+    /// Queue unloading replaced textures.
+    unload_queue: UnloadQueue<B>,
+}
+
+impl<B: Backend> TextTexture<B> {
+    /// Create an empty text texture. Nothing is rendered until the first draw.
+    ///
+    /// # Params
+    ///
+    /// - `unload_queue`: Queue to pass replaced textures to, usually [`crate::tree::LayoutTree::unload_queue`].
+    pub fn new(unload_queue: &UnloadQueue<B>) -> Self {
+        Self {
+            texture: None,
+            size: Vector2::default(),
+            lines: Vec::new(),
+            typeface: None,
+            font_size: f32::NAN,
+            dpi: Vector2::default(),
+            unload_queue: unload_queue.clone(),
+        }
+    }
+
+    /// Draw lines of text, rendering them first if they changed since the last draw.
+    ///
+    /// # Params
+    ///
+    /// - `backend`:     Backend to draw with.
+    /// - `style`:       Style of the node. The text is drawn with its typeface, `font_size` and `text_color`.
+    /// - `lines`:       Lines of text, in order, without line breaks. See [`crate::typeface::wrap_lines`].
+    /// - `content_box`: Box to draw the text in. Text is not clipped to the box.
+    pub fn draw(&mut self, backend: &mut B, style: &Style<B>, lines: &[Rope], content_box: Rectangle) {
+        let mut typeface = style.typeface.borrow_mut();
+        let dpi = typeface.dpi();
+        let typeface_ref = Rc::downgrade(&style.typeface);
+        let is_current = self.lines == lines
+            && self.font_size == style.font_size
+            && self.dpi == dpi
+            && self.typeface.as_ref().is_some_and(|typeface| Weak::ptr_eq(typeface, &typeface_ref));
+
+        if !is_current {
+            if let Some(texture) = self.texture.take() {
+                self.unload_queue.push(texture);
+            }
+            self.texture = Self::render(backend, &mut *typeface, lines).map(|(texture, size)| {
+                self.size = size;
+                texture
+            });
+            self.lines = lines.to_vec();
+            self.typeface = Some(typeface_ref);
+            self.font_size = style.font_size;
+            self.dpi = dpi;
+        }
+
+        let Some(texture) = &self.texture else { return };
+        let scale = dot_scale(&*typeface);
+        let rectangle = Rectangle::new(content_box.x, content_box.y, self.size.x * scale.x, self.size.y * scale.y);
+
+        backend.draw_texture_align(texture, rectangle.into(), style.text_color.into());
+    }
+
+    /// Draw the lines into a new texture.
+    ///
+    /// # Returns
+    ///
+    /// The texture and its size in dots, or `None` if the lines are empty.
+    fn render(backend: &mut B, typeface: &mut dyn Typeface<B>, lines: &[Rope]) -> Option<(B::Texture, Vector2)> {
+        let width = lines.iter()
+            .map(|line| typeface.measure_line(line.clone()).x)
+            .fold(0.0, f32::max)
+            .ceil();
+        let line_height = typeface.line_height();
+        let height = line_height * lines.len() as i32;
+        if width <= 0.0 || height <= 0 { return None; }
+
+        let mut image = backend.new_image(width as i32, height);
+        for (index, line) in lines.iter().enumerate() {
+            let mut pen = typeface.pen_position() + Vector2::new(0.0, (line_height * index as i32) as f32);
+            typeface.draw_line(&mut image, &mut pen, line.clone(), 0);
+        }
+
+        // The texture is unloaded through the queue, once it is no longer drawn
+        let texture = unsafe { backend.load_texture_from_image(&image) };
+        Some((texture, Vector2::new(width, height as f32)))
+    }
+}
+
+impl<B: Backend> Drop for TextTexture<B> {
+    fn drop(&mut self) {
+        if let Some(texture) = self.texture.take() {
+            self.unload_queue.push(texture);
+        }
+    }
+}
+
+/// Blinking caret of a text input. The caret is shown and hidden in turns, switching every [`Self::interval`], as long
+/// as the input is focused. Moving the caret or changing the text makes it visible again and restarts the blink.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use amity_ui::text::Caret;
+/// let mut caret = Caret::new();
+/// caret.update(Duration::ZERO, 0, true);
+/// assert!(caret.is_visible());
+/// caret.update(Duration::from_millis(400), 0, true);
+/// assert!(caret.is_visible());
+/// caret.update(Duration::from_millis(200), 0, true);
+/// assert!(!caret.is_visible());
+/// caret.update(Duration::from_millis(500), 0, true);
+/// assert!(caret.is_visible());
+/// caret.update(Duration::from_millis(400), 0, true);
+/// assert!(!caret.is_visible());
+///
+/// // Moving the caret shows it immediately
+/// caret.update(Duration::from_millis(100), 1, true);
+/// assert!(caret.is_visible());
+///
+/// // The caret is hidden while not focused
+/// caret.update(Duration::from_millis(100), 1, false);
+/// assert!(!caret.is_visible());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Caret {
+    /// Time the caret stays visible, or hidden, before switching.
+    pub interval: Duration,
+
+    /// Time since the blink was last restarted.
+    time: Duration,
+
+    /// Position of the caret during the last update, in UTF-8 bytes.
+    position: usize,
+
+    /// True if the input was focused during the last update.
+    is_focused: bool,
+}
+
+impl Caret {
+    /// Default value for `interval`.
+    pub const INTERVAL: Duration = Duration::from_millis(500);
+
+    /// Create a caret. It stays hidden until updated with focus.
+    pub const fn new() -> Self {
+        Self {
+            interval: Self::INTERVAL,
+            time: Duration::ZERO,
+            position: 0,
+            is_focused: false,
+        }
+    }
+
+    /// Make the caret visible and restart the blink. Should be called whenever the text changes.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.time = Duration::ZERO;
+    }
+
+    /// Advance the blink. Should be called every frame.
+    ///
+    /// # Params
+    ///
+    /// - `delta_time`: Time elapsed since the last frame, as returned by [`Backend::delta_time`].
+    /// - `position`:   Current position of the caret, in UTF-8 bytes. The blink restarts if it has changed.
+    /// - `is_focused`: True if the input is focused. Otherwise, the blink is paused, and the caret is hidden. The blink
+    ///   restarts once the input is focused again.
+    pub fn update(&mut self, delta_time: Duration, position: usize, is_focused: bool) {
+        if position != self.position || !self.is_focused {
+            self.position = position;
+            self.reset();
+        } else if is_focused {
+            self.time += delta_time;
+        }
+        self.is_focused = is_focused;
+    }
+
+    /// Check if the caret should be drawn in the current frame.
+    pub fn is_visible(&self) -> bool {
+        let interval = self.interval.as_nanos().max(1);
+        self.is_focused && (self.time.as_nanos() / interval).is_multiple_of(2)
+    }
+
+    /// Draw the caret as a vertical line, if it is visible.
+    ///
+    /// # Params
+    ///
+    /// - `backend`:     Backend to draw with.
+    /// - `typeface`:    Typeface the text is drawn with, used to measure it.
+    /// - `text`:        Text the caret is in.
+    /// - `content_box`: Box the text is drawn in, starting at the top-left corner.
+    /// - `color`:       Color of the caret, usually the text color.
+    pub fn draw<B: Backend>(
+        &self,
+        backend: &mut B,
+        typeface: &mut dyn Typeface<B>,
+        text: &Rope,
+        content_box: Rectangle,
+        color: Color,
+    ) {
+        if !self.is_visible() { return; }
+
+        let position = caret_position(typeface, text, self.position.min(text.len()));
+        let start = Vector2::new(content_box.x, content_box.y) + position;
+        let end = start + Vector2::new(0.0, line_height(typeface));
+        backend.draw_line(start.into(), end.into(), color.into());
+    }
+}
+
+impl Default for Caret {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Scale to convert dots used by the typeface into pixels.
+pub(crate) fn dot_scale<B: Backend>(typeface: &dyn Typeface<B>) -> Vector2 {
+    let dpi = typeface.dpi();
+    Vector2::new(
+        if dpi.x > 0.0 { 96.0 / dpi.x } else { 1.0 },
+        if dpi.y > 0.0 { 96.0 / dpi.y } else { 1.0 },
+    )
+}
+
+/// Height of a line of text, in pixels.
+fn line_height<B: Backend>(typeface: &dyn Typeface<B>) -> f32 {
+    typeface.line_height() as f32 * dot_scale(typeface).y
+}
+
+/// Position of the top of the caret placed at the given byte, relative to the start of the text, in pixels. The
+/// horizontal position is measured by summing glyph advances from the start of the line.
+fn caret_position<B: Backend>(typeface: &mut dyn Typeface<B>, text: &Rope, byte: usize) -> Vector2 {
+    let scale = dot_scale(typeface);
+    let start = line_start(text, byte);
+    let x = typeface.measure_line(text.slice(start, byte - start)).x * scale.x;
+    let y = text.byte_to_line(byte) as f32 * line_height(typeface);
+    Vector2::new(x, y)
+}
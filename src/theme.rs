@@ -1,9 +1,11 @@
-use std::collections::HashMap;
-use crate::{backend::{Backend, Color, MouseCursor}, border::Border, node::{Node, NodeType}, tag_list::TagList, typeface::Typeface};
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc, time::Duration};
+use crate::{backend::{Backend, Color, MouseCursor}, border::Border, node::{Node, NodeType}, style::Style, tag_list::{TagID, TagList}, typeface::Typeface};
 
 /// Node theme.
+///
+/// Rules are shared between clones of a theme, so themes are cheap to pass down the tree.
 pub struct Theme<B: Backend> {
-    pub rules: HashMap<NodeType, Vec<Rule<B>>>,
+    pub rules: HashMap<NodeType, Vec<Rc<Rule<B>>>>,
 }
 
 impl<B: Backend> Theme<B> {
@@ -12,9 +14,178 @@ impl<B: Backend> Theme<B> {
             rules: HashMap::new(),
         }
     }
+
+    /// Create a theme extending this one with rules of another theme.
+    ///
+    /// Rules are merged per node type. Rules of the child theme are placed after rules of this theme, so they take
+    /// precedence if both match the same node.
+    ///
+    /// # Params
+    ///
+    /// - `child`: Theme to layer on top of this one.
+    ///
+    /// # Returns
+    ///
+    /// A theme containing rules of both themes.
+    pub fn derive(&self, child: &Theme<B>) -> Theme<B> {
+        let mut result = self.clone();
+
+        for (node_type, rules) in &child.rules {
+            result.rules.entry(*node_type)
+                .or_default()
+                .extend(rules.iter().cloned());
+        }
+
+        result
+    }
+
+    /// Find rules of the theme matching a node.
+    ///
+    /// # Params
+    ///
+    /// - `node_type`: Type of the node.
+    /// - `tags`:      Tags of the node.
+    ///
+    /// # Returns
+    ///
+    /// Matching rules, in the order they should be applied.
+    pub fn select<'a>(&'a self, node_type: NodeType, tags: &'a TagList) -> impl Iterator<Item = &'a Rc<Rule<B>>> {
+        self.rules.get(&node_type)
+            .into_iter()
+            .flatten()
+            .filter(move |rule| rule.selector.test(node_type, tags))
+    }
 }
 
-pub type StyleDelegate<B> = Box<dyn FnMut(&mut Node<B>) -> Rule<B>>;
+impl<B: Backend> Clone for Theme<B> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            rules: self.rules.clone(),
+        }
+    }
+}
+
+impl<B: Backend> Default for Theme<B> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Callback updating the style of a node dynamically. Delegates are called every frame, before the node is drawn,
+/// and are given the backend, for example to read [`Backend::delta_time`]. Fields of the returned rule are applied on
+/// top of the node's style.
+///
+/// # See Also
+///
+/// - [`Node::update_style`]
+pub type StyleDelegate<B> = Box<dyn Fn(&mut Node<B>, &B) -> Rule<B>>;
+
+/// Create a style delegate animating the node's style towards a target.
+///
+/// The target is checked every frame. The first transition starts from the style the node has without the delegate,
+/// and whenever the target changes, the animation starts over from the current, possibly intermediate, style. Every
+/// call advances the animation by [`Backend::delta_time`], and the target is reached once `duration` has passed. See
+/// [`Style::lerp`] for how each field changes.
+///
+/// The delegate holds the state of the animation, so it should only be attached to a single node, with
+/// [`NodeData::add_style_delegate`](crate::node::NodeData::add_style_delegate).
+///
+/// ```
+/// # use std::{cell::{Cell, RefCell}, rc::Rc, time::Duration};
+/// # use amity_ui::{backend::{Color, headless::HeadlessBackend}, node::{NodeVariant, vframe}, style::Style,
+/// #     theme::transition, tree::LayoutTree, utils::simple_constructor};
+/// let black = Style::default().with_background_color(Color::new(0, 0, 0, 255));
+/// let white = Style::default().with_background_color(Color::new(200, 200, 200, 255));
+/// let is_lit = Rc::new(Cell::new(true));
+///
+/// let node = Rc::new(RefCell::new(simple_constructor::<HeadlessBackend>(NodeVariant::Space, black.clone())));
+/// let target = is_lit.clone();
+/// node.borrow_mut().data.add_style_delegate(transition(
+///     move |_| if target.get() { white.clone() } else { black.clone() },
+///     Duration::from_millis(100),
+/// ));
+///
+/// let mut tree = LayoutTree::new(vframe((), [node.clone()]), HeadlessBackend::new());
+/// tree.backend.set_delta_time(Duration::from_millis(50));
+/// let mut frame = || {
+///     tree.draw();
+///     node.borrow().data.style().background_color().r
+/// };
+///
+/// assert_eq!([frame(), frame(), frame()], [100, 200, 200]);
+///
+/// // A new target starts a new transition
+/// is_lit.set(false);
+/// assert_eq!([frame(), frame(), frame()], [100, 0, 0]);
+/// ```
+///
+/// # Params
+///
+/// - `target`:   Function picking the style to end up with, given the node.
+/// - `duration`: Time the transition takes.
+pub fn transition<B: Backend + 'static>(
+    target: impl Fn(&Node<B>) -> Style<B> + 'static,
+    duration: Duration,
+) -> StyleDelegate<B> {
+    let state: RefCell<Option<Transition<B>>> = RefCell::new(None);
+
+    Box::new(move |node, backend| {
+        let target = target(node);
+        let mut state = state.borrow_mut();
+
+        let mut transition = match state.take() {
+            Some(transition) if transition.target == target => transition,
+            // The target changed; start over from where the previous transition stopped
+            Some(transition) => Transition::new(transition.current, target),
+            None => Transition::new(node.data.style().clone(), target),
+        };
+
+        transition.elapsed += backend.delta_time();
+        let t = if duration.is_zero() { 1.0 } else { transition.elapsed.as_secs_f32() / duration.as_secs_f32() };
+        transition.current = transition.start.lerp(&transition.target, t);
+        let fields = StyleTemplate::from_style(&transition.current);
+        *state = Some(transition);
+
+        Rule {
+            selector: Selector {
+                node_type: node.variant.node_type(),
+                tags: TagList::default(),
+                reject_all: false,
+            },
+            fields,
+            style_delegate: None,
+            breadcrumbs: Breadcrumbs::default(),
+        }
+    })
+}
+
+/// State of a [`transition`].
+struct Transition<B: Backend> {
+    /// Style the transition started from.
+    start: Style<B>,
+
+    /// Style the transition ends with.
+    target: Style<B>,
+
+    /// Style as of the last frame.
+    current: Style<B>,
+
+    /// Time since the transition started.
+    elapsed: Duration,
+}
+
+impl<B: Backend> Transition<B> {
+    fn new(start: Style<B>, target: Style<B>) -> Self {
+        Self {
+            current: start.clone(),
+            start,
+            target,
+            elapsed: Duration::ZERO,
+        }
+    }
+}
 
 /// Rules specify changes that are to be made to the node's style.
 pub struct Rule<B: Backend> {
@@ -24,13 +195,26 @@ pub struct Rule<B: Backend> {
     /// Fields affected by this rule and their values.
     pub fields: StyleTemplate<B>,
 
-    /// Callback for updating the style dynamically. May be null.
+    /// Callback for updating the style dynamically, called for every node matching the rule. May be null.
     pub style_delegate: Option<StyleDelegate<B>>,
 
     /// Breadcrumbs, if any, assigned to nodes matching this rule.
     pub breadcrumbs: Breadcrumbs<B>,
 }
 
+impl<B: Backend> Rule<B> {
+    /// Apply the rule to a style. Breadcrumbs of the rule are added to the style, so they apply to the children of
+    /// the node.
+    ///
+    /// # Params
+    ///
+    /// - `style`: Style to change.
+    pub fn apply(&self, style: &mut Style<B>) {
+        self.fields.apply(style);
+        style.breadcrumbs.crumbs.extend(self.breadcrumbs.crumbs.iter().cloned());
+    }
+}
+
 /// Selector is used to pick a node based on its type and specified tags.
 pub struct Selector {
     /// Type of the node to match.
@@ -43,40 +227,271 @@ pub struct Selector {
     pub reject_all: bool,
 }
 
+impl Selector {
+    /// Check if the selector matches a node: the node is of the selected type, and has every tag of the selector.
+    ///
+    /// # Params
+    ///
+    /// - `node_type`: Type of the node.
+    /// - `tags`:      Tags of the node.
+    #[inline]
+    pub fn test(&self, node_type: NodeType, tags: &TagList) -> bool {
+        !self.reject_all && self.node_type == node_type && self.tags.is_subset(tags)
+    }
+}
+
+/// Rules applied to a branch of the tree, used to implement tree-aware selectors, such as `children`.
+///
+/// Breadcrumbs form a stack: a node introducing its own rules pushes them for the duration of resizing its children.
+/// A rule introduces rules for children of the nodes it matches through [`Rule::breadcrumbs`]:
+///
+/// ```
+/// # use std::{cell::RefCell, rc::Rc};
+/// # use amity_ui::{backend::{Color, headless::HeadlessBackend}, node::{Node, NodeType, label, vframe}, tags,
+/// #     tag_list::TagList, theme::{Breadcrumbs, Rule, Selector, StyleTemplate, Theme}, tree::LayoutTree};
+/// let blue = Color::new(0, 0, 255, 255);
+/// let label_rule = Rule {
+///     selector: Selector { node_type: NodeType::Label, tags: TagList::new(), reject_all: false },
+///     fields: StyleTemplate { text_color: Some(blue), ..Default::default() },
+///     style_delegate: None,
+///     breadcrumbs: Breadcrumbs::default(),
+/// };
+///
+/// // Labels inside of a sidebar are blue
+/// let mut theme = Theme::new();
+/// theme.rules.insert(NodeType::Frame, vec![Rc::new(Rule {
+///     selector: Selector { node_type: NodeType::Frame, tags: tags!(sidebar), reject_all: false },
+///     fields: StyleTemplate::default(),
+///     style_delegate: None,
+///     breadcrumbs: Breadcrumbs::from_rules([label_rule]),
+/// })]);
+///
+/// let inside: Rc<RefCell<Node<HeadlessBackend>>> = Rc::new(RefCell::new(label((), "Inside")));
+/// let outside = Rc::new(RefCell::new(label((), "Outside")));
+/// let sidebar = vframe(tags!(sidebar), [inside.clone()]);
+/// let root = vframe(theme, [sidebar, outside.clone()]);
+///
+/// let mut tree = LayoutTree::new(root, HeadlessBackend::new());
+/// tree.draw();
+/// assert_eq!(inside.borrow().data.style().text_color(), blue);
+/// assert_ne!(outside.borrow().data.style().text_color(), blue);
+/// ```
 pub struct Breadcrumbs<B: Backend> {
     /// All rules activated by this instance.
-    crumbs: Vec<Vec<Rule<B>>>,
+    crumbs: Vec<Rc<[Rc<Rule<B>>]>>,
 
     /// Cached children instances.
     children: Vec<Self>,
 }
 
+impl<B: Backend> Breadcrumbs<B> {
+    /// Create breadcrumbs activating a set of rules.
+    ///
+    /// # Params
+    ///
+    /// - `rules`: Rules to activate.
+    pub fn from_rules(rules: impl IntoIterator<Item = Rule<B>>) -> Self {
+        let rules: Rc<[Rc<Rule<B>>]> = rules.into_iter().map(Rc::new).collect();
+
+        Self {
+            crumbs: vec![rules],
+            children: Vec::new(),
+        }
+    }
+
+    /// True if no rules are active.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.crumbs.is_empty()
+    }
+
+    /// Activate a set of rules. The rules stay active until the returned guard is dropped.
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use amity_ui::{backend::{Color, Vector2, headless::HeadlessBackend}, node::{NodeType, label},
+    /// #     tag_list::TagList, theme::{Breadcrumbs, Rule, Selector, StyleTemplate}};
+    /// let blue = Color::new(0, 0, 255, 255);
+    /// let rules: Rc<[Rc<Rule<HeadlessBackend>>]> = Rc::new([Rc::new(Rule {
+    ///     selector: Selector { node_type: NodeType::Label, tags: TagList::new(), reject_all: false },
+    ///     fields: StyleTemplate { text_color: Some(blue), ..Default::default() },
+    ///     style_delegate: None,
+    ///     breadcrumbs: Breadcrumbs::default(),
+    /// })]);
+    ///
+    /// let mut breadcrumbs = Breadcrumbs::default();
+    /// let mut child = label((), "Child");
+    /// let (window, dpi) = (Vector2::new(100.0, 100.0), Vector2::new(96.0, 96.0));
+    /// {
+    ///     let mut guard = breadcrumbs.push(rules);
+    ///     assert_eq!(guard.load().count(), 1);
+    ///
+    ///     child.resize(window, dpi, &mut guard);
+    ///     assert_eq!(child.data.style().text_color(), blue);
+    /// }
+    ///
+    /// // Once the guard is dropped, the rules no longer apply
+    /// assert!(breadcrumbs.is_empty());
+    /// child.resize(window, dpi, &mut breadcrumbs);
+    /// assert_ne!(child.data.style().text_color(), blue);
+    /// ```
+    ///
+    /// # Params
+    ///
+    /// - `rules`: Rules to activate.
+    ///
+    /// # Returns
+    ///
+    /// A guard giving access to the breadcrumbs while the rules are active.
+    pub fn push(&mut self, rules: Rc<[Rc<Rule<B>>]>) -> BreadcrumbsGuard<'_, B> {
+        let previous_len = self.crumbs.len();
+        self.crumbs.push(rules);
+
+        BreadcrumbsGuard {
+            breadcrumbs: self,
+            previous_len,
+        }
+    }
+
+    /// Activate every rule active in other breadcrumbs. The rules stay active until the returned guard is dropped.
+    ///
+    /// # Params
+    ///
+    /// - `other`: Breadcrumbs to take rules from.
+    ///
+    /// # Returns
+    ///
+    /// A guard giving access to the breadcrumbs while the rules are active.
+    pub fn push_all(&mut self, other: &Breadcrumbs<B>) -> BreadcrumbsGuard<'_, B> {
+        let previous_len = self.crumbs.len();
+        self.crumbs.extend(other.crumbs.iter().cloned());
+
+        BreadcrumbsGuard {
+            breadcrumbs: self,
+            previous_len,
+        }
+    }
+
+    /// Get all currently active rules, in the order they were activated.
+    pub fn load(&self) -> impl Iterator<Item = &Rc<Rule<B>>> {
+        self.crumbs.iter().flat_map(|rules| rules.iter())
+    }
+
+    /// Get breadcrumbs extending these with another set of rules. Instances are cached, so descending into a child
+    /// with the same rules again will reuse the previously created breadcrumbs.
+    ///
+    /// # Params
+    ///
+    /// - `rules`: Rules activated by the child. Rules are compared by address, not by content.
+    ///
+    /// # Returns
+    ///
+    /// Breadcrumbs of the child. If `rules` is empty, these breadcrumbs are returned unchanged.
+    pub fn child(&mut self, rules: &Rc<[Rc<Rule<B>>]>) -> &mut Self {
+        if rules.is_empty() { return self; }
+
+        // The cache may hold children created before rules were pushed onto or popped off these breadcrumbs, so the
+        // whole chain has to match
+        let cached = self.children.iter().position(|child| {
+            child.crumbs.len() == self.crumbs.len() + 1
+                && child.crumbs.iter()
+                    .zip(self.crumbs.iter().chain([rules]))
+                    .all(|(a, b)| Rc::ptr_eq(a, b))
+        });
+
+        let index = cached.unwrap_or_else(|| {
+            let mut crumbs = self.crumbs.clone();
+            crumbs.push(rules.clone());
+            self.children.push(Self { crumbs, children: Vec::new() });
+            self.children.len() - 1
+        });
+
+        &mut self.children[index]
+    }
+}
+
+impl<B: Backend> Clone for Breadcrumbs<B> {
+    /// Clone the active rules. The child cache is not cloned.
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            crumbs: self.crumbs.clone(),
+            children: Vec::new(),
+        }
+    }
+}
+
+impl<B: Backend> Default for Breadcrumbs<B> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            crumbs: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+}
+
+/// Keeps rules pushed with [`Breadcrumbs::push`] active. The rules are removed when the guard is dropped.
+pub struct BreadcrumbsGuard<'a, B: Backend> {
+    breadcrumbs: &'a mut Breadcrumbs<B>,
+
+    /// Number of crumbs before the push.
+    previous_len: usize,
+}
+
+impl<B: Backend> std::ops::Deref for BreadcrumbsGuard<'_, B> {
+    type Target = Breadcrumbs<B>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.breadcrumbs
+    }
+}
+
+impl<B: Backend> std::ops::DerefMut for BreadcrumbsGuard<'_, B> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.breadcrumbs
+    }
+}
+
+impl<B: Backend> Drop for BreadcrumbsGuard<'_, B> {
+    #[inline]
+    fn drop(&mut self) {
+        self.breadcrumbs.crumbs.truncate(self.previous_len);
+    }
+}
+
+/// Set of changes to make to a [`Style`]. Fields left as [`None`] keep the value already in the style.
 pub struct StyleTemplate<B: Backend> {
     // Text options
 
     /// Main typeface to be used for text.
     ///
     /// Changing the typeface requires a resize.
-    typeface: Box<dyn Typeface<B>>,
+    pub typeface: Option<Rc<RefCell<dyn Typeface<B>>>>,
 
     /// Size of the font in use, in pixels.
     ///
     /// Changing the size requires a resize.
-    font_size: f32,
+    pub font_size: Option<f32>,
 
     /// Text color.
-    text_color: Color,
+    pub text_color: Option<Color>,
 
     // Background & content
 
     /// Color of lines belonging to the node, especially important to separators and sliders.
-    line_color: Color,
+    pub line_color: Option<Color>,
 
     /// Background color of the node.
-    background_color: Color,
+    pub background_color: Option<Color>,
 
     /// Background color for selected text.
-    selection_background_color: Color,
+    pub selection_background_color: Option<Color>,
+
+    /// Radius of rounded corners, in pixels. Used by scrollbar tracks and handles.
+    pub corner_radius: Option<f32>,
 
     // Spacing
 
@@ -85,39 +500,473 @@ pub struct StyleTemplate<B: Backend> {
     /// Updating margins requires a resize.
     ///
     /// See: `is_side_array`.
-    margin: [f32; 4],
+    pub margin: Option<[f32; 4]>,
 
     /// Border size, placed between margin and padding. `[left, right, top, bottom]`.
     ///
     /// Updating border requires a resize.
     ///
     /// See: `is_side_array`
-    border: [f32; 4],
+    pub border: Option<[f32; 4]>,
 
     /// Padding (inner margin) of the node. `[left, right, top, bottom]`.
     ///
     /// Updating padding requires a resize.
     ///
     /// See: `is_side_array`
-    padding: [f32; 4],
+    pub padding: Option<[f32; 4]>,
 
     /// Margin/gap between two neighboring elements; for container nodes that support it.
     ///
     /// Updating the gap requires a resize.
-    gap: [f32; 2],
+    pub gap: Option<[f32; 2]>,
+
+    /// Width of scrollbars, measured across the axis they scroll along.
+    ///
+    /// Updating the width requires a resize.
+    pub scrollbar_width: Option<f32>,
 
     /// Border style to use.
     ///
     /// Updating border requires a resize.
-    border_style: Box<dyn Border<B>>,
+    pub border_style: Option<Rc<dyn Border<B>>>,
 
     // Misc
 
     /// Apply tint to all node contents, including children.
-    tint: Color,
+    pub tint: Option<Color>,
 
     /// Cursor icon to use while this node is hovered.
     ///
-    /// Custom image cursors are not supported yet.
-    mouse_cursor: MouseCursor,
+    /// May be an image cursor created with [`MouseCursor::image`].
+    pub mouse_cursor: Option<MouseCursor>,
+}
+
+impl<B: Backend> StyleTemplate<B> {
+    /// Create a template setting every field to its value in the given style.
+    pub fn from_style(style: &Style<B>) -> Self {
+        Self {
+            typeface: Some(style.typeface.clone()),
+            font_size: Some(style.font_size),
+            text_color: Some(style.text_color),
+            line_color: Some(style.line_color),
+            background_color: Some(style.background_color),
+            selection_background_color: Some(style.selection_background_color),
+            corner_radius: Some(style.corner_radius),
+            margin: Some(style.margin),
+            border: Some(style.border),
+            padding: Some(style.padding),
+            gap: Some(style.gap),
+            scrollbar_width: Some(style.scrollbar_width),
+            border_style: Some(style.border_style.clone()),
+            tint: Some(style.tint),
+            mouse_cursor: Some(style.mouse_cursor.clone()),
+        }
+    }
+
+    /// Apply the changes to a style. Fields that are not set are left unchanged, so templates applied one after
+    /// another cascade: each field ends up with the value of the last template that sets it.
+    ///
+    /// ```
+    /// # use amity_ui::{backend::{Color, headless::HeadlessBackend}, style::Style, theme::StyleTemplate};
+    /// let red = Color::new(255, 0, 0, 255);
+    /// let blue = Color::new(0, 0, 255, 255);
+    /// let base = StyleTemplate { background_color: Some(red), padding: Some([4.0; 4]), ..Default::default() };
+    /// let overrides = StyleTemplate { background_color: Some(blue), margin: Some([2.0; 4]), ..Default::default() };
+    ///
+    /// let mut style = Style::<HeadlessBackend>::default().with_gap([8.0; 2]);
+    /// base.apply(&mut style);
+    /// overrides.apply(&mut style);
+    ///
+    /// assert_eq!(style.background_color(), blue);
+    /// assert_eq!(style.padding(), [4.0; 4]);
+    /// assert_eq!(style.margin(), [2.0; 4]);
+    /// assert_eq!(style.gap(), [8.0; 2]);
+    /// ```
+    ///
+    /// # Params
+    ///
+    /// - `style`: Style to change.
+    pub fn apply(&self, style: &mut Style<B>) {
+        if let Some(value) = &self.typeface { style.typeface = value.clone(); }
+        if let Some(value) = &self.font_size { style.font_size = *value; }
+        if let Some(value) = &self.text_color { style.text_color = *value; }
+        if let Some(value) = &self.line_color { style.line_color = *value; }
+        if let Some(value) = &self.background_color { style.background_color = *value; }
+        if let Some(value) = &self.selection_background_color { style.selection_background_color = *value; }
+        if let Some(value) = &self.corner_radius { style.corner_radius = *value; }
+        if let Some(value) = &self.margin { style.margin = *value; }
+        if let Some(value) = &self.border { style.border = *value; }
+        if let Some(value) = &self.padding { style.padding = *value; }
+        if let Some(value) = &self.gap { style.gap = *value; }
+        if let Some(value) = &self.scrollbar_width { style.scrollbar_width = *value; }
+        if let Some(value) = &self.border_style { style.border_style = value.clone(); }
+        if let Some(value) = &self.tint { style.tint = *value; }
+        if let Some(value) = &self.mouse_cursor { style.mouse_cursor = value.clone(); }
+    }
+}
+
+impl<B: Backend> Default for StyleTemplate<B> {
+    /// Template that doesn't change any fields.
+    #[inline]
+    fn default() -> Self {
+        Self {
+            typeface: None,
+            font_size: None,
+            text_color: None,
+            line_color: None,
+            background_color: None,
+            selection_background_color: None,
+            corner_radius: None,
+            margin: None,
+            border: None,
+            padding: None,
+            gap: None,
+            scrollbar_width: None,
+            border_style: None,
+            tint: None,
+            mouse_cursor: None,
+        }
+    }
+}
+
+/// Load a theme from a stylesheet.
+///
+/// A stylesheet is a list of rules. Each rule starts with a selector: the name of a node type, optionally followed by
+/// tags the node must have, each prefixed with a dot. Properties set by the rule follow in braces, separated by
+/// semicolons. Comments are written between `/*` and `*/`.
+///
+/// ```text
+/// button.primary {
+///     background-color: #2277cc;
+///     padding: 8;            /* all sides */
+///     margin: 0 0 4 4;       /* left, right, top, bottom */
+/// }
+/// ```
+///
+/// Supported properties are `font-size`, `text-color`, `line-color`, `background-color`,
+/// `selection-background-color`, `corner-radius`, `margin`, `border`, `padding`, `gap`, `scrollbar-width`, `tint` and
+/// `mouse-cursor`. Colors are given in hex, as accepted by [`Color::from_hex`]. Side properties take one value for all
+/// sides, or four values; `gap` takes one value for both axes, or two. Mouse cursors are named in kebab case, for
+/// example `pointer` or `not-allowed`.
+///
+/// ```
+/// # use amity_ui::{backend::{Color, raylib::RaylibBackend}, node::NodeType, tag_list::TagID, theme::parse_stylesheet};
+/// let theme = parse_stylesheet::<RaylibBackend>("button.primary { background-color: #2277cc; padding: 8; }")?;
+/// let rule = &theme.rules[&NodeType::Button][0];
+///
+/// assert!(rule.selector.tags.contains(TagID::named("primary")));
+/// assert!(rule.fields.background_color == Some(Color::rgb(0x22, 0x77, 0xcc)));
+/// assert!(rule.fields.padding == Some([8.0; 4]));
+/// assert!(rule.fields.margin.is_none());
+/// # Ok::<(), amity_ui::theme::ParseStylesheetError>(())
+/// ```
+///
+/// Rules apply to nodes of the theme in the order they are written:
+///
+/// ```
+/// # use std::{cell::RefCell, rc::Rc};
+/// # use amity_ui::{backend::{Color, MouseCursor, headless::HeadlessBackend}, node::{Node, NodeType, label, vframe},
+/// #     tags, tree::LayoutTree, theme::parse_stylesheet};
+/// let theme = parse_stylesheet::<HeadlessBackend>("
+///     /* Default look */
+///     label {
+///         text-color: #333333;
+///         margin: 1 2 3 4;
+///         gap: 2 6
+///     }
+///     label.link { text-color: #2277cc; mouse-cursor: pointer; }
+///     frame { padding: 8 }
+/// ")?;
+/// assert_eq!(theme.rules[&NodeType::Label].len(), 2);
+/// assert_eq!(theme.rules[&NodeType::Frame].len(), 1);
+///
+/// let link: Rc<RefCell<Node<HeadlessBackend>>> = Rc::new(RefCell::new(label(tags!(link), "Home")));
+/// let text = Rc::new(RefCell::new(label((), "Welcome")));
+/// let root = vframe(theme, [link.clone(), text.clone()]);
+/// let mut tree = LayoutTree::new(root.clone(), HeadlessBackend::new());
+/// tree.draw();
+///
+/// let link = link.borrow();
+/// assert_eq!(link.data.style().text_color(), Color::rgb(0x22, 0x77, 0xcc));
+/// assert_eq!(*link.data.style().mouse_cursor(), MouseCursor::Pointer);
+/// assert_eq!(link.data.style().margin(), [1.0, 2.0, 3.0, 4.0]);
+/// assert_eq!(link.data.style().gap(), [2.0, 6.0]);
+/// assert_eq!(text.borrow().data.style().text_color(), Color::rgb(0x33, 0x33, 0x33));
+/// assert_eq!(root.borrow().data.style().padding(), [8.0; 4]);
+/// # Ok::<(), amity_ui::theme::ParseStylesheetError>(())
+/// ```
+///
+/// # Params
+///
+/// - `src`: Source of the stylesheet.
+///
+/// # Returns
+///
+/// Theme with a rule for each rule in the stylesheet, or an error pointing at the first problem found.
+pub fn parse_stylesheet<B: Backend>(src: &str) -> Result<Theme<B>, ParseStylesheetError> {
+    let mut parser = StylesheetParser { src, position: 0 };
+    let mut theme = Theme::new();
+
+    while parser.skip_space()?.is_some() {
+        let rule = parser.rule()?;
+        theme.rules.entry(rule.selector.node_type)
+            .or_default()
+            .push(Rc::new(rule));
+    }
+
+    Ok(theme)
+}
+
+/// Error returned by [`parse_stylesheet`].
+///
+/// ```
+/// # use amity_ui::{backend::headless::HeadlessBackend, theme::{StylesheetErrorKind, parse_stylesheet}};
+/// let error = parse_stylesheet::<HeadlessBackend>("button {\n    padding: 4;\n    colour: #ffffff;\n}");
+/// let error = error.err().unwrap();
+///
+/// assert_eq!((error.line, error.column), (3, 5));
+/// assert_eq!(error.kind, StylesheetErrorKind::UnknownProperty("colour".to_owned()));
+/// assert_eq!(error.to_string(), "3:5: unknown property `colour`");
+///
+/// let error = parse_stylesheet::<HeadlessBackend>("button { padding: 1 2; }").err().unwrap();
+/// assert_eq!(error.kind, StylesheetErrorKind::InvalidValue("padding".to_owned()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseStylesheetError {
+    /// Line the error was found on, starting from 1.
+    pub line: usize,
+
+    /// Column the error was found at, in characters, starting from 1.
+    pub column: usize,
+
+    /// What went wrong.
+    pub kind: StylesheetErrorKind,
+}
+
+impl fmt::Display for ParseStylesheetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.kind)
+    }
+}
+
+impl std::error::Error for ParseStylesheetError {}
+
+/// Kind of a [`ParseStylesheetError`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StylesheetErrorKind {
+    /// The stylesheet ended in the middle of a rule or a comment.
+    UnexpectedEnd,
+    /// A different character was expected.
+    Expected(char),
+    /// A name was expected, for example of a node type, tag or property.
+    ExpectedName,
+    /// No node type has the given name.
+    UnknownNodeType(String),
+    /// No property has the given name.
+    UnknownProperty(String),
+    /// The value given to the property is invalid.
+    InvalidValue(String),
+}
+
+impl fmt::Display for StylesheetErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => write!(f, "unexpected end of stylesheet"),
+            Self::Expected(expected) => write!(f, "expected `{expected}`"),
+            Self::ExpectedName => write!(f, "expected a name"),
+            Self::UnknownNodeType(name) => write!(f, "unknown node type `{name}`"),
+            Self::UnknownProperty(name) => write!(f, "unknown property `{name}`"),
+            Self::InvalidValue(name) => write!(f, "invalid value for property `{name}`"),
+        }
+    }
+}
+
+/// State of [`parse_stylesheet`].
+struct StylesheetParser<'a> {
+    src: &'a str,
+
+    /// Byte offset of the next character to read.
+    position: usize,
+}
+
+impl<'a> StylesheetParser<'a> {
+    /// Create an error located at the given byte offset.
+    fn error_at(&self, position: usize, kind: StylesheetErrorKind) -> ParseStylesheetError {
+        let before = &self.src[..position];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+        ParseStylesheetError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            kind,
+        }
+    }
+
+    /// Text left to parse.
+    #[inline]
+    fn rest(&self) -> &'a str {
+        &self.src[self.position..]
+    }
+
+    /// Skip whitespace and comments.
+    ///
+    /// # Returns
+    ///
+    /// The next character, or `None` if the stylesheet has ended.
+    fn skip_space(&mut self) -> Result<Option<char>, ParseStylesheetError> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.position += rest.len() - trimmed.len();
+
+            if !trimmed.starts_with("/*") { return Ok(trimmed.chars().next()); }
+
+            match trimmed[2..].find("*/") {
+                Some(end) => self.position += end + 4,
+                None => return Err(self.error_at(self.position, StylesheetErrorKind::UnexpectedEnd)),
+            }
+        }
+    }
+
+    /// Skip whitespace and consume the given character.
+    fn expect(&mut self, expected: char) -> Result<(), ParseStylesheetError> {
+        match self.skip_space()? {
+            Some(next) if next == expected => {
+                self.position += next.len_utf8();
+                Ok(())
+            }
+            Some(_) => Err(self.error_at(self.position, StylesheetErrorKind::Expected(expected))),
+            None => Err(self.error_at(self.position, StylesheetErrorKind::UnexpectedEnd)),
+        }
+    }
+
+    /// Read a name made of letters, digits, dashes and underscores.
+    ///
+    /// # Returns
+    ///
+    /// Byte offset of the name, and the name.
+    fn name(&mut self) -> Result<(usize, &'a str), ParseStylesheetError> {
+        if self.skip_space()?.is_none() {
+            return Err(self.error_at(self.position, StylesheetErrorKind::UnexpectedEnd));
+        }
+
+        let start = self.position;
+        let length = self.rest()
+            .find(|c: char| !c.is_alphanumeric() && c != '-' && c != '_')
+            .unwrap_or(self.rest().len());
+        if length == 0 {
+            return Err(self.error_at(start, StylesheetErrorKind::ExpectedName));
+        }
+
+        self.position += length;
+        Ok((start, &self.src[start..self.position]))
+    }
+
+    /// Read a rule: a selector followed by properties in braces.
+    fn rule<B: Backend>(&mut self) -> Result<Rule<B>, ParseStylesheetError> {
+        let (start, name) = self.name()?;
+        let node_type = NodeType::from_name(name)
+            .ok_or_else(|| self.error_at(start, StylesheetErrorKind::UnknownNodeType(name.to_owned())))?;
+
+        let mut tags = TagList::default();
+        while self.rest().starts_with('.') {
+            self.position += 1;
+            tags.insert(TagID::named(self.name()?.1));
+        }
+
+        self.expect('{')?;
+
+        let mut fields = StyleTemplate::default();
+        loop {
+            match self.skip_space()? {
+                Some('}') => {
+                    self.position += 1;
+                    break;
+                }
+                Some(_) => self.property(&mut fields)?,
+                None => return Err(self.error_at(self.position, StylesheetErrorKind::UnexpectedEnd)),
+            }
+        }
+
+        Ok(Rule {
+            selector: Selector { node_type, tags, reject_all: false },
+            fields,
+            style_delegate: None,
+            breadcrumbs: Breadcrumbs::default(),
+        })
+    }
+
+    /// Read a property, and set it in the template. The semicolon after the last property of a rule may be omitted.
+    fn property<B: Backend>(&mut self, fields: &mut StyleTemplate<B>) -> Result<(), ParseStylesheetError> {
+        let (start, name) = self.name()?;
+        self.expect(':')?;
+        self.skip_space()?;
+
+        let value_start = self.position;
+        let length = self.rest().find([';', '}']).unwrap_or(self.rest().len());
+        let value = self.src[value_start..value_start + length].trim();
+        self.position += length;
+
+        if self.rest().starts_with(';') {
+            self.position += 1;
+        }
+
+        let invalid = || self.error_at(value_start, StylesheetErrorKind::InvalidValue(name.to_owned()));
+        let color = || Color::from_hex(value).map_err(|_| invalid());
+        let numbers = |counts: &[usize]| -> Result<Vec<f32>, ParseStylesheetError> {
+            let numbers = value.split_whitespace()
+                .map(str::parse)
+                .collect::<Result<Vec<f32>, _>>()
+                .map_err(|_| invalid())?;
+            if !counts.contains(&numbers.len()) { return Err(invalid()); }
+            Ok(numbers)
+        };
+        let sides = || numbers(&[1, 4]).map(|numbers| match *numbers {
+            [all] => [all; 4],
+            [left, right, top, bottom] => [left, right, top, bottom],
+            _ => unreachable!(),
+        });
+
+        match name {
+            "font-size" => fields.font_size = Some(numbers(&[1])?[0]),
+            "text-color" => fields.text_color = Some(color()?),
+            "line-color" => fields.line_color = Some(color()?),
+            "background-color" => fields.background_color = Some(color()?),
+            "selection-background-color" => fields.selection_background_color = Some(color()?),
+            "corner-radius" => fields.corner_radius = Some(numbers(&[1])?[0]),
+            "margin" => fields.margin = Some(sides()?),
+            "border" => fields.border = Some(sides()?),
+            "padding" => fields.padding = Some(sides()?),
+            "gap" => fields.gap = Some(match *numbers(&[1, 2])? {
+                [both] => [both; 2],
+                [x, y] => [x, y],
+                _ => unreachable!(),
+            }),
+            "scrollbar-width" => fields.scrollbar_width = Some(numbers(&[1])?[0]),
+            "tint" => fields.tint = Some(color()?),
+            "mouse-cursor" => fields.mouse_cursor = Some(mouse_cursor_from_name(value).ok_or_else(invalid)?),
+            _ => return Err(self.error_at(start, StylesheetErrorKind::UnknownProperty(name.to_owned()))),
+        }
+
+        Ok(())
+    }
+}
+
+/// Find a system cursor by its kebab case name, as used in stylesheets.
+fn mouse_cursor_from_name(name: &str) -> Option<MouseCursor> {
+    Some(match name {
+        "system-default" => MouseCursor::SystemDefault,
+        "none"           => MouseCursor::None,
+        "pointer"        => MouseCursor::Pointer,
+        "crosshair"      => MouseCursor::Crosshair,
+        "text"           => MouseCursor::Text,
+        "all-scroll"     => MouseCursor::AllScroll,
+        "resize-ew"      => MouseCursor::ResizeEW,
+        "resize-ns"      => MouseCursor::ResizeNS,
+        "resize-nesw"    => MouseCursor::ResizeNESW,
+        "resize-nwse"    => MouseCursor::ResizeNWSE,
+        "not-allowed"    => MouseCursor::NotAllowed,
+        _ => return None,
+    })
 }
@@ -1,31 +1,51 @@
 use std::{cell::RefCell, collections::LinkedList, rc::{Rc, Weak}};
-use crate::{backend::{Backend, Rectangle}, context::TreeContextData, focus::Focusable, input::{InputBinding, InputLayer}, node::Node, scroll::Scrollable, style::SideArray, theme::Breadcrumbs};
+use crate::{backend::{Backend, MouseCursor, Rectangle, Vector2}, context::{TreeActionContext, TreeContextData}, event::SimpleEvent, action::{Actionable, bubble_action}, focus::{FocusIO, FocusSpace, Focusable}, hover, input::{FluidInputAction, GamepadNavigation, InputActionID, InputBinding, InputLayer, InputStrokeItem}, node::{Node, NodeData, NodeVariant}, resource::UnloadQueue, style::{Side, SideArray}, theme::Breadcrumbs};
 
+/// Candidate for directional focus navigation.
 pub struct WithPriority<B: Backend> {
     /// Pick priority based on tree distance from the focused node.
-    priority: i32,
+    pub priority: i32,
 
     /// Square of the distance between this node and the focused node.
-    distance2: f32,
+    pub distance2: f32,
 
     /// The node.
-    node: Box<dyn Focusable<B>>,
+    pub node: Weak<RefCell<Node<B>>>,
+}
+
+/// Position of a node in tab order. Nodes with a [`NodeData::tab_index`] come first, sorted by the index, followed by
+/// nodes without one. Ties are resolved by tree order.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+struct TabKey {
+    is_unindexed: bool,
+    index: i32,
+    order: u32,
 }
 
+/// Collects nodes for keyboard and gamepad focus navigation. Updated with every focusable node as the tree is drawn.
 pub struct FocusDirection<B: Backend> {
     /// Available space box of the focused item after last frame.
     pub last_focus_box: Rectangle,
 
-    /// Nodes that may get focus with tab navigation.
-    pub prev: Box<dyn Focusable<B>>,
-    pub next: Box<dyn Focusable<B>>,
+    /// Nodes that may get focus with tab navigation. Available after [`Self::finish`].
+    pub prev: Option<Weak<RefCell<Node<B>>>>,
+    pub next: Option<Weak<RefCell<Node<B>>>>,
+
+    /// First and last focusable nodes in tab order. Available after [`Self::finish`].
+    pub first: Option<Weak<RefCell<Node<B>>>>,
+    pub last: Option<Weak<RefCell<Node<B>>>>,
 
-    /// First and last focusable nodes in the tree.
-    pub first: Box<dyn Focusable<B>>,
-    pub last: Box<dyn Focusable<B>>,
+    /// Nodes reachable with tab navigation, in tree order.
+    tab_order: Vec<(TabKey, Weak<RefCell<Node<B>>>)>,
+
+    /// Position of the focused node in tab order, if it has been visited.
+    focus_key: Option<TabKey>,
+
+    /// Number of nodes visited so far.
+    visited: u32,
 
     /// Focusable nodes, by direction from the focused node.
-    pub positional: SideArray<WithPriority<B>>,
+    pub positional: SideArray<Option<WithPriority<B>>>,
 
     /// Focus priority for the currently drawn node.
     ///
@@ -38,14 +58,226 @@ pub struct FocusDirection<B: Backend> {
 
     /// Current tree depth.
     depth: u32,
+
+    /// Focus trap containing the focused node, if any. While set, only nodes inside the trap are collected, so
+    /// navigation can't leave it.
+    ///
+    /// # See Also
+    ///
+    /// - [`NodeData::is_focus_trap`]
+    pub trap: Option<Weak<RefCell<Node<B>>>>,
+
+    /// True while nodes inside [`Self::trap`] are being visited.
+    is_in_trap: bool,
+}
+
+impl<B: Backend> FocusDirection<B> {
+    /// Create empty focus direction data.
+    pub fn new() -> Self {
+        Self {
+            last_focus_box: Rectangle::default(),
+            prev: None,
+            next: None,
+            first: None,
+            last: None,
+            tab_order: Vec::new(),
+            focus_key: None,
+            visited: 0,
+            positional: SideArray::default(),
+            priority: 0,
+            priority_direction: 1,
+            depth: 0,
+            trap: None,
+            is_in_trap: false,
+        }
+    }
+
+    /// Clear collected nodes before a new pass over the tree.
+    ///
+    /// # Params
+    ///
+    /// - `focus_box`: Box of the focused node, as of the last frame. Directional navigation is relative to it.
+    /// - `trap`:      Focus trap containing the focused node, if any.
+    pub fn reset(&mut self, focus_box: Rectangle, trap: Option<Weak<RefCell<Node<B>>>>) {
+        *self = Self {
+            last_focus_box: focus_box,
+            trap,
+            ..Self::new()
+        };
+    }
+
+    /// Mark the start or end of the visited branch if the node is the active focus trap.
+    ///
+    /// # Returns
+    ///
+    /// True if the node is the active trap.
+    fn enter_trap(&mut self, node: &Rc<RefCell<Node<B>>>, value: bool) -> bool {
+        let is_trap = self.trap.as_ref().is_some_and(|trap| trap.as_ptr() == Rc::as_ptr(node));
+        if is_trap {
+            self.is_in_trap = value;
+        }
+        is_trap
+    }
+
+    /// Node to focus with [`FluidInputAction::FocusNext`]. Wraps around to the first node.
+    #[inline]
+    pub fn next_node(&self) -> Option<Rc<RefCell<Node<B>>>> {
+        self.next.as_ref().or(self.first.as_ref())?.upgrade()
+    }
+
+    /// Node to focus with [`FluidInputAction::FocusPrevious`]. Wraps around to the last node.
+    #[inline]
+    pub fn previous_node(&self) -> Option<Rc<RefCell<Node<B>>>> {
+        self.prev.as_ref().or(self.last.as_ref())?.upgrade()
+    }
+
+    /// Check if the focused node has been visited during this pass.
+    #[inline]
+    pub fn found_focus(&self) -> bool {
+        self.priority_direction < 0
+    }
+
+    /// Visit a focusable node. Nodes must be visited in tree order.
+    ///
+    /// # Params
+    ///
+    /// - `node`:       The visited node.
+    /// - `is_focused`: True if the node is currently focused.
+    /// - `node_box`:   Box of the node, in window coordinates.
+    /// - `depth`:      Depth of the node in the tree.
+    /// - `tab_index`:  [Tab index](NodeData::tab_index) of the node.
+    pub fn update(
+        &mut self,
+        node: Weak<RefCell<Node<B>>>,
+        is_focused: bool,
+        node_box: Rectangle,
+        depth: u32,
+        tab_index: Option<i32>,
+    ) {
+        if self.trap.is_some() && !self.is_in_trap { return; }
+
+        // Priority changes with every step up or down the tree
+        self.priority += self.priority_direction * depth.abs_diff(self.depth) as i32;
+        self.depth = depth;
+
+        // Nodes with a negative index are left out of tab navigation, but can still be navigated away from
+        let key = TabKey {
+            is_unindexed: tab_index.is_none_or(i32::is_negative),
+            index: tab_index.unwrap_or_default().max(0),
+            order: self.visited,
+        };
+        self.visited += 1;
+        if tab_index.is_none_or(|index| index >= 0) {
+            self.tab_order.push((key, node.clone()));
+        }
+
+        if is_focused {
+            self.focus_key = Some(key);
+            self.priority_direction = -1;
+            return;
+        }
+
+        self.update_positional(node, node_box);
+    }
+
+    /// Find [`Self::prev`], [`Self::next`], [`Self::first`] and [`Self::last`] once every node has been visited.
+    pub fn finish(&mut self) {
+        type Entry<B> = (TabKey, Weak<RefCell<Node<B>>>);
+        let node = |entry: Option<&Entry<B>>| entry.map(|(_, node)| node.clone());
+        let order = &self.tab_order;
+
+        self.first = node(order.iter().min_by_key(|(key, _)| *key));
+        self.last = node(order.iter().max_by_key(|(key, _)| *key));
+
+        let Some(focus) = self.focus_key else { return };
+        self.prev = node(order.iter().filter(|(key, _)| *key < focus).max_by_key(|(key, _)| *key));
+        self.next = node(order.iter().filter(|(key, _)| *key > focus).min_by_key(|(key, _)| *key));
+    }
+
+    /// Consider the node as a candidate for each direction it lies in, relative to the focus box.
+    fn update_positional(&mut self, node: Weak<RefCell<Node<B>>>, node_box: Rectangle) {
+        let focus_box = self.last_focus_box;
+        let center = node_box.center();
+        let distance2 = (center - focus_box.center()).length_squared();
+
+        for side in [Side::Left, Side::Right, Side::Top, Side::Bottom] {
+            let in_direction = match side {
+                Side::Left   => center.x <= focus_box.x,
+                Side::Right  => center.x >= focus_box.right(),
+                Side::Top    => center.y <= focus_box.y,
+                Side::Bottom => center.y >= focus_box.bottom(),
+            };
+            if !in_direction { continue; }
+
+            // Prefer nodes close in the tree, then nodes close on screen
+            let is_better = match &self.positional[side] {
+                Some(best) => self.priority > best.priority
+                    || (self.priority == best.priority && distance2 < best.distance2),
+                None => true,
+            };
+
+            if is_better {
+                self.positional[side] = Some(WithPriority {
+                    priority: self.priority,
+                    distance2,
+                    node: node.clone(),
+                });
+            }
+        }
+    }
+
+    /// Get the best node to move focus to in the given direction.
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use amity_ui::{backend::{Rectangle, headless::HeadlessBackend}, node::{Node, button}, style::Side};
+    /// # use amity_ui::tree::FocusDirection;
+    /// let new_button = |text: &str| Rc::new(RefCell::new(button::<HeadlessBackend>((), text)));
+    /// let [far_left, near_left, focused, right, top] = ["Far left", "Near left", "Focused", "Right", "Top"]
+    ///     .map(new_button);
+    /// let boxes = [
+    ///     (&far_left,  Rectangle::new(  0.0, 100.0, 50.0, 50.0)),
+    ///     (&near_left, Rectangle::new( 50.0, 100.0, 40.0, 50.0)),
+    ///     (&focused,   Rectangle::new(100.0, 100.0, 50.0, 50.0)),
+    ///     (&right,     Rectangle::new(200.0, 100.0, 50.0, 50.0)),
+    ///     (&top,       Rectangle::new(100.0,   0.0, 50.0, 50.0)),
+    /// ];
+    ///
+    /// let mut direction = FocusDirection::new();
+    /// direction.reset(boxes[2].1, None);
+    /// for (node, node_box) in boxes {
+    ///     direction.update(Rc::downgrade(node), Rc::ptr_eq(node, &focused), node_box, 1, None);
+    /// }
+    /// direction.finish();
+    ///
+    /// let is = |found: Option<Rc<RefCell<Node<_>>>>, node| found.is_some_and(|found| Rc::ptr_eq(&found, node));
+    /// assert!(is(direction.in_direction(Side::Left), &near_left));
+    /// assert!(is(direction.in_direction(Side::Right), &right));
+    /// assert!(is(direction.in_direction(Side::Top), &top));
+    /// assert!(direction.in_direction(Side::Bottom).is_none());
+    ///
+    /// // Tab navigation follows tree order
+    /// assert!(is(direction.previous_node(), &near_left));
+    /// assert!(is(direction.next_node(), &right));
+    /// ```
+    pub fn in_direction(&self, side: Side) -> Option<Rc<RefCell<Node<B>>>> {
+        self.positional[side].as_ref()?.node.upgrade()
+    }
 }
 
-/// A class for iterating over the node tree.
-pub struct TreeAction<B: Backend> {
+impl<B: Backend> Default for FocusDirection<B> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// State shared by every [`TreeAction`].
+pub struct TreeActionData<B: Backend> {
     /// Node to descend into; `before_draw` and `after_draw` will only be emitted for this node and its children.
     ///
-    /// May be null to enable iteration over the entire tree.
-    pub start_node: Node<B>,
+    /// May be `None` to enable iteration over the entire tree.
+    pub start_node: Option<Weak<RefCell<Node<B>>>>,
 
     /// If true, this action is complete and no callbacks should be ran.
     ///
@@ -60,19 +292,179 @@ pub struct TreeAction<B: Backend> {
     /// This means that if an action is restarted, the old run will be unregistered, preventing the action from
     /// running twice at a time.
     ///
-    /// Only applies to actions started using `Node::start_action`, introduced in 0.7.2, and not `Node::run_action`.
+    /// Only applies to actions started using [`TreeActionContext::start_action`], and not
+    /// [`TreeActionContext::run_action`].
     pub generation: i32,
 
     /// Subscriber for events, i.e. `then`
-    finished: Box<dyn FnMut()>,
+    finished: SimpleEvent,
 
     /// Set to true once the action has descended into `start_node`.
-    in_start_node: bool,
+    pub(crate) in_start_node: bool,
 
     /// Set to true once `before_tree` is called. Set to `false` afterwards.
-    in_tree: bool,
+    pub(crate) in_tree: bool,
+}
+
+impl<B: Backend> TreeActionData<B> {
+    /// Create data for an action iterating over the entire tree.
+    pub fn new() -> Self {
+        Self {
+            start_node: None,
+            to_stop: false,
+            generation: 0,
+            finished: SimpleEvent::new(),
+            in_start_node: false,
+            in_tree: false,
+        }
+    }
+
+    /// Create data for an action that only visits the given node and its children.
+    pub fn with_start_node(node: &Rc<RefCell<Node<B>>>) -> Self {
+        Self {
+            start_node: Some(Rc::downgrade(node)),
+            ..Self::new()
+        }
+    }
+
+    /// Check if the given node is the start node.
+    pub(crate) fn is_start_node(&self, node: &Node<B>) -> bool {
+        self.start_node.as_ref()
+            .and_then(Weak::upgrade)
+            .is_some_and(|start_node| std::ptr::eq(start_node.as_ptr(), node))
+    }
+
+    /// True if draw callbacks should be emitted for the node that is currently drawn.
+    #[inline]
+    pub fn in_start_node(&self) -> bool {
+        self.in_start_node
+    }
+
+    /// True if the action is in the middle of a tree pass.
+    #[inline]
+    pub fn in_tree(&self) -> bool {
+        self.in_tree
+    }
+
+    /// Add a callback to run once the action finishes.
+    pub fn then(&mut self, callback: impl FnMut(&()) + 'static) {
+        self.finished.subscribe(callback);
+    }
+}
+
+impl<B: Backend> Default for TreeActionData<B> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An action iterating over the node tree. Actions are started with [`TreeActionContext::run_action`] and receive
+/// callbacks as the tree is drawn.
+///
+/// All callbacks do nothing by default. An action stops after its first full pass of the tree, or earlier if
+/// [`Self::stop`] is called.
+///
+/// ```
+/// # use std::{cell::RefCell, rc::Rc};
+/// # use amity_ui::{backend::headless::HeadlessBackend, node::{Node, NodeVariant, label, vframe}};
+/// # use amity_ui::tree::{LayoutTree, TreeAction, TreeActionData};
+/// type B = HeadlessBackend;
+/// type Log = Rc<RefCell<Vec<String>>>;
+///
+/// // Records every callback, and stops once it reaches the `stop_at` label
+/// struct Recorder { data: TreeActionData<B>, log: Log, stop_at: Option<&'static str> }
+///
+/// fn name(node: &Node<B>) -> String {
+///     match &node.variant {
+///         NodeVariant::Label(label) => label.text.value().into_owned(),
+///         variant => variant.node_type().name().to_string(),
+///     }
+/// }
+///
+/// impl TreeAction<B> for Recorder {
+///     fn action_data(&self) -> &TreeActionData<B> { &self.data }
+///     fn action_data_mut(&mut self) -> &mut TreeActionData<B> { &mut self.data }
+///     fn before_tree(&mut self, _root: &mut Node<B>) { self.log.borrow_mut().push("tree".into()); }
+///     fn before_draw(&mut self, node: &mut Node<B>) {
+///         self.log.borrow_mut().push(name(node));
+///         if self.stop_at.is_some_and(|stop_at| name(node) == stop_at) {
+///             self.stop();
+///         }
+///     }
+///     fn after_draw(&mut self, node: &mut Node<B>) { self.log.borrow_mut().push(format!("/{}", name(node))); }
+///     fn after_tree(&mut self) { self.log.borrow_mut().push("end".into()); }
+/// }
+///
+/// fn recorder(mut data: TreeActionData<B>, log: &Log, stop_at: Option<&'static str>) -> Rc<RefCell<Recorder>> {
+///     let finished = log.clone();
+///     data.then(move |_| finished.borrow_mut().push("finished".into()));
+///     Rc::new(RefCell::new(Recorder { data, log: log.clone(), stop_at }))
+/// }
+///
+/// let new_label = |text: &str| Rc::new(RefCell::new(label((), text)));
+/// let inner = vframe((), [new_label("b"), new_label("c")]);
+/// let root = vframe((), [new_label("a"), inner.clone()]);
+/// let mut tree = LayoutTree::new(root, HeadlessBackend::new());
+/// let log = Log::default();
+///
+/// // Draw callbacks are only emitted for the start node and its children
+/// tree.actions.push_back(recorder(TreeActionData::with_start_node(&inner), &log, None));
+/// tree.draw();
+/// assert_eq!(*log.borrow(), ["tree", "frame", "b", "/b", "c", "/c", "/frame", "end", "finished"]);
+///
+/// // The action only runs for a single pass
+/// log.borrow_mut().clear();
+/// tree.draw();
+/// assert!(log.borrow().is_empty());
+///
+/// // Stopping the action ends it early
+/// tree.actions.push_back(recorder(TreeActionData::new(), &log, Some("b")));
+/// tree.draw();
+/// assert_eq!(*log.borrow(), ["tree", "frame", "a", "/a", "frame", "b", "finished"]);
+/// ```
+pub trait TreeAction<B: Backend> {
+    /// State of the action.
+    fn action_data(&self) -> &TreeActionData<B>;
+
+    /// State of the action.
+    fn action_data_mut(&mut self) -> &mut TreeActionData<B>;
+
+    /// Called before the tree is drawn.
+    ///
+    /// # Params
+    ///
+    /// - `root`: Root node of the tree.
+    fn before_tree(&mut self, _root: &mut Node<B>) {}
+
+    /// Called before a node is drawn. Only called for the start node and its children, if the start node is set.
+    fn before_draw(&mut self, _node: &mut Node<B>) {}
+
+    /// Called after a node and its children are drawn. Only called for the start node and its children, if the start
+    /// node is set.
+    fn after_draw(&mut self, _node: &mut Node<B>) {}
+
+    /// Called after the whole tree has been drawn.
+    fn after_tree(&mut self) {}
+
+    /// Stop the action. No more callbacks will be called, and subscribers of the `finished` event will run.
+    ///
+    /// Does nothing if the action is already stopped.
+    fn stop(&mut self) {
+        let data = self.action_data_mut();
+        if data.to_stop { return; }
+
+        data.to_stop = true;
+        data.generation += 1;
+        data.in_start_node = false;
+        data.in_tree = false;
+        data.finished.emit();
+    }
 }
 
+/// Shared reference to a tree action.
+pub type TreeActionRef<B> = Rc<RefCell<dyn TreeAction<B>>>;
+
 /// Global data for the layout tree.
 pub struct LayoutTree<B: Backend> {
     // Nodes
@@ -87,31 +479,41 @@ pub struct LayoutTree<B: Backend> {
 
     /// Currently focused node.
     ///
-    /// Changing this value directly is discouraged. Some nodes might not want the focus! Be gentle, call
-    /// `Focusable.focus()` instead and let the node set the value on its own.
-    pub focus: Box<dyn Focusable<B>>,
+    /// Changing the focus directly is discouraged. Some nodes might not want the focus! Be gentle, call
+    /// [`Focusable::focus`] instead and let the node set the value on its own.
+    pub focus: FocusSpace<B>,
 
-    /// Deepest hovered scrollable node.
-    pub scroll: Scrollable<B>,
+    /// Mouse cursor last passed to the backend.
+    pub mouse_cursor: MouseCursor,
+
+    /// Deepest hovered [scrollable](crate::scroll::Scrollable) node, if any. It is scrolled with the mouse wheel.
+    pub scroll: Weak<RefCell<Node<B>>>,
 
     // Input
 
     /// Focus direction data.
     pub focus_direction: FocusDirection<B>,
 
-    /// Padding box of the currently focused node. Only available after the node has been drawn.
+    /// Padding box of the currently focused node, in window coordinates, as reported by [`NodeData::padding_box`].
+    /// Only available after the node has been drawn.
+    ///
+    /// # See Also
     ///
-    /// See_also: `focusDirection.lastFocusBox`.
+    /// - [`FocusDirection::last_focus_box`]
     pub focus_box: Rectangle,
 
     /// Tree actions queued to execute during next draw.
-    pub actions: LinkedList<TreeAction<B>>,
+    pub actions: LinkedList<TreeActionRef<B>>,
 
     /// Input strokes bound to emit given action signals.
     ///
     /// Input layers have to be sorted.
     pub bound_inputs: Vec<InputLayer<B>>,
 
+    /// Gamepad bindings and focus navigation, if gamepad input is enabled. Actions emitted by the gamepad are passed
+    /// to the focused node along with [`Self::active_actions`].
+    pub gamepad_navigation: Option<GamepadNavigation<B>>,
+
     /// Actions that are currently held down.
     pub down_actions: LinkedList<InputBinding<B>>,
 
@@ -121,8 +523,12 @@ pub struct LayoutTree<B: Backend> {
     /// Access to core input and output facilities.
     pub backend: B,
 
-    /// True if keyboard input was handled during the last frame; updated after tree rendering has completed.
-    pub was_keyboard_handled: bool,
+    /// Textures that are no longer used, unloaded when the next frame begins. Text of nodes is drawn into textures
+    /// that are queued here when replaced or dropped.
+    pub unload_queue: UnloadQueue<B>,
+
+    /// True if keyboard input was handled during the last frame. See [`Self::was_keyboard_handled`].
+    was_keyboard_handled: bool,
 
     /// Miscelleanous, technical properties.
 
@@ -135,7 +541,16 @@ pub struct LayoutTree<B: Backend> {
     /// True if the current tree branch is marked as disabled (doesn't take input).
     pub is_branch_disabled: bool,
 
-    /// Current breadcrumbs. These are assigned to any node that is resized or drawn at the time.
+    /// True while a subtree is drawn by a portal. Nodes are then drawn as they are, without updating their state.
+    is_mirroring: bool,
+
+    /// Subtrees drawn by portals during the current frame.
+    mirrored: Vec<Weak<RefCell<Node<B>>>>,
+
+    /// Subtrees drawn by portals during the last frame. Nodes in them block input.
+    last_mirrored: Vec<Weak<RefCell<Node<B>>>>,
+
+    /// Current breadcrumbs. These are assigned to any node that is resized at the time.
     ///
     /// Any node that introduces its own breadcrumbs will push onto this stack, and pop once finished.
     pub breadcrumbs: Breadcrumbs<B>,
@@ -143,6 +558,1085 @@ pub struct LayoutTree<B: Backend> {
     /// Context for the new I/O system.
     pub context: TreeContextData<B>,
 
+    /// Node that was focused during the last [`Self::scroll_to_focus`] call.
+    last_focus: Weak<RefCell<Node<B>>>,
+
+    /// DPI used during the last resize.
+    dpi: Vector2,
+
+    /// True if a style delegate changed the size of a node during the current draw. The tree is resized on the next
+    /// frame.
+    is_resize_requested: bool,
+
+    /// Nodes with [`NodeData::auto_focus`] that were shown since the last frame. Focused after the tree is drawn.
+    auto_focus: Vec<Weak<RefCell<Node<B>>>>,
+
     /// Incremented for every `filter_actions` access to prevent nested accesses from breaking previously made ranges.
     action_access_counter: i32,
 }
+
+impl<B: Backend> LayoutTree<B> {
+    /// Create a tree for the given root node.
+    ///
+    /// # Params
+    ///
+    /// - `root`:    Root node of the tree.
+    /// - `backend`: Backend to draw the tree with and to read input from.
+    pub fn new(root: Rc<RefCell<Node<B>>>, backend: B) -> Self {
+        Self {
+            root,
+            hover: Weak::new(),
+            focus: FocusSpace::new(),
+            mouse_cursor: MouseCursor::SystemDefault,
+            scroll: Weak::new(),
+            focus_direction: FocusDirection::new(),
+            focus_box: Rectangle::default(),
+            actions: LinkedList::new(),
+            bound_inputs: Vec::new(),
+            gamepad_navigation: None,
+            down_actions: LinkedList::new(),
+            active_actions: LinkedList::new(),
+            backend,
+            unload_queue: UnloadQueue::new(),
+            was_keyboard_handled: false,
+            depth: 0,
+            scissors: Rectangle::default(),
+            is_branch_disabled: false,
+            is_mirroring: false,
+            mirrored: Vec::new(),
+            last_mirrored: Vec::new(),
+            breadcrumbs: Breadcrumbs::default(),
+            context: TreeContextData::new(),
+            last_focus: Weak::new(),
+            dpi: Vector2::default(),
+            is_resize_requested: false,
+            auto_focus: Vec::new(),
+            action_access_counter: 0,
+        }
+    }
+
+    /// Draw a frame. This should be called by the application once every frame.
+    ///
+    /// In order, this:
+    ///
+    /// 1. prepares the backend and starts actions queued in [`Self::actions`] with [`Self::begin_frame`],
+    /// 2. removes nodes marked for removal, and recalculates node sizes if needed, with [`Self::remove_pending_nodes`]
+    ///    and [`Self::resize`],
+    /// 3. updates the hovered node, and scrolls with the mouse wheel, with [`Self::update_hover`] and
+    ///    [`Self::update_scroll`],
+    /// 4. finds input actions and passes them to the focused node, or uses them to move focus, with
+    ///    [`Self::update_actions`],
+    /// 5. draws the tree, running tree actions and style delegates along the way, and moves focus into nodes with
+    ///    [`NodeData::auto_focus`] that were just shown, with [`Self::draw_tree`].
+    ///
+    /// Each step can also be run on its own, for example to inject input between steps in tests:
+    ///
+    /// ```
+    /// # use std::{cell::{Cell, RefCell}, rc::Rc};
+    /// # use amity_ui::{backend::{Backend, headless::HeadlessBackend}, input::*,
+    /// #     node::{Node, NodeVariant, button, vframe}, style::Style, tree::LayoutTree};
+    /// # use raylib::consts::MouseButton;
+    /// let new_button = || -> Rc<RefCell<Node<HeadlessBackend>>> {
+    ///     Rc::new(RefCell::new(button(Style::default().with_padding([10.0; 4]), "")))
+    /// };
+    /// let [first, second] = [new_button(), new_button()];
+    /// let presses = Rc::new(RefCell::new(Vec::new()));
+    /// for (name, node) in [("first", &first), ("second", &second)] {
+    ///     let NodeVariant::Button(button) = &mut node.borrow_mut().variant else { unreachable!() };
+    ///     let presses = presses.clone();
+    ///     button.on_press.subscribe(move |_| presses.borrow_mut().push(name));
+    /// }
+    /// let root = vframe((), [first.clone(), second.clone()]);
+    /// let mut tree = LayoutTree::new(root, HeadlessBackend::new());
+    /// tree.bound_inputs.push(InputLayer {
+    ///     modifiers: InputStroke { input: Vec::new() },
+    ///     bindings: vec![InputBinding {
+    ///         action: FluidInputAction::Press.id(),
+    ///         trigger: InputStrokeItem::MouseButton(MouseButton::MOUSE_BUTTON_LEFT),
+    ///     }],
+    /// });
+    /// tree.draw();
+    ///
+    /// // Hover is computed before actions are dispatched
+    /// let position = second.borrow().data.padding_box().center();
+    /// tree.backend.next_frame();
+    /// tree.backend.set_mouse_position(position);
+    /// tree.begin_frame();
+    /// tree.remove_pending_nodes();
+    /// tree.resize();
+    /// tree.update_hover(tree.backend.mouse_position());
+    /// assert!(tree.hover.upgrade().is_some_and(|hover| Rc::ptr_eq(&hover, &second)));
+    ///
+    /// // A click injected after that lands on the hovered node
+    /// tree.backend.press_mouse_button(MouseButton::MOUSE_BUTTON_LEFT);
+    /// tree.update_scroll();
+    /// tree.update_actions();
+    /// assert_eq!(*presses.borrow(), ["second"]);
+    /// tree.draw_tree();
+    /// ```
+    pub fn draw(&mut self)
+    where
+        B::KeyboardKey: Copy,
+        B::MouseButton: Copy,
+        B::GamepadButton: Copy,
+        B::GamepadID: Copy,
+    {
+        self.begin_frame();
+        self.remove_pending_nodes();
+        self.resize();
+        self.update_hover(self.backend.mouse_position().into());
+        self.update_scroll();
+        self.update_actions();
+        self.draw_tree();
+    }
+
+    /// Let the backend prepare for the frame with [`Backend::begin_frame`], unload textures queued in
+    /// [`Self::unload_queue`], and start actions queued in [`Self::actions`]. This is the first step of [`Self::draw`].
+    pub fn begin_frame(&mut self) {
+        self.backend.begin_frame();
+
+        // SAFETY: The textures were queued during the previous frame, which has ended, and were all loaded with
+        // this backend
+        unsafe { self.unload_queue.unload_all(&mut self.backend) };
+        while let Some(action) = self.actions.pop_front() {
+            self.context.actions.start_action(action);
+        }
+    }
+
+    /// Draw every node, running tree actions and style delegates along the way. Once done, focus is moved into nodes
+    /// with [`NodeData::auto_focus`] that were just shown, and scrolled into view. This is the last step of
+    /// [`Self::draw`].
+    ///
+    /// Auto focus scrolls to the focused node during the same frame:
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use amity_ui::{backend::{Rectangle, headless::HeadlessBackend}, canvas::CanvasSpace, focus::FocusIO,
+    /// #     hover::HoverSpace,
+    /// #     node::{NodeVariant, checkbox, vframe}, scroll::{Scrollable, scroll_frame},
+    /// #     scroll_input::{ScrollInput, ScrollInputHandle}, style::Style, tree::LayoutTree};
+    /// # let canvas = || Box::new(CanvasSpace::new(Rectangle::default()));
+    /// # let bar = ScrollInput::new(false, canvas(), ScrollInputHandle::new(Box::new(HoverSpace::new()), canvas()));
+    /// let new_checkbox = || Rc::new(RefCell::new(checkbox(Style::default().with_padding([17.0; 4]), false)));
+    /// let field = new_checkbox();
+    /// let dialog = vframe((), [field.clone()]);
+    /// dialog.borrow_mut().data.auto_focus = true;
+    /// dialog.borrow_mut().data.set_hidden(true);
+    ///
+    /// // The dialog is placed below 20 checkboxes, out of view
+    /// let children = (0..20).map(|_| new_checkbox()).chain([dialog.clone()]);
+    /// let root = scroll_frame(Scrollable::<HeadlessBackend>::new(None, Some(bar)), (), children);
+    /// let mut tree = LayoutTree::new(root.clone(), HeadlessBackend::new());
+    /// tree.draw();
+    ///
+    /// dialog.borrow_mut().data.set_hidden(false);
+    /// tree.draw();
+    /// assert!(tree.focus.is_focused(&field));
+    /// assert_eq!(tree.focus_box, field.borrow().data.padding_box());
+    ///
+    /// let NodeVariant::Scrollable(scrollable) = &root.borrow().variant else { unreachable!() };
+    /// assert_eq!(scrollable.scroll().y, 450.0);
+    /// ```
+    pub fn draw_tree(&mut self) {
+        let window = self.backend.window_size().into();
+        let root = self.root.clone();
+
+        self.depth = 0;
+        self.is_branch_disabled = false;
+        self.focus_direction.reset(self.focus_box, self.focus_trap().as_ref().map(Rc::downgrade));
+        self.scissors = Rectangle::from_parts(Vector2::default(), window);
+
+        self.context.actions.before_tree(&mut root.borrow_mut());
+        self.draw_node(&root, Rectangle::from_parts(Vector2::default(), window));
+        self.focus_direction.finish();
+        self.context.actions.after_tree();
+
+        // Subtrees drawn by portals block input until they are no longer mirrored
+        for (targets, value) in [(&self.last_mirrored, false), (&self.mirrored, true)] {
+            for target in targets.iter().filter_map(Weak::upgrade) {
+                set_mirrored(&target, value);
+            }
+        }
+        self.last_mirrored = std::mem::take(&mut self.mirrored);
+
+        if std::mem::take(&mut self.is_resize_requested) {
+            self.root.borrow_mut().data.update_size();
+        }
+
+        // Remember where focus came from, so it can be returned once the node is dismissed
+        for node in std::mem::take(&mut self.auto_focus) {
+            let Some(node) = node.upgrade() else { continue; };
+            let previous = self.focus.focus().filter(|focus| !branch_contains(&node, focus));
+
+            if self.focus_first(&node) {
+                node.borrow_mut().data.opener = previous.as_ref().map_or_else(Weak::new, Rc::downgrade);
+            }
+        }
+
+        // The newly focused node was drawn during this pass, so its box is already up to date
+        if let Some(focus) = self.focus.focus()
+            && !Weak::ptr_eq(&Rc::downgrade(&focus), &self.last_focus)
+        {
+            self.focus_box = focus.borrow().data.padding_box;
+        }
+
+        self.scroll_to_focus();
+    }
+
+    /// If the focus has changed since the last call, scroll the nearest scrollable ancestor of the focused node to
+    /// reveal [`Self::focus_box`]. Called by [`Self::draw`] once the focused node has been drawn.
+    pub fn scroll_to_focus(&mut self) {
+        let focus = self.focus.focus();
+        let focus_ref = focus.as_ref().map_or_else(Weak::new, Rc::downgrade);
+        if Weak::ptr_eq(&focus_ref, &self.last_focus) { return; }
+
+        self.last_focus = focus_ref;
+        let Some(focus) = focus else { return; };
+        let parent = focus.borrow().data.parent.upgrade();
+
+        if let Some(ancestor) = parent.and_then(|parent| find_scrollable(&parent))
+            && let NodeVariant::Scrollable(scrollable) = &mut ancestor.borrow_mut().variant
+        {
+            scrollable.scroll_to(self.focus_box);
+        }
+    }
+
+    /// Scroll [`Self::scroll`] by the mouse wheel motion reported by the backend.
+    pub fn update_scroll(&mut self) {
+        let motion: Vector2 = self.backend.scroll().into();
+        if motion == Vector2::default() { return; }
+
+        if let Some(node) = self.scroll.upgrade()
+            && let NodeVariant::Scrollable(scrollable) = &mut node.borrow_mut().variant
+        {
+            scrollable.scroll_wheel(motion);
+        }
+    }
+
+    /// Check if the tree handled keyboard input during the last frame: the focused node handled an action bound to a
+    /// keyboard key, took typed text, or reported handling input in its `focus_impl`. Applications can check this to
+    /// avoid reacting to keys that were already used by the UI.
+    ///
+    /// The flag is reset at the start of every [`Self::update_actions`] call, and set once input is processed.
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use amity_ui::{backend::headless::HeadlessBackend, focus::Focusable, input::*, node::{NodeVariant, TextInput},
+    /// #     rope::Rope, tree::LayoutTree, utils::simple_constructor};
+    /// # use raylib::consts::KeyboardKey;
+    /// let input = NodeVariant::TextInput(TextInput::new(Rope::from_str("abc")));
+    /// let node = Rc::new(RefCell::new(simple_constructor::<HeadlessBackend>(input, ())));
+    /// let mut tree = LayoutTree::new(node.clone(), HeadlessBackend::new());
+    /// tree.bound_inputs.push(InputLayer {
+    ///     modifiers: InputStroke { input: Vec::new() },
+    ///     bindings: vec![InputBinding {
+    ///         action: FluidInputAction::Backspace.id(),
+    ///         trigger: InputStrokeItem::KeyboardKey(KeyboardKey::KEY_BACKSPACE),
+    ///     }],
+    /// });
+    /// node.clone().focus(&mut tree.focus);
+    ///
+    /// tree.backend.press_key(KeyboardKey::KEY_BACKSPACE);
+    /// tree.draw();
+    /// assert!(tree.was_keyboard_handled());
+    ///
+    /// // Unbound keys are left to the application
+    /// tree.backend.release_key(KeyboardKey::KEY_BACKSPACE);
+    /// tree.backend.next_frame();
+    /// tree.backend.press_key(KeyboardKey::KEY_F1);
+    /// tree.draw();
+    /// assert!(!tree.was_keyboard_handled());
+    /// ```
+    #[inline]
+    pub const fn was_keyboard_handled(&self) -> bool {
+        self.was_keyboard_handled
+    }
+
+    /// Find input actions triggered by held keys and buttons, updating [`Self::down_actions`] and
+    /// [`Self::active_actions`], and pass them to the focused node. Actions bound to mouse buttons are passed to the
+    /// hovered node instead. [`Self::was_keyboard_handled`] is updated to reflect if the focused node handled keyboard
+    /// input.
+    ///
+    /// Layers are checked in order of [`Self::bound_inputs`]; only the first layer with any binding held is used, so
+    /// `ctrl+z` doesn't also trigger `z`. Actions emitted by [`Self::gamepad_navigation`] are passed to the focused node
+    /// as well.
+    pub fn update_actions(&mut self)
+    where
+        B::KeyboardKey: Copy,
+        B::MouseButton: Copy,
+        B::GamepadButton: Copy,
+        B::GamepadID: Copy,
+    {
+        self.down_actions.clear();
+        self.active_actions.clear();
+        self.was_keyboard_handled = false;
+
+        let bindings: Vec<_> = self.held_bindings(|item| item.is_down(&self.backend)).cloned().collect();
+        for binding in bindings {
+            if binding.trigger.is_active(&self.backend) {
+                self.active_actions.push_back(binding);
+            } else {
+                self.down_actions.push_back(binding);
+            }
+        }
+
+        let gamepad_actions = self.gamepad_navigation.as_mut()
+            .map(|navigation| navigation.update(&self.backend))
+            .unwrap_or_default();
+
+        let focus = self.focus.focus().filter(|focus| !focus.blocks_input());
+        let hover = self.hover.upgrade().filter(|hover| !hover.blocks_input());
+
+        // Actions are tagged with the device they come from
+        let device = |binding: &InputBinding<B>| match binding.trigger {
+            InputStrokeItem::KeyboardKey(_) => Device::Keyboard,
+            InputStrokeItem::MouseButton(_) => Device::Mouse,
+            _ => Device::Gamepad,
+        };
+        let active = self.active_actions.iter().map(|binding| (binding.action, true, device(binding)));
+        let down = self.down_actions.iter().map(|binding| (binding.action, false, device(binding)));
+        let gamepad = gamepad_actions.into_iter().map(|action| (action, true, Device::Gamepad));
+        let actions: Vec<_> = active.chain(down).chain(gamepad).collect();
+        let mut is_handled = false;
+
+        // Mouse buttons act on the hovered node. Focus navigation is done by the tree if the focused node doesn't
+        // handle it
+        for (action, is_active, device) in actions {
+            let is_action_handled = if device == Device::Mouse {
+                hover.as_ref().is_some_and(|hover| self.node_action(hover, &action, is_active))
+            } else {
+                focus.as_ref().is_some_and(|focus| self.node_action(focus, &action, is_active))
+                    || (is_active && self.navigate_focus(&action))
+            };
+            is_handled |= is_action_handled && device == Device::Keyboard;
+        }
+
+        let Some(mut focus) = focus else {
+            self.was_keyboard_handled = is_handled;
+            return;
+        };
+
+        // Text inputs take typed text
+        if let NodeVariant::TextInput(input) = &mut focus.borrow_mut().variant {
+            is_handled |= input.input_text(&mut self.backend);
+        }
+
+        self.was_keyboard_handled = focus.focus_impl() || is_handled;
+    }
+
+    /// Move focus in response to a focus navigation action, such as [`FluidInputAction::FocusNext`], using the nodes
+    /// collected in [`Self::focus_direction`] during the last draw. Tab navigation wraps around, and stays within the
+    /// [focus trap](NodeData::is_focus_trap) containing the focused node, if any.
+    ///
+    /// # Returns
+    ///
+    /// True if the action was a focus navigation action, and a node to focus was found.
+    fn navigate_focus(&mut self, action: &InputActionID) -> bool {
+        use FluidInputAction::*;
+
+        let target = if action.is(FocusNext) {
+            self.focus_direction.next_node()
+        } else if action.is(FocusPrevious) {
+            self.focus_direction.previous_node()
+        } else if let Some((_, side)) = [(FocusLeft, Side::Left), (FocusRight, Side::Right), (FocusUp, Side::Top),
+            (FocusDown, Side::Bottom)].into_iter().find(|(direction, _)| action.is(*direction))
+        {
+            self.focus_direction.in_direction(side)
+        } else {
+            return false;
+        };
+
+        let Some(mut target) = target else { return false };
+        target.focus(&mut self.focus);
+        true
+    }
+
+    /// Focus the first node within `scope` that can take focus, in tab order; see [`NodeData::tab_index`]. The scope
+    /// itself can be focused too. Nodes that block input, and nodes left out of tab navigation, are skipped. If there
+    /// is no such node, focus is cleared.
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use amity_ui::{backend::headless::HeadlessBackend, focus::{FocusIO, Focusable}, node::{Node, button, vframe},
+    /// #     tree::LayoutTree};
+    /// type Ref = Rc<RefCell<Node<HeadlessBackend>>>;
+    /// let new_button = |text: &str| -> Ref { Rc::new(RefCell::new(button((), text))) };
+    /// let [outside, disabled, first, second] = ["Outside", "Disabled", "First", "Second"].map(new_button);
+    /// disabled.borrow_mut().data.set_disabled(true);
+    /// second.borrow_mut().data.tab_index = Some(1);
+    /// let dialog = vframe((), [disabled, first.clone(), second.clone()]);
+    /// let empty = vframe((), []);
+    /// let root = vframe((), [outside.clone(), dialog.clone(), empty.clone()]);
+    /// let mut tree = LayoutTree::new(root, HeadlessBackend::new());
+    /// tree.draw();
+    ///
+    /// // Nodes with a tab index come first
+    /// assert!(tree.focus_first(&dialog));
+    /// assert!(tree.focus.is_focused(&second));
+    ///
+    /// // Focus is cleared if nothing in scope can be focused
+    /// assert!(!tree.focus_first(&empty));
+    /// assert!(tree.focus.focus().is_none());
+    /// ```
+    ///
+    /// # Params
+    ///
+    /// - `scope`: Branch to search.
+    ///
+    /// # Returns
+    ///
+    /// True if a node was focused, false if focus was cleared.
+    pub fn focus_first(&mut self, scope: &Rc<RefCell<Node<B>>>) -> bool {
+        let mut first: Option<(TabKey, Rc<RefCell<Node<B>>>)> = None;
+        let mut stack = vec![scope.clone()];
+        let mut order = 0;
+
+        while let Some(node) = stack.pop() {
+            let borrowed = node.borrow();
+            if borrowed.data.blocks_input() { continue; }
+
+            let tab_index = borrowed.data.tab_index;
+            if borrowed.variant.is_focusable() && tab_index.is_none_or(|index| index >= 0) {
+                let key = TabKey {
+                    is_unindexed: tab_index.is_none(),
+                    index: tab_index.unwrap_or_default(),
+                    order,
+                };
+                order += 1;
+
+                if first.as_ref().is_none_or(|(first, _)| key < *first) {
+                    first = Some((key, node.clone()));
+                }
+            }
+
+            stack.extend(borrowed.data.children.iter().rev().cloned());
+        }
+
+        let Some((_, mut target)) = first else {
+            self.focus.clear_focus();
+            return false;
+        };
+        target.focus(&mut self.focus);
+        true
+    }
+
+    /// Find the focus trap containing the focused node: the nearest ancestor of the focused node, or the node itself,
+    /// with [`NodeData::is_focus_trap`] set.
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use amity_ui::{backend::headless::HeadlessBackend, focus::{FocusIO, Focusable}, input::*,
+    /// #     node::{Node, button, vframe}, tree::LayoutTree};
+    /// # use raylib::consts::KeyboardKey;
+    /// type Ref = Rc<RefCell<Node<HeadlessBackend>>>;
+    /// let new_button = |text: &str| -> Ref { Rc::new(RefCell::new(button((), text))) };
+    /// let [before, first, last, after] = ["Before", "First", "Last", "After"].map(new_button);
+    /// let dialog = vframe((), [first.clone(), last.clone()]);
+    /// dialog.borrow_mut().data.is_focus_trap = true;
+    /// let root = vframe((), [before.clone(), dialog.clone(), after.clone()]);
+    ///
+    /// let mut tree = LayoutTree::new(root, HeadlessBackend::new());
+    /// tree.bound_inputs.push(InputLayer {
+    ///     modifiers: InputStroke { input: Vec::new() },
+    ///     bindings: vec![InputBinding {
+    ///         action: FluidInputAction::FocusNext.id(),
+    ///         trigger: InputStrokeItem::KeyboardKey(KeyboardKey::KEY_TAB),
+    ///     }],
+    /// });
+    /// let tab = |tree: &mut LayoutTree<HeadlessBackend>| {
+    ///     tree.backend.press_key(KeyboardKey::KEY_TAB);
+    ///     tree.draw();
+    ///     tree.backend.release_key(KeyboardKey::KEY_TAB);
+    ///     tree.backend.next_frame();
+    /// };
+    ///
+    /// last.clone().focus(&mut tree.focus);
+    /// tree.draw();
+    /// assert!(Rc::ptr_eq(&tree.focus_trap().unwrap(), &dialog));
+    ///
+    /// // Tab wraps around within the dialog, instead of moving to the button after it
+    /// tab(&mut tree);
+    /// assert!(tree.focus.is_focused(&first));
+    /// tab(&mut tree);
+    /// assert!(tree.focus.is_focused(&last));
+    ///
+    /// // Without the trap, focus moves on, once the tree is drawn again
+    /// dialog.borrow_mut().data.is_focus_trap = false;
+    /// tree.draw();
+    /// tab(&mut tree);
+    /// assert!(tree.focus.is_focused(&after));
+    /// ```
+    pub fn focus_trap(&self) -> Option<Rc<RefCell<Node<B>>>> {
+        let mut node = self.focus.focus();
+
+        while let Some(current) = node {
+            if current.borrow().data.is_focus_trap { return Some(current); }
+            node = current.borrow().data.parent();
+        }
+
+        None
+    }
+
+    /// Pass an action to the focused or hovered node. If the node doesn't handle it, the action bubbles up to its
+    /// ancestors; see [`bubble_action`]. Text inputs are also given the backend, so they can use the clipboard. Nodes
+    /// with [`NodeData::dismiss_on_cancel`] handle [`FluidInputAction::Cancel`] by closing.
+    ///
+    /// # Returns
+    ///
+    /// True if any node handled the action.
+    fn node_action(&mut self, target: &Rc<RefCell<Node<B>>>, action: &InputActionID, is_active: bool) -> bool {
+        let backend = &mut self.backend;
+        let focus = &mut self.focus;
+
+        bubble_action(target, |node| {
+            if is_active && action.is(FluidInputAction::Cancel) && node.borrow().data.dismiss_on_cancel {
+                dismiss(node, focus);
+                return true;
+            }
+
+            if let NodeVariant::TextInput(input) = &mut node.borrow_mut().variant {
+                return input.action_impl(backend, action, is_active);
+            }
+
+            node.action_impl(None, 0, action, is_active)
+        })
+    }
+
+    /// Draw a node and its children.
+    ///
+    /// # Params
+    ///
+    /// - `node`:       Node to draw.
+    /// - `margin_box`: Space given to the node by its parent.
+    fn draw_node(&mut self, node_ref: &Rc<RefCell<Node<B>>>, margin_box: Rectangle) {
+        // Nodes drawn through a portal were already updated where they are placed in the tree
+        let is_mirror = self.is_mirroring;
+        let is_focused = !is_mirror && self.focus.is_focused(node_ref);
+        let mut node = node_ref.borrow_mut();
+        if node.data.is_hidden() { return; }
+
+        if !is_mirror && std::mem::take(&mut node.data.is_auto_focus_pending) && node.data.auto_focus {
+            self.auto_focus.push(Rc::downgrade(node_ref));
+        }
+
+        let is_branch_disabled = self.enter_disabled_branch(&mut node.data);
+        if !is_mirror {
+            self.context.actions.before_draw(&mut node);
+        }
+        self.depth += 1;
+
+        // Nodes can't be marked for resize while their ancestors are borrowed, so the whole tree is resized later
+        if !is_mirror && node.update_style(&self.backend) {
+            self.is_resize_requested = true;
+        }
+
+        let style = node.data.style();
+        let border_box = style.border_box(margin_box);
+        let padding_box = style.padding_box(border_box);
+        let content_box = style.content_box(padding_box);
+        let [gap_x, gap_y] = style.gap;
+
+        // Apply tint and opacity to the node and its children
+        let previous_tint = self.context.tint();
+        let mut node_tint = style.tint;
+        node_tint.a = (node_tint.a as f32 * node.data.opacity()).round() as u8;
+        if self.context.set_tint(previous_tint.multiply(node_tint)) {
+            self.backend.set_tint(self.context.tint().into());
+        }
+
+        self.backend.draw_rectangle(border_box.into(), style.background_color.into());
+        style.border_style.apply(&mut self.backend, border_box, SideArray(style.border));
+
+        if !is_mirror {
+            node.data.margin_box = margin_box;
+            node.data.padding_box = padding_box;
+            node.data.content_box = content_box;
+        }
+        if is_focused {
+            self.focus_box = padding_box;
+        }
+        if !is_mirror && node.variant.is_focusable() && !node.data.blocks_input() {
+            let tab_index = node.data.tab_index;
+            self.focus_direction.update(Rc::downgrade(node_ref), is_focused, padding_box, self.depth, tab_index);
+        }
+        let is_trap = !is_mirror && self.focus_direction.enter_trap(node_ref, true);
+
+        let Node { data, variant } = &mut *node;
+        match variant {
+            NodeVariant::ScrollInput(scroll_input) => {
+                scroll_input.draw(&mut self.backend, content_box, data.style(), data.theme());
+            }
+            NodeVariant::Label(label) => {
+                let (style, texture) = data.text_texture(&self.unload_queue);
+                label.draw(&mut self.backend, style, content_box, texture);
+            }
+            NodeVariant::Button(button) => {
+                let (style, texture) = data.text_texture(&self.unload_queue);
+                button.label.draw(&mut self.backend, style, content_box, texture);
+            }
+            NodeVariant::TextInput(input) => {
+                if !is_mirror {
+                    input.update(self.backend.delta_time(), is_focused);
+                }
+                let (style, texture) = data.text_texture(&self.unload_queue);
+                input.draw(&mut self.backend, style, content_box, texture);
+            }
+            NodeVariant::Tooltip(tooltip) if !is_mirror => {
+                let (style, texture) = data.text_texture(&self.unload_queue);
+                tooltip.draw(&mut self.backend, style, texture);
+            }
+            NodeVariant::Checkbox(checkbox) => checkbox.draw(&mut self.backend, data.style(), content_box),
+            NodeVariant::Scrollable(scrollable) if !is_mirror => scrollable.update_momentum(self.backend.delta_time()),
+            NodeVariant::Portal(portal) => self.draw_mirror(&portal.target, content_box, portal.scale),
+            _ => {}
+        }
+
+        let children: Vec<_> = data.children.iter()
+            .map(|child| {
+                let child = child.borrow();
+                (child.data.min_size(), child.data.layout)
+            })
+            .collect();
+        let gap = Vector2::new(gap_x, gap_y);
+
+        // Scrollables offset their children by the scroll position, and clip them to the viewport
+        let (children_box, viewport) = if let NodeVariant::Scrollable(scrollable) = variant {
+            let padding = padding_box.size() - content_box.size();
+            let content_size = data.layout.mode.measure(&children, gap, content_box.size()) + padding;
+
+            scrollable.resize_content(content_size, padding_box.size());
+            let viewport = if is_mirror {
+                scrollable.viewport(padding_box)
+            } else {
+                scrollable.update_viewport(padding_box)
+            };
+            (data.style().content_box(scrollable.content_box(padding_box)), Some(viewport))
+        } else {
+            (content_box, None)
+        };
+        let boxes = data.layout.mode.arrange(&children, gap, children_box);
+
+        // Frames clip their children to the content box if they overflow
+        let clip_box = if viewport.is_some() {
+            viewport
+        } else if data.clip_children {
+            Some(padding_box)
+        } else if let NodeVariant::Frame(_) = variant
+            && boxes.iter().any(|child_box| content_box.union(child_box) != content_box)
+        {
+            Some(content_box)
+        } else {
+            None
+        };
+
+        let scissors = self.scissors;
+        if let Some(clip_box) = clip_box {
+            self.backend.set_area(clip_box.into());
+            self.scissors = self.backend.area().into();
+        }
+
+        for (child, child_box) in data.children.iter().zip(boxes) {
+            self.draw_node(child, child_box);
+        }
+
+        if clip_box.is_some() {
+            self.backend.restore_area();
+            self.scissors = scissors;
+        }
+
+        if let NodeVariant::Scrollable(scrollable) = variant {
+            scrollable.draw_scroll_bars(&mut self.backend, padding_box, data.style(), data.theme());
+        }
+
+        if self.context.set_tint(previous_tint) {
+            self.backend.set_tint(previous_tint.into());
+        }
+
+        if is_trap {
+            self.focus_direction.enter_trap(node_ref, false);
+        }
+
+        self.depth -= 1;
+        if !is_mirror {
+            self.context.actions.after_draw(&mut node);
+        }
+        self.exit_disabled_branch(is_branch_disabled);
+    }
+
+    /// Draw a subtree referenced by a [`Portal`](crate::node::Portal) in the portal's content box. The subtree is drawn
+    /// with the size it was given during its last draw, scaled with [`Backend::set_scale`]. Nothing is drawn if the
+    /// portal is itself mirrored, or inside its target.
+    ///
+    /// # Params
+    ///
+    /// - `target`:      Subtree to draw.
+    /// - `content_box`: Content box of the portal.
+    /// - `scale`:       Scale to draw the subtree at.
+    fn draw_mirror(&mut self, target: &Weak<RefCell<Node<B>>>, content_box: Rectangle, scale: f32) {
+        if self.is_mirroring || scale <= 0.0 { return; }
+        let Some(target) = target.upgrade() else { return };
+
+        // The target is borrowed if it is being drawn, which happens if the portal is inside it
+        let Ok(node) = target.try_borrow() else { return };
+        let size = node.data.margin_box.size();
+        let is_disabled_inherited = node.data.is_disabled_inherited;
+        drop(node);
+
+        self.mirrored.push(Rc::downgrade(&target));
+
+        // Positions are scaled along with sizes, so the box is moved to keep its corner at the portal's
+        let previous_scale = self.backend.scale();
+        let is_branch_disabled = std::mem::replace(&mut self.is_branch_disabled, is_disabled_inherited);
+        self.backend.set_scale(previous_scale * scale);
+        self.is_mirroring = true;
+
+        self.draw_node(&target, Rectangle::from_parts(content_box.start() / scale, size));
+
+        self.is_mirroring = false;
+        self.backend.set_scale(previous_scale);
+        self.is_branch_disabled = is_branch_disabled;
+    }
+
+    /// Recalculate the size of every node in the tree, if a resize is pending. Nodes are given the window as the
+    /// available space. A change in the window's DPI also triggers a resize, since text has to be measured again.
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use amity_ui::{backend::{Vector2, headless::HeadlessBackend}, node::{Node, NodeVariant, vframe}};
+    /// # use amity_ui::{style::Style, tree::LayoutTree, utils::simple_constructor};
+    /// let padding = |value| Style::default().with_padding([value; 4]);
+    /// let leaf: Rc<RefCell<Node<HeadlessBackend>>> =
+    ///     Rc::new(RefCell::new(simple_constructor(NodeVariant::Space, padding(5.0))));
+    /// let middle = vframe((), [leaf.clone()]);
+    /// let root = vframe(padding(10.0), [middle.clone()]);
+    /// let mut tree = LayoutTree::new(root.clone(), HeadlessBackend::new());
+    ///
+    /// tree.resize();
+    /// assert_eq!(root.borrow().data.min_size(), Vector2::new(30.0, 30.0));
+    /// assert!(!root.borrow().data.is_resize_pending() && !middle.borrow().data.is_resize_pending());
+    ///
+    /// // Changing the size of a node marks its ancestors
+    /// leaf.borrow_mut().data.set_style(padding(20.0));
+    /// assert!(root.borrow().data.is_resize_pending() && middle.borrow().data.is_resize_pending());
+    ///
+    /// tree.resize();
+    /// assert_eq!(root.borrow().data.min_size(), Vector2::new(60.0, 60.0));
+    /// assert!(!root.borrow().data.is_resize_pending() && !leaf.borrow().data.is_resize_pending());
+    ///
+    /// // Hidden nodes take no space
+    /// leaf.borrow_mut().data.set_hidden(true);
+    /// tree.resize();
+    /// assert_eq!(root.borrow().data.min_size(), Vector2::new(20.0, 20.0));
+    /// ```
+    pub fn resize(&mut self) {
+        let dpi: Vector2 = self.backend.dpi().into();
+        let mut root = self.root.borrow_mut();
+        if dpi != self.dpi {
+            self.dpi = dpi;
+            root.data.update_size();
+        }
+        if !root.data.is_resize_pending() { return; }
+
+        root.resize(self.backend.window_size().into(), dpi, &mut self.breadcrumbs);
+    }
+
+    /// Enter a node during a tree pass. The node inherits [`Self::is_branch_disabled`], and if the node is disabled,
+    /// the flag is set for its children.
+    ///
+    /// # Returns
+    ///
+    /// Previous value of `is_branch_disabled`, to be passed to [`Self::exit_disabled_branch`] once the node and its
+    /// children are done.
+    pub fn enter_disabled_branch(&mut self, node: &mut NodeData<B>) -> bool {
+        let previous = self.is_branch_disabled;
+
+        node.is_disabled_inherited = previous;
+        if node.is_disabled() {
+            self.is_branch_disabled = true;
+        }
+
+        previous
+    }
+
+    /// Leave a node entered with [`Self::enter_disabled_branch`].
+    ///
+    /// # Params
+    ///
+    /// - `previous`: Value returned by `enter_disabled_branch`.
+    #[inline]
+    pub fn exit_disabled_branch(&mut self, previous: bool) {
+        self.is_branch_disabled = previous;
+    }
+
+    /// Detach every node marked with [`NodeData::remove`] from its parent, dropping its subtree.
+    ///
+    /// If the focused node was inside a removed subtree, focus moves to the closest remaining sibling of the removed
+    /// node that can take focus, preferring the next one over the previous one at the same distance. If there is no
+    /// such sibling, focus is cleared. If the hovered node was removed, hover is cleared.
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use amity_ui::{backend::{Vector2, headless::HeadlessBackend}, focus::{FocusIO, Focusable}, layout::Layout};
+    /// # use amity_ui::{node::{button, label, vframe}, tree::LayoutTree};
+    /// let new_button = |text: &str| Rc::new(RefCell::new(button::<HeadlessBackend>(Layout::fill(), text)));
+    /// let [first, second, third] = ["First", "Second", "Third"].map(new_button);
+    /// let caption = Rc::new(RefCell::new(label(Layout::fill(), "Caption")));
+    /// let root = vframe((), [first.clone(), second.clone(), caption.clone(), third.clone()]);
+    /// let mut tree = LayoutTree::new(root.clone(), HeadlessBackend::new());
+    /// second.clone().focus(&mut tree.focus);
+    /// tree.draw();
+    ///
+    /// // The label next to the removed button can't take focus, so focus moves to the previous button instead
+    /// second.borrow_mut().data.remove();
+    /// tree.draw();
+    /// assert_eq!(root.borrow().data.children().len(), 3);
+    /// assert!(second.borrow().data.parent().is_none());
+    /// assert!(tree.focus.is_focused(&first));
+    ///
+    /// // Focus moves past the label to the closest button that can take it, and hover is cleared
+    /// tree.update_hover(Vector2::new(10.0, 10.0));
+    /// assert!(first.borrow().data.is_hovered());
+    /// first.borrow_mut().data.remove();
+    /// tree.remove_pending_nodes();
+    /// assert!(tree.focus.is_focused(&third));
+    /// assert!(tree.hover.upgrade().is_none() && !first.borrow().data.is_hovered());
+    ///
+    /// // Focus is cleared if no sibling can take it
+    /// third.borrow_mut().data.remove();
+    /// tree.remove_pending_nodes();
+    /// assert!(tree.focus.focus().is_none());
+    /// ```
+    pub fn remove_pending_nodes(&mut self) {
+        let mut focus = self.focus.focus();
+        let hover = self.hover.upgrade();
+        let mut stack = vec![self.root.clone()];
+
+        while let Some(node) = stack.pop() {
+            let mut node = node.borrow_mut();
+            let children = &mut node.data.children;
+            let mut index = 0;
+
+            while index < children.len() {
+                if !children[index].borrow().data.is_removed() {
+                    index += 1;
+                    continue;
+                }
+
+                let removed = children.remove(index);
+                removed.borrow_mut().data.parent = Weak::new();
+
+                if let Some(focused) = &focus && branch_contains(&removed, focused) {
+                    // Look at following and preceding siblings in turn, starting with the closest ones
+                    let sibling = (0..children.len())
+                        .flat_map(|distance| [Some(index + distance), index.checked_sub(distance + 1)])
+                        .filter_map(|index| children.get(index?))
+                        .find(|sibling| {
+                            let sibling = sibling.borrow();
+                            sibling.variant.is_focusable() && !sibling.data.blocks_input()
+                        })
+                        .cloned();
+
+                    self.focus.set_focus(sibling.as_ref());
+                    focus = sibling;
+                }
+
+                if let Some(hovered) = &hover && branch_contains(&removed, hovered) {
+                    hovered.borrow_mut().data.set_hovered(false);
+                    self.hover = Weak::new();
+                }
+            }
+
+            stack.extend(children.iter().cloned());
+        }
+    }
+
+    /// Find the node under the mouse and update [`Self::hover`], [`Self::scroll`] and the nodes' `is_hovered` flags.
+    /// The mouse cursor is then updated to match the hovered node.
+    ///
+    /// # Params
+    ///
+    /// - `mouse`: Position of the mouse, in window coordinates.
+    pub fn update_hover(&mut self, mouse: Vector2) {
+        hover::update_hover(&mut self.hover, &self.root, mouse);
+        self.scroll = self.hover.upgrade()
+            .and_then(|node| find_scrollable(&node))
+            .as_ref()
+            .map_or_else(Weak::new, Rc::downgrade);
+        self.update_mouse_cursor();
+    }
+
+    /// Set the mouse cursor to the one requested by the hovered node's style, or to the system default if no node is
+    /// hovered. The backend is only called if the cursor changed.
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use amity_ui::backend::{Backend, MouseCursor, Vector2, headless::HeadlessBackend};
+    /// # use amity_ui::{layout::Layout, node::{hframe, label}, style::Style, tree::LayoutTree};
+    /// let with_cursor = |text: &str, mouse_cursor: MouseCursor| {
+    ///     let mut node = label(Layout::fill(), text);
+    ///     node.data.set_style(Style::default().with_mouse_cursor(mouse_cursor));
+    ///     Rc::new(RefCell::new(node))
+    /// };
+    ///
+    /// // Two nodes next to each other, each taking half of the 800×600 window
+    /// let root = hframe((), [with_cursor("Text", MouseCursor::Text), with_cursor("Link", MouseCursor::Pointer)]);
+    /// let mut tree = LayoutTree::new(root, HeadlessBackend::new());
+    /// tree.draw();
+    ///
+    /// tree.update_hover(Vector2::new(100.0, 100.0));
+    /// assert_eq!(tree.backend.mouse_cursor(), MouseCursor::Text);
+    ///
+    /// tree.update_hover(Vector2::new(500.0, 100.0));
+    /// assert_eq!(tree.backend.mouse_cursor(), MouseCursor::Pointer);
+    ///
+    /// // The backend isn't called again while the cursor stays the same
+    /// tree.backend.set_mouse_cursor(MouseCursor::NotAllowed);
+    /// tree.update_hover(Vector2::new(600.0, 100.0));
+    /// assert_eq!(tree.backend.mouse_cursor(), MouseCursor::NotAllowed);
+    ///
+    /// // Moving out of the window resets the cursor
+    /// tree.update_hover(Vector2::new(900.0, 100.0));
+    /// assert_eq!(tree.backend.mouse_cursor(), MouseCursor::SystemDefault);
+    /// ```
+    pub fn update_mouse_cursor(&mut self) {
+        let cursor = self.hover.upgrade()
+            .map_or(MouseCursor::SystemDefault, |node| node.borrow().data.style().mouse_cursor.clone());
+
+        if cursor == self.mouse_cursor { return; }
+
+        self.backend.set_mouse_cursor(cursor.clone());
+        self.mouse_cursor = cursor;
+    }
+
+    /// Find the binding triggered by the given held inputs.
+    ///
+    /// Layers are checked in order of [`Self::bound_inputs`], which should be sorted, so more specific layers, with
+    /// more modifiers, take precedence. For example, `ctrl+shift+z` wins over `ctrl+z` if both could match.
+    /// [`Self::update_actions`] picks bindings the same way.
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use amity_ui::{backend::headless::HeadlessBackend, input::*, node::NodeVariant, tree::LayoutTree,
+    /// #     utils::simple_constructor};
+    /// # use raylib::consts::KeyboardKey::{self, *};
+    /// let key = |key: KeyboardKey| InputStrokeItem::<HeadlessBackend>::KeyboardKey(key);
+    /// let layer = |modifiers: Vec<_>, action: FluidInputAction| InputLayer {
+    ///     modifiers: InputStroke { input: modifiers },
+    ///     bindings: vec![InputBinding { action: action.id(), trigger: key(KEY_Z) }],
+    /// };
+    /// let node = Rc::new(RefCell::new(simple_constructor(NodeVariant::Space, ())));
+    /// let mut tree = LayoutTree::new(node, HeadlessBackend::new());
+    /// tree.bound_inputs = vec![
+    ///     layer(vec![key(KEY_LEFT_CONTROL)], FluidInputAction::Undo),
+    ///     layer(vec![key(KEY_LEFT_CONTROL), key(KEY_LEFT_SHIFT)], FluidInputAction::Redo),
+    /// ];
+    /// tree.bound_inputs.sort();
+    ///
+    /// // The layer with more modifiers wins
+    /// let held = [key(KEY_LEFT_CONTROL), key(KEY_LEFT_SHIFT), key(KEY_Z)];
+    /// assert!(tree.resolve_action(&held).unwrap().action.is(FluidInputAction::Redo));
+    /// assert!(tree.resolve_action(&held[0..1]).is_none());
+    ///
+    /// // Layers with modifiers that aren't held are skipped
+    /// let held = [key(KEY_LEFT_CONTROL), key(KEY_Z)];
+    /// assert!(tree.resolve_action(&held).unwrap().action.is(FluidInputAction::Undo));
+    /// assert!(tree.resolve_action(&[key(KEY_Z)]).is_none());
+    ///
+    /// // Pressed keys are resolved the same way
+    /// for key in [KEY_LEFT_CONTROL, KEY_LEFT_SHIFT, KEY_Z] {
+    ///     tree.backend.press_key(key);
+    /// }
+    /// tree.update_actions();
+    /// let actions: Vec<_> = tree.active_actions.iter().map(|binding| binding.action).collect();
+    /// assert_eq!(actions, [FluidInputAction::Redo.id()]);
+    /// ```
+    ///
+    /// # Params
+    ///
+    /// - `held`: Keys and buttons that are currently held down.
+    ///
+    /// # Returns
+    ///
+    /// The first binding, in the first layer with all modifiers held, whose trigger is held; or `None` if no binding
+    /// matches.
+    pub fn resolve_action(&self, held: &[InputStrokeItem<B>]) -> Option<&InputBinding<B>>
+    where
+        InputStrokeItem<B>: PartialEq,
+    {
+        self.held_bindings(|item| held.contains(item)).next()
+    }
+
+    /// Find the first layer, in order of [`Self::bound_inputs`], with all modifiers held and at least one trigger
+    /// held.
+    ///
+    /// # Params
+    ///
+    /// - `is_held`: Check if a key or button is held down.
+    ///
+    /// # Returns
+    ///
+    /// Bindings of the layer whose trigger is held.
+    fn held_bindings(&self, is_held: impl Fn(&InputStrokeItem<B>) -> bool) -> impl Iterator<Item = &InputBinding<B>> {
+        let layer = self.bound_inputs.iter()
+            .filter(|layer| layer.modifiers.iter().all(&is_held))
+            .find(|layer| layer.bindings.iter().any(|binding| is_held(&binding.trigger)));
+
+        layer.into_iter()
+            .flat_map(|layer| &layer.bindings)
+            .filter(move |binding| is_held(&binding.trigger))
+    }
+}
+
+/// Input device an action was emitted by.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Device {
+    Keyboard,
+    Mouse,
+    Gamepad,
+}
+
+/// Hide a node with [`NodeData::dismiss_on_cancel`] and dispatch [`NodeData::on_dismiss`]. If focus is inside the
+/// node, it returns to [`NodeData::opener`], or is cleared if there is none.
+fn dismiss<B: Backend>(node: &Rc<RefCell<Node<B>>>, focus: &mut FocusSpace<B>) {
+    {
+        let data = &mut node.borrow_mut().data;
+        data.set_hidden(true);
+        data.on_dismiss.dispatch(&());
+    }
+
+    if focus.focus().is_none_or(|focused| !branch_contains(node, &focused)) { return; }
+
+    match node.borrow().data.opener.upgrade() {
+        Some(mut opener) => opener.focus(focus),
+        None => focus.clear_focus(),
+    }
+}
+
+/// Find the closest node, starting with the given node and moving up to its ancestors, that is
+/// [scrollable](crate::scroll::Scrollable).
+fn find_scrollable<B: Backend>(node: &Rc<RefCell<Node<B>>>) -> Option<Rc<RefCell<Node<B>>>> {
+    let mut node = node.clone();
+
+    loop {
+        let parent = {
+            let borrowed = node.borrow();
+            if let NodeVariant::Scrollable(_) = borrowed.variant { break Some(node.clone()); }
+            borrowed.data.parent.upgrade()
+        };
+        node = parent?;
+    }
+}
+
+/// Check if the node is `branch` or one of its descendants.
+fn branch_contains<B: Backend>(branch: &Rc<RefCell<Node<B>>>, node: &Rc<RefCell<Node<B>>>) -> bool {
+    Rc::ptr_eq(branch, node) || branch.borrow().data.children.iter().any(|child| branch_contains(child, node))
+}
+
+/// Mark the node and its descendants as drawn through a [`Portal`](crate::node::Portal), or clear the mark.
+fn set_mirrored<B: Backend>(node: &Rc<RefCell<Node<B>>>, value: bool) {
+    let mut node = node.borrow_mut();
+    node.data.is_mirrored = value;
+    for child in &node.data.children {
+        set_mirrored(child, value);
+    }
+}
@@ -1,3 +1,5 @@
+use std::{cell::RefCell, collections::HashMap, fmt, ops::Deref};
+use fontdue::{Font, FontSettings, Metrics};
 use crate::{backend::{Backend, Vector2}, rope::Rope};
 
 /// Low-level interface for drawing text. Represents a single typeface.
@@ -11,6 +13,15 @@ pub trait Typeface<B: Backend> {
     /// List glyphs in the typeface.
     fn glyph_count(&self) -> usize;
 
+    /// Check if the typeface has a glyph for the given character.
+    ///
+    /// Used by [`FallbackTypeface`] to pick a typeface for each glyph. Typefaces that can't tell use the default
+    /// implementation, which assumes every glyph is present.
+    #[inline]
+    fn has_glyph(&self, _glyph: char) -> bool {
+        true
+    }
+
     /// Get initial pen position.
     fn pen_position(&self) -> Vector2;
 
@@ -28,6 +39,15 @@ pub trait Typeface<B: Backend> {
     /// Get advance vector for the given glyph. Uses dots, not pixels, as the unit.
     fn advance(&mut self, glyph: char) -> Vector2;
 
+    /// Get the kerning adjustment between two adjacent glyphs, in dots. The value is added to the advance of `left`
+    /// when `right` follows it.
+    ///
+    /// Typefaces without kerning information use the default implementation, which always returns zero.
+    #[inline]
+    fn kerning(&self, _left: char, _right: char) -> f32 {
+        0.0
+    }
+
     /// Get curently set DPI.
     fn dpi(&self) -> Vector2;
 
@@ -53,4 +73,727 @@ pub trait Typeface<B: Backend> {
     /// - `text`:         Text to draw.
     /// - `paletteIndex`: If the image has a palette, this is the index to get colors from.
     fn draw_line(&self, target: &mut B::Image, pen_position: &mut Vector2, text: Rope, palette_index: u8);
+
+    /// Measure a single line of text. Tabs advance the pen to the next multiple of `indent_width`.
+    ///
+    /// # Params
+    ///
+    /// - `text`: Text to measure. Line breaks are not handled; use [`Self::measure`] for multi-line text.
+    ///
+    /// # Returns
+    ///
+    /// Width of the text and the line height, in dots, using the size set with `set_size`.
+    fn measure_line(&mut self, text: Rope) -> Vector2 {
+        let mut previous = None;
+        let width = text.chars().fold(0.0, |pen, glyph| {
+            let pen = advance_pen(self, pen, previous, glyph);
+            previous = Some(glyph);
+            pen
+        });
+
+        Vector2::new(width, self.line_height() as f32)
+    }
+
+    /// Measure text, which may span multiple lines.
+    ///
+    /// # Params
+    ///
+    /// - `text`: Text to measure. Lines are separated with `\n`.
+    ///
+    /// # Returns
+    ///
+    /// Width of the longest line, and sum of the heights of all lines, in dots. Empty text still occupies one line.
+    ///
+    /// ```
+    /// # use amity_ui::backend::{Vector2, headless::{HeadlessBackend, HeadlessImage}};
+    /// # use amity_ui::{rope::Rope, typeface::Typeface};
+    /// // Every glyph is 10 pixels wide, and lines are 20 pixels tall
+    /// struct Monospace { dpi: Vector2, indent_width: i32 }
+    ///
+    /// impl Typeface<HeadlessBackend> for Monospace {
+    ///     fn advance(&mut self, _glyph: char) -> Vector2 { Vector2::new(10.0 * self.dpi.x / 96.0, 0.0) }
+    ///     fn line_height(&self) -> i32 { (20.0 * self.dpi.y / 96.0) as i32 }
+    ///     fn set_size(&mut self, dpi: Vector2, _size: f32) { self.dpi = dpi; }
+    /// #   fn glyph_count(&self) -> usize { 128 }
+    /// #   fn pen_position(&self) -> Vector2 { Vector2::default() }
+    /// #   fn indent_width(&self) -> &i32 { &self.indent_width }
+    /// #   fn indent_width_mut(&mut self) -> &mut i32 { &mut self.indent_width }
+    /// #   fn dpi(&self) -> Vector2 { self.dpi }
+    /// #   fn draw_line(&self, _: &mut HeadlessImage, _: &mut Vector2, _: Rope, _: u8) {}
+    ///     // ...
+    /// }
+    ///
+    /// let mut typeface = Monospace { dpi: Vector2::new(96.0, 96.0), indent_width: 40 };
+    /// assert_eq!(typeface.measure(Rope::from_str("")), Vector2::new(0.0, 20.0));
+    /// assert_eq!(typeface.measure(Rope::from_str("ab\ncde\n")), Vector2::new(30.0, 60.0));
+    /// assert_eq!(typeface.measure_line(Rope::from_str("a\tb")), Vector2::new(50.0, 20.0));
+    ///
+    /// typeface.set_size(Vector2::new(192.0, 192.0), 16.0);
+    /// assert_eq!(typeface.measure(Rope::from_str("ab\ncde")), Vector2::new(60.0, 80.0));
+    /// ```
+    fn measure(&mut self, text: Rope) -> Vector2 {
+        let mut pen = 0.0;
+        let mut width: f32 = 0.0;
+        let mut lines = 1;
+        let mut previous = None;
+
+        for glyph in text.chars() {
+            if glyph == '\n' {
+                width = width.max(pen);
+                pen = 0.0;
+                lines += 1;
+                previous = None;
+            } else {
+                pen = advance_pen(self, pen, previous, glyph);
+                previous = Some(glyph);
+            }
+        }
+
+        Vector2::new(width.max(pen), (lines * self.line_height()) as f32)
+    }
+}
+
+/// Move the pen past a glyph.
+///
+/// # Params
+///
+/// - `typeface`: Typeface to measure the glyph with.
+/// - `pen`:      Horizontal position of the pen, relative to the start of the line.
+/// - `previous`: Glyph preceding `glyph` in the line, if any. Used for kerning.
+/// - `glyph`:    Glyph to move past.
+///
+/// # Returns
+///
+/// New horizontal position of the pen.
+fn advance_pen<B: Backend, T: Typeface<B> + ?Sized>(typeface: &mut T, pen: f32, previous: Option<char>, glyph: char) -> f32 {
+    let indent_width = *typeface.indent_width() as f32;
+
+    // Tabs align to the next multiple of the indent width
+    if glyph == '\t' && indent_width > 0.0 {
+        return ((pen / indent_width).floor() + 1.0) * indent_width;
+    }
+
+    let kerning = previous.map_or(0.0, |previous| typeface.kerning(previous, glyph));
+
+    pen + kerning + typeface.advance(glyph).x
+}
+
+/// Typeface without any glyphs. Used as a placeholder when no font has been loaded; measures all text as empty
+/// and draws nothing.
+#[derive(Clone, Copy, Default)]
+pub struct EmptyTypeface {
+    indent_width: i32,
+    dpi: Vector2,
+}
+
+impl<B: Backend> Typeface<B> for EmptyTypeface {
+    #[inline]
+    fn glyph_count(&self) -> usize {
+        0
+    }
+
+    #[inline]
+    fn has_glyph(&self, _glyph: char) -> bool {
+        false
+    }
+
+    #[inline]
+    fn pen_position(&self) -> Vector2 {
+        Vector2::default()
+    }
+
+    #[inline]
+    fn line_height(&self) -> i32 {
+        0
+    }
+
+    #[inline]
+    fn indent_width(&self) -> &i32 {
+        &self.indent_width
+    }
+
+    #[inline]
+    fn indent_width_mut(&mut self) -> &mut i32 {
+        &mut self.indent_width
+    }
+
+    #[inline]
+    fn advance(&mut self, _glyph: char) -> Vector2 {
+        Vector2::default()
+    }
+
+    #[inline]
+    fn dpi(&self) -> Vector2 {
+        self.dpi
+    }
+
+    #[inline]
+    fn set_size(&mut self, dpi: Vector2, _size: f32) {
+        self.dpi = dpi;
+    }
+
+    #[inline]
+    fn draw_line(&self, _target: &mut B::Image, _pen_position: &mut Vector2, _text: Rope, _palette_index: u8) {}
+}
+
+/// Break text into lines that fit in the given width.
+///
+/// Lines are broken after whitespace where possible. If a single word doesn't fit in a line, it is broken between
+/// characters instead. Line breaks (`\n`) in the text always end a line. Whitespace at the end of a line is kept with
+/// the line and may overflow it.
+///
+/// # Params
+///
+/// - `typeface`:  Typeface to measure the text with.
+/// - `text`:      Text to wrap.
+/// - `max_width`: Maximum width of a line, in dots.
+///
+/// # Returns
+///
+/// Slices of `text`, one for each line, not including the line breaks. There's always at least one line.
+///
+/// ```
+/// # use amity_ui::backend::{Vector2, headless::{HeadlessBackend, HeadlessImage}};
+/// # use amity_ui::{rope::Rope, typeface::{Typeface, wrap_lines}};
+/// // Every glyph, including spaces, is 10 dots wide
+/// struct Monospace { indent_width: i32 }
+///
+/// impl Typeface<HeadlessBackend> for Monospace {
+///     fn advance(&mut self, _glyph: char) -> Vector2 { Vector2::new(10.0, 0.0) }
+/// #   fn glyph_count(&self) -> usize { 128 }
+/// #   fn pen_position(&self) -> Vector2 { Vector2::default() }
+/// #   fn line_height(&self) -> i32 { 20 }
+/// #   fn indent_width(&self) -> &i32 { &self.indent_width }
+/// #   fn indent_width_mut(&mut self) -> &mut i32 { &mut self.indent_width }
+/// #   fn dpi(&self) -> Vector2 { Vector2::new(96.0, 96.0) }
+/// #   fn set_size(&mut self, _dpi: Vector2, _size: f32) {}
+/// #   fn draw_line(&self, _: &mut HeadlessImage, _: &mut Vector2, _: Rope, _: u8) {}
+///     // ...
+/// }
+///
+/// let typeface = &mut Monospace { indent_width: 0 };
+///
+/// // exact fit
+/// assert_eq!(wrap_lines(typeface, Rope::from_str("aaa bbb"), 70.0), ["aaa bbb"]);
+/// assert_eq!(wrap_lines(typeface, Rope::from_str("aaa bbbb"), 70.0), ["aaa ", "bbbb"]);
+///
+/// // words longer than a line are broken between characters, never inside one
+/// assert_eq!(wrap_lines(typeface, Rope::from_str("abcdefgh"), 30.0), ["abc", "def", "gh"]);
+/// assert_eq!(wrap_lines(typeface, Rope::from_str("äöüß"), 20.0), ["äö", "üß"]);
+///
+/// // line breaks always end a line
+/// assert_eq!(wrap_lines(typeface, Rope::from_str("ab\n\ncd"), 100.0), ["ab", "", "cd"]);
+/// assert_eq!(wrap_lines(typeface, Rope::from_str(""), 100.0), [""]);
+/// ```
+pub fn wrap_lines<B: Backend>(typeface: &mut dyn Typeface<B>, text: Rope, max_width: f32) -> Vec<Rope> {
+    let mut lines = Vec::new();
+    let mut line_start = 0;
+    let mut pen = 0.0;
+
+    // Start of the text after the last whitespace in the line
+    let mut last_break = None;
+
+    let mut previous = None;
+
+    let mut index = 0;
+    for glyph in text.chars() {
+        let glyph_start = index;
+        index += glyph.len_utf8();
+
+        if glyph == '\n' {
+            lines.push(text.slice(line_start, glyph_start - line_start));
+            line_start = index;
+            pen = 0.0;
+            last_break = None;
+            previous = None;
+            continue;
+        }
+
+        let next_pen = advance_pen(typeface, pen, previous, glyph);
+
+        // Whitespace can always stay on the line
+        if glyph.is_whitespace() {
+            pen = next_pen;
+            last_break = Some(index);
+            previous = Some(glyph);
+            continue;
+        }
+
+        if next_pen <= max_width || glyph_start == line_start {
+            pen = next_pen;
+            previous = Some(glyph);
+            continue;
+        }
+
+        // Break after the last whitespace, or in the middle of the word if there was none
+        let break_at = last_break.unwrap_or(glyph_start);
+        lines.push(text.slice(line_start, break_at - line_start));
+        line_start = break_at;
+        last_break = None;
+
+        previous = None;
+        pen = text.slice(break_at, glyph_start - break_at).chars()
+            .fold(0.0, |pen, glyph| {
+                let pen = advance_pen(typeface, pen, previous, glyph);
+                previous = Some(glyph);
+                pen
+            });
+        pen = advance_pen(typeface, pen, previous, glyph);
+        previous = Some(glyph);
+    }
+
+    lines.push(text.slice(line_start, text.len() - line_start));
+    lines
+}
+
+/// Image glyphs can be drawn into by [`FontdueTypeface`].
+pub trait GlyphTarget {
+    /// Width of the image, in pixels.
+    fn width(&self) -> i32;
+
+    /// Height of the image, in pixels.
+    fn height(&self) -> i32;
+
+    /// Blend a single pixel of a rasterized glyph into the image. Called only for pixels within the image.
+    ///
+    /// # Params
+    ///
+    /// - `x`, `y`:         Position of the pixel.
+    /// - `coverage`:       How much of the pixel is covered by the glyph, from 0 to 255.
+    /// - `palette_index`:  If the image has a palette, this is the index to get colors from.
+    fn blend_glyph_pixel(&mut self, x: i32, y: i32, coverage: u8, palette_index: u8);
+}
+
+/// Error returned when a font file couldn't be loaded by [`FontdueTypeface`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadTypefaceError(&'static str);
+
+impl fmt::Display for LoadTypefaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to load typeface: {}", self.0)
+    }
+}
+
+impl std::error::Error for LoadTypefaceError {}
+
+/// Typeface loaded from a TrueType or OpenType font file, rendered with [`fontdue`].
+///
+/// Rasterized glyphs are cached until the size or DPI changes. If the DPI differs between axes, glyphs are rasterized
+/// for the vertical DPI and stretched horizontally.
+pub struct FontdueTypeface {
+    font: Font,
+    indent_width: i32,
+    dpi: Vector2,
+    size: f32,
+
+    /// Rasterized glyphs for the current size and DPI.
+    cache: RefCell<HashMap<char, (Metrics, Vec<u8>)>>,
+}
+
+impl FontdueTypeface {
+    /// Load a typeface from the contents of a TTF or OTF file.
+    ///
+    /// The typeface uses a DPI of 96 and a size of zero until [`Typeface::set_size`] is called.
+    ///
+    /// ```
+    /// # use amity_ui::backend::{Color, Vector2, headless::{HeadlessBackend, HeadlessImage}};
+    /// # use amity_ui::{rope::Rope, typeface::{FontdueTypeface, Typeface}};
+    /// let data = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/fonts/DejaVuSansMono.ttf"));
+    /// let mut typeface = FontdueTypeface::from_bytes(data.as_slice())?;
+    /// let typeface: &mut dyn Typeface<HeadlessBackend> = &mut typeface;
+    /// typeface.set_size(Vector2::new(96.0, 96.0), 16.0);
+    ///
+    /// assert!(typeface.has_glyph('A'));
+    /// assert!(!typeface.has_glyph('中'));
+    /// assert!(typeface.line_height() > 0);
+    ///
+    /// let advance = typeface.advance('A').x;
+    /// assert!(advance > 0.0);
+    /// assert_eq!(typeface.advance('i').x, advance);
+    ///
+    /// // doubling the DPI doubles the advance
+    /// let line_height = typeface.line_height();
+    /// typeface.set_size(Vector2::new(192.0, 192.0), 16.0);
+    /// assert_eq!(typeface.advance('A').x, advance * 2.0);
+    ///
+    /// // each axis is scaled on its own
+    /// typeface.set_size(Vector2::new(192.0, 96.0), 16.0);
+    /// assert_eq!(typeface.advance('A').x, advance * 2.0);
+    /// assert_eq!(typeface.line_height(), line_height);
+    /// typeface.set_size(Vector2::new(96.0, 192.0), 16.0);
+    /// assert_eq!(typeface.advance('A').x, advance);
+    /// assert!(typeface.line_height() > line_height);
+    /// typeface.set_size(Vector2::new(192.0, 192.0), 16.0);
+    ///
+    /// // drawing moves the pen and leaves some coverage on the image
+    /// let mut image = HeadlessImage::new(100, 50, Color::new(0, 0, 0, 0));
+    /// let mut pen = typeface.pen_position();
+    /// typeface.draw_line(&mut image, &mut pen, Rope::from_str("AB"), 0);
+    /// assert_eq!(pen.x, advance * 4.0);
+    /// assert!(image.pixels.iter().any(|pixel| pixel.a > 0));
+    ///
+    /// // glyphs drawn with a wider horizontal DPI are stretched to match their advance
+    /// let ink_width = |typeface: &mut dyn Typeface<HeadlessBackend>, dpi| {
+    ///     typeface.set_size(dpi, 16.0);
+    ///     let mut image = HeadlessImage::new(100, 50, Color::new(0, 0, 0, 0));
+    ///     typeface.draw_line(&mut image, &mut typeface.pen_position(), Rope::from_str("A"), 0);
+    ///     let columns: Vec<_> = (0..100).filter(|&x| (0..50).any(|y| image.get(x, y).unwrap().a > 0)).collect();
+    ///     columns.last().unwrap() - columns.first().unwrap() + 1
+    /// };
+    /// let width = ink_width(typeface, Vector2::new(96.0, 96.0));
+    /// assert!((ink_width(typeface, Vector2::new(192.0, 96.0)) - width * 2).abs() <= 1);
+    /// # Ok::<(), amity_ui::typeface::LoadTypefaceError>(())
+    /// ```
+    pub fn from_bytes(data: impl Deref<Target = [u8]>) -> Result<Self, LoadTypefaceError> {
+        let font = Font::from_bytes(data, FontSettings::default())
+            .map_err(LoadTypefaceError)?;
+
+        Ok(Self {
+            font,
+            indent_width: 0,
+            dpi: Vector2::new(96.0, 96.0),
+            size: 0.0,
+            cache: RefCell::default(),
+        })
+    }
+
+    /// Size of the font in dots, accounting for vertical DPI. Glyphs are rasterized at this size, and then stretched
+    /// horizontally by [`Self::stretch`].
+    #[inline]
+    fn scale(&self) -> f32 {
+        self.size * self.dpi.y / 96.0
+    }
+
+    /// Ratio of horizontal to vertical DPI. Horizontal metrics are multiplied by it.
+    #[inline]
+    fn stretch(&self) -> f32 {
+        if self.dpi.y > 0.0 { self.dpi.x / self.dpi.y } else { 1.0 }
+    }
+
+    /// Rasterize a glyph, stretching the bitmap horizontally if the DPI differs between axes. Columns of the
+    /// stretched bitmap are interpolated linearly from the nearest columns of the original.
+    fn rasterize(&self, glyph: char) -> (Metrics, Vec<u8>) {
+        let (mut metrics, bitmap) = self.font.rasterize(glyph, self.scale());
+        let stretch = self.stretch();
+        if stretch == 1.0 || metrics.width == 0 { return (metrics, bitmap); }
+
+        let width = ((metrics.width as f32 * stretch).round() as usize).max(1);
+        let mut stretched = vec![0; width * metrics.height];
+        for (source, target) in bitmap.chunks_exact(metrics.width).zip(stretched.chunks_exact_mut(width)) {
+            for (x, coverage) in target.iter_mut().enumerate() {
+                let position = ((x as f32 + 0.5) / stretch - 0.5).clamp(0.0, (metrics.width - 1) as f32);
+                let (left, right) = (source[position as usize] as f32, source[position.ceil() as usize] as f32);
+                *coverage = (left + (right - left) * position.fract()).round() as u8;
+            }
+        }
+
+        metrics.xmin = (metrics.xmin as f32 * stretch).round() as i32;
+        metrics.width = width;
+        metrics.advance_width *= stretch;
+        (metrics, stretched)
+    }
+
+    /// Advance of a glyph including kerning against the previous glyph, without rasterizing it.
+    fn glyph_advance(&self, pen: f32, previous: Option<char>, glyph: char) -> f32 {
+        let indent_width = self.indent_width as f32;
+
+        if glyph == '\t' && indent_width > 0.0 {
+            return ((pen / indent_width).floor() + 1.0) * indent_width;
+        }
+
+        let scale = self.scale();
+        let kerning = previous
+            .and_then(|previous| self.font.horizontal_kern(previous, glyph, scale))
+            .unwrap_or(0.0);
+
+        pen + (kerning + self.font.metrics(glyph, scale).advance_width) * self.stretch()
+    }
+}
+
+impl<B: Backend> Typeface<B> for FontdueTypeface
+where
+    B::Image: GlyphTarget,
+{
+    #[inline]
+    fn glyph_count(&self) -> usize {
+        self.font.glyph_count() as usize
+    }
+
+    #[inline]
+    fn has_glyph(&self, glyph: char) -> bool {
+        self.font.has_glyph(glyph)
+    }
+
+    fn pen_position(&self) -> Vector2 {
+        let ascent = self.font.horizontal_line_metrics(self.scale())
+            .map_or(0.0, |metrics| metrics.ascent);
+
+        Vector2::new(0.0, ascent.round())
+    }
+
+    fn line_height(&self) -> i32 {
+        self.font.horizontal_line_metrics(self.scale())
+            .map_or(0.0, |metrics| metrics.new_line_size)
+            .ceil() as i32
+    }
+
+    #[inline]
+    fn indent_width(&self) -> &i32 {
+        &self.indent_width
+    }
+
+    #[inline]
+    fn indent_width_mut(&mut self) -> &mut i32 {
+        &mut self.indent_width
+    }
+
+    fn advance(&mut self, glyph: char) -> Vector2 {
+        let metrics = self.font.metrics(glyph, self.scale());
+
+        Vector2::new(metrics.advance_width * self.stretch(), metrics.advance_height)
+    }
+
+    fn kerning(&self, left: char, right: char) -> f32 {
+        self.font.horizontal_kern(left, right, self.scale()).unwrap_or(0.0) * self.stretch()
+    }
+
+    #[inline]
+    fn dpi(&self) -> Vector2 {
+        self.dpi
+    }
+
+    fn set_size(&mut self, dpi: Vector2, size: f32) {
+        if self.dpi == dpi && self.size == size { return; }
+
+        self.dpi = dpi;
+        self.size = size;
+        self.cache.get_mut().clear();
+    }
+
+    fn draw_line(&self, target: &mut B::Image, pen_position: &mut Vector2, text: Rope, palette_index: u8) {
+        let scale = self.scale();
+        let mut cache = self.cache.borrow_mut();
+        let start = pen_position.x;
+        let mut pen = 0.0;
+        let mut previous = None;
+
+        for glyph in text.chars() {
+            let next_pen = self.glyph_advance(pen, previous, glyph);
+
+            // Glyphs are drawn after kerning is applied
+            let kerning = previous
+                .and_then(|previous| self.font.horizontal_kern(previous, glyph, scale))
+                .unwrap_or(0.0) * self.stretch();
+            previous = Some(glyph);
+
+            if !glyph.is_whitespace() {
+                let (metrics, bitmap) = cache.entry(glyph)
+                    .or_insert_with(|| self.rasterize(glyph));
+
+                // `ymin` is the offset of the bottom of the bitmap from the baseline
+                let left = (start + pen + kerning).round() as i32 + metrics.xmin;
+                let top = pen_position.y.round() as i32 - metrics.ymin - metrics.height as i32;
+
+                for (index, &coverage) in bitmap.iter().enumerate() {
+                    if coverage == 0 { continue; }
+
+                    let x = left + (index % metrics.width) as i32;
+                    let y = top + (index / metrics.width) as i32;
+
+                    if x < 0 || y < 0 || x >= target.width() || y >= target.height() { continue; }
+
+                    target.blend_glyph_pixel(x, y, coverage, palette_index);
+                }
+            }
+
+            pen = next_pen;
+        }
+
+        pen_position.x = start + pen;
+    }
+}
+
+/// Typeface composed of a chain of typefaces. Each glyph is taken from the first typeface in the chain that has it,
+/// so, for example, a Latin typeface can be followed by CJK and emoji typefaces.
+///
+/// Glyphs missing from every typeface are taken from the first one.
+///
+/// ```
+/// # use amity_ui::backend::{Vector2, headless::{HeadlessBackend, HeadlessImage}};
+/// # use amity_ui::{rope::Rope, typeface::{FallbackTypeface, Typeface}};
+/// // Typeface with a few glyphs of the same width
+/// struct Face { glyphs: &'static str, width: f32, line_height: i32, dpi: Vector2, indent_width: i32 }
+///
+/// impl Typeface<HeadlessBackend> for Face {
+///     fn has_glyph(&self, glyph: char) -> bool { self.glyphs.contains(glyph) }
+///     fn advance(&mut self, _glyph: char) -> Vector2 { Vector2::new(self.width, 0.0) }
+///     fn line_height(&self) -> i32 { self.line_height }
+///     fn dpi(&self) -> Vector2 { self.dpi }
+///     fn set_size(&mut self, dpi: Vector2, _size: f32) { self.dpi = dpi; }
+/// #   fn glyph_count(&self) -> usize { self.glyphs.chars().count() }
+/// #   fn pen_position(&self) -> Vector2 { Vector2::default() }
+/// #   fn indent_width(&self) -> &i32 { &self.indent_width }
+/// #   fn indent_width_mut(&mut self) -> &mut i32 { &mut self.indent_width }
+/// #   fn draw_line(&self, _: &mut HeadlessImage, _: &mut Vector2, _: Rope, _: u8) {}
+///     // ...
+/// }
+///
+/// let latin = Face { glyphs: "ab", width: 10.0, line_height: 20, dpi: Vector2::default(), indent_width: 0 };
+/// let cjk = Face { glyphs: "ab中", width: 16.0, line_height: 24, dpi: Vector2::default(), indent_width: 0 };
+/// let mut typeface = FallbackTypeface::new(vec![Box::new(latin), Box::new(cjk)]);
+///
+/// // `中` is missing from the first typeface, so the second one is used
+/// assert_eq!(typeface.advance('a').x, 10.0);
+/// assert_eq!(typeface.advance('中').x, 16.0);
+/// assert_eq!(typeface.advance('z').x, 10.0);
+/// assert_eq!(typeface.measure(Rope::from_str("a中b")), Vector2::new(36.0, 24.0));
+///
+/// typeface.set_size(Vector2::new(144.0, 144.0), 12.0);
+/// assert!(typeface.typefaces().iter().all(|face| face.dpi() == Vector2::new(144.0, 144.0)));
+/// ```
+pub struct FallbackTypeface<B: Backend> {
+    typefaces: Vec<Box<dyn Typeface<B>>>,
+    indent_width: i32,
+    dpi: Vector2,
+}
+
+impl<B: Backend> FallbackTypeface<B> {
+    /// Create a fallback chain out of the given typefaces, in order of priority.
+    pub fn new(typefaces: Vec<Box<dyn Typeface<B>>>) -> Self {
+        Self {
+            typefaces,
+            indent_width: 0,
+            dpi: Vector2::default(),
+        }
+    }
+
+    /// Add a typeface to the end of the chain.
+    ///
+    /// Call [`Typeface::set_size`] afterwards so the new typeface uses the same size as the rest.
+    pub fn push(&mut self, typeface: impl Typeface<B> + 'static) {
+        self.typefaces.push(Box::new(typeface));
+    }
+
+    /// Typefaces in the chain, in order of priority.
+    #[inline]
+    pub fn typefaces(&self) -> &[Box<dyn Typeface<B>>] {
+        &self.typefaces
+    }
+
+    /// Find the index of the typeface to use for a glyph.
+    ///
+    /// # Returns
+    ///
+    /// Index of the first typeface with the glyph, the first typeface if none has it, or `None` if the chain is empty.
+    fn member(&self, glyph: char) -> Option<usize> {
+        if self.typefaces.is_empty() { return None; }
+
+        Some(self.typefaces.iter()
+            .position(|typeface| typeface.has_glyph(glyph))
+            .unwrap_or(0))
+    }
+}
+
+impl<B: Backend> Typeface<B> for FallbackTypeface<B> {
+    fn glyph_count(&self) -> usize {
+        self.typefaces.iter()
+            .map(|typeface| typeface.glyph_count())
+            .sum()
+    }
+
+    fn has_glyph(&self, glyph: char) -> bool {
+        self.typefaces.iter()
+            .any(|typeface| typeface.has_glyph(glyph))
+    }
+
+    /// Pen position is placed so that the baseline fits the tallest typeface in the chain.
+    fn pen_position(&self) -> Vector2 {
+        self.typefaces.iter()
+            .map(|typeface| typeface.pen_position())
+            .fold(Vector2::default(), |a, b| Vector2::new(a.x.max(b.x), a.y.max(b.y)))
+    }
+
+    fn line_height(&self) -> i32 {
+        self.typefaces.iter()
+            .map(|typeface| typeface.line_height())
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[inline]
+    fn indent_width(&self) -> &i32 {
+        &self.indent_width
+    }
+
+    #[inline]
+    fn indent_width_mut(&mut self) -> &mut i32 {
+        &mut self.indent_width
+    }
+
+    fn advance(&mut self, glyph: char) -> Vector2 {
+        match self.member(glyph) {
+            Some(index) => self.typefaces[index].advance(glyph),
+            None => Vector2::default(),
+        }
+    }
+
+    /// Kerning is only applied between glyphs taken from the same typeface.
+    fn kerning(&self, left: char, right: char) -> f32 {
+        match (self.member(left), self.member(right)) {
+            (Some(a), Some(b)) if a == b => self.typefaces[a].kerning(left, right),
+            _ => 0.0,
+        }
+    }
+
+    #[inline]
+    fn dpi(&self) -> Vector2 {
+        self.dpi
+    }
+
+    fn set_size(&mut self, dpi: Vector2, size: f32) {
+        self.dpi = dpi;
+
+        for typeface in &mut self.typefaces {
+            typeface.set_size(dpi, size);
+        }
+    }
+
+    fn draw_line(&self, target: &mut B::Image, pen_position: &mut Vector2, text: Rope, palette_index: u8) {
+        let line_start = pen_position.x;
+        let indent_width = self.indent_width as f32;
+
+        // Draw text in runs of glyphs that use the same typeface
+        let mut run_start = 0;
+        let mut run_member = None;
+        let mut draw_run = |pen_position: &mut Vector2, member: Option<usize>, start: usize, end: usize| {
+            if let Some(member) = member && end > start {
+                self.typefaces[member].draw_line(target, pen_position, text.slice(start, end - start), palette_index);
+            }
+        };
+
+        let mut index = 0;
+        for glyph in text.chars() {
+            let glyph_start = index;
+            index += glyph.len_utf8();
+
+            // Tabs are measured from the start of the whole line, not the run
+            if glyph == '\t' && indent_width > 0.0 {
+                draw_run(pen_position, run_member, run_start, glyph_start);
+                let pen = pen_position.x - line_start;
+                pen_position.x = line_start + ((pen / indent_width).floor() + 1.0) * indent_width;
+                run_start = index;
+                run_member = None;
+                continue;
+            }
+
+            let member = self.member(glyph);
+            if member != run_member {
+                draw_run(pen_position, run_member, run_start, glyph_start);
+                run_start = glyph_start;
+                run_member = member;
+            }
+        }
+
+        draw_run(pen_position, run_member, run_start, text.len());
+    }
 }
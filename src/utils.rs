@@ -0,0 +1,92 @@
+use crate::{backend::Backend, layout::Layout, node::{Node, NodeData, NodeVariant}, style::Style, tag_list::TagList, theme::Theme};
+
+/// Property that can be passed to a node constructor, such as a [`Layout`] or a [`TagList`].
+///
+/// Tuples of params are params too, applied in order, so a constructor can take any number of them:
+///
+/// ```
+/// # use amity_ui::{backend::headless::HeadlessBackend, layout::Layout, node::{HitPassthrough, label}, tags};
+/// let node = label::<HeadlessBackend>((Layout::fill(), tags!(heading), HitPassthrough::Passthrough), "Title");
+///
+/// assert!(node.data.layout == Layout::fill());
+/// assert!(node.data.tags == tags!(heading));
+/// assert!(node.data.hit_passthrough == HitPassthrough::Passthrough);
+/// ```
+pub trait NodeParam<B: Backend> {
+    /// Set the property on the node.
+    ///
+    /// # Params
+    ///
+    /// - `node`: Node to change.
+    fn apply(self, node: &mut Node<B>);
+}
+
+impl<B: Backend> NodeParam<B> for Layout {
+    #[inline]
+    fn apply(self, node: &mut Node<B>) {
+        node.data.layout = self;
+    }
+}
+
+impl<B: Backend> NodeParam<B> for TagList {
+    #[inline]
+    fn apply(self, node: &mut Node<B>) {
+        node.data.tags = self;
+    }
+}
+
+impl<B: Backend> NodeParam<B> for Theme<B> {
+    /// Set the theme explicitly, overriding the theme inherited from the parent.
+    #[inline]
+    fn apply(self, node: &mut Node<B>) {
+        node.data.set_theme(self);
+    }
+}
+
+impl<B: Backend> NodeParam<B> for Style<B> {
+    #[inline]
+    fn apply(self, node: &mut Node<B>) {
+        node.data.set_style(self);
+    }
+}
+
+/// Implement [`NodeParam`] for tuples of params.
+macro_rules! impl_node_param_tuple {
+    ($($param:ident),*) => {
+        impl<B: Backend, $($param: NodeParam<B>),*> NodeParam<B> for ($($param,)*) {
+            #[allow(non_snake_case, unused_variables)]
+            #[inline]
+            fn apply(self, node: &mut Node<B>) {
+                let ($($param,)*) = self;
+                $($param.apply(node);)*
+            }
+        }
+    };
+}
+
+impl_node_param_tuple!();
+impl_node_param_tuple!(P1);
+impl_node_param_tuple!(P1, P2);
+impl_node_param_tuple!(P1, P2, P3);
+impl_node_param_tuple!(P1, P2, P3, P4);
+impl_node_param_tuple!(P1, P2, P3, P4, P5);
+impl_node_param_tuple!(P1, P2, P3, P4, P5, P6);
+
+/// Create a node. Node components build their factory functions on top of this, for example [`crate::node::label`].
+///
+/// # Params
+///
+/// - `variant`: Content of the node.
+/// - `params`:  Properties to set on the node: a single [`NodeParam`], or a tuple of them.
+///
+/// # Returns
+///
+/// A node with default data, changed by `params`.
+pub fn simple_constructor<B: Backend>(variant: NodeVariant<B>, params: impl NodeParam<B>) -> Node<B> {
+    let mut node = Node {
+        data: NodeData::new(),
+        variant,
+    };
+    params.apply(&mut node);
+    node
+}